@@ -1,7 +1,11 @@
-use crate::cli::Commands;
-use crate::cli::formats::LogFormat;
-use clap::Parser;
+use crate::cli::formats::{LogFormat, OutputFormat};
+use crate::cli::{AuthCommands, Commands, GetImages};
+use crate::config::Settings;
+use crate::k8s::{ClientOptions, parse_go_duration};
+use clap::parser::ValueSource;
+use clap::{ArgMatches, Parser};
 use std::path::PathBuf;
+use std::time::Duration;
 
 /// Command line arguments for the Kimspect application
 #[derive(Parser, Debug)]
@@ -33,6 +37,31 @@ pub struct Args {
     )]
     pub log_format: LogFormat,
 
+    /// Append log messages to a file instead of writing them to stderr
+    #[arg(long = "log-file", value_name = "PATH", global = true)]
+    pub log_file: Option<PathBuf>,
+
+    /// Path to the config file (default: ~/.config/kimspect/config.toml)
+    #[arg(long = "config", value_name = "PATH", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Config profile to apply on top of the config defaults
+    #[arg(long = "profile", value_name = "NAME", global = true)]
+    pub profile: Option<String>,
+
+    /// Kubeconfig context to use (default: the current context)
+    #[arg(long = "context", value_name = "NAME", global = true)]
+    pub context: Option<String>,
+
+    /// Timeout of each request to the API server, e.g. 30s or 1m (0 disables the timeout)
+    #[arg(
+        long = "request-timeout",
+        value_name = "DURATION",
+        global = true,
+        value_parser = parse_request_timeout
+    )]
+    pub request_timeout: Option<Duration>,
+
     /// The command to execute
     #[command(subcommand)]
     pub command: Commands,
@@ -49,4 +78,251 @@ impl Args {
             .clone()
             .or_else(|| std::env::var("KUBECONFIG").ok().map(PathBuf::from))
     }
+
+    /// Fill in the options not given on the command line from config file settings
+    ///
+    /// # Arguments
+    ///
+    /// * `settings` - The settings from the config file
+    /// * `matches` - The parsed command line, used to tell given options from defaults
+    pub fn apply_settings(&mut self, settings: &Settings, matches: &ArgMatches) {
+        let matches = command_matches(matches);
+        let given = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+
+        match &mut self.command {
+            Commands::Get { resource } => match resource {
+                GetImages::Images {
+                    namespace,
+                    exclude_registry,
+                    exclude_namespace,
+                    all_namespaces,
+                    group_by,
+                    output,
+                    columns,
+                    sort_by,
+                    ..
+                } => {
+                    if !given("namespace")
+                        && !given("all_namespaces")
+                        && !given("namespace_selector")
+                    {
+                        if let Some(namespaces) = &settings.namespaces {
+                            *namespace = namespaces.clone();
+                        }
+                        if let Some(all) = settings.all_namespaces {
+                            *all_namespaces = all;
+                        }
+                    }
+                    // An explicit registry filter replaces the configured exclusions
+                    if !given("registry") && !given("exclude_registry") {
+                        if let Some(registries) = &settings.exclude_registries {
+                            *exclude_registry = registries.clone();
+                        }
+                    }
+                    if !given("exclude_namespace") {
+                        if let Some(namespaces) = &settings.exclude_namespaces {
+                            *exclude_namespace = namespaces.clone();
+                        }
+                    }
+                    // Grouped images have their own columns and order
+                    let grouped = group_by.is_some();
+                    apply_output(settings, output, given("output"), !grouped);
+                    if !given("columns") && !given("output") && !grouped {
+                        if let Some(configured) = &settings.columns {
+                            *columns = configured.clone();
+                        }
+                    }
+                    if !given("sort_by") && !grouped {
+                        if let Some(field) = settings.sort_by {
+                            *sort_by = Some(field);
+                        }
+                    }
+                }
+                GetImages::Registries {
+                    namespace,
+                    all_namespaces,
+                    output,
+                    ..
+                }
+                | GetImages::ImageErrors {
+                    namespace,
+                    all_namespaces,
+                    output,
+                    ..
+                }
+                | GetImages::PullTimes {
+                    namespace,
+                    all_namespaces,
+                    output,
+                    ..
+                }
+                | GetImages::ArchMismatches {
+                    namespace,
+                    all_namespaces,
+                    output,
+                    ..
+                }
+                | GetImages::ImageMismatches {
+                    namespace,
+                    all_namespaces,
+                    output,
+                    ..
+                } => {
+                    if !given("namespace") && !given("all_namespaces") {
+                        apply_namespace(settings, namespace, all_namespaces);
+                    }
+                    apply_output(settings, output, given("output"), false);
+                }
+                GetImages::Nodes { output, .. } => {
+                    apply_output(settings, output, given("output"), false);
+                }
+                GetImages::RolloutImages {
+                    namespace, output, ..
+                } => {
+                    if !given("namespace") {
+                        if let Some(first) = settings.namespaces.as_ref().and_then(|n| n.first()) {
+                            *namespace = first.clone();
+                        }
+                    }
+                    apply_output(settings, output, given("output"), false);
+                }
+            },
+            Commands::Auth { action } => match action {
+                AuthCommands::Check {
+                    namespace,
+                    all_namespaces,
+                    output,
+                    ..
+                } => {
+                    if !given("namespace") && !given("all_namespaces") {
+                        apply_namespace(settings, namespace, all_namespaces);
+                    }
+                    apply_output(settings, output, given("output"), false);
+                }
+            },
+            Commands::Tui {
+                namespace,
+                all_namespaces,
+                ..
+            }
+            | Commands::Serve {
+                namespace,
+                all_namespaces,
+                ..
+            }
+            | Commands::Pin {
+                namespace,
+                all_namespaces,
+                ..
+            } => {
+                if !given("namespace") && !given("all_namespaces") {
+                    apply_namespace(settings, namespace, all_namespaces);
+                }
+            }
+            Commands::Webhook {
+                disallow_registry,
+                disallow_class,
+                deny_latest,
+                require_digest,
+                ..
+            } => {
+                if !given("disallow_registry") {
+                    if let Some(registries) = &settings.disallowed_registries {
+                        *disallow_registry = registries.clone();
+                    }
+                }
+                if !given("disallow_class") {
+                    if let Some(classes) = &settings.disallowed_classes {
+                        *disallow_class = classes.clone();
+                    }
+                }
+                if !given("deny_latest") {
+                    *deny_latest = settings.deny_latest.unwrap_or(*deny_latest);
+                }
+                if !given("require_digest") {
+                    *require_digest = settings.require_digest.unwrap_or(*require_digest);
+                }
+            }
+            Commands::Plugin { .. } => {}
+        }
+    }
+
+    /// Get the options for connecting to the cluster
+    ///
+    /// # Returns
+    ///
+    /// * `ClientOptions` - The kubeconfig, context and request timeout to use
+    pub fn client_options(&self) -> ClientOptions {
+        let kubeconfig = match &self.command {
+            Commands::Get { resource } => resource.get_kubeconfig_path(),
+            Commands::Auth {
+                action: AuthCommands::Check { kubeconfig, .. },
+            }
+            | Commands::Tui { kubeconfig, .. }
+            | Commands::Serve { kubeconfig, .. }
+            | Commands::Pin { kubeconfig, .. } => kubeconfig.clone(),
+            Commands::Webhook { .. } | Commands::Plugin { .. } => None,
+        };
+
+        ClientOptions {
+            kubeconfig: kubeconfig.or_else(|| self.kubeconfig.clone()),
+            context: self.context.clone(),
+            request_timeout: self.request_timeout,
+        }
+    }
+}
+
+/// Longest accepted request timeout, deadlines further away overflow the clock
+const MAX_REQUEST_TIMEOUT: Duration = Duration::from_secs(7 * 24 * 3600);
+
+/// Parse a request timeout like kubectl: a duration such as `30s` or `1m`, or seconds
+fn parse_request_timeout(value: &str) -> Result<Duration, String> {
+    let timeout = value
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_secs)
+        .or_else(|| parse_go_duration(value))
+        .ok_or_else(|| {
+            format!(
+                "Invalid duration '{}', expected seconds or a unit such as 30s, 1m or 1h",
+                value
+            )
+        })?;
+    if timeout > MAX_REQUEST_TIMEOUT {
+        return Err(format!(
+            "Request timeout '{}' is too long, the maximum is 168h",
+            value
+        ));
+    }
+    Ok(timeout)
+}
+
+/// Get the matches of the innermost subcommand, which holds the command options
+fn command_matches(matches: &ArgMatches) -> &ArgMatches {
+    match matches.subcommand() {
+        Some((_, subcommand)) => command_matches(subcommand),
+        None => matches,
+    }
+}
+
+/// Apply the configured output format unless one was given on the command line
+///
+/// Configured custom columns and templates are skipped by commands that cannot show them.
+fn apply_output(settings: &Settings, output: &mut OutputFormat, given: bool, custom: bool) {
+    match &settings.output {
+        Some(configured) if !given && (custom || !configured.selects_fields()) => {
+            *output = configured.clone();
+        }
+        _ => {}
+    }
+}
+
+/// Apply the configured namespace of a command querying a single namespace
+fn apply_namespace(settings: &Settings, namespace: &mut String, all_namespaces: &mut bool) {
+    if let Some(first) = settings.namespaces.as_ref().and_then(|n| n.first()) {
+        *namespace = first.clone();
+    }
+    if let Some(all) = settings.all_namespaces {
+        *all_namespaces = all;
+    }
 }
@@ -1,8 +1,14 @@
-use crate::cli::formats::OutputFormat;
+use crate::cli::formats::{ImageGroupBy, OutputFormat, PinFormat};
+use crate::k8s::{ImageField, PullGroupBy, WorkloadKind};
+use crate::registry::MirrorRule;
+use crate::serve::parse_listen_addr;
 use clap::Subcommand;
-use std::path::PathBuf;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 
 /// CLI command structure for Kimspect
+// Parsed once per run, boxing the options would only make them harder to match on
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Get information about Kubernetes resources
@@ -11,6 +17,276 @@ pub enum Commands {
         #[command(subcommand)]
         resource: GetImages,
     },
+
+    /// Inspect the access of the current identity
+    Auth {
+        /// The check to run
+        #[command(subcommand)]
+        action: AuthCommands,
+    },
+
+    /// Browse pods and images interactively, refreshed live from the cluster
+    Tui {
+        /// Kubernetes namespace to browse (defaults to "default")
+        #[arg(
+            short,
+            long,
+            default_value = "default",
+            conflicts_with = "all_namespaces"
+        )]
+        namespace: String,
+
+        /// Browse pods across all namespaces
+        #[arg(short = 'A', long = "all-namespaces", conflicts_with = "namespace")]
+        all_namespaces: bool,
+
+        /// Start with a registry filter (matches part of the registry)
+        #[arg(short = 'R', long = "registry")]
+        registry: Option<String>,
+
+        /// Start with an image filter (matches part of the image name or version)
+        #[arg(short = 'i', long = "image")]
+        image: Option<String>,
+
+        /// Start with a node filter (matches part of the node name)
+        #[arg(short = 'N', long = "node")]
+        node: Option<String>,
+
+        /// Map a pull-through mirror prefix to its upstream origin (MIRROR=UPSTREAM, repeatable)
+        #[arg(long = "mirror", value_name = "MIRROR=UPSTREAM")]
+        mirror: Vec<MirrorRule>,
+
+        /// Path to kubeconfig file (default: ~/.kube/config)
+        #[arg(long = "kubeconfig")]
+        kubeconfig: Option<PathBuf>,
+    },
+
+    /// Run a server exporting the image inventory, kept up to date by watching the cluster
+    Serve {
+        /// Kubernetes namespace to watch (defaults to "default")
+        #[arg(
+            short,
+            long,
+            default_value = "default",
+            conflicts_with = "all_namespaces"
+        )]
+        namespace: String,
+
+        /// Watch pods across all namespaces
+        #[arg(short = 'A', long = "all-namespaces", conflicts_with = "namespace")]
+        all_namespaces: bool,
+
+        /// Address to serve Prometheus metrics (/metrics) on (e.g. :9090, 127.0.0.1:9090)
+        #[arg(
+            long = "metrics-addr",
+            value_name = "ADDR",
+            default_value = ":9090",
+            value_parser = parse_listen_addr
+        )]
+        metrics_addr: SocketAddr,
+
+        /// Address to serve the unauthenticated JSON inventory (/images, /registries) on, e.g.
+        /// 127.0.0.1:9091 (default: not served)
+        #[arg(long = "api-addr", value_name = "ADDR", value_parser = parse_listen_addr)]
+        api_addr: Option<SocketAddr>,
+
+        /// Map a pull-through mirror prefix to its upstream origin (MIRROR=UPSTREAM, repeatable)
+        #[arg(long = "mirror", value_name = "MIRROR=UPSTREAM")]
+        mirror: Vec<MirrorRule>,
+
+        /// Path to kubeconfig file (default: ~/.kube/config)
+        #[arg(long = "kubeconfig")]
+        kubeconfig: Option<PathBuf>,
+    },
+
+    /// Run a validating admission webhook checking images against a registry and tag policy
+    Webhook {
+        /// Address to serve the webhook on, at /validate (e.g. :8443, 127.0.0.1:8443)
+        #[arg(
+            long = "addr",
+            value_name = "ADDR",
+            default_value = ":8443",
+            value_parser = parse_listen_addr
+        )]
+        addr: SocketAddr,
+
+        /// PEM certificate chain to serve HTTPS with, as required by the API server
+        #[arg(
+            long = "tls-cert",
+            value_name = "FILE",
+            requires = "tls_key",
+            required_unless_present = "insecure"
+        )]
+        tls_cert: Option<PathBuf>,
+
+        /// PEM private key of the certificate
+        #[arg(long = "tls-key", value_name = "FILE", requires = "tls_cert")]
+        tls_key: Option<PathBuf>,
+
+        /// Serve plain HTTP without a certificate, e.g. behind a TLS-terminating proxy
+        #[arg(long = "insecure", conflicts_with_all = ["tls_cert", "tls_key"])]
+        insecure: bool,
+
+        /// Deny objects breaking the policy (default: dry-run, admitting them with warnings)
+        #[arg(long = "enforce")]
+        enforce: bool,
+
+        /// Disallow images from a registry host and its subdomains (repeatable)
+        #[arg(long = "disallow-registry", value_name = "HOST")]
+        disallow_registry: Vec<String>,
+
+        /// Disallow images from registries of a class, e.g. public (repeatable)
+        #[arg(long = "disallow-class", value_name = "CLASS")]
+        disallow_class: Vec<String>,
+
+        /// Reject images using the latest tag, explicitly or implicitly, unless pinned to a digest
+        #[arg(long = "deny-latest")]
+        deny_latest: bool,
+
+        /// Reject images that are not pinned to a digest
+        #[arg(long = "require-digest")]
+        require_digest: bool,
+    },
+
+    /// Print patches pinning workload images to the digests their pods run (never applied)
+    Pin {
+        /// Only pin this workload, as KIND/NAME (e.g. deployment/web, sts/db)
+        #[arg(value_name = "KIND/NAME")]
+        workload: Option<String>,
+
+        /// Kubernetes namespace to pin workloads in (defaults to "default")
+        #[arg(
+            short,
+            long,
+            default_value = "default",
+            conflicts_with = "all_namespaces"
+        )]
+        namespace: String,
+
+        /// Pin workloads across all namespaces
+        #[arg(short = 'A', long = "all-namespaces", conflicts_with = "namespace")]
+        all_namespaces: bool,
+
+        /// Format of the pins (kubectl: patch commands, patch: strategic merge patch documents,
+        /// kustomize: entries of a kustomization's images list)
+        #[arg(long = "format", default_value = "kubectl")]
+        format: PinFormat,
+
+        /// Path to kubeconfig file (default: ~/.kube/config)
+        #[arg(long = "kubeconfig")]
+        kubeconfig: Option<PathBuf>,
+    },
+
+    /// Package kimspect as a kubectl plugin
+    Plugin {
+        /// The packaging task to run
+        #[command(subcommand)]
+        action: PluginCommands,
+    },
+}
+
+impl Commands {
+    /// Check if this command needs a connection to the Kubernetes cluster
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - False when the command reads its input from manifest files
+    pub fn needs_cluster(&self) -> bool {
+        match self {
+            Commands::Get { resource } => resource.get_from_file().is_none(),
+            Commands::Auth { .. }
+            | Commands::Tui { .. }
+            | Commands::Serve { .. }
+            | Commands::Pin { .. } => true,
+            Commands::Webhook { .. } | Commands::Plugin { .. } => false,
+        }
+    }
+
+    /// Check that the output format is supported by this command
+    ///
+    /// Custom columns and templates select image fields, so only the `get images`
+    /// listing has them.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - An error describing the unsupported combination
+    pub fn check_output(&self) -> Result<(), String> {
+        let (output, command) = match self {
+            Commands::Get {
+                resource:
+                    GetImages::Images {
+                        output, group_by, ..
+                    },
+            } => match group_by {
+                Some(group_by) => (output, format!("get images --group-by {}", group_by)),
+                None => return Ok(()),
+            },
+            Commands::Get { resource } => (resource.get_output(), resource.name().to_string()),
+            Commands::Auth {
+                action: AuthCommands::Check { output, .. },
+            } => (output, "auth check".to_string()),
+            Commands::Tui { .. }
+            | Commands::Serve { .. }
+            | Commands::Webhook { .. }
+            | Commands::Pin { .. }
+            | Commands::Plugin { .. } => {
+                return Ok(());
+            }
+        };
+
+        if output.selects_fields() {
+            return Err(format!(
+                "Output format {} is only supported by get images, not by {}",
+                output.name(),
+                command
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Access checks for the current identity
+#[derive(Subcommand, Debug)]
+pub enum AuthCommands {
+    /// Check which commands will work with the permissions of the current identity
+    Check {
+        /// Kubernetes namespace to check namespaced permissions in (defaults to "default")
+        #[arg(
+            short,
+            long,
+            default_value = "default",
+            conflicts_with = "all_namespaces"
+        )]
+        namespace: String,
+
+        /// Check namespaced permissions across all namespaces
+        #[arg(short = 'A', long = "all-namespaces", conflicts_with = "namespace")]
+        all_namespaces: bool,
+
+        /// Output format (default: normal, wide: shows additional columns)
+        #[arg(short = 'o', long = "output", default_value = "normal")]
+        output: OutputFormat,
+
+        /// Path to kubeconfig file (default: ~/.kube/config)
+        #[arg(long = "kubeconfig")]
+        kubeconfig: Option<PathBuf>,
+    },
+}
+
+/// Tasks for distributing kimspect as a kubectl plugin
+#[derive(Subcommand, Debug)]
+pub enum PluginCommands {
+    /// Print the krew plugin manifest of a release
+    Manifest {
+        /// Released version (default: the version of this binary)
+        #[arg(long = "version", value_name = "VERSION")]
+        version: Option<String>,
+
+        /// SHA-256 checksums of the release archives, as written by sha256sum (checksums.txt of
+        /// each release)
+        #[arg(long = "checksums", value_name = "PATH")]
+        checksums: PathBuf,
+    },
 }
 
 /// Resource types that can be queried in the Kubernetes cluster
@@ -18,14 +294,28 @@ pub enum Commands {
 pub enum GetImages {
     /// List pod images and their registries
     Images {
-        /// Kubernetes namespace to query (defaults to "default", ignored when --node is specified)
+        /// Kubernetes namespaces to query, comma-separated (defaults to "default", ignored when
+        /// --node is specified)
         #[arg(
             short,
             long,
             default_value = "default",
+            value_delimiter = ',',
             conflicts_with = "all_namespaces"
         )]
-        namespace: String,
+        namespace: Vec<String>,
+
+        /// Query the namespaces matching a label selector (e.g. team=payments)
+        #[arg(
+            long = "namespace-selector",
+            value_name = "SELECTOR",
+            conflicts_with_all = ["namespace", "all_namespaces"]
+        )]
+        namespace_selector: Option<String>,
+
+        /// Leave out a namespace, also with --all-namespaces (comma-separated, repeatable)
+        #[arg(long = "exclude-namespace", value_delimiter = ',')]
+        exclude_namespace: Vec<String>,
 
         /// Filter pods by node name
         #[arg(short = 'N', long = "node", conflicts_with = "all_namespaces")]
@@ -47,10 +337,46 @@ pub enum GetImages {
         #[arg(short = 'A', long = "all-namespaces", conflicts_with = "namespace")]
         all_namespaces: bool,
 
-        /// Output format (default: normal, wide: shows additional columns)
+        /// Also list the images of CronJob and recent Job pod templates, with status Scheduled
+        #[arg(long = "include-scheduled", conflicts_with = "from_file")]
+        include_scheduled: bool,
+
+        /// Map a pull-through mirror prefix to its upstream origin (MIRROR=UPSTREAM, repeatable)
+        #[arg(long = "mirror", value_name = "MIRROR=UPSTREAM")]
+        mirror: Vec<MirrorRule>,
+
+        /// Group images instead of listing every container
+        #[arg(long = "group-by")]
+        group_by: Option<ImageGroupBy>,
+
+        /// Read pods and workloads from a YAML/JSON file, a directory of manifests or stdin (-)
+        /// instead of the cluster
+        #[arg(long = "from-file", value_name = "PATH")]
+        from_file: Option<PathBuf>,
+
+        /// Output format (default: normal, wide: shows additional columns,
+        /// custom-columns=HEADER:field,...: shows the given fields under the given headers,
+        /// jsonpath=TEMPLATE or go-template=TEMPLATE: renders a template over the image records)
         #[arg(short = 'o', long = "output", default_value = "normal")]
         output: OutputFormat,
 
+        /// Show only these fields, in order (comma-separated, e.g. namespace,pod,image,digest)
+        #[arg(
+            long = "columns",
+            value_name = "FIELDS",
+            value_delimiter = ',',
+            conflicts_with_all = ["output", "group_by"]
+        )]
+        columns: Vec<ImageField>,
+
+        /// Sort images on a field (e.g. namespace, registry, size; size sorts largest first)
+        #[arg(long = "sort-by", value_name = "FIELD", conflicts_with = "group_by")]
+        sort_by: Option<ImageField>,
+
+        /// Leave out the header row
+        #[arg(long = "no-headers")]
+        no_headers: bool,
+
         /// Path to kubeconfig file (default: ~/.kube/config)
         #[arg(long = "kubeconfig")]
         kubeconfig: Option<PathBuf>,
@@ -71,6 +397,151 @@ pub enum GetImages {
         #[arg(short = 'A', long = "all-namespaces", conflicts_with = "namespace")]
         all_namespaces: bool,
 
+        /// Also list the registries of CronJob and recent Job pod templates, marked as scheduled
+        #[arg(long = "include-scheduled")]
+        include_scheduled: bool,
+
+        /// Map a pull-through mirror prefix to its upstream origin (MIRROR=UPSTREAM, repeatable)
+        #[arg(long = "mirror", value_name = "MIRROR=UPSTREAM")]
+        mirror: Vec<MirrorRule>,
+
+        /// Output format (default: normal, wide: shows additional columns)
+        #[arg(short = 'o', long = "output", default_value = "normal")]
+        output: OutputFormat,
+
+        /// Path to kubeconfig file (default: ~/.kube/config)
+        #[arg(long = "kubeconfig")]
+        kubeconfig: Option<PathBuf>,
+    },
+
+    /// List containers whose images cannot be pulled (ImagePullBackOff, ErrImagePull, ...)
+    ImageErrors {
+        /// Kubernetes namespace to query (defaults to "default")
+        #[arg(
+            short,
+            long,
+            default_value = "default",
+            conflicts_with = "all_namespaces"
+        )]
+        namespace: String,
+
+        /// Query pods across all namespaces
+        #[arg(short = 'A', long = "all-namespaces", conflicts_with = "namespace")]
+        all_namespaces: bool,
+
+        /// Output format (default: normal, wide: shows additional columns)
+        #[arg(short = 'o', long = "output", default_value = "normal")]
+        output: OutputFormat,
+
+        /// Path to kubeconfig file (default: ~/.kube/config)
+        #[arg(long = "kubeconfig")]
+        kubeconfig: Option<PathBuf>,
+    },
+
+    /// Report image pull durations from recent kubelet events, slowest first
+    PullTimes {
+        /// Kubernetes namespace to query (defaults to "default")
+        #[arg(
+            short,
+            long,
+            default_value = "default",
+            conflicts_with = "all_namespaces"
+        )]
+        namespace: String,
+
+        /// Query pods across all namespaces
+        #[arg(short = 'A', long = "all-namespaces", conflicts_with = "namespace")]
+        all_namespaces: bool,
+
+        /// Aggregate pull durations per image or per node
+        #[arg(long = "group-by")]
+        group_by: Option<PullGroupBy>,
+
+        /// Output format (default: normal, wide: shows additional columns)
+        #[arg(short = 'o', long = "output", default_value = "normal")]
+        output: OutputFormat,
+
+        /// Path to kubeconfig file (default: ~/.kube/config)
+        #[arg(long = "kubeconfig")]
+        kubeconfig: Option<PathBuf>,
+    },
+
+    /// List nodes with their container runtime, kubelet version, OS image and architecture
+    Nodes {
+        /// Filter by node name
+        #[arg(short = 'N', long = "node")]
+        node: Option<String>,
+
+        /// Output format (default: normal, wide: shows additional columns)
+        #[arg(short = 'o', long = "output", default_value = "normal")]
+        output: OutputFormat,
+
+        /// Path to kubeconfig file (default: ~/.kube/config)
+        #[arg(long = "kubeconfig")]
+        kubeconfig: Option<PathBuf>,
+    },
+
+    /// List containers whose image has no platform matching the architecture of their node
+    ArchMismatches {
+        /// Kubernetes namespace to query (defaults to "default")
+        #[arg(
+            short,
+            long,
+            default_value = "default",
+            conflicts_with = "all_namespaces"
+        )]
+        namespace: String,
+
+        /// Query pods across all namespaces
+        #[arg(short = 'A', long = "all-namespaces", conflicts_with = "namespace")]
+        all_namespaces: bool,
+
+        /// Output format (default: normal, wide: shows additional columns)
+        #[arg(short = 'o', long = "output", default_value = "normal")]
+        output: OutputFormat,
+
+        /// Path to kubeconfig file (default: ~/.kube/config)
+        #[arg(long = "kubeconfig")]
+        kubeconfig: Option<PathBuf>,
+    },
+
+    /// List containers whose image differs from their workload's pod template or from the image
+    /// they run
+    ImageMismatches {
+        /// Kubernetes namespace to query (defaults to "default")
+        #[arg(
+            short,
+            long,
+            default_value = "default",
+            conflicts_with = "all_namespaces"
+        )]
+        namespace: String,
+
+        /// Query pods across all namespaces
+        #[arg(short = 'A', long = "all-namespaces", conflicts_with = "namespace")]
+        all_namespaces: bool,
+
+        /// Output format (default: normal, wide: shows additional columns)
+        #[arg(short = 'o', long = "output", default_value = "normal")]
+        output: OutputFormat,
+
+        /// Path to kubeconfig file (default: ~/.kube/config)
+        #[arg(long = "kubeconfig")]
+        kubeconfig: Option<PathBuf>,
+    },
+
+    /// Show the image versions the pods of a workload run while it rolls out
+    RolloutImages {
+        /// Kind of the workload (deployment, statefulset or daemonset)
+        kind: WorkloadKind,
+
+        /// Name of the workload
+        name: String,
+
+        /// Kubernetes namespace of the workload (defaults to "default")
+        #[arg(short, long, default_value = "default")]
+        namespace: String,
+
         /// Output format (default: normal, wide: shows additional columns)
         #[arg(short = 'o', long = "output", default_value = "normal")]
         output: OutputFormat,
@@ -89,9 +560,32 @@ impl GetImages {
     /// * `Option<PathBuf>` - The path to the kubeconfig file if specified
     pub fn get_kubeconfig_path(&self) -> Option<PathBuf> {
         match self {
-            GetImages::Images { kubeconfig, .. } | GetImages::Registries { kubeconfig, .. } => {
-                kubeconfig.clone()
-            }
+            GetImages::Images { kubeconfig, .. }
+            | GetImages::Registries { kubeconfig, .. }
+            | GetImages::ImageErrors { kubeconfig, .. }
+            | GetImages::PullTimes { kubeconfig, .. }
+            | GetImages::Nodes { kubeconfig, .. }
+            | GetImages::ArchMismatches { kubeconfig, .. }
+            | GetImages::ImageMismatches { kubeconfig, .. }
+            | GetImages::RolloutImages { kubeconfig, .. } => kubeconfig.clone(),
+        }
+    }
+
+    /// Get the manifest path to read instead of the cluster
+    ///
+    /// # Returns
+    ///
+    /// * `Option<&Path>` - The file, directory or `-` for stdin if offline mode is requested
+    pub fn get_from_file(&self) -> Option<&Path> {
+        match self {
+            GetImages::Images { from_file, .. } => from_file.as_deref(),
+            GetImages::Registries { .. }
+            | GetImages::ImageErrors { .. }
+            | GetImages::PullTimes { .. }
+            | GetImages::Nodes { .. }
+            | GetImages::ArchMismatches { .. }
+            | GetImages::ImageMismatches { .. }
+            | GetImages::RolloutImages { .. } => None,
         }
     }
 
@@ -99,12 +593,20 @@ impl GetImages {
     ///
     /// # Returns
     ///
-    /// * `&str` - The namespace to query
+    /// * `&str` - The namespace to query, the first one when several are given (empty for
+    ///   cluster-scoped resources)
     pub fn get_namespace(&self) -> &str {
         match self {
-            GetImages::Images { namespace, .. } | GetImages::Registries { namespace, .. } => {
-                namespace
+            GetImages::Images { namespace, .. } => {
+                namespace.first().map(String::as_str).unwrap_or_default()
             }
+            GetImages::Registries { namespace, .. }
+            | GetImages::ImageErrors { namespace, .. }
+            | GetImages::PullTimes { namespace, .. }
+            | GetImages::ArchMismatches { namespace, .. }
+            | GetImages::ImageMismatches { namespace, .. }
+            | GetImages::RolloutImages { namespace, .. } => namespace,
+            GetImages::Nodes { .. } => "",
         }
     }
 
@@ -112,11 +614,53 @@ impl GetImages {
     ///
     /// # Returns
     ///
-    /// * `bool` - True if all namespaces should be queried
+    /// * `bool` - True if all namespaces should be queried (always true for cluster-scoped resources)
     pub fn is_all_namespaces(&self) -> bool {
         match self {
             GetImages::Images { all_namespaces, .. }
-            | GetImages::Registries { all_namespaces, .. } => *all_namespaces,
+            | GetImages::Registries { all_namespaces, .. }
+            | GetImages::ImageErrors { all_namespaces, .. }
+            | GetImages::PullTimes { all_namespaces, .. }
+            | GetImages::ArchMismatches { all_namespaces, .. }
+            | GetImages::ImageMismatches { all_namespaces, .. } => *all_namespaces,
+            GetImages::Nodes { .. } => true,
+            GetImages::RolloutImages { .. } => false,
+        }
+    }
+
+    /// Get the output format for this command
+    ///
+    /// # Returns
+    ///
+    /// * `&OutputFormat` - The output format to display results with
+    pub fn get_output(&self) -> &OutputFormat {
+        match self {
+            GetImages::Images { output, .. }
+            | GetImages::Registries { output, .. }
+            | GetImages::ImageErrors { output, .. }
+            | GetImages::PullTimes { output, .. }
+            | GetImages::Nodes { output, .. }
+            | GetImages::ArchMismatches { output, .. }
+            | GetImages::ImageMismatches { output, .. }
+            | GetImages::RolloutImages { output, .. } => output,
+        }
+    }
+
+    /// Get the command line of this command
+    ///
+    /// # Returns
+    ///
+    /// * `&'static str` - The command as typed (e.g. get images)
+    pub fn name(&self) -> &'static str {
+        match self {
+            GetImages::Images { .. } => "get images",
+            GetImages::Registries { .. } => "get registries",
+            GetImages::ImageErrors { .. } => "get image-errors",
+            GetImages::PullTimes { .. } => "get pull-times",
+            GetImages::Nodes { .. } => "get nodes",
+            GetImages::ArchMismatches { .. } => "get arch-mismatches",
+            GetImages::ImageMismatches { .. } => "get image-mismatches",
+            GetImages::RolloutImages { .. } => "get rollout-images",
         }
     }
 }
@@ -1,5 +1,10 @@
+use crate::k8s::{ImageField, PullGroupBy};
+use crate::utils::{GoTemplate, JsonPath};
 use clap::ValueEnum;
+use clap::builder::PossibleValue;
+use serde::Deserialize;
 use std::fmt;
+use std::str::FromStr;
 
 /// Logging format options for Kimspect
 #[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
@@ -20,12 +25,19 @@ impl fmt::Display for LogFormat {
 }
 
 /// Output format options for displaying Kubernetes resource data
-#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
 pub enum OutputFormat {
     /// Standard output format with essential columns
     Normal,
     /// Extended output format with additional columns
     Wide,
+    /// Selected image fields under custom headers (custom-columns=HEADER:field,...)
+    CustomColumns(Vec<CustomColumn>),
+    /// A kubectl JSONPath template over the image records (jsonpath=...)
+    JsonPath(String),
+    /// A Go template over the image records (go-template=...)
+    GoTemplate(String),
 }
 
 impl fmt::Display for OutputFormat {
@@ -33,10 +45,61 @@ impl fmt::Display for OutputFormat {
         match self {
             OutputFormat::Normal => write!(f, "normal"),
             OutputFormat::Wide => write!(f, "wide"),
+            OutputFormat::CustomColumns(columns) => {
+                let specs: Vec<String> = columns.iter().map(ToString::to_string).collect();
+                write!(f, "custom-columns={}", specs.join(","))
+            }
+            OutputFormat::JsonPath(template) => write!(f, "jsonpath={}", template),
+            OutputFormat::GoTemplate(template) => write!(f, "go-template={}", template),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(specs) = s.strip_prefix("custom-columns=") {
+            let columns = specs
+                .split(',')
+                .filter(|spec| !spec.trim().is_empty())
+                .map(str::parse)
+                .collect::<Result<Vec<CustomColumn>, _>>()?;
+            if columns.is_empty() {
+                return Err("custom-columns needs at least one HEADER:field column".to_string());
+            }
+            return Ok(OutputFormat::CustomColumns(columns));
+        }
+        // Templates are checked now so syntax errors are reported before querying the cluster
+        if let Some(template) = s.strip_prefix("jsonpath=") {
+            JsonPath::parse(template).map_err(|e| e.to_string())?;
+            return Ok(OutputFormat::JsonPath(template.to_string()));
+        }
+        if let Some(template) = s.strip_prefix("go-template=") {
+            GoTemplate::parse(template).map_err(|e| e.to_string())?;
+            return Ok(OutputFormat::GoTemplate(template.to_string()));
+        }
+
+        match s.to_lowercase().as_str() {
+            "normal" => Ok(OutputFormat::Normal),
+            "wide" => Ok(OutputFormat::Wide),
+            _ => Err(format!(
+                "Invalid output format '{}', expected normal, wide, custom-columns=HEADER:field,..., \
+                 jsonpath=TEMPLATE or go-template=TEMPLATE",
+                s
+            )),
         }
     }
 }
 
+impl TryFrom<String> for OutputFormat {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
 impl OutputFormat {
     /// Check if this format includes registry information
     ///
@@ -64,4 +127,136 @@ impl OutputFormat {
     pub fn includes_node(&self) -> bool {
         matches!(self, OutputFormat::Wide)
     }
+
+    /// Get the name of this format as given before `=` on the command line
+    ///
+    /// # Returns
+    ///
+    /// * `&'static str` - The name (e.g. wide, jsonpath)
+    pub fn name(&self) -> &'static str {
+        match self {
+            OutputFormat::Normal => "normal",
+            OutputFormat::Wide => "wide",
+            OutputFormat::CustomColumns(_) => "custom-columns",
+            OutputFormat::JsonPath(_) => "jsonpath",
+            OutputFormat::GoTemplate(_) => "go-template",
+        }
+    }
+
+    /// Check if this format selects image fields, which only `get images` has
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True for custom columns and templates
+    pub fn selects_fields(&self) -> bool {
+        !matches!(self, OutputFormat::Normal | OutputFormat::Wide)
+    }
+
+    /// Check if this format renders a template instead of a table
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True for JSONPath and Go templates
+    pub fn is_template(&self) -> bool {
+        matches!(
+            self,
+            OutputFormat::JsonPath(_) | OutputFormat::GoTemplate(_)
+        )
+    }
+}
+
+/// A column of custom-columns output: a header and the image field shown under it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomColumn {
+    /// Header of the column
+    pub header: String,
+    /// Image field shown in the column
+    pub field: ImageField,
+}
+
+impl From<ImageField> for CustomColumn {
+    fn from(field: ImageField) -> Self {
+        CustomColumn {
+            header: field.header(),
+            field,
+        }
+    }
+}
+
+impl fmt::Display for CustomColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.header, self.field)
+    }
+}
+
+impl FromStr for CustomColumn {
+    type Err = String;
+
+    /// Parse a HEADER:field column, or a bare field shown under its default header
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some((header, field)) if !header.trim().is_empty() => Ok(CustomColumn {
+                header: header.trim().to_string(),
+                field: field.parse()?,
+            }),
+            Some(_) => Err(format!("Missing header in column '{}'", s)),
+            None => s.parse::<ImageField>().map(CustomColumn::from),
+        }
+    }
+}
+
+impl ValueEnum for PullGroupBy {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[PullGroupBy::Image, PullGroupBy::Node]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            PullGroupBy::Image => {
+                PossibleValue::new("image").help("Aggregate pull durations per image")
+            }
+            PullGroupBy::Node => {
+                PossibleValue::new("node").help("Aggregate pull durations per node")
+            }
+        })
+    }
+}
+
+/// Grouping options for the images view
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum ImageGroupBy {
+    /// Group images by their upstream origin, resolving pull-through mirrors
+    Upstream,
+    /// Group images by the Helm release (or other managing tool instance) that deployed them
+    Release,
+}
+
+impl fmt::Display for ImageGroupBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageGroupBy::Upstream => write!(f, "upstream"),
+            ImageGroupBy::Release => write!(f, "release"),
+        }
+    }
+}
+
+/// Formats of the digest pins printed by `kimspect pin`
+#[derive(Debug, Clone, Copy, ValueEnum, PartialEq, Eq)]
+pub enum PinFormat {
+    /// `kubectl patch` commands with a strategic merge patch per workload
+    Kubectl,
+    /// Strategic merge patch documents naming their workload, for `--patch-file` or kustomize
+    Patch,
+    /// Entries of a kustomization's `images` list
+    Kustomize,
+}
+
+impl fmt::Display for PinFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PinFormat::Kubectl => write!(f, "kubectl"),
+            PinFormat::Patch => write!(f, "patch"),
+            PinFormat::Kustomize => write!(f, "kustomize"),
+        }
+    }
 }
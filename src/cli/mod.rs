@@ -1,7 +1,9 @@
 mod args;
 mod commands;
 mod formats;
+mod plugin;
 
 pub use args::Args;
-pub use commands::{Commands, GetImages};
-pub use formats::{LogFormat, OutputFormat};
+pub use commands::{AuthCommands, Commands, GetImages, PluginCommands};
+pub use formats::{CustomColumn, ImageGroupBy, LogFormat, OutputFormat, PinFormat};
+pub use plugin::{PLUGIN_PREFIX, krew_manifest, parse_checksums, plugin_args, plugin_name};
@@ -0,0 +1,235 @@
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::{OsStr, OsString};
+use std::path::Path;
+
+/// Prefix of the executables kubectl runs as plugins
+pub const PLUGIN_PREFIX: &str = "kubectl-";
+
+/// Repository releases are downloaded from
+const RELEASES_URL: &str = "https://github.com/koithos/kimspect/releases/download";
+
+/// A platform release archives are built for, see .github/workflows/release.yml
+struct ReleasePlatform {
+    /// Operating system as krew names it
+    os: &'static str,
+    /// Architecture as krew names it
+    arch: &'static str,
+    /// Rust target triple the archive is named after
+    target: &'static str,
+    /// Archive extension
+    archive: &'static str,
+    /// Binary inside the archive
+    bin: &'static str,
+}
+
+/// Platforms with release archives
+const RELEASE_PLATFORMS: [ReleasePlatform; 4] = [
+    ReleasePlatform {
+        os: "linux",
+        arch: "amd64",
+        target: "x86_64-unknown-linux-musl",
+        archive: "tar.gz",
+        bin: "kimspect",
+    },
+    ReleasePlatform {
+        os: "darwin",
+        arch: "amd64",
+        target: "x86_64-apple-darwin",
+        archive: "tar.gz",
+        bin: "kimspect",
+    },
+    ReleasePlatform {
+        os: "darwin",
+        arch: "arm64",
+        target: "aarch64-apple-darwin",
+        archive: "tar.gz",
+        bin: "kimspect",
+    },
+    ReleasePlatform {
+        os: "windows",
+        arch: "amd64",
+        target: "x86_64-pc-windows-msvc",
+        archive: "zip",
+        bin: "kimspect.exe",
+    },
+];
+
+/// Get the kubectl plugin name the binary was invoked as
+///
+/// kubectl runs `kubectl foo-bar` as the executable `kubectl-foo_bar`.
+///
+/// # Arguments
+///
+/// * `argv0` - The first command line argument, the path the binary was run as
+///
+/// # Returns
+///
+/// * `Option<String>` - The plugin command (e.g. kimspect, images), None outside kubectl
+pub fn plugin_name(argv0: &OsStr) -> Option<String> {
+    let file_name = Path::new(argv0).file_name()?.to_str()?;
+    let file_name = file_name.strip_suffix(".exe").unwrap_or(file_name);
+    file_name
+        .strip_prefix(PLUGIN_PREFIX)
+        .filter(|name| !name.is_empty())
+        .map(|name| name.replace('_', "-"))
+}
+
+/// Rewrite the command line of a kubectl plugin invocation
+///
+/// `kubectl images` is a shortcut for `kubectl kimspect get images`, and the
+/// namespace flags kubectl users put before the command are moved after it,
+/// where the commands take them.
+///
+/// # Arguments
+///
+/// * `name` - The plugin command, from [`plugin_name`]
+/// * `args` - The command line, starting with the binary
+///
+/// # Returns
+///
+/// * `Vec<OsString>` - The command line to parse
+pub fn plugin_args(name: &str, args: Vec<OsString>) -> Vec<OsString> {
+    let mut args = args.into_iter();
+    let mut rewritten: Vec<OsString> = args.next().into_iter().collect();
+    if name == "images" {
+        rewritten.extend(["get".into(), "images".into()]);
+    }
+
+    let mut namespace = Vec::new();
+    while let Some(arg) = args.next() {
+        let text = arg.to_string_lossy();
+        if text == "--" {
+            rewritten.push(arg);
+            rewritten.extend(args.by_ref());
+            break;
+        }
+        if text == "-n" || text == "--namespace" {
+            namespace.push(arg);
+            namespace.extend(args.next());
+        } else if text.starts_with("--namespace=") || (text.starts_with("-n") && text.len() > 2) {
+            namespace.push(arg);
+        } else {
+            rewritten.push(arg);
+        }
+    }
+
+    // Flags after a `--` separator would be taken as values
+    match rewritten.iter().position(|arg| arg == "--") {
+        Some(separator) => {
+            rewritten.splice(separator..separator, namespace);
+        }
+        None => rewritten.extend(namespace),
+    }
+    rewritten
+}
+
+/// A krew plugin manifest
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct KrewManifest {
+    api_version: &'static str,
+    kind: &'static str,
+    metadata: KrewMetadata,
+    spec: KrewSpec,
+}
+
+#[derive(Debug, Serialize)]
+struct KrewMetadata {
+    name: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct KrewSpec {
+    version: String,
+    homepage: &'static str,
+    short_description: &'static str,
+    description: &'static str,
+    platforms: Vec<KrewPlatform>,
+}
+
+#[derive(Debug, Serialize)]
+struct KrewPlatform {
+    selector: KrewSelector,
+    uri: String,
+    sha256: String,
+    bin: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct KrewSelector {
+    match_labels: BTreeMap<&'static str, &'static str>,
+}
+
+/// Parse a checksum file in the format written by `sha256sum`
+///
+/// # Arguments
+///
+/// * `content` - Lines of `<sha256>  <file>`, the file optionally prefixed by `*`
+///
+/// # Returns
+///
+/// * `HashMap<String, String>` - Checksums by file name
+pub fn parse_checksums(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (sha256, file) = line.trim().split_once(char::is_whitespace)?;
+            let file = file.trim_start().trim_start_matches('*');
+            let file = Path::new(file).file_name()?.to_str()?;
+            Some((file.to_string(), sha256.to_lowercase()))
+        })
+        .collect()
+}
+
+/// Generate the krew plugin manifest of a release
+///
+/// # Arguments
+///
+/// * `version` - The released version, with or without leading `v`
+/// * `checksums` - SHA-256 checksums of the release archives by file name
+///
+/// # Returns
+///
+/// * `Result<String>` - The manifest as YAML, or an error if an archive has no checksum
+pub fn krew_manifest(version: &str, checksums: &HashMap<String, String>) -> Result<String> {
+    let tag = format!("v{}", version.trim_start_matches('v'));
+
+    let platforms = RELEASE_PLATFORMS
+        .iter()
+        .map(|platform| {
+            let asset = format!("kimspect-{}.{}", platform.target, platform.archive);
+            let Some(sha256) = checksums.get(&asset) else {
+                bail!("No checksum for {} in the checksum file", asset);
+            };
+            Ok(KrewPlatform {
+                selector: KrewSelector {
+                    match_labels: BTreeMap::from([("os", platform.os), ("arch", platform.arch)]),
+                },
+                uri: format!("{}/{}/{}", RELEASES_URL, tag, asset),
+                sha256: sha256.clone(),
+                bin: platform.bin,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let manifest = KrewManifest {
+        api_version: "krew.googlecontainertools.github.com/v1alpha2",
+        kind: "Plugin",
+        metadata: KrewMetadata { name: "kimspect" },
+        spec: KrewSpec {
+            version: tag,
+            homepage: "https://github.com/koithos/kimspect",
+            short_description: "Inspect container images on your pods and nodes.",
+            description: "kimspect is a kubernetes container image inspection tool that provides\n\
+                          comprehensive visibility into container images running inside your cluster\n\
+                          and can get container image & registry information by pods, namespaces and nodes\n\
+                          in your cluster\n",
+            platforms,
+        },
+    };
+    serde_yaml::to_string(&manifest).context("Failed to serialize krew manifest")
+}
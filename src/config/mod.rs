@@ -0,0 +1,257 @@
+//! Persistent defaults and named profiles read from `~/.config/kimspect/config.toml`
+
+use crate::cli::OutputFormat;
+use crate::k8s::ImageField;
+use crate::registry::{MirrorRule, RegistryRule};
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+/// Environment variable overriding the path of the config file
+pub const CONFIG_ENV: &str = "KIMSPECT_CONFIG";
+
+/// Options that can be set in the config file, as defaults or in a profile
+///
+/// Options given on the command line always take precedence.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+#[non_exhaustive]
+pub struct Settings {
+    /// Output format of every command
+    pub output: Option<OutputFormat>,
+    /// Namespaces to query (commands taking a single namespace use the first one)
+    pub namespaces: Option<Vec<String>>,
+    /// Whether to query all namespaces
+    pub all_namespaces: Option<bool>,
+    /// Registries left out of `get images`
+    pub exclude_registries: Option<Vec<String>>,
+    /// Namespaces left out of `get images`
+    pub exclude_namespaces: Option<Vec<String>>,
+    /// Fields shown by `get images`, in order (like `--columns`)
+    pub columns: Option<Vec<ImageField>>,
+    /// Field `get images` sorts on (like `--sort-by`)
+    pub sort_by: Option<ImageField>,
+    /// Registry classes by host (e.g. "registry.corp" = "internal")
+    pub registries: BTreeMap<String, String>,
+    /// Upstream origins by mirror prefix (e.g. "mirror.corp/dockerhub" = "docker.io")
+    pub mirrors: BTreeMap<String, String>,
+    /// Registries `webhook` disallows images from
+    pub disallowed_registries: Option<Vec<String>>,
+    /// Registry classes `webhook` disallows images from
+    pub disallowed_classes: Option<Vec<String>>,
+    /// Whether `webhook` rejects the `latest` tag
+    pub deny_latest: Option<bool>,
+    /// Whether `webhook` requires images pinned to a digest
+    pub require_digest: Option<bool>,
+}
+
+impl Settings {
+    /// Override these settings with the ones set in a profile
+    ///
+    /// Lists set in the profile replace the defaults; registry and mirror rules
+    /// are combined, the profile winning for the same host or prefix. Namespaces
+    /// and all-namespaces are taken together from the profile if it sets either,
+    /// so a profile's namespaces are not widened by a default `all-namespaces`.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - The profile settings
+    ///
+    /// # Returns
+    ///
+    /// * `Settings` - The combined settings
+    pub fn merge(self, profile: Settings) -> Settings {
+        let mut registries = self.registries;
+        registries.extend(profile.registries);
+        let mut mirrors = self.mirrors;
+        mirrors.extend(profile.mirrors);
+        let (namespaces, all_namespaces) =
+            if profile.namespaces.is_some() || profile.all_namespaces.is_some() {
+                (profile.namespaces, profile.all_namespaces)
+            } else {
+                (self.namespaces, self.all_namespaces)
+            };
+
+        Settings {
+            output: profile.output.or(self.output),
+            namespaces,
+            all_namespaces,
+            exclude_registries: profile.exclude_registries.or(self.exclude_registries),
+            exclude_namespaces: profile.exclude_namespaces.or(self.exclude_namespaces),
+            columns: profile.columns.or(self.columns),
+            sort_by: profile.sort_by.or(self.sort_by),
+            registries,
+            mirrors,
+            disallowed_registries: profile.disallowed_registries.or(self.disallowed_registries),
+            disallowed_classes: profile.disallowed_classes.or(self.disallowed_classes),
+            deny_latest: profile.deny_latest.or(self.deny_latest),
+            require_digest: profile.require_digest.or(self.require_digest),
+        }
+    }
+
+    /// Get the registry classification rules
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<RegistryRule>>` - The rules or an error for an invalid host or class
+    pub fn registry_rules(&self) -> Result<Vec<RegistryRule>> {
+        self.registries
+            .iter()
+            .map(|(host, class)| {
+                format!("{}={}", host, class)
+                    .parse::<RegistryRule>()
+                    .map_err(anyhow::Error::msg)
+            })
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to parse registries in config file")
+    }
+
+    /// Get the mirror rules
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<MirrorRule>>` - The rules or an error for an empty mirror or upstream
+    pub fn mirror_rules(&self) -> Result<Vec<MirrorRule>> {
+        self.mirrors
+            .iter()
+            .map(|(mirror, upstream)| {
+                format!("{}={}", mirror, upstream)
+                    .parse::<MirrorRule>()
+                    .map_err(anyhow::Error::msg)
+            })
+            .collect::<Result<Vec<_>>>()
+            .context("Failed to parse mirrors in config file")
+    }
+}
+
+/// Contents of the config file
+///
+/// ```toml
+/// [defaults]
+/// exclude-registries = ["registry.k8s.io"]
+///
+/// [defaults.registries]
+/// "registry.corp" = "internal"
+///
+/// [profiles.audit]
+/// all-namespaces = true
+/// output = "wide"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct Config {
+    /// Settings applied to every command
+    pub defaults: Settings,
+    /// Named settings selected with `--profile`
+    pub profiles: BTreeMap<String, Settings>,
+}
+
+impl Config {
+    /// Parse a config file
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The TOML content of the config file
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Config>` - The parsed config or an error for invalid TOML or unknown options
+    pub fn parse(content: &str) -> Result<Config> {
+        toml::from_str(content).context("Failed to parse config file")
+    }
+
+    /// Get the default path of the config file
+    ///
+    /// # Returns
+    ///
+    /// * `Option<PathBuf>` - `$KIMSPECT_CONFIG`, else `$XDG_CONFIG_HOME/kimspect/config.toml`
+    ///   or `~/.config/kimspect/config.toml`, or None if no home directory is known
+    pub fn default_path() -> Option<PathBuf> {
+        if let Ok(path) = std::env::var(CONFIG_ENV) {
+            return Some(PathBuf::from(path));
+        }
+
+        let config_home = std::env::var("XDG_CONFIG_HOME")
+            .ok()
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var("HOME")
+                    .ok()
+                    .map(|home| Path::new(&home).join(".config"))
+            })?;
+        Some(config_home.join("kimspect").join("config.toml"))
+    }
+
+    /// Load the config file
+    ///
+    /// A missing file at the default location is not an error, as the config
+    /// file is optional; a missing file given explicitly is.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The config file given on the command line, if any
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Config>` - The loaded config (empty without a config file) or an error
+    pub fn load(path: Option<&Path>) -> Result<Config> {
+        let (path, explicit) = match path {
+            Some(path) => (path.to_path_buf(), true),
+            None => match Self::default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Config::default()),
+            },
+        };
+
+        if !explicit && !path.exists() {
+            debug!(path = %path.display(), "No config file found");
+            return Ok(Config::default());
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+        let config = Self::parse(&content)
+            .with_context(|| format!("Invalid config file {}", path.display()))?;
+
+        info!(
+            path = %path.display(),
+            profiles = config.profiles.len(),
+            "Loaded config file"
+        );
+        Ok(config)
+    }
+
+    /// Get the settings to use, the defaults overridden by a profile
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - The profile selected with `--profile`, if any
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Settings>` - The settings or an error if the profile is not defined
+    pub fn settings(&self, profile: Option<&str>) -> Result<Settings> {
+        let Some(name) = profile else {
+            return Ok(self.defaults.clone());
+        };
+
+        let Some(profile) = self.profiles.get(name) else {
+            let available: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            if available.is_empty() {
+                bail!("Profile '{}' not found, no profiles are configured", name);
+            }
+            bail!(
+                "Profile '{}' not found, available profiles: {}",
+                name,
+                available.join(", ")
+            );
+        };
+
+        debug!(profile = %name, "Using config profile");
+        Ok(self.defaults.clone().merge(profile.clone()))
+    }
+}
@@ -0,0 +1,445 @@
+use std::fmt;
+
+/// An API permission used by kimspect commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct Permission {
+    /// API verb (e.g. list, get)
+    pub verb: &'static str,
+    /// API group of the resource (empty for the core group)
+    pub group: &'static str,
+    /// Resource name (e.g. pods, nodes)
+    pub resource: &'static str,
+    /// Whether the resource lives in a namespace
+    pub namespaced: bool,
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.verb, self.resource)
+    }
+}
+
+/// List pods, used to read container images
+pub const LIST_PODS: Permission = Permission {
+    verb: "list",
+    group: "",
+    resource: "pods",
+    namespaced: true,
+};
+
+/// List nodes, used for image sizes and node platforms
+pub const LIST_NODES: Permission = Permission {
+    verb: "list",
+    group: "",
+    resource: "nodes",
+    namespaced: false,
+};
+
+/// Get namespaces, used to report unknown namespaces
+pub const GET_NAMESPACES: Permission = Permission {
+    verb: "get",
+    group: "",
+    resource: "namespaces",
+    namespaced: false,
+};
+
+/// List namespaces, used to resolve namespace selectors
+pub const LIST_NAMESPACES: Permission = Permission {
+    verb: "list",
+    group: "",
+    resource: "namespaces",
+    namespaced: false,
+};
+
+/// List deployments, used to find registries, compare pods with their pod template and follow
+/// rollouts
+pub const LIST_DEPLOYMENTS: Permission = Permission {
+    verb: "list",
+    group: "apps",
+    resource: "deployments",
+    namespaced: true,
+};
+
+/// List events, used for pull times and pull failure messages
+pub const LIST_EVENTS: Permission = Permission {
+    verb: "list",
+    group: "",
+    resource: "events",
+    namespaced: true,
+};
+
+/// Watch pods, used to keep long-running inventories up to date
+pub const WATCH_PODS: Permission = Permission {
+    verb: "watch",
+    group: "",
+    resource: "pods",
+    namespaced: true,
+};
+
+/// Watch nodes, used to keep image sizes of long-running inventories up to date
+pub const WATCH_NODES: Permission = Permission {
+    verb: "watch",
+    group: "",
+    resource: "nodes",
+    namespaced: false,
+};
+
+/// List statefulsets, used to compare pods with their pod template and follow rollouts
+pub const LIST_STATEFULSETS: Permission = Permission {
+    verb: "list",
+    group: "apps",
+    resource: "statefulsets",
+    namespaced: true,
+};
+
+/// List daemonsets, used to compare pods with their pod template and follow rollouts
+pub const LIST_DAEMONSETS: Permission = Permission {
+    verb: "list",
+    group: "apps",
+    resource: "daemonsets",
+    namespaced: true,
+};
+
+/// List cronjobs, used to include the images of scheduled workloads
+pub const LIST_CRONJOBS: Permission = Permission {
+    verb: "list",
+    group: "batch",
+    resource: "cronjobs",
+    namespaced: true,
+};
+
+/// List jobs, used to include the images of recent jobs
+pub const LIST_JOBS: Permission = Permission {
+    verb: "list",
+    group: "batch",
+    resource: "jobs",
+    namespaced: true,
+};
+
+/// List replicasets, used to find the current revision of a Deployment rollout
+pub const LIST_REPLICASETS: Permission = Permission {
+    verb: "list",
+    group: "apps",
+    resource: "replicasets",
+    namespaced: true,
+};
+
+/// Get replicasets, used to follow pod owners when grouping images by release
+pub const GET_REPLICASETS: Permission = Permission {
+    verb: "get",
+    group: "apps",
+    resource: "replicasets",
+    namespaced: true,
+};
+
+/// Get deployments, used to follow pod owners when grouping images by release
+pub const GET_DEPLOYMENTS: Permission = Permission {
+    verb: "get",
+    group: "apps",
+    resource: "deployments",
+    namespaced: true,
+};
+
+/// Get statefulsets, used to follow pod owners when grouping images by release
+pub const GET_STATEFULSETS: Permission = Permission {
+    verb: "get",
+    group: "apps",
+    resource: "statefulsets",
+    namespaced: true,
+};
+
+/// Get daemonsets, used to follow pod owners when grouping images by release
+pub const GET_DAEMONSETS: Permission = Permission {
+    verb: "get",
+    group: "apps",
+    resource: "daemonsets",
+    namespaced: true,
+};
+
+/// Get jobs, used to follow pod owners when grouping images by release
+pub const GET_JOBS: Permission = Permission {
+    verb: "get",
+    group: "batch",
+    resource: "jobs",
+    namespaced: true,
+};
+
+/// Get cronjobs, used to follow pod owners when grouping images by release
+pub const GET_CRONJOBS: Permission = Permission {
+    verb: "get",
+    group: "batch",
+    resource: "cronjobs",
+    namespaced: true,
+};
+
+/// Every permission checked by `kimspect auth check`, in display order
+pub const PERMISSIONS: [Permission; 19] = [
+    LIST_PODS,
+    LIST_NODES,
+    GET_NAMESPACES,
+    LIST_NAMESPACES,
+    LIST_DEPLOYMENTS,
+    LIST_EVENTS,
+    WATCH_PODS,
+    WATCH_NODES,
+    LIST_STATEFULSETS,
+    LIST_DAEMONSETS,
+    LIST_CRONJOBS,
+    LIST_JOBS,
+    LIST_REPLICASETS,
+    GET_REPLICASETS,
+    GET_DEPLOYMENTS,
+    GET_STATEFULSETS,
+    GET_DAEMONSETS,
+    GET_JOBS,
+    GET_CRONJOBS,
+];
+
+/// Permissions a command needs to work fully
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct CommandPermissions {
+    /// Command line of the command (e.g. get images)
+    pub command: &'static str,
+    /// Permissions without which the command fails
+    pub required: &'static [Permission],
+    /// Permissions without which the command still works, with the feature that is lost
+    pub optional: &'static [(Permission, &'static str)],
+}
+
+/// Permissions of every command that talks to the cluster
+pub const COMMAND_PERMISSIONS: [CommandPermissions; 13] = [
+    CommandPermissions {
+        command: "get images",
+        required: &[LIST_PODS],
+        optional: &[
+            (LIST_NODES, "image sizes and node info"),
+            (GET_NAMESPACES, "unknown namespace errors"),
+            (LIST_NAMESPACES, "namespace selectors"),
+            (LIST_CRONJOBS, "scheduled CronJob images"),
+            (LIST_JOBS, "scheduled Job images"),
+            (GET_REPLICASETS, "releases of ReplicaSet owners"),
+            (GET_DEPLOYMENTS, "releases of Deployment owners"),
+            (GET_STATEFULSETS, "releases of StatefulSet owners"),
+            (GET_DAEMONSETS, "releases of DaemonSet owners"),
+            (GET_JOBS, "releases of Job owners"),
+            (GET_CRONJOBS, "releases of CronJob owners"),
+        ],
+    },
+    CommandPermissions {
+        command: "get registries",
+        required: &[LIST_DEPLOYMENTS],
+        optional: &[
+            (GET_NAMESPACES, "unknown namespace errors"),
+            (LIST_CRONJOBS, "scheduled CronJob registries"),
+            (LIST_JOBS, "scheduled Job registries"),
+        ],
+    },
+    CommandPermissions {
+        command: "get image-errors",
+        required: &[LIST_PODS],
+        optional: &[
+            (LIST_EVENTS, "failure messages"),
+            (GET_NAMESPACES, "unknown namespace errors"),
+        ],
+    },
+    CommandPermissions {
+        command: "get pull-times",
+        required: &[LIST_EVENTS, LIST_PODS],
+        optional: &[(GET_NAMESPACES, "unknown namespace errors")],
+    },
+    CommandPermissions {
+        command: "get nodes",
+        required: &[LIST_NODES],
+        optional: &[],
+    },
+    CommandPermissions {
+        command: "get arch-mismatches",
+        required: &[LIST_PODS, LIST_NODES],
+        optional: &[(GET_NAMESPACES, "unknown namespace errors")],
+    },
+    CommandPermissions {
+        command: "get image-mismatches",
+        required: &[LIST_PODS],
+        optional: &[
+            (LIST_DEPLOYMENTS, "Deployment template checks"),
+            (LIST_STATEFULSETS, "StatefulSet template checks"),
+            (LIST_DAEMONSETS, "DaemonSet template checks"),
+            (GET_NAMESPACES, "unknown namespace errors"),
+        ],
+    },
+    CommandPermissions {
+        command: "get rollout-images deployment",
+        required: &[LIST_PODS, LIST_DEPLOYMENTS, LIST_REPLICASETS],
+        optional: &[(GET_NAMESPACES, "unknown namespace errors")],
+    },
+    CommandPermissions {
+        command: "get rollout-images statefulset",
+        required: &[LIST_PODS, LIST_STATEFULSETS],
+        optional: &[(GET_NAMESPACES, "unknown namespace errors")],
+    },
+    CommandPermissions {
+        command: "get rollout-images daemonset",
+        required: &[LIST_PODS, LIST_DAEMONSETS],
+        optional: &[(GET_NAMESPACES, "unknown namespace errors")],
+    },
+    CommandPermissions {
+        command: "tui",
+        required: &[LIST_PODS, WATCH_PODS],
+        optional: &[
+            (LIST_NODES, "image sizes"),
+            (WATCH_NODES, "image size updates"),
+        ],
+    },
+    CommandPermissions {
+        command: "serve",
+        required: &[LIST_PODS, WATCH_PODS],
+        optional: &[
+            (LIST_NODES, "image sizes and node metrics"),
+            (WATCH_NODES, "image size updates"),
+        ],
+    },
+    CommandPermissions {
+        command: "pin",
+        required: &[LIST_PODS],
+        optional: &[],
+    },
+];
+
+/// Result of a SelfSubjectAccessReview for one permission
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct PermissionCheck {
+    /// The permission that was reviewed
+    pub permission: Permission,
+    /// Namespace the permission was reviewed in (None for cluster-wide)
+    pub namespace: Option<String>,
+    /// Whether the current identity holds the permission
+    pub allowed: bool,
+    /// Reason given by the authorizer (may be empty)
+    pub reason: String,
+}
+
+impl PermissionCheck {
+    /// Create the result of reviewing a permission
+    ///
+    /// # Arguments
+    ///
+    /// * `permission` - The permission that was reviewed
+    /// * `namespace` - Namespace the permission was reviewed in (None for cluster-wide)
+    /// * `allowed` - Whether the current identity holds the permission
+    /// * `reason` - Reason given by the authorizer
+    ///
+    /// # Returns
+    ///
+    /// * `PermissionCheck` - The review result
+    pub fn new(
+        permission: Permission,
+        namespace: Option<String>,
+        allowed: bool,
+        reason: impl Into<String>,
+    ) -> Self {
+        Self {
+            permission,
+            namespace,
+            allowed,
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Whether a command will work for the current identity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccessStatus {
+    /// Every permission is granted
+    Ok,
+    /// The command works, but some features are missing
+    Degraded,
+    /// The command fails
+    Denied,
+}
+
+impl fmt::Display for AccessStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessStatus::Ok => write!(f, "ok"),
+            AccessStatus::Degraded => write!(f, "degraded"),
+            AccessStatus::Denied => write!(f, "denied"),
+        }
+    }
+}
+
+/// Access of the current identity to one command
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CommandAccess {
+    /// Command line of the command (e.g. get images)
+    pub command: &'static str,
+    /// Whether the command will work
+    pub status: AccessStatus,
+    /// Grant for each of [`PERMISSIONS`]: None if the command does not use it
+    pub permissions: Vec<Option<bool>>,
+    /// Missing permissions and their effect (e.g. "list nodes: no image sizes")
+    pub missing: Vec<String>,
+}
+
+/// Work out which commands will work from the results of permission reviews
+///
+/// Permissions that were not reviewed are treated as unused, e.g. namespace
+/// lookups when querying all namespaces.
+///
+/// # Arguments
+///
+/// * `checks` - Results of the permission reviews
+///
+/// # Returns
+///
+/// * `Vec<CommandAccess>` - Access to each command, in the order of [`COMMAND_PERMISSIONS`]
+pub fn evaluate_access(checks: &[PermissionCheck]) -> Vec<CommandAccess> {
+    let allowed = |permission: &Permission| {
+        checks
+            .iter()
+            .find(|check| check.permission == *permission)
+            .map(|check| check.allowed)
+    };
+
+    COMMAND_PERMISSIONS
+        .iter()
+        .map(|command| {
+            let mut status = AccessStatus::Ok;
+            let mut missing = Vec::new();
+
+            for permission in command.required {
+                if allowed(permission) == Some(false) {
+                    status = AccessStatus::Denied;
+                    missing.push(format!("{}: required", permission));
+                }
+            }
+            for (permission, feature) in command.optional {
+                if allowed(permission) == Some(false) {
+                    if status == AccessStatus::Ok {
+                        status = AccessStatus::Degraded;
+                    }
+                    missing.push(format!("{}: no {}", permission, feature));
+                }
+            }
+
+            let permissions = PERMISSIONS
+                .iter()
+                .map(|permission| {
+                    let used = command.required.contains(permission)
+                        || command.optional.iter().any(|(p, _)| p == permission);
+                    if used { allowed(permission) } else { None }
+                })
+                .collect();
+
+            CommandAccess {
+                command: command.command,
+                status,
+                permissions,
+                missing,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,194 @@
+use crate::k8s::PodImage;
+use serde::Deserialize;
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// A field of a pod image that can be shown as a column or sorted on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
+pub enum ImageField {
+    /// Name of the pod
+    Pod,
+    /// Namespace of the pod
+    Namespace,
+    /// Name of the container
+    Container,
+    /// Registry hosting the image
+    Registry,
+    /// Classification of the registry
+    Class,
+    /// Upstream origin of a mirrored image
+    Upstream,
+    /// Image name without registry and tag
+    Image,
+    /// Tag or digest the image is referenced by
+    Version,
+    /// Full image reference as pulled
+    Reference,
+    /// State of the container
+    Status,
+    /// Size of the image on its node
+    Size,
+    /// Digest of the running image
+    Digest,
+    /// Node the pod runs on
+    Node,
+    /// Helm release that deployed the pod
+    Release,
+    /// Chart the pod was rendered from
+    Chart,
+    /// Tool managing the pod
+    ManagedBy,
+}
+
+impl ImageField {
+    /// Every field, in the order they are listed in help and error messages
+    pub const ALL: [ImageField; 16] = [
+        ImageField::Pod,
+        ImageField::Namespace,
+        ImageField::Container,
+        ImageField::Registry,
+        ImageField::Class,
+        ImageField::Upstream,
+        ImageField::Image,
+        ImageField::Version,
+        ImageField::Reference,
+        ImageField::Status,
+        ImageField::Size,
+        ImageField::Digest,
+        ImageField::Node,
+        ImageField::Release,
+        ImageField::Chart,
+        ImageField::ManagedBy,
+    ];
+
+    /// Get the name of the field as given on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            ImageField::Pod => "pod",
+            ImageField::Namespace => "namespace",
+            ImageField::Container => "container",
+            ImageField::Registry => "registry",
+            ImageField::Class => "class",
+            ImageField::Upstream => "upstream",
+            ImageField::Image => "image",
+            ImageField::Version => "version",
+            ImageField::Reference => "reference",
+            ImageField::Status => "status",
+            ImageField::Size => "size",
+            ImageField::Digest => "digest",
+            ImageField::Node => "node",
+            ImageField::Release => "release",
+            ImageField::Chart => "chart",
+            ImageField::ManagedBy => "managed-by",
+        }
+    }
+
+    /// Get the key of the field in template records (e.g. managedBy)
+    pub fn key(&self) -> String {
+        let mut words = self.name().split('-');
+        let first = words.next().unwrap_or_default().to_string();
+        words.fold(first, |mut key, word| {
+            let mut chars = word.chars();
+            if let Some(c) = chars.next() {
+                key.extend(c.to_uppercase());
+                key.push_str(chars.as_str());
+            }
+            key
+        })
+    }
+
+    /// Get the default column header of the field (e.g. MANAGED-BY)
+    pub fn header(&self) -> String {
+        self.name().to_uppercase()
+    }
+}
+
+impl fmt::Display for ImageField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for ImageField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase().replace('_', "-");
+        ImageField::ALL
+            .into_iter()
+            .find(|field| field.name() == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = ImageField::ALL.iter().map(ImageField::name).collect();
+                format!(
+                    "Unknown image field '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl TryFrom<String> for ImageField {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl PodImage {
+    /// Get the value of a field for display
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - The field to read
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The value of the field (empty if unknown)
+    pub fn field(&self, field: ImageField) -> String {
+        match field {
+            ImageField::Pod => self.pod_name.clone(),
+            ImageField::Namespace => self.namespace.clone(),
+            ImageField::Container => self.container_name.clone(),
+            ImageField::Registry => self.registry.clone(),
+            ImageField::Class => self.registry_class.clone(),
+            ImageField::Upstream if self.upstream_registry.is_empty() => String::new(),
+            ImageField::Upstream => format!("{}/{}", self.upstream_registry, self.upstream_image),
+            ImageField::Image => self.image_name.clone(),
+            ImageField::Version => self.image_version.clone(),
+            ImageField::Reference => self.reference(),
+            ImageField::Status => self.status.clone(),
+            ImageField::Size => self.image_size.clone(),
+            ImageField::Digest => self.digest.clone(),
+            ImageField::Node => self.node_name.clone(),
+            ImageField::Release => self.release.clone(),
+            ImageField::Chart => self.chart.clone(),
+            ImageField::ManagedBy => self.managed_by.clone(),
+        }
+    }
+}
+
+/// Sort pod images on a field
+///
+/// Sizes are compared by their number of bytes, largest first, with unknown
+/// sizes last; other fields are compared as text. The sort is stable, so
+/// images with the same value keep their order.
+///
+/// # Arguments
+///
+/// * `images` - The pod images to sort
+/// * `field` - The field to sort on
+pub fn sort_pod_images(images: &mut [PodImage], field: ImageField) {
+    match field {
+        ImageField::Size => images.sort_by(|a, b| match (a.image_size_bytes, b.image_size_bytes) {
+            (Some(a), Some(b)) => b.cmp(&a),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }),
+        _ => images.sort_by_cached_key(|image| image.field(field)),
+    }
+}
@@ -0,0 +1,208 @@
+use crate::k8s::{ImageQuery, K8sClient, PodSource};
+use anyhow::{Context, Result};
+use futures::{FutureExt, Stream, StreamExt};
+use k8s_openapi::api::core::v1::{Node, Pod};
+use kube::Api;
+use kube::runtime::reflector::{self, Store};
+use kube::runtime::{WatchStreamExt, watcher};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+
+/// A cache of pods and nodes, kept up to date by watching the cluster
+///
+/// Queries run against the cache instead of listing pods on every request, and
+/// the revision tells readers when the cache changed. Clones share the cache.
+#[derive(Clone)]
+pub struct Inventory {
+    /// Pods in the watched namespaces
+    pods: Store<Pod>,
+    /// Nodes of the cluster, used to look up image sizes
+    nodes: Store<Node>,
+    /// Number of changes applied to the cache
+    revision: Arc<AtomicU64>,
+    /// Last error of each failing watch by resource, cleared when it recovers
+    errors: Arc<Mutex<BTreeMap<&'static str, String>>>,
+}
+
+impl Inventory {
+    /// Create an inventory of a fixed set of pods and nodes, not watching anything
+    ///
+    /// # Arguments
+    ///
+    /// * `pods` - The pods to inventory
+    /// * `nodes` - The nodes the pods run on
+    ///
+    /// # Returns
+    ///
+    /// * `Inventory` - A ready inventory holding the objects
+    pub fn from_objects(pods: Vec<Pod>, nodes: Vec<Node>) -> Self {
+        Self {
+            pods: filled_store(pods),
+            nodes: filled_store(nodes),
+            revision: Arc::new(AtomicU64::new(1)),
+            errors: Arc::default(),
+        }
+    }
+
+    /// Get the number of changes applied to the cache, to tell when to query it again
+    pub fn revision(&self) -> u64 {
+        self.revision.load(Ordering::Relaxed)
+    }
+
+    /// Get the last errors of the failing watches, None while they are healthy
+    pub fn last_error(&self) -> Option<String> {
+        let errors = self.errors.lock().unwrap_or_else(|e| e.into_inner());
+        let messages: Vec<&str> = errors.values().map(String::as_str).collect();
+        (!messages.is_empty()).then(|| messages.join("; "))
+    }
+
+    /// Wait until the pods have been listed once
+    ///
+    /// # Returns
+    ///
+    /// * `Result<()>` - Ok once the cache is filled, an error if the watch stopped
+    pub async fn wait_until_ready(&self) -> Result<()> {
+        self.pods
+            .wait_until_ready()
+            .await
+            .context("Pod watch stopped before listing pods")
+    }
+
+    /// Check if the pods have been listed once
+    pub fn is_ready(&self) -> bool {
+        matches!(self.pods.wait_until_ready().now_or_never(), Some(Ok(())))
+    }
+
+    /// Get the pods in the cache
+    pub fn pods(&self) -> Vec<Arc<Pod>> {
+        self.pods.state()
+    }
+
+    /// Get the nodes in the cache
+    pub fn nodes(&self) -> Vec<Arc<Node>> {
+        self.nodes.state()
+    }
+
+    /// Record the outcome of a watch event
+    fn record<E: std::fmt::Display>(&self, resource: &'static str, result: Result<(), E>) {
+        let mut errors = self.errors.lock().unwrap_or_else(|e| e.into_inner());
+        match result {
+            Ok(()) => {
+                errors.remove(resource);
+                self.revision.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                warn!(resource = %resource, error = %e, "Watch failed, retrying");
+                errors.insert(resource, format!("Failed to watch {}: {}", resource, e));
+            }
+        }
+    }
+}
+
+impl PodSource for Inventory {
+    async fn list_pods(&self, query: &ImageQuery) -> Result<Vec<Pod>> {
+        Ok(self
+            .pods
+            .state()
+            .iter()
+            .filter(|pod| query.matches_pod(pod))
+            .map(|pod| Pod::clone(pod))
+            .collect())
+    }
+
+    async fn list_nodes(&self) -> Result<Vec<Node>> {
+        Ok(self
+            .nodes
+            .state()
+            .iter()
+            .map(|node| Node::clone(node))
+            .collect())
+    }
+}
+
+/// Create a store holding the given objects
+fn filled_store<K>(objects: Vec<K>) -> Store<K>
+where
+    K: kube::Resource<DynamicType = ()> + Clone + 'static,
+{
+    let (store, mut writer) = reflector::store();
+    writer.apply_watcher_event(&watcher::Event::Init);
+    for object in objects {
+        writer.apply_watcher_event(&watcher::Event::InitApply(object));
+    }
+    writer.apply_watcher_event(&watcher::Event::InitDone);
+    store
+}
+
+/// Reflect the objects of an API into a store
+fn reflect<K>(
+    api: Api<K>,
+) -> (
+    Store<K>,
+    impl Stream<Item = Result<(), watcher::Error>> + Send,
+)
+where
+    K: kube::Resource<DynamicType = ()>
+        + Clone
+        + serde::de::DeserializeOwned
+        + std::fmt::Debug
+        + Send
+        + Sync
+        + 'static,
+{
+    let (store, writer) = reflector::store();
+    let events = reflector::reflector(writer, watcher(api, watcher::Config::default()))
+        .default_backoff()
+        .map(|event| event.map(|_| ()));
+    (store, events)
+}
+
+impl K8sClient {
+    /// Watch pods and nodes into an inventory
+    ///
+    /// The inventory is filled while the returned future runs, which is until it
+    /// is dropped; failed watches are retried with backoff.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace to watch pods in, None for all namespaces
+    ///
+    /// # Returns
+    ///
+    /// * `(Inventory, impl Future)` - The inventory and the future driving the watches
+    pub fn watch_inventory(
+        &self,
+        namespace: Option<&str>,
+    ) -> (Inventory, impl Future<Output = ()> + Send + 'static) {
+        debug!(namespace = ?namespace, "Watching pods and nodes");
+        let pods_api: Api<Pod> = match namespace {
+            Some(namespace) => Api::namespaced(self.client.clone(), namespace),
+            None => Api::all(self.client.clone()),
+        };
+        let (pods, pod_events) = reflect(pods_api);
+        let (nodes, node_events) = reflect(Api::<Node>::all(self.client.clone()));
+
+        let inventory = Inventory {
+            pods,
+            nodes,
+            revision: Arc::new(AtomicU64::new(0)),
+            errors: Arc::default(),
+        };
+
+        let recorder = inventory.clone();
+        let watch = async move {
+            let pod_events = pod_events.map(|result| ("pods", result));
+            let node_events = node_events.map(|result| ("nodes", result));
+            futures::stream::select(pod_events, node_events)
+                .for_each(|(resource, result)| {
+                    recorder.record(resource, result);
+                    async {}
+                })
+                .await;
+        };
+        (inventory, watch)
+    }
+}
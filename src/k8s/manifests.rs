@@ -0,0 +1,379 @@
+use crate::k8s::{
+    HELM_CHART_LABEL, HELM_RELEASE_ANNOTATION, INSTANCE_LABEL, ImageQuery, MANAGED_BY_LABEL,
+    PodSource,
+};
+use anyhow::{Context, Result, bail};
+use k8s_openapi::api::batch::v1::{CronJob, Job};
+use k8s_openapi::api::core::v1::{Pod, PodTemplateSpec};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{ObjectMeta, OwnerReference};
+use k8s_openapi::jiff::Timestamp;
+use serde::Deserialize;
+use serde_json::Value;
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+use tracing::{debug, info, instrument};
+
+/// Path that makes [`FilePodSource::load`] read from standard input
+pub const STDIN_PATH: &str = "-";
+
+/// How long after they finished the images of Jobs are still listed as scheduled
+pub const RECENT_JOB_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// File extensions read when loading a directory of manifests
+const MANIFEST_EXTENSIONS: [&str; 3] = ["yaml", "yml", "json"];
+
+/// Pods loaded from YAML or JSON manifests instead of a live cluster
+///
+/// Accepts Pods, workloads with a pod template (Deployments, StatefulSets,
+/// DaemonSets, ReplicaSets, ReplicationControllers, Jobs and CronJobs) and
+/// `List` objects such as `kubectl get pods -o json` dumps. Workloads are
+/// turned into a single pod named after the workload and owned by it.
+#[derive(Debug, Clone, Default)]
+pub struct FilePodSource {
+    /// Pods read from the manifests
+    pods: Vec<Pod>,
+}
+
+impl FilePodSource {
+    /// Create a source from already loaded pods
+    pub fn new(pods: Vec<Pod>) -> Self {
+        Self { pods }
+    }
+
+    /// Load pods from a manifest file, a directory of manifests or standard input (`-`)
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file or directory to read, or `-` for standard input
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self>` - The loaded pods or an error if a manifest cannot be read or parsed
+    #[instrument]
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut pods = Vec::new();
+
+        if path.as_os_str() == STDIN_PATH {
+            let mut content = String::new();
+            std::io::stdin()
+                .read_to_string(&mut content)
+                .context("Failed to read manifests from stdin")?;
+            pods.extend(parse_manifests(&content).context("Failed to parse manifests from stdin")?);
+        } else if path.is_dir() {
+            for file in manifest_files(path)? {
+                pods.extend(load_file(&file)?);
+            }
+        } else {
+            pods.extend(load_file(path)?);
+        }
+
+        info!(
+            total_pods = pods.len(),
+            "Successfully loaded pods from manifests"
+        );
+        Ok(Self { pods })
+    }
+
+    /// Get the loaded pods
+    pub fn pods(&self) -> &[Pod] {
+        &self.pods
+    }
+}
+
+impl PodSource for FilePodSource {
+    async fn list_pods(&self, query: &ImageQuery) -> Result<Vec<Pod>> {
+        // Manifests without a namespace are applied to the current namespace,
+        // so they belong to whichever namespace is queried.
+        let pods: Vec<Pod> = self
+            .pods
+            .iter()
+            .cloned()
+            .map(|mut pod| {
+                if pod.metadata.namespace.is_none() && !query.is_all_namespaces() {
+                    pod.metadata.namespace = Some(query.get_namespace().to_string());
+                }
+                pod
+            })
+            .collect();
+        pods.list_pods(query).await
+    }
+}
+
+/// Parse pods from YAML or JSON content, which may contain several documents
+///
+/// # Arguments
+///
+/// * `content` - The manifest content
+///
+/// # Returns
+///
+/// * `Result<Vec<Pod>>` - The pods and workload pod templates found, or a parse error
+pub fn parse_manifests(content: &str) -> Result<Vec<Pod>> {
+    let mut pods = Vec::new();
+
+    for document in serde_yaml::Deserializer::from_str(content) {
+        let value = Value::deserialize(document).context("Invalid YAML or JSON document")?;
+        collect_pods(value, &mut pods)?;
+    }
+
+    Ok(pods)
+}
+
+/// Convert a Kubernetes object, such as one submitted for admission, into pods
+///
+/// # Arguments
+///
+/// * `object` - The object, a Pod, a workload with a pod template or a List
+///
+/// # Returns
+///
+/// * `Result<Vec<Pod>>` - The pods and workload pod templates found, empty for other kinds
+pub fn object_pods(object: Value) -> Result<Vec<Pod>> {
+    let mut pods = Vec::new();
+    collect_pods(object, &mut pods)?;
+    Ok(pods)
+}
+
+/// Read and parse a single manifest file
+fn load_file(path: &Path) -> Result<Vec<Pod>> {
+    debug!(path = %path.display(), "Reading manifest file");
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    parse_manifests(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// List manifest files in a directory and its subdirectories, in a stable order
+fn manifest_files(dir: &Path) -> Result<Vec<std::path::PathBuf>> {
+    let mut files = Vec::new();
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory {}", dir.display()))?;
+
+    for entry in entries {
+        let path = entry
+            .with_context(|| format!("Failed to read directory {}", dir.display()))?
+            .path();
+        if path.is_dir() {
+            files.extend(manifest_files(&path)?);
+        } else if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| MANIFEST_EXTENSIONS.contains(&ext))
+        {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Convert a Kubernetes object into pods, recursing into lists
+fn collect_pods(value: Value, pods: &mut Vec<Pod>) -> Result<()> {
+    if value.is_null() {
+        return Ok(());
+    }
+
+    if let Some(items) = value.get("items").and_then(Value::as_array) {
+        // Typed lists from the API (e.g. PodList) omit the kind of their items
+        let item_kind = value
+            .get("kind")
+            .and_then(Value::as_str)
+            .and_then(|kind| kind.strip_suffix("List"))
+            .filter(|kind| !kind.is_empty());
+
+        for item in items {
+            let mut item = item.clone();
+            if let (Some(kind), Some(object)) = (item_kind, item.as_object_mut()) {
+                object
+                    .entry("kind")
+                    .or_insert_with(|| Value::String(kind.to_string()));
+            }
+            collect_pods(item, pods)?;
+        }
+        return Ok(());
+    }
+
+    let kind = value
+        .get("kind")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let template_path: &[&str] = match kind {
+        "Pod" => {
+            let pod: Pod = serde_json::from_value(value).context("Invalid Pod manifest")?;
+            pods.push(pod);
+            return Ok(());
+        }
+        "Deployment"
+        | "StatefulSet"
+        | "DaemonSet"
+        | "ReplicaSet"
+        | "ReplicationController"
+        | "Job" => &["spec", "template"],
+        "CronJob" => &["spec", "jobTemplate", "spec", "template"],
+        _ => {
+            debug!(kind = %kind, "Skipping manifest without pod template");
+            return Ok(());
+        }
+    };
+
+    let metadata: ObjectMeta = value
+        .get("metadata")
+        .cloned()
+        .map(serde_json::from_value)
+        .transpose()
+        .with_context(|| format!("Invalid {} metadata", kind))?
+        .unwrap_or_default();
+
+    let Some(template) = template_path
+        .iter()
+        .try_fold(&value, |v, key| v.get(key))
+        .cloned()
+    else {
+        bail!(
+            "{} {} has no pod template",
+            kind,
+            metadata.name.unwrap_or_default()
+        );
+    };
+    let template: PodTemplateSpec = serde_json::from_value(template)
+        .with_context(|| format!("Invalid pod template in {}", kind))?;
+
+    let api_version = value
+        .get("apiVersion")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    pods.push(template_pod(kind, api_version, metadata, template));
+
+    Ok(())
+}
+
+/// Turn the pod template of a workload into a pod named after and owned by the workload
+///
+/// There is no owner to look up for such pods, so the workload's release
+/// metadata is kept on the pod.
+///
+/// # Arguments
+///
+/// * `kind` - Kind of the workload (e.g. CronJob)
+/// * `api_version` - API version of the workload (e.g. batch/v1)
+/// * `metadata` - Metadata of the workload
+/// * `template` - The pod template of the workload
+///
+/// # Returns
+///
+/// * `Pod` - A pod with the template's spec and labels
+pub(crate) fn template_pod(
+    kind: &str,
+    api_version: &str,
+    metadata: ObjectMeta,
+    template: PodTemplateSpec,
+) -> Pod {
+    let template_metadata = template.metadata.unwrap_or_default();
+
+    let mut labels = template_metadata.labels.unwrap_or_default();
+    for key in [MANAGED_BY_LABEL, INSTANCE_LABEL, HELM_CHART_LABEL] {
+        if let Some(value) = metadata.labels.as_ref().and_then(|l| l.get(key)) {
+            labels
+                .entry(key.to_string())
+                .or_insert_with(|| value.clone());
+        }
+    }
+    let mut annotations = template_metadata.annotations.unwrap_or_default();
+    if let Some(release) = metadata
+        .annotations
+        .as_ref()
+        .and_then(|a| a.get(HELM_RELEASE_ANNOTATION))
+    {
+        annotations
+            .entry(HELM_RELEASE_ANNOTATION.to_string())
+            .or_insert_with(|| release.clone());
+    }
+
+    Pod {
+        metadata: ObjectMeta {
+            name: metadata.name.clone(),
+            namespace: metadata.namespace.clone(),
+            labels: (!labels.is_empty()).then_some(labels),
+            annotations: (!annotations.is_empty()).then_some(annotations),
+            owner_references: Some(vec![OwnerReference {
+                api_version: api_version.to_string(),
+                kind: kind.to_string(),
+                name: metadata.name.unwrap_or_default(),
+                uid: metadata.uid.unwrap_or_default(),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        },
+        spec: template.spec,
+        status: None,
+    }
+}
+
+/// Turn CronJobs and recent Jobs into pods of their templates
+///
+/// Jobs created by a CronJob are left out, as the CronJob lists their template
+/// already, and so are active Jobs, whose pods are listed as running. Other
+/// Jobs are kept for [`RECENT_JOB_WINDOW`] after they finished.
+///
+/// # Arguments
+///
+/// * `cronjobs` - The listed CronJobs
+/// * `jobs` - The listed Jobs
+/// * `now` - The current time
+///
+/// # Returns
+///
+/// * `Vec<Pod>` - A pod per CronJob and recent Job, CronJobs first
+pub fn scheduled_pods(cronjobs: Vec<CronJob>, jobs: Vec<Job>, now: Timestamp) -> Vec<Pod> {
+    let cronjob_pods = cronjobs.into_iter().filter_map(|cronjob| {
+        let template = cronjob.spec?.job_template.spec?.template;
+        Some(template_pod(
+            "CronJob",
+            "batch/v1",
+            cronjob.metadata,
+            template,
+        ))
+    });
+    let job_pods = jobs
+        .into_iter()
+        .filter(|job| is_recent_job(job, now))
+        .filter_map(|job| {
+            let template = job.spec?.template;
+            Some(template_pod("Job", "batch/v1", job.metadata, template))
+        });
+    cronjob_pods.chain(job_pods).collect()
+}
+
+/// Check whether a Job finished recently and was not created by a CronJob
+fn is_recent_job(job: &Job, now: Timestamp) -> bool {
+    let from_cronjob = job
+        .metadata
+        .owner_references
+        .iter()
+        .flatten()
+        .any(|owner| owner.kind == "CronJob");
+    let status = job.status.as_ref();
+    let active = status.and_then(|status| status.active).unwrap_or_default();
+    if from_cronjob || active > 0 {
+        return false;
+    }
+
+    let finished = status
+        .and_then(|status| status.completion_time.as_ref())
+        .or_else(|| {
+            status?
+                .conditions
+                .iter()
+                .flatten()
+                .filter(|condition| condition.status == "True")
+                .find(|condition| matches!(condition.type_.as_str(), "Complete" | "Failed"))?
+                .last_transition_time
+                .as_ref()
+        })
+        .or_else(|| status.and_then(|status| status.start_time.as_ref()))
+        .or(job.metadata.creation_timestamp.as_ref());
+    finished.is_none_or(|finished| {
+        now.as_second() - finished.0.as_second() <= RECENT_JOB_WINDOW.as_secs() as i64
+    })
+}
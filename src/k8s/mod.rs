@@ -1,14 +1,83 @@
-use crate::utils::{KNOWN_REGISTRIES, strip_registry};
+use crate::registry::{ImageReference, RegistryClient};
 use anyhow::{Context, Result};
-use k8s_openapi::api::apps::v1::Deployment;
+use futures::StreamExt;
+use k8s_openapi::NamespaceResourceScope;
+use k8s_openapi::api::apps::v1::{DaemonSet, Deployment, ReplicaSet, StatefulSet};
+use k8s_openapi::api::authorization::v1::{
+    ResourceAttributes, SelfSubjectAccessReview, SelfSubjectAccessReviewSpec,
+};
+use k8s_openapi::api::batch::v1::{CronJob, Job};
 use k8s_openapi::api::core::v1::Node;
-use k8s_openapi::api::core::v1::Pod;
-use kube::{Api, Client, Config, api::ListParams};
+use k8s_openapi::api::core::v1::{ContainerStatus, Event, Pod, PodSpec};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{ObjectMeta, OwnerReference};
+use kube::{
+    Api, Client, Config,
+    api::{ListParams, PostParams},
+    config::{KubeConfigOptions, Kubeconfig},
+};
+use serde::de::DeserializeOwned;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::Duration;
 use thiserror::Error;
-use tracing::{debug, error, info, instrument};
+use tracing::{debug, error, info, instrument, warn};
+
+mod access;
+mod columns;
+mod inventory;
+mod manifests;
+mod nodes;
+mod pins;
+mod pulls;
+mod query;
+mod releases;
+mod rollouts;
+mod status;
+mod workloads;
+
+pub use access::{
+    AccessStatus, COMMAND_PERMISSIONS, CommandAccess, CommandPermissions, GET_CRONJOBS,
+    GET_DAEMONSETS, GET_DEPLOYMENTS, GET_JOBS, GET_NAMESPACES, GET_REPLICASETS, GET_STATEFULSETS,
+    LIST_CRONJOBS, LIST_DAEMONSETS, LIST_DEPLOYMENTS, LIST_EVENTS, LIST_JOBS, LIST_NAMESPACES,
+    LIST_NODES, LIST_PODS, LIST_REPLICASETS, LIST_STATEFULSETS, PERMISSIONS, Permission,
+    PermissionCheck, WATCH_NODES, WATCH_PODS, evaluate_access,
+};
+pub use columns::{ImageField, sort_pod_images};
+pub use inventory::Inventory;
+pub use manifests::{
+    FilePodSource, RECENT_JOB_WINDOW, object_pods, parse_manifests, scheduled_pods,
+};
+pub use nodes::{ArchMismatch, NodeInfo, find_arch_mismatches, manifest_reference, process_node};
+pub use pins::{
+    ImagePin, collect_image_pins, find_image_pins, render_kubectl_patches, render_kustomize_images,
+    render_patch_documents,
+};
+pub use pulls::{
+    ImagePull, PullGroupBy, PullMessage, PullSummary, format_duration, parse_go_duration,
+    parse_pull_message, process_pull_events, summarize_pulls,
+};
+pub use query::{ImageQuery, PodSource, collect_pod_images};
+pub use releases::{
+    HELM_CHART_LABEL, HELM_RELEASE_ANNOTATION, INSTANCE_LABEL, MANAGED_BY_LABEL, ReleaseGroup,
+    ReleaseInfo, group_by_release,
+};
+pub use rollouts::{
+    ContainerRollout, ImageVersion, RolloutImages, WorkloadKind, WorkloadRevision,
+    summarize_rollout,
+};
+pub use status::{
+    IMAGE_ERROR_REASONS, ImageError, attach_event_messages, container_state, process_pod_errors,
+};
+pub use workloads::{
+    ImageMismatch, MismatchKind, WorkloadTemplates, find_image_mismatches, pod_workload,
+};
+
+/// Maximum number of image manifests fetched from registries at the same time
+const MAX_CONCURRENT_MANIFESTS: usize = 8;
 
 /// Represents a container image running in a Kubernetes pod
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
 pub struct PodImage {
     /// Name of the pod containing the image
     pub pod_name: String,
@@ -28,10 +97,46 @@ pub struct PodImage {
     pub digest: String,
     /// Image size in a human readable format (if available)
     pub image_size: String,
+    /// Image size in bytes (if available)
+    pub image_size_bytes: Option<u64>,
+    /// Current state of the container (e.g. Running, ImagePullBackOff)
+    pub status: String,
+    /// Upstream registry when the image is pulled through a mirror (empty otherwise)
+    pub upstream_registry: String,
+    /// Image name within the upstream registry when pulled through a mirror (empty otherwise)
+    pub upstream_image: String,
+    /// Classification of the registry (e.g. public, internal, vendor)
+    pub registry_class: String,
+    /// Helm release that deployed the pod (empty if unknown)
+    pub release: String,
+    /// Chart name and version the pod was rendered from (empty if unknown)
+    pub chart: String,
+    /// Tool managing the pod, from the app.kubernetes.io/managed-by label (empty if unset)
+    pub managed_by: String,
+}
+
+impl PodImage {
+    /// Get the image reference as pulled (e.g. docker.io/nginx:1.25, ghcr.io/org/app@sha256:...)
+    pub fn reference(&self) -> String {
+        if self.registry.is_empty() {
+            // Invalid references are kept verbatim in the image name
+            return self.image_name.clone();
+        }
+        let separator = if self.image_version.starts_with('@') {
+            ""
+        } else {
+            ":"
+        };
+        format!(
+            "{}/{}{}{}",
+            self.registry, self.image_name, separator, self.image_version
+        )
+    }
 }
 
 /// Errors that can occur when interacting with Kubernetes
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum K8sError {
     /// Configuration-related errors
     #[error("Configuration error: {0}")]
@@ -45,6 +150,85 @@ pub enum K8sError {
     /// Resource not found errors
     #[error("Resource not found: {0}")]
     ResourceNotFound(String),
+    /// The current user is not allowed to perform a request
+    #[error("Permission denied: cannot {verb} {resource} {scope}")]
+    PermissionDenied {
+        /// The API verb that was refused (e.g. list, get)
+        verb: String,
+        /// The resource the verb was applied to (e.g. pods, nodes)
+        resource: String,
+        /// Where the request applied (e.g. in namespace "web", cluster-wide)
+        scope: String,
+    },
+}
+
+impl K8sError {
+    /// Build a permission error for a verb on a resource
+    ///
+    /// # Arguments
+    ///
+    /// * `verb` - The API verb that was refused
+    /// * `resource` - The resource the verb was applied to
+    /// * `namespace` - The namespace of the request, or `None` for cluster-wide requests
+    pub fn permission_denied(verb: &str, resource: &str, namespace: Option<&str>) -> Self {
+        let scope = match namespace {
+            Some(namespace) => format!("in namespace \"{}\"", namespace),
+            None => "cluster-wide".to_string(),
+        };
+        K8sError::PermissionDenied {
+            verb: verb.to_string(),
+            resource: resource.to_string(),
+            scope,
+        }
+    }
+}
+
+/// Convert a failed Kubernetes API request into a [`K8sError`]
+///
+/// Forbidden responses name the verb and resource that were refused, so users
+/// know which RBAC rule is missing.
+///
+/// # Arguments
+///
+/// * `err` - The error returned by the API request
+/// * `verb` - The API verb of the request (e.g. list, get)
+/// * `resource` - The resource requested (e.g. pods, nodes)
+/// * `namespace` - The namespace of the request, or `None` for cluster-wide requests
+///
+/// # Returns
+///
+/// * `K8sError` - The classified error
+pub fn api_error(
+    err: kube::Error,
+    verb: &str,
+    resource: &str,
+    namespace: Option<&str>,
+) -> K8sError {
+    match err {
+        kube::Error::Api(status) if status.code == 403 => {
+            K8sError::permission_denied(verb, resource, namespace)
+        }
+        kube::Error::Api(status) if status.code == 401 => K8sError::ConnectionError(format!(
+            "Unauthorized: the API server rejected the credentials ({})",
+            status.message
+        )),
+        kube::Error::Api(status) => {
+            K8sError::ApiError(format!("{} ({})", status.message, status.reason))
+        }
+        e => K8sError::ConnectionError(format!("Failed to {} {}: {}", verb, resource, e)),
+    }
+}
+
+/// Options for connecting to the cluster, mirroring kubectl's global flags
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ClientOptions {
+    /// Kubeconfig file to read instead of $KUBECONFIG or ~/.kube/config
+    pub kubeconfig: Option<PathBuf>,
+    /// Kubeconfig context to use instead of the current context
+    pub context: Option<String>,
+    /// Timeout of each API request (zero disables timeouts, None keeps the defaults)
+    pub request_timeout: Option<Duration>,
 }
 
 /// Client for interacting with Kubernetes clusters
@@ -53,6 +237,13 @@ pub struct K8sClient {
     client: Client,
 }
 
+impl From<Client> for K8sClient {
+    /// Wrap a configured kube client, without checking that the cluster is accessible
+    fn from(client: Client) -> Self {
+        Self { client }
+    }
+}
+
 impl K8sClient {
     /// Create a new Kubernetes client
     ///
@@ -61,14 +252,50 @@ impl K8sClient {
     /// * `Result<Self>` - A new K8sClient instance or an error if initialization fails
     #[instrument(skip_all)]
     pub async fn new() -> Result<Self> {
-        debug!("Initializing Kubernetes client");
+        Self::with_options(&ClientOptions::default()).await
+    }
 
-        let kubeconfig_path = Self::get_kubeconfig_path()?;
-        debug!(path = %kubeconfig_path, "Using kubeconfig path");
+    /// Create a new Kubernetes client with a kubeconfig, context or request timeout
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - The connection options
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self>` - A new K8sClient instance or an error if initialization fails
+    #[instrument(skip_all)]
+    pub async fn with_options(options: &ClientOptions) -> Result<Self> {
+        debug!(options = ?options, "Initializing Kubernetes client");
 
-        let mut config = Config::infer()
-            .await
-            .context("Failed to infer Kubernetes configuration")?;
+        let mut config = if options.kubeconfig.is_none() && options.context.is_none() {
+            let kubeconfig_path = Self::get_kubeconfig_path()?;
+            debug!(path = %kubeconfig_path, "Using kubeconfig path");
+
+            Config::infer()
+                .await
+                .context("Failed to infer Kubernetes configuration")?
+        } else {
+            let kubeconfig = match &options.kubeconfig {
+                Some(path) => Kubeconfig::read_from(path)
+                    .with_context(|| format!("Failed to read kubeconfig {}", path.display()))?,
+                None => Kubeconfig::read().context("Failed to read kubeconfig")?,
+            };
+            let kube_options = KubeConfigOptions {
+                context: options.context.clone(),
+                ..Default::default()
+            };
+            Config::from_custom_kubeconfig(kubeconfig, &kube_options)
+                .await
+                .context("Failed to load Kubernetes configuration")?
+        };
+
+        if let Some(timeout) = options.request_timeout {
+            let timeout = (!timeout.is_zero()).then_some(timeout);
+            config.connect_timeout = timeout;
+            config.read_timeout = timeout;
+            config.write_timeout = timeout;
+        }
 
         if let Some(host) = config.cluster_url.host() {
             if should_bypass_proxy(host) && config.proxy_url.is_some() {
@@ -119,222 +346,52 @@ impl K8sClient {
 
     /// Check if the Kubernetes cluster is accessible
     ///
+    /// Only the API server version is requested, which every authenticated user
+    /// may read; permissions are checked by the requests of each command.
+    ///
     /// # Returns
     ///
     /// * `Result<bool>` - True if the cluster is accessible, false otherwise
     #[instrument(skip(self))]
     pub async fn is_accessible(&self) -> Result<bool> {
         debug!("Checking cluster accessibility");
-        let api: Api<Pod> = Api::namespaced(self.client.clone(), "default");
 
-        match api.list(&Default::default()).await {
-            Ok(_) => {
-                debug!("Successfully connected to cluster");
+        match self.client.apiserver_version().await {
+            Ok(version) => {
+                debug!(version = %version.git_version, "Successfully connected to cluster");
                 Ok(true)
             }
-            Err(e) => match e {
-                kube::Error::Api(api_err) => {
-                    error!("Kubernetes API error occurred");
-                    Err(
-                        K8sError::ApiError(format!("{} ({})", api_err.message, api_err.reason))
-                            .into(),
-                    )
-                }
-                _ => {
-                    error!("Failed to connect to Kubernetes cluster");
-                    Err(
-                        K8sError::ConnectionError("Failed to connect to Kubernetes cluster".into())
-                            .into(),
-                    )
-                }
-            },
+            Err(e) => {
+                error!(error = %e, "Failed to connect to Kubernetes cluster");
+                Err(api_error(e, "get", "the server version", None).into())
+            }
         }
     }
 
-    /// Get pod images matching the specified criteria
+    /// Get pod images matching a query
     ///
     /// # Arguments
     ///
-    /// * `namespace` - The namespace to search in
-    /// * `node_name` - Optional node name filter
-    /// * `pod_name` - Optional pod name filter
-    /// * `registry_filter` - Optional registry filter
-    /// * `all_namespaces` - Whether to search in all namespaces
+    /// * `query` - The filters selecting pods and images
     ///
     /// # Returns
     ///
     /// * `Result<Vec<PodImage>>` - List of matching pod images or an error
-    #[instrument(skip(self), fields(
-        namespace = %namespace,
-        node = ?node_name,
-        pod = ?pod_name,
-        registry = ?registry_filter,
-        exclude_registry = ?exclude_registry_filter,
-        all_namespaces = %all_namespaces
-    ))]
-    pub async fn get_pod_images(
-        &self,
-        namespace: &str,
-        node_name: Option<&str>,
-        pod_name: Option<&str>,
-        registry_filter: Option<&str>,
-        exclude_registry_filter: &[String],
-        all_namespaces: bool,
-    ) -> Result<Vec<PodImage>> {
-        debug!(
-            namespace = %namespace,
-            node = ?node_name,
-            pod = ?pod_name,
-            registry = ?registry_filter,
-            exclude_registry = ?exclude_registry_filter,
-            all_namespaces = %all_namespaces,
-            "Fetching pod images"
-        );
-
-        if !all_namespaces && !self.namespace_exists(namespace).await? {
-            let resource = format!("Namespace {} not found", namespace);
-            return Err(K8sError::ResourceNotFound(resource).into());
-        }
-
-        let list_params = Self::build_list_params(node_name, pod_name);
-        let pods = self.get_pods_api(namespace, all_namespaces, node_name)?;
-
-        let pods_list = pods
-            .list(&list_params)
-            .await
-            .context("Failed to list pods")?;
-
-        debug!("Found {} pods", pods_list.items.len());
-
-        if pods_list.items.is_empty() {
-            let resource = match (node_name, pod_name) {
-                (Some(node), Some(pod)) => format!("pod {} on node {}", pod, node),
-                (Some(node), None) => format!("pods on node {}", node),
-                (None, Some(pod)) => format!("pod {}", pod),
-                (None, None) => format!("pods in namespace {}", namespace),
-            };
-            return Err(K8sError::ResourceNotFound(resource).into());
-        }
-
-        let mut all_images = Vec::new();
-        for pod in pods_list {
-            if !Self::should_process_pod(&pod, all_namespaces, node_name, pod_name) {
-                continue;
-            }
-
-            let pod_images = process_pod(&pod);
-            debug!(images = pod_images.len(), "Processed pod images");
-            all_images.extend(pod_images);
-        }
-
-        if let Some(registry_filter) = registry_filter {
-            let before_count = all_images.len();
-            all_images.retain(|image| image.registry == registry_filter);
-            debug!(
-                before = before_count,
-                after = all_images.len(),
-                registry = %registry_filter,
-                "Filtered images by registry"
-            );
-        }
-
-        if !exclude_registry_filter.is_empty() {
-            let before_count = all_images.len();
-            all_images.retain(|image| !exclude_registry_filter.contains(&image.registry));
-            debug!(
-                before = before_count,
-                after = all_images.len(),
-                registries = ?exclude_registry_filter,
-                "Filtered images by exclude_registry"
-            );
-        }
-
-        let nodes_api: Api<Node> = Api::all(self.client.clone());
-
-        // hashmap: node_name -> { digest -> size_bytes }
-        let mut node_to_digest_size: std::collections::HashMap<
-            String,
-            std::collections::HashMap<String, u64>,
-        > = std::collections::HashMap::new();
-
-        // Determine needed nodes (assume every image has a digest)
-        let all_nodes: std::collections::HashSet<String> = all_images
-            .iter()
-            .filter(|pi| !pi.node_name.is_empty())
-            .map(|pi| pi.node_name.clone())
-            .collect();
-
-        node_to_digest_size.reserve(all_nodes.len());
-
-        let Ok(node_list) = nodes_api.list(&ListParams::default()).await else {
-            info!("Skipping node image size enrichment due to node list failure");
-            return Ok(all_images);
-        };
-
-        node_list
-            .into_iter()
-            .filter_map(|node| {
-                let name = node.metadata.name.clone()?;
-                if !all_nodes.contains(&name) {
-                    return None;
-                }
-                let node_images = node.status?.images?;
-
-                let digest_map: std::collections::HashMap<String, u64> = node_images
-                    .into_iter()
-                    .filter_map(|img| {
-                        let size = img.size_bytes.unwrap_or(0) as u64;
-                        img.names.and_then(|names| {
-                            names.iter().find_map(|name| {
-                                name.find('@')
-                                    .map(|idx| (name[idx + 1..].to_string(), size))
-                            })
-                        })
-                    })
-                    .fold(
-                        std::collections::HashMap::new(),
-                        |mut acc, (digest, size)| {
-                            acc.entry(digest)
-                                .and_modify(|v| *v = (*v).max(size))
-                                .or_insert(size);
-                            acc
-                        },
-                    );
-
-                (!digest_map.is_empty()).then_some((name, digest_map))
-            })
-            .for_each(|(name, digest_map)| {
-                node_to_digest_size.insert(name, digest_map);
-            });
-
-        all_images
-            .iter_mut()
-            .filter(|img| img.image_size.is_empty() && !img.node_name.is_empty())
-            .for_each(|img| {
-                if let Some(size) = node_to_digest_size
-                    .get(&img.node_name)
-                    .and_then(|dmap| dmap.get(&img.digest))
-                {
-                    img.image_size = format_bytes(*size);
-                }
-            });
-
-        info!(
-            total_images = all_images.len(),
-            "Successfully retrieved pod images"
-        );
-        Ok(all_images)
+    #[instrument(skip(self))]
+    pub async fn get_pod_images(&self, query: &ImageQuery) -> Result<Vec<PodImage>> {
+        debug!("Fetching pod images");
+        collect_pod_images(self, query).await
     }
 
     /// Build list parameters for pod queries
-    fn build_list_params(node_name: Option<&str>, pod_name: Option<&str>) -> ListParams {
+    fn build_list_params(query: &ImageQuery) -> ListParams {
         let mut field_selectors = Vec::new();
 
-        if let Some(node) = node_name {
+        if let Some(node) = query.get_node() {
             field_selectors.push(format!("spec.nodeName={}", node));
         }
 
-        if let Some(name) = pod_name {
+        if let Some(name) = query.get_pod() {
             field_selectors.push(format!("metadata.name={}", name));
         }
 
@@ -356,28 +413,6 @@ impl K8sClient {
         Ok(api)
     }
 
-    /// Check if a pod should be processed based on filters
-    fn should_process_pod(
-        pod: &Pod,
-        _all_namespaces: bool,
-        node_name: Option<&str>,
-        pod_name: Option<&str>,
-    ) -> bool {
-        if let Some(name) = pod_name {
-            if pod.metadata.name.as_deref() != Some(name) {
-                return false;
-            }
-        }
-
-        if let Some(node) = node_name {
-            if pod.spec.as_ref().and_then(|s| s.node_name.as_deref()) != Some(node) {
-                return false;
-            }
-        }
-
-        true
-    }
-
     /// Get unique container image registries used in the cluster
     ///
     /// # Arguments
@@ -417,6 +452,14 @@ impl K8sClient {
         let deployments = deployments_api
             .list(&Default::default())
             .await
+            .map_err(|e| {
+                api_error(
+                    e,
+                    "list",
+                    "deployments",
+                    (!all_namespaces).then_some(namespace),
+                )
+            })
             .context("Failed to list deployments")?;
 
         debug!("Found {} deployments", deployments.items.len());
@@ -433,7 +476,9 @@ impl K8sClient {
                     for container in pod_spec.containers {
                         if let Some(image) = container.image {
                             let registry = extract_registry(&image);
-                            registries.insert(registry);
+                            if !registry.is_empty() {
+                                registries.insert(registry);
+                            }
                         }
                     }
                 }
@@ -450,190 +495,953 @@ impl K8sClient {
         Ok(registries_vec)
     }
 
-    /// Check if a namespace exists
+    /// Get unique container image registries of CronJob and Job pod templates
+    ///
+    /// Jobs with active pods are left out, and so are workloads whose list is forbidden.
     ///
     /// # Arguments
     ///
-    /// * `namespace` - The name of the namespace to check
+    /// * `namespace` - The namespace to search in
+    /// * `all_namespaces` - Whether to search in all namespaces
     ///
     /// # Returns
     ///
-    /// * `Result<bool>` - True if the namespace exists, false otherwise, or an error if the API call fails
-    #[instrument(skip(self), fields(namespace = %namespace))]
-    pub async fn namespace_exists(&self, namespace: &str) -> Result<bool> {
-        debug!(namespace = %namespace, "Checking if namespace exists");
-        let namespaces_api: Api<k8s_openapi::api::core::v1::Namespace> =
-            Api::all(self.client.clone());
-        match namespaces_api.get(namespace).await {
-            Ok(_) => {
-                debug!(namespace = %namespace, "Namespace found");
-                Ok(true)
-            }
-            Err(kube::Error::Api(api_err)) if api_err.code == 404 => {
-                debug!(namespace = %namespace, "Namespace not found");
-                Ok(false)
-            }
-            Err(e) => {
-                error!(namespace = %namespace, error = %e, "Failed to check namespace existence");
-                Err(
-                    K8sError::ApiError(format!("Failed to check namespace {}: {}", namespace, e))
-                        .into(),
-                )
-            }
-        }
-    }
-}
+    /// * `Result<Vec<String>>` - Sorted list of unique registries or an error
+    #[instrument(skip(self), fields(
+        namespace = %namespace,
+        all_namespaces = %all_namespaces
+    ))]
+    pub async fn get_scheduled_registries(
+        &self,
+        namespace: &str,
+        all_namespaces: bool,
+    ) -> Result<Vec<String>> {
+        let query = ImageQuery::new()
+            .namespace(namespace)
+            .all_namespaces(all_namespaces);
+        let pods = self.list_scheduled_pods(&query).await?;
 
-/// Extract the registry from a container image reference
-///
-/// # Arguments
-///
-/// * `image` - The container image reference
-///
-/// # Returns
-///
-/// * `String` - The registry name
-pub fn extract_registry(image: &str) -> String {
-    // Split the image string by '/'
-    let parts: Vec<&str> = image.split('/').collect();
+        let registries: std::collections::BTreeSet<String> = pods
+            .iter()
+            .flat_map(process_pod)
+            .map(|image| image.registry)
+            .filter(|registry| !registry.is_empty())
+            .collect();
 
-    // If there's only one part (e.g., "ubuntu" or "nginx"), it's a Docker Hub official image
-    if parts.len() == 1 {
-        return "docker.io".to_string();
+        info!(
+            total_registries = registries.len(),
+            "Successfully retrieved unique registries from scheduled workloads"
+        );
+        Ok(registries.into_iter().collect())
     }
 
-    // If there are two parts without dots or colons in the first part (e.g., "library/ubuntu"),
-    // it's likely a Docker Hub image with namespace
-    if parts.len() == 2 && !parts[0].contains('.') && !parts[0].contains(':') {
-        return "docker.io".to_string();
-    }
+    /// Get containers whose images cannot be pulled, along with the failure details
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace to search in
+    /// * `all_namespaces` - Whether to search in all namespaces
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<ImageError>>` - List of image pull failures or an error
+    #[instrument(skip(self), fields(
+        namespace = %namespace,
+        all_namespaces = %all_namespaces
+    ))]
+    pub async fn get_image_errors(
+        &self,
+        namespace: &str,
+        all_namespaces: bool,
+    ) -> Result<Vec<ImageError>> {
+        debug!(
+            namespace = %namespace,
+            all_namespaces = %all_namespaces,
+            "Fetching image pull errors"
+        );
 
-    // Get the potential registry (first part)
-    let potential_registry = parts[0];
+        if !all_namespaces && !self.namespace_exists(namespace).await? {
+            let resource = format!("Namespace {} not found", namespace);
+            return Err(K8sError::ResourceNotFound(resource).into());
+        }
 
-    // Check for localhost variants (with or without port)
-    if potential_registry == "localhost"
-        || potential_registry.starts_with("localhost:")
-        || potential_registry.starts_with("127.0.0.1")
-        || potential_registry.starts_with("0.0.0.0")
-        || potential_registry.starts_with("[::1]")
-    {
-        return potential_registry.to_string();
-    }
+        let pods = self.get_pods_api(namespace, all_namespaces, None)?;
+        let pods_list = pods
+            .list(&ListParams::default())
+            .await
+            .map_err(|e| api_error(e, "list", "pods", (!all_namespaces).then_some(namespace)))
+            .context("Failed to list pods")?;
 
-    // Check for IPv4 address (with or without port)
-    let ip_parts: Vec<&str> = potential_registry.split(':').collect();
-    let ip = ip_parts[0];
-    if ip.split('.').filter(|&p| !p.is_empty()).count() == 4
-        && ip.split('.').all(|p| p.parse::<u8>().is_ok())
-    {
-        return potential_registry.to_string();
-    }
+        debug!("Found {} pods", pods_list.items.len());
 
-    // Check for IPv6 address (with or without port)
-    if potential_registry.starts_with('[') && potential_registry.contains(']') {
-        return potential_registry.to_string();
-    }
+        let mut errors: Vec<ImageError> = pods_list.iter().flat_map(process_pod_errors).collect();
 
-    // Check for known public registries
-    let known_registries = KNOWN_REGISTRIES;
-    for registry in &known_registries {
-        if potential_registry == *registry || potential_registry.ends_with(*registry) {
-            return potential_registry.to_string();
+        if errors.is_empty() {
+            info!("No image pull errors found");
+            return Ok(errors);
         }
-    }
-
-    // For any domain with dots (e.g., "my-registry.example.com") or with port (e.g., "registry:5000")
-    if potential_registry.contains('.') || potential_registry.contains(':') {
-        return potential_registry.to_string();
-    }
 
-    // Default to Docker Hub if none of the above matches
-    "docker.io".to_string()
-}
+        let events_api: Api<Event> = if all_namespaces {
+            Api::all(self.client.clone())
+        } else {
+            Api::namespaced(self.client.clone(), namespace)
+        };
 
-/// Split a container image reference into name and version
-///
-/// # Arguments
-///
-/// * `image` - The container image reference
-///
-/// # Returns
-///
-/// * `(String, String)` - Tuple of (image name, image version)
-pub fn split_image(image: &str) -> (String, String) {
-    // First check for a digest (SHA)
-    if let Some(digest_index) = image.find('@') {
-        // We have a digest, get the part before the digest
-        let image_with_tag = &image[..digest_index];
-        let digest = &image[digest_index..]; // includes the @ symbol
-
-        // Find the last colon which separates the image name from the tag
-        if let Some(tag_index) = image_with_tag.rfind(':') {
-            // Check if this colon is part of a port number in the registry
-            // Look for slashes to determine if this is likely a registry port
-            let last_slash_index = image_with_tag.rfind('/').unwrap_or(0);
-
-            if tag_index > last_slash_index {
-                // This colon is after the last slash, so it's a tag separator
-                let name = &image_with_tag[..tag_index];
-                let tag = &image_with_tag[tag_index + 1..];
-                (name.to_string(), format!("{}@{}", tag, &digest[1..]))
-            } else {
-                // This colon is part of the registry address, no tag specified
-                (
-                    image_with_tag.to_string(),
-                    format!("latest@{}", &digest[1..]),
-                )
+        let event_params = ListParams::default().fields("involvedObject.kind=Pod,reason=Failed");
+        match events_api.list(&event_params).await {
+            Ok(events) => {
+                debug!("Found {} failed pod events", events.items.len());
+                attach_event_messages(&mut errors, &events.items);
             }
-        } else {
-            // No tag present, use "latest" with the digest
-            (
-                image_with_tag.to_string(),
-                format!("latest@{}", &digest[1..]),
-            )
-        }
-    } else {
-        // No digest, handle image name and tag
-        // Find the last colon which might separate the image name from the tag
-        if let Some(tag_index) = image.rfind(':') {
-            // Check if this colon is part of a port number in the registry
-            // Look for slashes to determine if this is likely a registry port
-            let last_slash_index = image.rfind('/').unwrap_or(0);
-
-            if tag_index > last_slash_index {
-                // This colon is after the last slash, so it's a tag separator
-                let name = &image[..tag_index];
-                let tag = &image[tag_index + 1..];
-                return (name.to_string(), tag.to_string());
+            Err(e) => {
+                warn!(error = %e, "Skipping event enrichment due to event list failure");
             }
         }
 
-        // No valid tag separator found
-        (image.to_string(), "latest".to_string())
+        info!(
+            total_errors = errors.len(),
+            "Successfully retrieved image pull errors"
+        );
+        Ok(errors)
     }
-}
 
-/// Extract the digest of a container from a pod
-///
-/// # Arguments
-///
-/// * `pod` - The pod containing the container
-/// * `container_name` - The name of the container
-///
-/// # Returns
-///
-/// * `Option<String>` - The container digest if available
-fn extract_container_digest(pod: &Pod, container_name: &str) -> Option<String> {
-    let image_id = pod
-        .status
-        .as_ref()?
-        .container_statuses
-        .as_ref()?
-        .iter()
-        .find(|cs| cs.name == container_name)?
-        .image_id
-        .clone();
+    /// Get image pull durations recorded in kubelet events, slowest first
+    ///
+    /// Events are only retained by the API server for a limited time (one hour by
+    /// default), so only recent pulls are reported.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace to search in
+    /// * `all_namespaces` - Whether to search in all namespaces
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<ImagePull>>` - List of image pulls or an error
+    #[instrument(skip(self), fields(
+        namespace = %namespace,
+        all_namespaces = %all_namespaces
+    ))]
+    pub async fn get_image_pulls(
+        &self,
+        namespace: &str,
+        all_namespaces: bool,
+    ) -> Result<Vec<ImagePull>> {
+        debug!(
+            namespace = %namespace,
+            all_namespaces = %all_namespaces,
+            "Fetching image pull events"
+        );
+
+        if !all_namespaces && !self.namespace_exists(namespace).await? {
+            let resource = format!("Namespace {} not found", namespace);
+            return Err(K8sError::ResourceNotFound(resource).into());
+        }
+
+        let events_api: Api<Event> = if all_namespaces {
+            Api::all(self.client.clone())
+        } else {
+            Api::namespaced(self.client.clone(), namespace)
+        };
+
+        let events = events_api
+            .list(&ListParams::default().fields("involvedObject.kind=Pod"))
+            .await
+            .map_err(|e| api_error(e, "list", "events", (!all_namespaces).then_some(namespace)))
+            .context("Failed to list events")?;
+
+        debug!("Found {} pod events", events.items.len());
+
+        let pods = self.get_pods_api(namespace, all_namespaces, None)?;
+        let pod_images: Vec<PodImage> = pods
+            .list(&ListParams::default())
+            .await
+            .map_err(|e| api_error(e, "list", "pods", (!all_namespaces).then_some(namespace)))
+            .context("Failed to list pods")?
+            .iter()
+            .flat_map(process_pod)
+            .collect();
+
+        let pulls = process_pull_events(&events.items, &pod_images);
+
+        info!(
+            total_pulls = pulls.len(),
+            "Successfully retrieved image pulls"
+        );
+        Ok(pulls)
+    }
+
+    /// Get runtime and platform information of the cluster nodes
+    ///
+    /// # Arguments
+    ///
+    /// * `node_name` - Optional node name filter
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<NodeInfo>>` - List of node information or an error
+    #[instrument(skip(self), fields(node = ?node_name))]
+    pub async fn get_nodes(&self, node_name: Option<&str>) -> Result<Vec<NodeInfo>> {
+        debug!(node = ?node_name, "Fetching node information");
+
+        let nodes_api: Api<Node> = Api::all(self.client.clone());
+        let mut list_params = ListParams::default();
+        if let Some(node) = node_name {
+            list_params = list_params.fields(&format!("metadata.name={}", node));
+        }
+
+        let node_list = nodes_api
+            .list(&list_params)
+            .await
+            .map_err(|e| api_error(e, "list", "nodes", None))
+            .context("Failed to list nodes")?;
+
+        if node_list.items.is_empty() {
+            let resource = match node_name {
+                Some(node) => format!("node {}", node),
+                None => "nodes".to_string(),
+            };
+            return Err(K8sError::ResourceNotFound(resource).into());
+        }
+
+        let mut nodes: Vec<NodeInfo> = node_list.iter().filter_map(process_node).collect();
+        nodes.sort_by(|a, b| a.name.cmp(&b.name));
+
+        info!(total_nodes = nodes.len(), "Successfully retrieved nodes");
+        Ok(nodes)
+    }
+
+    /// Get containers whose image does not support the architecture of their node
+    ///
+    /// Image platforms are read from the registry; images whose manifests cannot be
+    /// fetched anonymously are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace to search in
+    /// * `all_namespaces` - Whether to search in all namespaces
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<ArchMismatch>>` - List of architecture mismatches or an error
+    #[instrument(skip(self), fields(
+        namespace = %namespace,
+        all_namespaces = %all_namespaces
+    ))]
+    pub async fn get_arch_mismatches(
+        &self,
+        namespace: &str,
+        all_namespaces: bool,
+    ) -> Result<Vec<ArchMismatch>> {
+        debug!(
+            namespace = %namespace,
+            all_namespaces = %all_namespaces,
+            "Checking image architectures"
+        );
+
+        if !all_namespaces && !self.namespace_exists(namespace).await? {
+            let resource = format!("Namespace {} not found", namespace);
+            return Err(K8sError::ResourceNotFound(resource).into());
+        }
+
+        let pods = self.get_pods_api(namespace, all_namespaces, None)?;
+        let images: Vec<PodImage> = pods
+            .list(&ListParams::default())
+            .await
+            .map_err(|e| api_error(e, "list", "pods", (!all_namespaces).then_some(namespace)))
+            .context("Failed to list pods")?
+            .iter()
+            .flat_map(process_pod)
+            .filter(|image| !image.node_name.is_empty())
+            .collect();
+
+        let nodes = self.get_nodes(None).await?;
+
+        let references: std::collections::HashSet<(String, String, String)> = images
+            .iter()
+            .map(|image| {
+                let (repository, reference) = manifest_reference(image);
+                (image.registry.clone(), repository, reference)
+            })
+            .collect();
+
+        debug!(
+            images = references.len(),
+            "Fetching image platforms from registries"
+        );
+
+        let registry_client = RegistryClient::new()?;
+        let lookups = references.into_iter().map(|key| {
+            let registry_client = &registry_client;
+            async move {
+                let result = registry_client.get_platforms(&key.0, &key.1, &key.2).await;
+                (key, result)
+            }
+        });
+
+        let results: Vec<_> = futures::stream::iter(lookups)
+            .buffer_unordered(MAX_CONCURRENT_MANIFESTS)
+            .collect()
+            .await;
+
+        let mut platforms = std::collections::HashMap::new();
+        for (key, result) in results {
+            match result {
+                Ok(list) => {
+                    platforms.insert(key, list);
+                }
+                Err(e) => {
+                    warn!(
+                        image = %format!("{}/{}:{}", key.0, key.1, key.2),
+                        error = %e,
+                        "Skipping architecture check, manifest unavailable"
+                    );
+                }
+            }
+        }
+
+        let mismatches = find_arch_mismatches(&images, &nodes, &platforms);
+
+        info!(
+            checked = platforms.len(),
+            mismatches = mismatches.len(),
+            "Successfully checked image architectures"
+        );
+        Ok(mismatches)
+    }
+
+    /// Get containers whose image differs from their workload's pod template or from what they run
+    ///
+    /// Templates are only listed for the kinds of workloads owning the pods; when a
+    /// kind cannot be listed its pods are only checked against what they run.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace to search in
+    /// * `all_namespaces` - Whether to search in all namespaces
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<ImageMismatch>>` - List of image mismatches or an error
+    #[instrument(skip(self), fields(
+        namespace = %namespace,
+        all_namespaces = %all_namespaces
+    ))]
+    pub async fn get_image_mismatches(
+        &self,
+        namespace: &str,
+        all_namespaces: bool,
+    ) -> Result<Vec<ImageMismatch>> {
+        debug!(
+            namespace = %namespace,
+            all_namespaces = %all_namespaces,
+            "Comparing pod images with templates and running images"
+        );
+
+        if !all_namespaces && !self.namespace_exists(namespace).await? {
+            let resource = format!("Namespace {} not found", namespace);
+            return Err(K8sError::ResourceNotFound(resource).into());
+        }
+
+        let pods = self
+            .get_pods_api(namespace, all_namespaces, None)?
+            .list(&ListParams::default())
+            .await
+            .map_err(|e| api_error(e, "list", "pods", (!all_namespaces).then_some(namespace)))
+            .context("Failed to list pods")?
+            .items;
+        debug!("Found {} pods", pods.len());
+
+        let kinds: HashSet<String> = pods
+            .iter()
+            .filter_map(|pod| {
+                let workload = pod_workload(pod);
+                workload.split_once('/').map(|(kind, _)| kind.to_string())
+            })
+            .collect();
+
+        let mut templates = WorkloadTemplates::new();
+        let scope = (!all_namespaces).then_some(namespace);
+        if kinds.contains("Deployment") {
+            let listed = self
+                .list_pod_templates::<Deployment>(
+                    scope,
+                    "deployments",
+                    &ListParams::default(),
+                    |deployment| deployment.spec.as_ref()?.template.spec.as_ref(),
+                )
+                .await;
+            extend_templates(&mut templates, listed);
+        }
+        if kinds.contains("StatefulSet") {
+            let listed = self
+                .list_pod_templates::<StatefulSet>(
+                    scope,
+                    "statefulsets",
+                    &ListParams::default(),
+                    |statefulset| statefulset.spec.as_ref()?.template.spec.as_ref(),
+                )
+                .await;
+            extend_templates(&mut templates, listed);
+        }
+        if kinds.contains("DaemonSet") {
+            let listed = self
+                .list_pod_templates::<DaemonSet>(
+                    scope,
+                    "daemonsets",
+                    &ListParams::default(),
+                    |daemonset| daemonset.spec.as_ref()?.template.spec.as_ref(),
+                )
+                .await;
+            extend_templates(&mut templates, listed);
+        }
+
+        let mismatches = find_image_mismatches(&pods, &templates);
+
+        info!(
+            pods = pods.len(),
+            templates = templates.len(),
+            mismatches = mismatches.len(),
+            "Successfully compared pod images"
+        );
+        Ok(mismatches)
+    }
+
+    /// Get the image rollout progress of a workload
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - The kind of the workload
+    /// * `namespace` - The namespace of the workload
+    /// * `name` - The name of the workload
+    ///
+    /// # Returns
+    ///
+    /// * `Result<RolloutImages>` - The image versions per container or an error if the
+    ///   workload is not found
+    #[instrument(skip(self), fields(kind = %kind, namespace = %namespace, name = %name))]
+    pub async fn get_rollout_images(
+        &self,
+        kind: WorkloadKind,
+        namespace: &str,
+        name: &str,
+    ) -> Result<RolloutImages> {
+        debug!(kind = %kind, namespace = %namespace, name = %name, "Fetching rollout images");
+
+        if !self.namespace_exists(namespace).await? {
+            let resource = format!("Namespace {} not found", namespace);
+            return Err(K8sError::ResourceNotFound(resource).into());
+        }
+
+        let params = ListParams::default().fields(&format!("metadata.name={}", name));
+        let scope = Some(namespace);
+        let workload = format!("{}/{}", kind.kind(), name);
+        let (template, revision) = match kind {
+            WorkloadKind::Deployment => {
+                let deployment = self
+                    .list_objects::<Deployment>(scope, "deployments", &params)
+                    .await?
+                    .into_iter()
+                    .next();
+                let replica_sets = match &deployment {
+                    Some(_) => {
+                        self.list_objects::<ReplicaSet>(
+                            scope,
+                            "replicasets",
+                            &ListParams::default(),
+                        )
+                        .await?
+                    }
+                    None => Vec::new(),
+                };
+                deployment.map(|deployment| {
+                    let revision = WorkloadRevision::of_deployment(&deployment, &replica_sets);
+                    (
+                        deployment.spec.and_then(|spec| spec.template.spec),
+                        revision,
+                    )
+                })
+            }
+            WorkloadKind::StatefulSet => self
+                .list_objects::<StatefulSet>(scope, "statefulsets", &params)
+                .await?
+                .into_iter()
+                .next()
+                .map(|statefulset| {
+                    let revision = WorkloadRevision::of_statefulset(&statefulset);
+                    (
+                        statefulset.spec.and_then(|spec| spec.template.spec),
+                        revision,
+                    )
+                }),
+            WorkloadKind::DaemonSet => self
+                .list_objects::<DaemonSet>(scope, "daemonsets", &params)
+                .await?
+                .into_iter()
+                .next()
+                .map(|daemonset| {
+                    let revision = WorkloadRevision::of_daemonset(&daemonset);
+                    (daemonset.spec.and_then(|spec| spec.template.spec), revision)
+                }),
+        }
+        .and_then(|(template, revision)| Some((template?, revision)))
+        .ok_or_else(|| {
+            let resource = format!("{} {} in namespace {}", kind.kind(), name, namespace);
+            K8sError::ResourceNotFound(resource)
+        })?;
+
+        let pods = self
+            .get_pods_api(namespace, false, None)?
+            .list(&ListParams::default())
+            .await
+            .map_err(|e| api_error(e, "list", "pods", Some(namespace)))
+            .context("Failed to list pods")?
+            .items;
+        let rollout = summarize_rollout(namespace, &workload, &template, revision.as_ref(), &pods);
+
+        info!(
+            workload = %workload,
+            containers = rollout.containers.len(),
+            "Successfully retrieved rollout images"
+        );
+        Ok(rollout)
+    }
+
+    /// Review the permissions kimspect commands need for the current identity
+    ///
+    /// Each permission is checked with a SelfSubjectAccessReview, so no resources
+    /// are read. Namespace lookups are only reviewed for a single namespace, as
+    /// they are skipped when querying all namespaces.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace to review namespaced permissions in
+    /// * `all_namespaces` - Whether to review namespaced permissions across all namespaces
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<PermissionCheck>>` - The result of each review or an error
+    #[instrument(skip(self), fields(
+        namespace = %namespace,
+        all_namespaces = %all_namespaces
+    ))]
+    pub async fn check_permissions(
+        &self,
+        namespace: &str,
+        all_namespaces: bool,
+    ) -> Result<Vec<PermissionCheck>> {
+        debug!(
+            namespace = %namespace,
+            all_namespaces = %all_namespaces,
+            "Reviewing permissions"
+        );
+
+        let reviews_api: Api<SelfSubjectAccessReview> = Api::all(self.client.clone());
+        let reviews = PERMISSIONS
+            .iter()
+            .filter(|permission| !(all_namespaces && **permission == GET_NAMESPACES))
+            .map(|permission| {
+                let reviews_api = &reviews_api;
+                let scope =
+                    (permission.namespaced && !all_namespaces).then(|| namespace.to_string());
+                let name = (*permission == GET_NAMESPACES).then(|| namespace.to_string());
+                async move {
+                    let review = SelfSubjectAccessReview {
+                        spec: SelfSubjectAccessReviewSpec {
+                            resource_attributes: Some(ResourceAttributes {
+                                verb: Some(permission.verb.to_string()),
+                                group: Some(permission.group.to_string()),
+                                resource: Some(permission.resource.to_string()),
+                                namespace: scope.clone(),
+                                name,
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    };
+                    let status = reviews_api
+                        .create(&PostParams::default(), &review)
+                        .await
+                        .map_err(|e| api_error(e, "create", "selfsubjectaccessreviews", None))?
+                        .status
+                        .unwrap_or_default();
+                    debug!(
+                        permission = %permission,
+                        allowed = status.allowed,
+                        "Reviewed permission"
+                    );
+                    Ok::<_, K8sError>(PermissionCheck::new(
+                        *permission,
+                        scope,
+                        status.allowed,
+                        status.reason.unwrap_or_default(),
+                    ))
+                }
+            });
+
+        let checks = futures::future::try_join_all(reviews)
+            .await
+            .context("Failed to review permissions")?;
+
+        info!(
+            total_checks = checks.len(),
+            denied = checks.iter().filter(|check| !check.allowed).count(),
+            "Successfully reviewed permissions"
+        );
+        Ok(checks)
+    }
+
+    /// Check if a namespace exists
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The name of the namespace to check
+    ///
+    /// # Returns
+    ///
+    /// * `Result<bool>` - True if the namespace exists or cannot be read, false otherwise, or an error if the API call fails
+    #[instrument(skip(self), fields(namespace = %namespace))]
+    pub async fn namespace_exists(&self, namespace: &str) -> Result<bool> {
+        debug!(namespace = %namespace, "Checking if namespace exists");
+        let namespaces_api: Api<k8s_openapi::api::core::v1::Namespace> =
+            Api::all(self.client.clone());
+        match namespaces_api.get(namespace).await {
+            Ok(_) => {
+                debug!(namespace = %namespace, "Namespace found");
+                Ok(true)
+            }
+            Err(kube::Error::Api(api_err)) if api_err.code == 404 => {
+                debug!(namespace = %namespace, "Namespace not found");
+                Ok(false)
+            }
+            Err(kube::Error::Api(api_err)) if api_err.code == 403 => {
+                // Users limited to a namespace may not read Namespace objects;
+                // the request for the resources themselves reports missing access
+                debug!(namespace = %namespace, "Not allowed to get namespace, assuming it exists");
+                Ok(true)
+            }
+            Err(e) => {
+                error!(namespace = %namespace, error = %e, "Failed to check namespace existence");
+                Err(
+                    K8sError::ApiError(format!("Failed to check namespace {}: {}", namespace, e))
+                        .into(),
+                )
+            }
+        }
+    }
+
+    /// List namespaced objects
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace to list in, or None for all namespaces
+    /// * `resource` - The plural resource name, for error messages
+    /// * `params` - The list parameters, e.g. a field selector
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<K>>` - The listed objects or an error
+    async fn list_objects<K>(
+        &self,
+        namespace: Option<&str>,
+        resource: &str,
+        params: &ListParams,
+    ) -> Result<Vec<K>>
+    where
+        K: kube::Resource<Scope = NamespaceResourceScope, DynamicType = ()>
+            + Clone
+            + DeserializeOwned
+            + std::fmt::Debug,
+    {
+        let api: Api<K> = match namespace {
+            Some(namespace) => Api::namespaced(self.client.clone(), namespace),
+            None => Api::all(self.client.clone()),
+        };
+        Ok(api
+            .list(params)
+            .await
+            .map_err(|e| api_error(e, "list", resource, namespace))
+            .with_context(|| format!("Failed to list {}", resource))?
+            .items)
+    }
+
+    /// List the pod templates of a kind of workload
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace to list in, or None for all namespaces
+    /// * `resource` - The plural resource name, for error messages
+    /// * `params` - The list parameters, e.g. a field selector on the name
+    /// * `template` - Get the pod spec of a workload's template
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<((String, String), PodSpec)>>` - The templates keyed by namespace and
+    ///   Kind/name, or an error
+    async fn list_pod_templates<K>(
+        &self,
+        namespace: Option<&str>,
+        resource: &str,
+        params: &ListParams,
+        template: fn(&K) -> Option<&PodSpec>,
+    ) -> Result<Vec<((String, String), PodSpec)>>
+    where
+        K: kube::Resource<Scope = NamespaceResourceScope, DynamicType = ()>
+            + Clone
+            + DeserializeOwned
+            + std::fmt::Debug,
+    {
+        let workloads = self.list_objects::<K>(namespace, resource, params).await?;
+
+        Ok(workloads
+            .iter()
+            .filter_map(|workload| {
+                let metadata = workload.meta();
+                let key = (
+                    metadata.namespace.clone().unwrap_or_default(),
+                    format!("{}/{}", K::kind(&()), metadata.name.as_deref()?),
+                );
+                Some((key, template(workload)?.clone()))
+            })
+            .collect())
+    }
+
+    /// List batch workloads, skipping them when the list fails
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace to list in, or None for all namespaces
+    /// * `resource` - The plural resource name, for error messages
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<K>` - The listed workloads, empty when the list failed
+    async fn list_batch_objects<K>(&self, namespace: Option<&str>, resource: &str) -> Vec<K>
+    where
+        K: kube::Resource<Scope = NamespaceResourceScope, DynamicType = ()>
+            + Clone
+            + DeserializeOwned
+            + std::fmt::Debug,
+    {
+        match self
+            .list_objects::<K>(namespace, resource, &ListParams::default())
+            .await
+        {
+            Ok(workloads) => workloads,
+            Err(e) => {
+                warn!(
+                    error = %format!("{:#}", e),
+                    "Skipping scheduled images due to {} list failure, run `kimspect auth check` to review permissions",
+                    resource
+                );
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// Add listed pod templates, skipping template checks when the list failed
+fn extend_templates(
+    templates: &mut WorkloadTemplates,
+    listed: Result<Vec<((String, String), PodSpec)>>,
+) {
+    match listed {
+        Ok(listed) => templates.extend(listed),
+        Err(e) => warn!(
+            error = %format!("{:#}", e),
+            "Skipping template checks due to workload list failure, run `kimspect auth check` to review permissions"
+        ),
+    }
+}
+
+impl PodSource for K8sClient {
+    #[instrument(skip(self))]
+    async fn list_pods(&self, query: &ImageQuery) -> Result<Vec<Pod>> {
+        let namespace = query.get_namespace();
+        let all_namespaces = query.is_all_namespaces();
+
+        if !all_namespaces && !self.namespace_exists(namespace).await? {
+            let resource = format!("Namespace {} not found", namespace);
+            return Err(K8sError::ResourceNotFound(resource).into());
+        }
+
+        let pods = self.get_pods_api(namespace, all_namespaces, query.get_node())?;
+        let pods_list = pods
+            .list(&Self::build_list_params(query))
+            .await
+            .map_err(|e| api_error(e, "list", "pods", (!all_namespaces).then_some(namespace)))
+            .context("Failed to list pods")?;
+
+        Ok(pods_list.items)
+    }
+
+    #[instrument(skip(self))]
+    async fn list_namespaces(&self, selector: &str) -> Result<Vec<String>> {
+        let namespaces_api: Api<k8s_openapi::api::core::v1::Namespace> =
+            Api::all(self.client.clone());
+        let namespaces = namespaces_api
+            .list(&ListParams::default().labels(selector))
+            .await
+            .map_err(|e| api_error(e, "list", "namespaces", None))
+            .context("Failed to list namespaces")?;
+        Ok(namespaces
+            .items
+            .into_iter()
+            .filter_map(|namespace| namespace.metadata.name)
+            .collect())
+    }
+
+    #[instrument(skip(self, owner), fields(kind = %owner.kind, name = %owner.name))]
+    async fn owner_metadata(
+        &self,
+        namespace: &str,
+        owner: &OwnerReference,
+    ) -> Result<Option<ObjectMeta>> {
+        let client = self.client.clone();
+        let name = owner.name.as_str();
+        let metadata = match owner.kind.as_str() {
+            "ReplicaSet" => {
+                Api::<ReplicaSet>::namespaced(client, namespace)
+                    .get_metadata(name)
+                    .await
+                    .map_err(|e| api_error(e, "get", "replicasets", Some(namespace)))?
+                    .metadata
+            }
+            "Deployment" => {
+                Api::<Deployment>::namespaced(client, namespace)
+                    .get_metadata(name)
+                    .await
+                    .map_err(|e| api_error(e, "get", "deployments", Some(namespace)))?
+                    .metadata
+            }
+            "StatefulSet" => {
+                Api::<StatefulSet>::namespaced(client, namespace)
+                    .get_metadata(name)
+                    .await
+                    .map_err(|e| api_error(e, "get", "statefulsets", Some(namespace)))?
+                    .metadata
+            }
+            "DaemonSet" => {
+                Api::<DaemonSet>::namespaced(client, namespace)
+                    .get_metadata(name)
+                    .await
+                    .map_err(|e| api_error(e, "get", "daemonsets", Some(namespace)))?
+                    .metadata
+            }
+            "Job" => {
+                Api::<Job>::namespaced(client, namespace)
+                    .get_metadata(name)
+                    .await
+                    .map_err(|e| api_error(e, "get", "jobs", Some(namespace)))?
+                    .metadata
+            }
+            "CronJob" => {
+                Api::<CronJob>::namespaced(client, namespace)
+                    .get_metadata(name)
+                    .await
+                    .map_err(|e| api_error(e, "get", "cronjobs", Some(namespace)))?
+                    .metadata
+            }
+            kind => {
+                debug!(kind = %kind, "Skipping unsupported owner kind");
+                return Ok(None);
+            }
+        };
+        Ok(Some(metadata))
+    }
+
+    #[instrument(skip(self))]
+    async fn list_scheduled_pods(&self, query: &ImageQuery) -> Result<Vec<Pod>> {
+        let namespace = (!query.is_all_namespaces()).then(|| query.get_namespace());
+
+        let cronjobs = self
+            .list_batch_objects::<CronJob>(namespace, "cronjobs")
+            .await;
+        let jobs = self.list_batch_objects::<Job>(namespace, "jobs").await;
+        Ok(scheduled_pods(
+            cronjobs,
+            jobs,
+            k8s_openapi::jiff::Timestamp::now(),
+        ))
+    }
+
+    #[instrument(skip(self))]
+    async fn list_nodes(&self) -> Result<Vec<Node>> {
+        let nodes_api: Api<Node> = Api::all(self.client.clone());
+        let nodes = nodes_api
+            .list(&ListParams::default())
+            .await
+            .map_err(|e| api_error(e, "list", "nodes", None))
+            .context("Failed to list nodes")?;
+        Ok(nodes.items)
+    }
+}
+
+/// Parse a container image reference, warning about invalid ones
+///
+/// Every function reading image references falls back the same way for
+/// invalid ones: the raw reference as name, with an empty registry and version.
+fn parse_image(image: &str) -> Option<ImageReference> {
+    match ImageReference::parse(image) {
+        Ok(reference) => Some(reference),
+        Err(e) => {
+            warn!(image = %image, error = %e, "Invalid image reference");
+            None
+        }
+    }
+}
+
+/// Extract the registry from a container image reference
+///
+/// Invalid references have an empty registry; use [`ImageReference::parse`]
+/// to report why a reference is invalid.
+///
+/// # Arguments
+///
+/// * `image` - The container image reference
+///
+/// # Returns
+///
+/// * `String` - The registry name, empty for invalid references
+pub fn extract_registry(image: &str) -> String {
+    parse_image(image)
+        .map(|reference| reference.registry().to_string())
+        .unwrap_or_default()
+}
+
+/// Split a container image reference into name and version
+///
+/// The name is the familiar repository name (e.g. `nginx` for
+/// `docker.io/library/nginx`) and the version is `tag`, `tag@digest` or `@digest`.
+/// Invalid references are returned unchanged with an empty version.
+///
+/// # Arguments
+///
+/// * `image` - The container image reference
+///
+/// # Returns
+///
+/// * `(String, String)` - Tuple of (image name, image version)
+pub fn split_image(image: &str) -> (String, String) {
+    match parse_image(image) {
+        Some(reference) => (reference.familiar_name(), reference.version()),
+        None => (image.to_string(), String::new()),
+    }
+}
+
+/// Extract the digest of a container from a pod
+///
+/// # Arguments
+///
+/// * `pod` - The pod containing the container
+/// * `container_name` - The name of the container
+///
+/// # Returns
+///
+/// * `Option<String>` - The container digest if available
+fn extract_container_digest(pod: &Pod, container_name: &str) -> Option<String> {
+    let image_id = find_container_status(pod, container_name)?.image_id.clone();
 
     // Try to find digest after '@' first (docker-pullable format)
     image_id
@@ -649,8 +1457,27 @@ fn extract_container_digest(pod: &Pod, container_name: &str) -> Option<String> {
         })
 }
 
+/// Find the status of a container in a pod
+///
+/// # Arguments
+///
+/// * `pod` - The pod containing the container
+/// * `container_name` - The name of the container
+///
+/// # Returns
+///
+/// * `Option<&ContainerStatus>` - The container status if reported by the kubelet
+fn find_container_status<'a>(pod: &'a Pod, container_name: &str) -> Option<&'a ContainerStatus> {
+    pod.status
+        .as_ref()?
+        .container_statuses
+        .as_ref()?
+        .iter()
+        .find(|cs| cs.name == container_name)
+}
+
 /// Format bytes to a human-readable string (e.g., 123.4MiB)
-fn format_bytes(bytes: u64) -> String {
+pub(crate) fn format_bytes(bytes: u64) -> String {
     const UNITS: [(u64, &str); 3] = [(1_073_741_824, "GiB"), (1_048_576, "MiB"), (1024, "KiB")];
 
     UNITS
@@ -678,15 +1505,24 @@ pub fn process_pod(pod: &Pod) -> Vec<PodImage> {
         .as_ref()
         .and_then(|spec| spec.node_name.clone())
         .unwrap_or_default();
+    let release = ReleaseInfo::from_metadata(&pod.metadata);
 
     if let Some(spec) = &pod.spec {
         let containers = &spec.containers;
         for container in containers {
             if let Some(image) = &container.image {
-                let registry = extract_registry(image);
-                let (_image_name, image_version) = split_image(image);
-                let image_name = strip_registry(&_image_name, &registry);
+                let (registry, image_name, image_version) = match parse_image(image) {
+                    Some(reference) => (
+                        reference.registry().to_string(),
+                        reference.familiar_repository().to_string(),
+                        reference.version(),
+                    ),
+                    None => (String::new(), image.clone(), String::new()),
+                };
                 let digest = extract_container_digest(pod, &container.name).unwrap_or_default();
+                let status = find_container_status(pod, &container.name)
+                    .map(container_state)
+                    .unwrap_or_default();
 
                 pod_images.push(PodImage {
                     pod_name: pod_name.clone(),
@@ -698,6 +1534,14 @@ pub fn process_pod(pod: &Pod) -> Vec<PodImage> {
                     registry,
                     digest,
                     image_size: String::new(),
+                    image_size_bytes: None,
+                    status,
+                    upstream_registry: String::new(),
+                    upstream_image: String::new(),
+                    registry_class: String::new(),
+                    release: release.release.clone(),
+                    chart: release.chart.clone(),
+                    managed_by: release.managed_by.clone(),
                 });
             }
         }
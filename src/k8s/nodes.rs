@@ -0,0 +1,139 @@
+use crate::k8s::PodImage;
+use crate::registry::{ImageReference, Platform};
+use k8s_openapi::api::core::v1::Node;
+use std::collections::HashMap;
+
+/// Runtime and platform information reported by a node's kubelet
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct NodeInfo {
+    /// Name of the node
+    pub name: String,
+    /// Operating system (e.g. linux)
+    pub operating_system: String,
+    /// CPU architecture (e.g. amd64, arm64)
+    pub architecture: String,
+    /// OS image (e.g. Ubuntu 22.04.4 LTS)
+    pub os_image: String,
+    /// Kernel version
+    pub kernel_version: String,
+    /// Container runtime and version (e.g. containerd://1.7.13)
+    pub container_runtime: String,
+    /// Kubelet version
+    pub kubelet_version: String,
+}
+
+/// Represents a container scheduled on a node whose architecture the image does not support
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ArchMismatch {
+    /// Name of the pod containing the container
+    pub pod_name: String,
+    /// Kubernetes namespace of the pod
+    pub namespace: String,
+    /// Name of the container
+    pub container_name: String,
+    /// Full image reference
+    pub image: String,
+    /// Name of the node the pod runs on
+    pub node_name: String,
+    /// Platform of the node (os/architecture)
+    pub node_platform: String,
+    /// Platforms listed in the image manifest
+    pub image_platforms: Vec<String>,
+}
+
+/// Extract runtime and platform information from a node
+///
+/// # Arguments
+///
+/// * `node` - The node to process
+///
+/// # Returns
+///
+/// * `Option<NodeInfo>` - The node information, or None if the node has no name or status
+pub fn process_node(node: &Node) -> Option<NodeInfo> {
+    let name = node.metadata.name.clone()?;
+    let info = node.status.as_ref()?.node_info.as_ref()?;
+
+    Some(NodeInfo {
+        name,
+        operating_system: info.operating_system.clone(),
+        architecture: info.architecture.clone(),
+        os_image: info.os_image.clone(),
+        kernel_version: info.kernel_version.clone(),
+        container_runtime: info.container_runtime_version.clone(),
+        kubelet_version: info.kubelet_version.clone(),
+    })
+}
+
+/// Get the registry repository path and manifest reference (tag or digest) of a pod image
+///
+/// Docker Hub official images are stored under the `library/` namespace.
+///
+/// # Arguments
+///
+/// * `image` - The pod image
+///
+/// # Returns
+///
+/// * `(String, String)` - Tuple of (repository, reference)
+pub fn manifest_reference(image: &PodImage) -> (String, String) {
+    let repository = ImageReference::parse(&format!("{}/{}", image.registry, image.image_name))
+        .map(|reference| reference.repository().to_string())
+        .unwrap_or_else(|_| image.image_name.clone());
+
+    let reference = match image.image_version.split_once('@') {
+        Some((_, digest)) => digest.to_string(),
+        None => image.image_version.clone(),
+    };
+
+    (repository, reference)
+}
+
+/// Find containers whose image manifest does not list a platform matching their node
+///
+/// Images without known platforms (e.g. private registries kimspect cannot read) are skipped.
+///
+/// # Arguments
+///
+/// * `images` - Pod images to check
+/// * `nodes` - Node information used to resolve the node platform
+/// * `platforms` - Image platforms keyed by (registry, repository, reference)
+///
+/// # Returns
+///
+/// * `Vec<ArchMismatch>` - List of containers running on an unsupported platform
+pub fn find_arch_mismatches(
+    images: &[PodImage],
+    nodes: &[NodeInfo],
+    platforms: &HashMap<(String, String, String), Vec<Platform>>,
+) -> Vec<ArchMismatch> {
+    images
+        .iter()
+        .filter_map(|image| {
+            let node = nodes.iter().find(|n| n.name == image.node_name)?;
+            let (repository, reference) = manifest_reference(image);
+            let image_platforms =
+                platforms.get(&(image.registry.clone(), repository, reference))?;
+
+            if image_platforms.is_empty()
+                || image_platforms
+                    .iter()
+                    .any(|p| p.architecture == node.architecture && p.os == node.operating_system)
+            {
+                return None;
+            }
+
+            Some(ArchMismatch {
+                pod_name: image.pod_name.clone(),
+                namespace: image.namespace.clone(),
+                container_name: image.container_name.clone(),
+                image: image.reference(),
+                node_name: node.name.clone(),
+                node_platform: format!("{}/{}", node.operating_system, node.architecture),
+                image_platforms: image_platforms.iter().map(|p| p.to_string()).collect(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,458 @@
+use crate::k8s::query::list_selected_pods;
+use crate::k8s::workloads::running_digest;
+use crate::k8s::{ImageQuery, PodSource, pod_workload};
+use crate::registry::{DEFAULT_TAG, ImageReference};
+use anyhow::{Context, Result};
+use k8s_openapi::api::core::v1::Pod;
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use tracing::{debug, info, instrument, warn};
+
+/// Kinds of workloads whose pod template can be patched, with their API version
+const PINNABLE_KINDS: [(&str, &str); 4] = [
+    ("Deployment", "apps/v1"),
+    ("StatefulSet", "apps/v1"),
+    ("DaemonSet", "apps/v1"),
+    ("ReplicaSet", "apps/v1"),
+];
+
+/// Short names kubectl accepts for the pinnable kinds
+const KIND_SHORT_NAMES: [(&str, &str); 4] = [
+    ("deploy", "Deployment"),
+    ("sts", "StatefulSet"),
+    ("ds", "DaemonSet"),
+    ("rs", "ReplicaSet"),
+];
+
+/// A container of a workload whose image tag can be pinned to the digest it runs
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub struct ImagePin {
+    /// Namespace of the workload
+    pub namespace: String,
+    /// Kind of the workload (e.g. Deployment)
+    pub kind: String,
+    /// Name of the workload
+    pub workload: String,
+    /// Whether the container is an init container
+    pub init: bool,
+    /// Name of the container
+    pub container: String,
+    /// The image as written in the pod spec (e.g. ghcr.io/org/web:1.2)
+    pub image: String,
+    /// The digest the pods run (e.g. sha256:...)
+    pub digest: String,
+}
+
+impl ImagePin {
+    /// Get the pinned image reference, keeping the tag for readers (e.g. ghcr.io/org/web:1.2@sha256:...)
+    pub fn pinned_image(&self) -> String {
+        let implicit_tag = ImageReference::parse(&self.image)
+            .map(|reference| reference.tag().is_none())
+            .unwrap_or(false);
+        if implicit_tag {
+            format!("{}:{}@{}", self.image, DEFAULT_TAG, self.digest)
+        } else {
+            format!("{}@{}", self.image, self.digest)
+        }
+    }
+
+    /// Check if the pin belongs to a workload given as KIND/NAME, like kubectl accepts it
+    ///
+    /// # Arguments
+    ///
+    /// * `workload` - The workload (e.g. deployment/web, deploy/web, Deployment/web)
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True if the kind and name match
+    pub fn matches_workload(&self, workload: &str) -> bool {
+        let Some((kind, name)) = workload.split_once('/') else {
+            return false;
+        };
+        let kind = KIND_SHORT_NAMES
+            .iter()
+            .find(|(short, _)| short.eq_ignore_ascii_case(kind))
+            .map_or(kind, |(_, kind)| kind);
+        // kubectl also accepts plurals (e.g. deployments/web)
+        let plural = kind.to_lowercase() == format!("{}s", self.kind.to_lowercase());
+        (self.kind.eq_ignore_ascii_case(kind) || plural) && self.workload == name
+    }
+
+    /// Get the image name as written, without its tag, as kustomize matches it
+    pub fn image_name(&self) -> &str {
+        split_tag(&self.image).0
+    }
+
+    /// Get the tag as written, `latest` when left out
+    pub fn tag(&self) -> &str {
+        split_tag(&self.image).1.unwrap_or(DEFAULT_TAG)
+    }
+}
+
+/// Split a tag-referenced image into its name and tag
+fn split_tag(image: &str) -> (&str, Option<&str>) {
+    // A colon before the last slash separates a registry port
+    match image.rsplit_once(':') {
+        Some((name, tag)) if !tag.contains('/') => (name, Some(tag)),
+        _ => (image, None),
+    }
+}
+
+/// Find the containers of workloads referencing images by tag, with the digest they run
+///
+/// Only Deployments, StatefulSets, DaemonSets and ReplicaSets are pinned, as
+/// Job templates cannot be changed and standalone pods are not rolled out.
+/// Containers whose pods run several images, e.g. during a rollout, or several
+/// digests of the same tag, e.g. after the tag moved, are left out with a warning.
+///
+/// # Arguments
+///
+/// * `pods` - The pods to read the running digests from
+///
+/// # Returns
+///
+/// * `Vec<ImagePin>` - One pin per workload container, sorted by namespace and workload
+pub fn find_image_pins<'a>(pods: impl IntoIterator<Item = &'a Pod>) -> Vec<ImagePin> {
+    // Digests per image of each workload container, keyed by a pin without image and digest
+    let mut images: BTreeMap<ImagePin, BTreeMap<String, BTreeSet<String>>> = BTreeMap::new();
+
+    for pod in pods {
+        let workload = pod_workload(pod);
+        let Some((kind, name)) = workload.split_once('/') else {
+            continue;
+        };
+        if !PINNABLE_KINDS.iter().any(|(pinnable, _)| *pinnable == kind) {
+            debug!(
+                pod = ?pod.metadata.name,
+                workload = %workload,
+                "Skipping pod of a workload that cannot be pinned"
+            );
+            continue;
+        }
+        let (Some(spec), Some(status)) = (&pod.spec, &pod.status) else {
+            continue;
+        };
+
+        let containers = spec
+            .init_containers
+            .iter()
+            .flatten()
+            .map(|container| (true, container))
+            .chain(spec.containers.iter().map(|container| (false, container)));
+        for (init, container) in containers {
+            let Some(image) = &container.image else {
+                continue;
+            };
+            if ImageReference::parse(image).is_ok_and(|reference| reference.digest().is_some()) {
+                continue;
+            }
+            let statuses = if init {
+                &status.init_container_statuses
+            } else {
+                &status.container_statuses
+            };
+            let Some(digest) = statuses
+                .iter()
+                .flatten()
+                .find(|status| status.name == container.name)
+                .and_then(running_digest)
+            else {
+                continue;
+            };
+
+            let pin = ImagePin {
+                namespace: pod.metadata.namespace.clone().unwrap_or_default(),
+                kind: kind.to_string(),
+                workload: name.to_string(),
+                init,
+                container: container.name.clone(),
+                image: String::new(),
+                digest: String::new(),
+            };
+            images
+                .entry(pin)
+                .or_default()
+                .entry(image.clone())
+                .or_default()
+                .insert(digest);
+        }
+    }
+
+    images
+        .into_iter()
+        .filter_map(|(pin, images)| {
+            let workload = format!("{}/{}", pin.kind, pin.workload);
+            if images.len() > 1 {
+                // Pinning either image could roll the workload back
+                warn!(
+                    namespace = %pin.namespace,
+                    workload = %workload,
+                    container = %pin.container,
+                    images = ?images.keys().collect::<Vec<_>>(),
+                    "Pods run several images of the container, not pinning it until the rollout completes"
+                );
+                return None;
+            }
+            let (image, digests) = images.into_iter().next()?;
+            if digests.len() > 1 {
+                warn!(
+                    namespace = %pin.namespace,
+                    workload = %workload,
+                    container = %pin.container,
+                    image = %image,
+                    digests = ?digests,
+                    "Pods run several digests of the same image, not pinning it"
+                );
+                return None;
+            }
+            let digest = digests.into_iter().next()?;
+            Some(ImagePin {
+                image,
+                digest,
+                ..pin
+            })
+        })
+        .collect()
+}
+
+/// Find the image pins of the pods selected by a query
+///
+/// # Arguments
+///
+/// * `source` - The source to list pods from
+/// * `query` - The query selecting namespaces and pods
+///
+/// # Returns
+///
+/// * `Result<Vec<ImagePin>>` - The pins or an error if pods cannot be listed
+#[instrument(skip(source))]
+pub async fn collect_image_pins<S>(source: &S, query: &ImageQuery) -> Result<Vec<ImagePin>>
+where
+    S: PodSource + Sync + ?Sized,
+{
+    let pods = list_selected_pods(source, query).await?;
+    let pins = find_image_pins(pods.iter().filter(|pod| query.matches_pod(pod)));
+    info!(pods = pods.len(), pins = pins.len(), "Found images to pin");
+    Ok(pins)
+}
+
+/// A strategic merge patch of the containers of a pod template
+#[derive(Serialize)]
+struct TemplatePatch {
+    /// API version, only in patch documents
+    #[serde(rename = "apiVersion", skip_serializing_if = "Option::is_none")]
+    api_version: Option<&'static str>,
+    /// Kind, only in patch documents
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kind: Option<String>,
+    /// Name and namespace, only in patch documents
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<PatchMetadata>,
+    spec: PatchSpec,
+}
+
+#[derive(Serialize)]
+struct PatchMetadata {
+    name: String,
+    namespace: String,
+}
+
+#[derive(Serialize)]
+struct PatchSpec {
+    template: PatchTemplate,
+}
+
+#[derive(Serialize)]
+struct PatchTemplate {
+    spec: PatchPodSpec,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PatchPodSpec {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    init_containers: Vec<PatchContainer>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    containers: Vec<PatchContainer>,
+}
+
+/// Containers are merged by name, so only the image changes
+#[derive(Serialize)]
+struct PatchContainer {
+    name: String,
+    image: String,
+}
+
+/// Group pins by workload, keeping their order
+fn pins_by_workload(pins: &[ImagePin]) -> Vec<Vec<&ImagePin>> {
+    let mut groups: Vec<Vec<&ImagePin>> = Vec::new();
+    for pin in pins {
+        match groups.last_mut() {
+            Some(group)
+                if (&group[0].namespace, &group[0].kind, &group[0].workload)
+                    == (&pin.namespace, &pin.kind, &pin.workload) =>
+            {
+                group.push(pin)
+            }
+            _ => groups.push(vec![pin]),
+        }
+    }
+    groups
+}
+
+/// Build the patch of a workload's containers
+fn template_patch(pins: &[&ImagePin], document: bool) -> TemplatePatch {
+    let containers = |init: bool| {
+        pins.iter()
+            .filter(|pin| pin.init == init)
+            .map(|pin| PatchContainer {
+                name: pin.container.clone(),
+                image: pin.pinned_image(),
+            })
+            .collect()
+    };
+    let first = pins[0];
+    let api_version = PINNABLE_KINDS
+        .iter()
+        .find(|(kind, _)| *kind == first.kind)
+        .map(|(_, api_version)| *api_version);
+
+    TemplatePatch {
+        api_version: api_version.filter(|_| document),
+        kind: document.then(|| first.kind.clone()),
+        metadata: document.then(|| PatchMetadata {
+            name: first.workload.clone(),
+            namespace: first.namespace.clone(),
+        }),
+        spec: PatchSpec {
+            template: PatchTemplate {
+                spec: PatchPodSpec {
+                    init_containers: containers(true),
+                    containers: containers(false),
+                },
+            },
+        },
+    }
+}
+
+/// Quote a value for a POSIX shell
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Render pins as `kubectl patch` commands, one per workload
+///
+/// # Arguments
+///
+/// * `pins` - The pins, grouped by workload as returned by [`find_image_pins`]
+///
+/// # Returns
+///
+/// * `Result<String>` - The commands, each preceded by a comment naming the workload
+pub fn render_kubectl_patches(pins: &[ImagePin]) -> Result<String> {
+    let mut out = String::new();
+    for group in pins_by_workload(pins) {
+        let first = group[0];
+        let patch = serde_json::to_string(&template_patch(&group, false))
+            .context("Failed to serialize patch")?;
+        out.push_str(&format!(
+            "# {} {}/{}\nkubectl patch {} {} --namespace {} --type strategic --patch {}\n",
+            first.kind,
+            first.namespace,
+            first.workload,
+            first.kind.to_lowercase(),
+            shell_quote(&first.workload),
+            shell_quote(&first.namespace),
+            shell_quote(&patch)
+        ));
+    }
+    Ok(out)
+}
+
+/// Render pins as strategic merge patch documents, one per workload
+///
+/// The documents name their target, so they can be applied with
+/// `kubectl patch --patch-file` or listed in a kustomization's `patches`.
+///
+/// # Arguments
+///
+/// * `pins` - The pins, grouped by workload as returned by [`find_image_pins`]
+///
+/// # Returns
+///
+/// * `Result<String>` - The YAML documents, separated by `---`
+pub fn render_patch_documents(pins: &[ImagePin]) -> Result<String> {
+    let documents = pins_by_workload(pins)
+        .into_iter()
+        .map(|group| serde_yaml::to_string(&template_patch(&group, true)))
+        .collect::<Result<Vec<_>, _>>()
+        .context("Failed to serialize patch")?;
+    Ok(documents.join("---\n"))
+}
+
+/// An entry of a kustomization's `images` list
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct KustomizeImage {
+    name: String,
+    new_tag: String,
+    digest: String,
+}
+
+#[derive(Serialize)]
+struct KustomizeImages {
+    images: Vec<KustomizeImage>,
+}
+
+/// Render pins as the `images` entries of a kustomization
+///
+/// Kustomize rewrites every use of an image name, so images whose tag runs
+/// different digests, or that are used with different tags, cannot be pinned
+/// this way; they are listed in a comment to be pinned per workload instead.
+///
+/// # Arguments
+///
+/// * `pins` - The pins to render
+///
+/// # Returns
+///
+/// * `Result<String>` - The `images:` YAML, preceded by comments on the images left out
+pub fn render_kustomize_images(pins: &[ImagePin]) -> Result<String> {
+    let mut versions: BTreeMap<&str, BTreeSet<(&str, &str)>> = BTreeMap::new();
+    for pin in pins {
+        versions
+            .entry(pin.image_name())
+            .or_default()
+            .insert((pin.tag(), pin.digest.as_str()));
+    }
+
+    let mut out = String::new();
+    let mut images = Vec::new();
+    for (name, versions) in versions {
+        if versions.len() > 1 {
+            let tags: Vec<&str> = versions.iter().map(|(tag, _)| *tag).collect();
+            out.push_str(&format!(
+                "# {} runs several versions ({}), pin it per workload\n",
+                name,
+                tags.join(", ")
+            ));
+            continue;
+        }
+        if let Some((tag, digest)) = versions.into_iter().next() {
+            images.push(KustomizeImage {
+                name: name.to_string(),
+                new_tag: tag.to_string(),
+                digest: digest.to_string(),
+            });
+        }
+    }
+
+    if !images.is_empty() {
+        out.push_str(
+            &serde_yaml::to_string(&KustomizeImages { images })
+                .context("Failed to serialize kustomize images")?,
+        );
+    }
+    Ok(out)
+}
@@ -0,0 +1,336 @@
+use crate::k8s::status::{event_timestamp, field_path_container};
+use crate::k8s::{PodImage, extract_registry, format_bytes};
+use k8s_openapi::api::core::v1::Event;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+/// Represents a single image pull recorded by the kubelet
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ImagePull {
+    /// Name of the pod the image was pulled for
+    pub pod_name: String,
+    /// Kubernetes namespace of the pod
+    pub namespace: String,
+    /// Name of the node that pulled the image
+    pub node_name: String,
+    /// Name of the container the image was pulled for
+    pub container_name: String,
+    /// Full image reference as reported by the kubelet
+    pub image: String,
+    /// Registry the image was pulled from
+    pub registry: String,
+    /// Time spent pulling the image (None when the image was already present)
+    pub duration: Option<Duration>,
+    /// Image size in a human readable format (if reported by the kubelet)
+    pub image_size: String,
+}
+
+impl ImagePull {
+    /// Check if the image was served from the node cache instead of being pulled
+    pub fn is_cached(&self) -> bool {
+        self.duration.is_none()
+    }
+}
+
+/// Keys image pulls can be summarized by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PullGroupBy {
+    /// Aggregate pull durations per image
+    Image,
+    /// Aggregate pull durations per node
+    Node,
+}
+
+impl fmt::Display for PullGroupBy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PullGroupBy::Image => write!(f, "image"),
+            PullGroupBy::Node => write!(f, "node"),
+        }
+    }
+}
+
+/// Aggregated pull statistics for an image or a node
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct PullSummary {
+    /// The image or node name the statistics belong to
+    pub key: String,
+    /// Number of actual pulls
+    pub pulls: usize,
+    /// Number of times the image was already present on the node
+    pub cached: usize,
+    /// Slowest observed pull
+    pub max: Duration,
+    /// Average pull duration
+    pub average: Duration,
+    /// Sum of all pull durations
+    pub total: Duration,
+}
+
+/// Message details of a kubelet pull event
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PullMessage {
+    /// Image reference quoted in the message
+    pub image: String,
+    /// Pull duration (None when the image was already present on the node)
+    pub duration: Option<Duration>,
+    /// Image size in bytes (if reported)
+    pub size_bytes: Option<u64>,
+}
+
+/// Parse the message of a kubelet `Pulled` event
+///
+/// Understands both `Successfully pulled image "nginx:1.25" in 1.2s (1.2s including waiting).
+/// Image size: 123 bytes.` and `Container image "nginx:1.25" already present on machine`.
+///
+/// # Arguments
+///
+/// * `message` - The event message
+///
+/// # Returns
+///
+/// * `Option<PullMessage>` - The parsed details or None if the message is not a pull result
+pub fn parse_pull_message(message: &str) -> Option<PullMessage> {
+    let image = quoted(message)?.to_string();
+
+    if message.contains("already present on machine") {
+        return Some(PullMessage {
+            image,
+            duration: None,
+            size_bytes: None,
+        });
+    }
+
+    if !message.starts_with("Successfully pulled image") {
+        return None;
+    }
+
+    let duration = message
+        .split(" in ")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .map(|d| d.trim_end_matches(['.', ',']))
+        .and_then(parse_go_duration);
+
+    let size_bytes = message
+        .split("Image size: ")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .and_then(|n| n.parse::<u64>().ok());
+
+    Some(PullMessage {
+        image,
+        duration: Some(duration.unwrap_or_default()),
+        size_bytes,
+    })
+}
+
+/// Parse a Go formatted duration such as `1m2.5s`, `850ms` or `1.234567s`
+///
+/// # Arguments
+///
+/// * `value` - The duration string
+///
+/// # Returns
+///
+/// * `Option<Duration>` - The parsed duration or None if the string is malformed
+pub fn parse_go_duration(value: &str) -> Option<Duration> {
+    const UNITS: [(&str, f64); 7] = [
+        ("ns", 1e-9),
+        ("us", 1e-6),
+        ("µs", 1e-6),
+        ("ms", 1e-3),
+        ("s", 1.0),
+        ("m", 60.0),
+        ("h", 3600.0),
+    ];
+
+    if value.is_empty() {
+        return None;
+    }
+
+    let mut seconds = 0.0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let number: f64 = rest[..number_end].parse().ok()?;
+        rest = &rest[number_end..];
+
+        let unit_end = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let (_, factor) = UNITS.iter().find(|(unit, _)| *unit == &rest[..unit_end])?;
+        rest = &rest[unit_end..];
+
+        seconds += number * factor;
+    }
+
+    // Values beyond the range of Duration come from user input, they must not panic
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// Format a pull duration in a compact human readable form (e.g. 1m2.5s, 850ms)
+pub fn format_duration(duration: Duration) -> String {
+    if duration < Duration::from_secs(1) {
+        return format!("{}ms", duration.as_millis());
+    }
+
+    // Round to tenths before splitting, so 119.96s is 2m0.0s rather than 1m60.0s
+    let tenths = (duration.as_secs_f64() * 10.0).round() as u64;
+    let (minutes, tenths) = (tenths / 600, tenths % 600);
+    if minutes > 0 {
+        format!("{}m{}.{}s", minutes, tenths / 10, tenths % 10)
+    } else {
+        format!("{}.{}s", tenths / 10, tenths % 10)
+    }
+}
+
+/// Build image pull records from kubelet events and correlate them with pod images
+///
+/// Durations are taken from the `Pulled` message when the kubelet reports it; older
+/// kubelets only emit timestamps, so the time between the `Pulling` and `Pulled`
+/// events of the same container is used instead. Results are sorted slowest first.
+///
+/// # Arguments
+///
+/// * `events` - Pod events to inspect
+/// * `pod_images` - Pod images used to resolve the node and registry of each pull
+///
+/// # Returns
+///
+/// * `Vec<ImagePull>` - List of image pulls, slowest first
+pub fn process_pull_events(events: &[Event], pod_images: &[PodImage]) -> Vec<ImagePull> {
+    let pulling_started: HashMap<(String, String, String), i64> = events
+        .iter()
+        .filter(|event| event.reason.as_deref() == Some("Pulling"))
+        .filter_map(|event| Some((event_container_key(event)?, event_timestamp(event))))
+        .collect();
+
+    let mut pulls: Vec<ImagePull> = events
+        .iter()
+        .filter(|event| event.reason.as_deref() == Some("Pulled"))
+        .filter_map(|event| {
+            let key = event_container_key(event)?;
+            let parsed = parse_pull_message(event.message.as_deref()?)?;
+
+            let duration = match parsed.duration {
+                Some(d) if d.is_zero() => pulling_started
+                    .get(&key)
+                    .map(|started| {
+                        Duration::from_secs(event_timestamp(event).saturating_sub(*started) as u64)
+                    })
+                    .or(Some(d)),
+                other => other,
+            };
+
+            let (namespace, pod_name, container_name) = key;
+            let pod_image = pod_images.iter().find(|pi| {
+                pi.namespace == namespace
+                    && pi.pod_name == pod_name
+                    && pi.container_name == container_name
+            });
+
+            let node_name = pod_image
+                .map(|pi| pi.node_name.clone())
+                .filter(|n| !n.is_empty())
+                .or_else(|| event.source.as_ref().and_then(|s| s.host.clone()))
+                .or_else(|| event.reporting_instance.clone())
+                .unwrap_or_default();
+
+            Some(ImagePull {
+                registry: pod_image
+                    .map(|pi| pi.registry.clone())
+                    .unwrap_or_else(|| extract_registry(&parsed.image)),
+                image: parsed.image,
+                pod_name,
+                namespace,
+                node_name,
+                container_name,
+                duration,
+                image_size: parsed.size_bytes.map(format_bytes).unwrap_or_default(),
+            })
+        })
+        .collect();
+
+    sort_slowest_first(&mut pulls);
+    pulls
+}
+
+/// Summarize image pulls per image or per node, slowest first
+///
+/// # Arguments
+///
+/// * `pulls` - The image pulls to summarize
+/// * `group` - The key to group by
+///
+/// # Returns
+///
+/// * `Vec<PullSummary>` - Aggregated statistics ordered by slowest pull
+pub fn summarize_pulls(pulls: &[ImagePull], group: PullGroupBy) -> Vec<PullSummary> {
+    let mut groups: HashMap<&str, Vec<&ImagePull>> = HashMap::new();
+    for pull in pulls {
+        let key = match group {
+            PullGroupBy::Image => pull.image.as_str(),
+            PullGroupBy::Node => pull.node_name.as_str(),
+        };
+        groups.entry(key).or_default().push(pull);
+    }
+
+    let mut summaries: Vec<PullSummary> = groups
+        .into_iter()
+        .map(|(key, pulls)| {
+            let durations: Vec<Duration> = pulls.iter().filter_map(|p| p.duration).collect();
+            let total: Duration = durations.iter().sum();
+            PullSummary {
+                key: key.to_string(),
+                pulls: durations.len(),
+                cached: pulls.len() - durations.len(),
+                max: durations.iter().max().copied().unwrap_or_default(),
+                average: total
+                    .checked_div(durations.len() as u32)
+                    .unwrap_or_default(),
+                total,
+            }
+        })
+        .collect();
+
+    summaries.sort_by(|a, b| b.max.cmp(&a.max).then_with(|| a.key.cmp(&b.key)));
+    summaries
+}
+
+/// Sort pulls by duration with the slowest first and cached images last
+fn sort_slowest_first(pulls: &mut [ImagePull]) {
+    pulls.sort_by(|a, b| {
+        b.duration
+            .cmp(&a.duration)
+            .then_with(|| a.image.cmp(&b.image))
+            .then_with(|| a.pod_name.cmp(&b.pod_name))
+    });
+}
+
+/// Build a (namespace, pod, container) key for an event on a pod container
+fn event_container_key(event: &Event) -> Option<(String, String, String)> {
+    let obj = &event.involved_object;
+    if obj.kind.as_deref() != Some("Pod") {
+        return None;
+    }
+    let container = field_path_container(obj.field_path.as_deref()?)?;
+    Some((
+        obj.namespace.clone().unwrap_or_default(),
+        obj.name.clone()?,
+        container.to_string(),
+    ))
+}
+
+/// Extract the first double-quoted value from a message
+fn quoted(message: &str) -> Option<&str> {
+    let start = message.find('"')? + 1;
+    let end = start + message[start..].find('"')?;
+    Some(&message[start..end])
+}
@@ -0,0 +1,690 @@
+use crate::k8s::{K8sError, PodImage, ReleaseInfo, format_bytes, process_pod};
+use anyhow::Result;
+use futures::{StreamExt, TryStreamExt};
+use k8s_openapi::api::core::v1::{Node, Pod};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{ObjectMeta, OwnerReference};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use tracing::{debug, info, instrument, warn};
+
+/// Namespace searched when a query names no namespace
+const DEFAULT_NAMESPACE: &str = "default";
+
+/// Maximum number of namespaces whose pods are listed at the same time
+const MAX_CONCURRENT_NAMESPACES: usize = 8;
+
+/// Status of images from CronJob and Job templates, which have no running container
+const SCHEDULED_STATUS: &str = "Scheduled";
+
+/// Filters selecting which pod images to inventory
+///
+/// Built with chained setters so new filters can be added without breaking callers:
+///
+/// ```
+/// use kimspect::ImageQuery;
+///
+/// let query = ImageQuery::new()
+///     .namespace("kube-system")
+///     .node("worker-1")
+///     .exclude_registry("registry.k8s.io");
+/// assert_eq!(query.get_namespace(), "kube-system");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImageQuery {
+    /// Namespaces to search in (ignored when searching all namespaces)
+    namespaces: Vec<String>,
+    /// Label selector of additional namespaces to search in
+    namespace_selector: Option<String>,
+    /// Namespaces to leave out, also when searching all namespaces
+    exclude_namespaces: Vec<String>,
+    /// Whether to search in all namespaces
+    all_namespaces: bool,
+    /// Only include pods scheduled on this node
+    node: Option<String>,
+    /// Only include the pod with this name
+    pod: Option<String>,
+    /// Only include images from this registry
+    registry: Option<String>,
+    /// Exclude images from these registries
+    exclude_registries: Vec<String>,
+    /// Look up pod owners for release metadata missing on the pod
+    resolve_owners: bool,
+    /// Include the images of CronJob and Job templates
+    include_scheduled: bool,
+}
+
+impl ImageQuery {
+    /// Create a query for all pod images in the "default" namespace
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Search in the given namespace only
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespaces = vec![namespace.into()];
+        self
+    }
+
+    /// Search in each of the given namespaces
+    pub fn namespaces<I, S>(mut self, namespaces: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.namespaces = namespaces.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Search the namespaces matching a label selector (e.g. team=payments)
+    ///
+    /// The selected namespaces replace the named ones and the "default" namespace.
+    pub fn namespace_selector(mut self, selector: impl Into<String>) -> Self {
+        self.namespace_selector = Some(selector.into());
+        self
+    }
+
+    /// Leave out the given namespace (can be called multiple times)
+    pub fn exclude_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.exclude_namespaces.push(namespace.into());
+        self
+    }
+
+    /// Leave out each of the given namespaces
+    pub fn exclude_namespaces<I, S>(mut self, namespaces: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.exclude_namespaces
+            .extend(namespaces.into_iter().map(Into::into));
+        self
+    }
+
+    /// Search in all namespaces (or only the configured one if `false`)
+    pub fn all_namespaces(mut self, all_namespaces: bool) -> Self {
+        self.all_namespaces = all_namespaces;
+        self
+    }
+
+    /// Only include pods scheduled on the given node
+    pub fn node(mut self, node: impl Into<String>) -> Self {
+        self.node = Some(node.into());
+        self
+    }
+
+    /// Only include the pod with the given name
+    pub fn pod(mut self, pod: impl Into<String>) -> Self {
+        self.pod = Some(pod.into());
+        self
+    }
+
+    /// Only include images pulled from the given registry
+    pub fn registry(mut self, registry: impl Into<String>) -> Self {
+        self.registry = Some(registry.into());
+        self
+    }
+
+    /// Exclude images pulled from the given registry (can be called multiple times)
+    pub fn exclude_registry(mut self, registry: impl Into<String>) -> Self {
+        self.exclude_registries.push(registry.into());
+        self
+    }
+
+    /// Exclude images pulled from any of the given registries
+    pub fn exclude_registries<I, S>(mut self, registries: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.exclude_registries
+            .extend(registries.into_iter().map(Into::into));
+        self
+    }
+
+    /// Look up pod owners (ReplicaSets, Deployments, ...) for Helm release metadata
+    ///
+    /// Helm only annotates the resources it creates, so pods often carry the
+    /// release name on their owning workload alone. This costs extra API calls.
+    pub fn resolve_owners(mut self, resolve_owners: bool) -> Self {
+        self.resolve_owners = resolve_owners;
+        self
+    }
+
+    /// Include the images of CronJob and Job pod templates, marked as scheduled
+    ///
+    /// Batch workloads often have no pods between runs, so their images are
+    /// missing from an inventory of running pods.
+    pub fn include_scheduled(mut self, include_scheduled: bool) -> Self {
+        self.include_scheduled = include_scheduled;
+        self
+    }
+
+    /// Get the namespace to search in (the first one when several are named)
+    pub fn get_namespace(&self) -> &str {
+        self.namespaces
+            .first()
+            .map(String::as_str)
+            .unwrap_or(DEFAULT_NAMESPACE)
+    }
+
+    /// Get the namespaces named explicitly, or "default" when no namespace is named
+    ///
+    /// Empty when namespaces are selected by label, as the selection replaces them.
+    pub fn get_namespaces(&self) -> Vec<&str> {
+        if self.namespace_selector.is_some() {
+            return Vec::new();
+        }
+        if self.namespaces.is_empty() {
+            return vec![DEFAULT_NAMESPACE];
+        }
+        self.namespaces.iter().map(String::as_str).collect()
+    }
+
+    /// Get the label selector of the namespaces to search
+    pub fn get_namespace_selector(&self) -> Option<&str> {
+        self.namespace_selector.as_deref()
+    }
+
+    /// Get the excluded namespaces
+    pub fn get_exclude_namespaces(&self) -> &[String] {
+        &self.exclude_namespaces
+    }
+
+    /// Check if the query searches all namespaces
+    pub fn is_all_namespaces(&self) -> bool {
+        self.all_namespaces
+    }
+
+    /// Get the node filter
+    pub fn get_node(&self) -> Option<&str> {
+        self.node.as_deref()
+    }
+
+    /// Get the pod name filter
+    pub fn get_pod(&self) -> Option<&str> {
+        self.pod.as_deref()
+    }
+
+    /// Get the registry filter
+    pub fn get_registry(&self) -> Option<&str> {
+        self.registry.as_deref()
+    }
+
+    /// Get the excluded registries
+    pub fn get_exclude_registries(&self) -> &[String] {
+        &self.exclude_registries
+    }
+
+    /// Check if pod owners are looked up for release metadata
+    pub fn is_resolving_owners(&self) -> bool {
+        self.resolve_owners
+    }
+
+    /// Check if the images of CronJob and Job templates are included
+    pub fn is_including_scheduled(&self) -> bool {
+        self.include_scheduled
+    }
+
+    /// Check if a pod matches the namespace, node and pod filters
+    ///
+    /// # Arguments
+    ///
+    /// * `pod` - The pod to check
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True if the pod should be inventoried
+    pub fn matches_pod(&self, pod: &Pod) -> bool {
+        let namespace = pod.metadata.namespace.as_deref().unwrap_or_default();
+        if self
+            .exclude_namespaces
+            .iter()
+            .any(|excluded| excluded == namespace)
+        {
+            return false;
+        }
+
+        // Namespaces matching a selector are only known once resolved by the source
+        if !self.all_namespaces
+            && self.namespace_selector.is_none()
+            && !self.get_namespaces().contains(&namespace)
+        {
+            return false;
+        }
+
+        if let Some(name) = &self.pod {
+            if pod.metadata.name.as_deref() != Some(name) {
+                return false;
+            }
+        }
+
+        if let Some(node) = &self.node {
+            if pod.spec.as_ref().and_then(|s| s.node_name.as_deref()) != Some(node) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Check if an image matches the registry filters
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The pod image to check
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True if the image should be included
+    pub fn matches_image(&self, image: &PodImage) -> bool {
+        if let Some(registry) = &self.registry {
+            if &image.registry != registry {
+                return false;
+            }
+        }
+
+        !self.exclude_registries.contains(&image.registry)
+    }
+
+    /// Describe the pods the query selects, used in not-found errors
+    fn describe_pods(&self) -> String {
+        match (&self.node, &self.pod) {
+            (Some(node), Some(pod)) => format!("pod {} on node {}", pod, node),
+            (Some(node), None) => format!("pods on node {}", node),
+            (None, Some(pod)) => format!("pod {}", pod),
+            (None, None) if self.all_namespaces => "pods in any namespace".to_string(),
+            (None, None) => match (self.get_namespaces().as_slice(), &self.namespace_selector) {
+                (_, Some(selector)) => format!("pods in namespaces matching {}", selector),
+                ([namespace], None) => format!("pods in namespace {}", namespace),
+                (namespaces, None) => format!("pods in namespaces {}", namespaces.join(", ")),
+            },
+        }
+    }
+
+    /// Narrow the query to a single namespace, used to list namespaces concurrently
+    fn for_namespace(&self, namespace: String) -> Self {
+        Self {
+            namespaces: vec![namespace],
+            namespace_selector: None,
+            ..self.clone()
+        }
+    }
+}
+
+/// A source of pods to inventory, such as a live cluster, manifest files or test fixtures
+pub trait PodSource {
+    /// List the pods selected by a query
+    ///
+    /// [`collect_pod_images`] calls this once per namespace, or once with a query
+    /// for all namespaces. Sources may return a superset of the selected pods;
+    /// the query filters are applied again.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The query selecting pods
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<Pod>>` - The pods or an error
+    fn list_pods(&self, query: &ImageQuery) -> impl Future<Output = Result<Vec<Pod>>> + Send;
+
+    /// List the names of the namespaces matching a label selector
+    ///
+    /// Sources without namespace information select no namespaces.
+    ///
+    /// # Arguments
+    ///
+    /// * `selector` - The label selector (e.g. team=payments)
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<String>>` - The namespace names or an error
+    fn list_namespaces(&self, selector: &str) -> impl Future<Output = Result<Vec<String>>> + Send {
+        let _ = selector;
+        async { Ok(Vec::new()) }
+    }
+
+    /// List the nodes of the cluster, used to look up image sizes
+    ///
+    /// Sources without node information return no nodes.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<Node>>` - The nodes or an error
+    fn list_nodes(&self) -> impl Future<Output = Result<Vec<Node>>> + Send {
+        async { Ok(Vec::new()) }
+    }
+
+    /// Get the metadata of an object owning a pod, used to find release metadata
+    ///
+    /// Sources without owner information return None.
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace of the owned object
+    /// * `owner` - The owner reference
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<ObjectMeta>>` - The owner metadata, None if unknown, or an error
+    fn owner_metadata(
+        &self,
+        namespace: &str,
+        owner: &OwnerReference,
+    ) -> impl Future<Output = Result<Option<ObjectMeta>>> + Send {
+        let _ = (namespace, owner);
+        async { Ok(None) }
+    }
+
+    /// List the pod templates of CronJobs and recent Jobs as pods
+    ///
+    /// Called like [`PodSource::list_pods`] when the query includes scheduled
+    /// images. Sources without batch workloads return no pods.
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The query selecting namespaces
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<Pod>>` - The templates as pods or an error
+    fn list_scheduled_pods(
+        &self,
+        query: &ImageQuery,
+    ) -> impl Future<Output = Result<Vec<Pod>>> + Send {
+        let _ = query;
+        async { Ok(Vec::new()) }
+    }
+}
+
+impl PodSource for [Pod] {
+    async fn list_pods(&self, query: &ImageQuery) -> Result<Vec<Pod>> {
+        Ok(self
+            .iter()
+            .filter(|pod| query.matches_pod(pod))
+            .cloned()
+            .collect())
+    }
+}
+
+impl PodSource for Vec<Pod> {
+    async fn list_pods(&self, query: &ImageQuery) -> Result<Vec<Pod>> {
+        self.as_slice().list_pods(query).await
+    }
+}
+
+/// Get the images of the pods selected by a query
+///
+/// # Arguments
+///
+/// * `source` - The source to list pods and nodes from
+/// * `query` - The query selecting pods and images
+///
+/// # Returns
+///
+/// * `Result<Vec<PodImage>>` - List of matching pod images or an error if no pods match
+#[instrument(skip(source))]
+pub async fn collect_pod_images<S>(source: &S, query: &ImageQuery) -> Result<Vec<PodImage>>
+where
+    S: PodSource + Sync + ?Sized,
+{
+    debug!("Collecting pod images");
+
+    let pods = list_selected_pods(source, query).await?;
+    debug!("Found {} pods", pods.len());
+
+    let scheduled = if query.is_including_scheduled() {
+        list_namespace_pods(source, query, true).await?
+    } else {
+        Vec::new()
+    };
+    debug!("Found {} scheduled pod templates", scheduled.len());
+
+    if pods.is_empty() && scheduled.is_empty() {
+        return Err(K8sError::ResourceNotFound(query.describe_pods()).into());
+    }
+
+    let mut owners = HashMap::new();
+    let mut all_images = Vec::new();
+    let selected = pods
+        .iter()
+        .map(|pod| (pod, false))
+        .chain(scheduled.iter().map(|pod| (pod, true)))
+        .filter(|(pod, _)| query.matches_pod(pod));
+    for (pod, scheduled) in selected {
+        let mut pod_images = process_pod(pod);
+        if scheduled {
+            for image in &mut pod_images {
+                image.status = SCHEDULED_STATUS.to_string();
+            }
+        }
+
+        if query.is_resolving_owners() {
+            let mut release = ReleaseInfo::from_metadata(&pod.metadata);
+            if !release.has_release() {
+                release.merge(resolve_owner_release(source, pod, &mut owners).await);
+                pod_images.iter_mut().for_each(|image| release.apply(image));
+            }
+        }
+
+        all_images.extend(pod_images);
+    }
+
+    let before_count = all_images.len();
+    all_images.retain(|image| query.matches_image(image));
+    debug!(
+        before = before_count,
+        after = all_images.len(),
+        "Filtered images by registry"
+    );
+
+    match source.list_nodes().await {
+        Ok(nodes) => enrich_image_sizes(&mut all_images, &nodes),
+        Err(e) => warn!(
+            error = %e,
+            "Skipping image sizes due to node list failure, run `kimspect auth check` to review permissions"
+        ),
+    }
+
+    info!(
+        total_images = all_images.len(),
+        "Successfully retrieved pod images"
+    );
+    Ok(all_images)
+}
+
+/// List the pods of every namespace a query selects
+///
+/// Namespaces are listed concurrently, one request each, unless the query
+/// searches all namespaces.
+///
+/// # Arguments
+///
+/// * `source` - The source to list pods from
+/// * `query` - The query selecting namespaces and pods
+///
+/// # Returns
+///
+/// * `Result<Vec<Pod>>` - The pods of all selected namespaces or an error
+pub(crate) async fn list_selected_pods<S>(source: &S, query: &ImageQuery) -> Result<Vec<Pod>>
+where
+    S: PodSource + Sync + ?Sized,
+{
+    list_namespace_pods(source, query, false).await
+}
+
+/// List running pods or scheduled pod templates of every namespace a query selects
+async fn list_namespace_pods<S>(source: &S, query: &ImageQuery, scheduled: bool) -> Result<Vec<Pod>>
+where
+    S: PodSource + Sync + ?Sized,
+{
+    let list = |query: ImageQuery| async move {
+        if scheduled {
+            source.list_scheduled_pods(&query).await
+        } else {
+            source.list_pods(&query).await
+        }
+    };
+
+    if query.is_all_namespaces() {
+        return list(query.clone()).await;
+    }
+
+    let mut namespaces: Vec<String> = match query.get_namespace_selector() {
+        Some(selector) => {
+            let selected = source.list_namespaces(selector).await?;
+            debug!(selector = %selector, namespaces = ?selected, "Resolved namespace selector");
+            selected
+        }
+        None => query
+            .get_namespaces()
+            .into_iter()
+            .map(str::to_string)
+            .collect(),
+    };
+
+    let mut seen = HashSet::new();
+    namespaces.retain(|namespace| {
+        !query.get_exclude_namespaces().contains(namespace) && seen.insert(namespace.clone())
+    });
+    debug!(namespaces = ?namespaces, "Listing pods per namespace");
+
+    let pods: Vec<Vec<Pod>> = futures::stream::iter(namespaces)
+        .map(|namespace| list(query.for_namespace(namespace)))
+        .buffered(MAX_CONCURRENT_NAMESPACES)
+        .try_collect()
+        .await?;
+
+    Ok(pods.into_iter().flatten().collect())
+}
+
+/// Maximum depth of owner references to follow (e.g. Pod -> ReplicaSet -> Deployment)
+const MAX_OWNER_DEPTH: usize = 3;
+
+/// Find release metadata on the owners of a pod
+///
+/// Owner metadata is cached by (namespace, kind, name), as many pods share owners.
+///
+/// # Arguments
+///
+/// * `source` - The source to look up owners from
+/// * `pod` - The pod whose owners to follow
+/// * `owners` - Cache of owner metadata already looked up
+///
+/// # Returns
+///
+/// * `ReleaseInfo` - Release metadata of the closest owners carrying it
+async fn resolve_owner_release<S>(
+    source: &S,
+    pod: &Pod,
+    owners: &mut HashMap<(String, String, String), Option<ObjectMeta>>,
+) -> ReleaseInfo
+where
+    S: PodSource + Sync + ?Sized,
+{
+    let namespace = pod.metadata.namespace.clone().unwrap_or_default();
+    let mut release = ReleaseInfo::default();
+    let mut owner = controller_of(&pod.metadata);
+
+    for _ in 0..MAX_OWNER_DEPTH {
+        let Some(reference) = owner else {
+            break;
+        };
+
+        let key = (
+            namespace.clone(),
+            reference.kind.clone(),
+            reference.name.clone(),
+        );
+        if !owners.contains_key(&key) {
+            let metadata = match source.owner_metadata(&namespace, &reference).await {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    debug!(kind = %reference.kind, name = %reference.name, error = %e, "Failed to look up pod owner");
+                    None
+                }
+            };
+            owners.insert(key.clone(), metadata);
+        }
+
+        let Some(metadata) = owners.get(&key).and_then(Option::as_ref) else {
+            break;
+        };
+        release.merge(ReleaseInfo::from_metadata(metadata));
+        if release.has_release() {
+            break;
+        }
+        owner = controller_of(metadata);
+    }
+
+    release
+}
+
+/// Get the controlling owner reference of an object, or its first owner
+fn controller_of(metadata: &ObjectMeta) -> Option<OwnerReference> {
+    let references = metadata.owner_references.as_ref()?;
+    references
+        .iter()
+        .find(|reference| reference.controller == Some(true))
+        .or_else(|| references.first())
+        .cloned()
+}
+
+/// Fill in image sizes from the images nodes report as present
+///
+/// # Arguments
+///
+/// * `images` - The pod images to enrich
+/// * `nodes` - The nodes the pods run on
+fn enrich_image_sizes(images: &mut [PodImage], nodes: &[Node]) {
+    let needed_nodes: HashSet<&str> = images
+        .iter()
+        .filter(|pi| !pi.node_name.is_empty())
+        .map(|pi| pi.node_name.as_str())
+        .collect();
+
+    // node_name -> { digest -> size_bytes }
+    let node_to_digest_size: HashMap<String, HashMap<String, u64>> = nodes
+        .iter()
+        .filter_map(|node| {
+            let name = node.metadata.name.clone()?;
+            if !needed_nodes.contains(name.as_str()) {
+                return None;
+            }
+            let node_images = node.status.as_ref()?.images.as_ref()?;
+
+            let digest_map = node_images
+                .iter()
+                .filter_map(|img| {
+                    let size = img.size_bytes.unwrap_or(0) as u64;
+                    img.names.as_ref().and_then(|names| {
+                        names.iter().find_map(|name| {
+                            name.find('@')
+                                .map(|idx| (name[idx + 1..].to_string(), size))
+                        })
+                    })
+                })
+                .fold(HashMap::new(), |mut acc, (digest, size)| {
+                    acc.entry(digest)
+                        .and_modify(|v: &mut u64| *v = (*v).max(size))
+                        .or_insert(size);
+                    acc
+                });
+
+            (!digest_map.is_empty()).then_some((name, digest_map))
+        })
+        .collect();
+
+    images
+        .iter_mut()
+        .filter(|img| img.image_size.is_empty() && !img.node_name.is_empty())
+        .for_each(|img| {
+            if let Some(size) = node_to_digest_size
+                .get(&img.node_name)
+                .and_then(|dmap| dmap.get(&img.digest))
+            {
+                img.image_size = format_bytes(*size);
+                img.image_size_bytes = Some(*size);
+            }
+        });
+}
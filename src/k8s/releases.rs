@@ -0,0 +1,173 @@
+use crate::k8s::PodImage;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use std::collections::BTreeMap;
+
+/// Label naming the tool that manages a resource (e.g. Helm, kustomize)
+pub const MANAGED_BY_LABEL: &str = "app.kubernetes.io/managed-by";
+
+/// Label naming the instance of an application, set to the release name by Helm charts
+pub const INSTANCE_LABEL: &str = "app.kubernetes.io/instance";
+
+/// Label holding the chart name and version (e.g. nginx-15.4.2)
+pub const HELM_CHART_LABEL: &str = "helm.sh/chart";
+
+/// Annotation Helm sets on every resource of a release
+pub const HELM_RELEASE_ANNOTATION: &str = "meta.helm.sh/release-name";
+
+/// Release metadata read from the labels and annotations of a pod or its owners
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ReleaseInfo {
+    /// Helm release name (or application instance for other tools)
+    pub release: String,
+    /// Chart name and version
+    pub chart: String,
+    /// Tool managing the resource (e.g. Helm, kustomize)
+    pub managed_by: String,
+}
+
+impl ReleaseInfo {
+    /// Read release metadata from an object
+    ///
+    /// The release name comes from the Helm annotation, falling back to the
+    /// `app.kubernetes.io/instance` label when a managing tool is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `metadata` - Metadata of a pod, pod template or workload
+    ///
+    /// # Returns
+    ///
+    /// * `ReleaseInfo` - The release metadata (empty fields when not set)
+    pub fn from_metadata(metadata: &ObjectMeta) -> Self {
+        let label = |key: &str| {
+            metadata
+                .labels
+                .as_ref()
+                .and_then(|labels| labels.get(key))
+                .cloned()
+                .unwrap_or_default()
+        };
+        let annotation = metadata
+            .annotations
+            .as_ref()
+            .and_then(|annotations| annotations.get(HELM_RELEASE_ANNOTATION))
+            .cloned();
+
+        let managed_by = label(MANAGED_BY_LABEL);
+        let release = annotation
+            .filter(|release| !release.is_empty())
+            .unwrap_or_else(|| {
+                if managed_by.is_empty() {
+                    String::new()
+                } else {
+                    label(INSTANCE_LABEL)
+                }
+            });
+
+        Self {
+            release,
+            chart: label(HELM_CHART_LABEL),
+            managed_by,
+        }
+    }
+
+    /// Check if no release metadata was found
+    pub fn is_empty(&self) -> bool {
+        self.release.is_empty() && self.chart.is_empty() && self.managed_by.is_empty()
+    }
+
+    /// Check if the release name is known
+    pub fn has_release(&self) -> bool {
+        !self.release.is_empty()
+    }
+
+    /// Fill in fields missing here from another source (e.g. the owning workload)
+    pub fn merge(&mut self, other: ReleaseInfo) {
+        if self.release.is_empty() {
+            self.release = other.release;
+        }
+        if self.chart.is_empty() {
+            self.chart = other.chart;
+        }
+        if self.managed_by.is_empty() {
+            self.managed_by = other.managed_by;
+        }
+    }
+
+    /// Record the release metadata on a pod image
+    pub fn apply(&self, image: &mut PodImage) {
+        image.release = self.release.clone();
+        image.chart = self.chart.clone();
+        image.managed_by = self.managed_by.clone();
+    }
+}
+
+/// Images deployed by one release
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ReleaseGroup {
+    /// Release name (empty for images not deployed by a release)
+    pub release: String,
+    /// Namespace of the release
+    pub namespace: String,
+    /// Charts the release's pods were rendered from
+    pub charts: Vec<String>,
+    /// Tools managing the release's pods
+    pub managed_by: Vec<String>,
+    /// Distinct image references pulled by the release
+    pub images: Vec<String>,
+    /// Number of containers in the release
+    pub containers: usize,
+}
+
+/// Group pod images by the release that deployed them
+///
+/// # Arguments
+///
+/// * `images` - The pod images to group
+///
+/// # Returns
+///
+/// * `Vec<ReleaseGroup>` - Releases sorted by namespace and name, images without a release last
+pub fn group_by_release(images: &[PodImage]) -> Vec<ReleaseGroup> {
+    let mut groups: BTreeMap<(bool, String, String), ReleaseGroup> = BTreeMap::new();
+
+    for image in images {
+        let key = (
+            image.release.is_empty(),
+            image.namespace.clone(),
+            image.release.clone(),
+        );
+        let group = groups.entry(key).or_insert_with(|| ReleaseGroup {
+            release: image.release.clone(),
+            namespace: image.namespace.clone(),
+            charts: Vec::new(),
+            managed_by: Vec::new(),
+            images: Vec::new(),
+            containers: 0,
+        });
+
+        let reference = image.reference();
+        for (values, value) in [
+            (&mut group.charts, &image.chart),
+            (&mut group.managed_by, &image.managed_by),
+            (&mut group.images, &reference),
+        ] {
+            if !value.is_empty() && !values.contains(value) {
+                values.push(value.clone());
+            }
+        }
+        group.containers += 1;
+    }
+
+    groups
+        .into_values()
+        .map(|mut group| {
+            group.charts.sort();
+            group.managed_by.sort();
+            group.images.sort();
+            group
+        })
+        .collect()
+}
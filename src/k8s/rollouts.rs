@@ -0,0 +1,398 @@
+use crate::k8s::pod_workload;
+use crate::k8s::workloads::{POD_TEMPLATE_HASH_LABEL, running_digest};
+use clap::ValueEnum;
+use k8s_openapi::api::apps::v1::{DaemonSet, Deployment, ReplicaSet, StatefulSet};
+use k8s_openapi::api::core::v1::{Pod, PodSpec};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+/// Workload kinds whose rollout can be followed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum WorkloadKind {
+    /// A Deployment, rolled out through ReplicaSets
+    #[value(aliases = ["deploy", "deployments"])]
+    Deployment,
+    /// A StatefulSet
+    #[value(name = "statefulset", aliases = ["sts", "statefulsets"])]
+    StatefulSet,
+    /// A DaemonSet
+    #[value(name = "daemonset", aliases = ["ds", "daemonsets"])]
+    DaemonSet,
+}
+
+impl WorkloadKind {
+    /// Get the kind as written in manifests and owner references (e.g. Deployment)
+    pub fn kind(&self) -> &'static str {
+        match self {
+            WorkloadKind::Deployment => "Deployment",
+            WorkloadKind::StatefulSet => "StatefulSet",
+            WorkloadKind::DaemonSet => "DaemonSet",
+        }
+    }
+}
+
+impl fmt::Display for WorkloadKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkloadKind::Deployment => write!(f, "deployment"),
+            WorkloadKind::StatefulSet => write!(f, "statefulset"),
+            WorkloadKind::DaemonSet => write!(f, "daemonset"),
+        }
+    }
+}
+
+/// Annotation holding the revision of a Deployment and of its ReplicaSets
+const DEPLOYMENT_REVISION_ANNOTATION: &str = "deployment.kubernetes.io/revision";
+
+/// Label StatefulSet and DaemonSet controllers set to the revision of a pod
+const CONTROLLER_REVISION_HASH_LABEL: &str = "controller-revision-hash";
+
+/// Annotation holding the generation of a DaemonSet's pod template
+const DAEMONSET_GENERATION_ANNOTATION: &str = "deprecated.daemonset.template.generation";
+
+/// Label DaemonSet controllers set to the template generation of a pod
+const POD_TEMPLATE_GENERATION_LABEL: &str = "pod-template-generation";
+
+/// The pod label telling pods of a workload's current revision apart
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct WorkloadRevision {
+    /// Label the workload controller sets on its pods
+    pub label: &'static str,
+    /// Value of the label on pods of the current revision
+    pub value: String,
+}
+
+impl WorkloadRevision {
+    /// Create a revision from the pod label telling it apart
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - Label the workload controller sets on its pods
+    /// * `value` - Value of the label on pods of the revision
+    ///
+    /// # Returns
+    ///
+    /// * `WorkloadRevision` - The revision
+    pub fn new(label: &'static str, value: impl Into<String>) -> Self {
+        Self {
+            label,
+            value: value.into(),
+        }
+    }
+
+    /// Get the current revision of a Deployment from its ReplicaSets
+    ///
+    /// # Arguments
+    ///
+    /// * `deployment` - The Deployment
+    /// * `replica_sets` - ReplicaSets of its namespace
+    ///
+    /// # Returns
+    ///
+    /// * `Option<WorkloadRevision>` - The pod template hash of the ReplicaSet of the
+    ///   Deployment's revision, or None before the controller created it
+    pub fn of_deployment(
+        deployment: &Deployment,
+        replica_sets: &[ReplicaSet],
+    ) -> Option<WorkloadRevision> {
+        let revision = deployment
+            .metadata
+            .annotations
+            .as_ref()?
+            .get(DEPLOYMENT_REVISION_ANNOTATION)?;
+        let owned = |replica_set: &&ReplicaSet| {
+            replica_set
+                .metadata
+                .owner_references
+                .iter()
+                .flatten()
+                .any(|owner| {
+                    owner.kind == "Deployment"
+                        && Some(&owner.name) == deployment.metadata.name.as_ref()
+                        && deployment
+                            .metadata
+                            .uid
+                            .as_ref()
+                            .is_none_or(|uid| *uid == owner.uid)
+                })
+        };
+        let value = replica_sets
+            .iter()
+            .filter(owned)
+            .find(|replica_set| {
+                replica_set
+                    .metadata
+                    .annotations
+                    .as_ref()
+                    .and_then(|annotations| annotations.get(DEPLOYMENT_REVISION_ANNOTATION))
+                    == Some(revision)
+            })?
+            .metadata
+            .labels
+            .as_ref()?
+            .get(POD_TEMPLATE_HASH_LABEL)?;
+        Some(WorkloadRevision {
+            label: POD_TEMPLATE_HASH_LABEL,
+            value: value.clone(),
+        })
+    }
+
+    /// Get the current revision of a StatefulSet
+    ///
+    /// # Returns
+    ///
+    /// * `Option<WorkloadRevision>` - The update revision, or None before the
+    ///   controller reported it
+    pub fn of_statefulset(statefulset: &StatefulSet) -> Option<WorkloadRevision> {
+        let value = statefulset.status.as_ref()?.update_revision.clone()?;
+        Some(WorkloadRevision {
+            label: CONTROLLER_REVISION_HASH_LABEL,
+            value,
+        })
+    }
+
+    /// Get the current revision of a DaemonSet
+    ///
+    /// # Returns
+    ///
+    /// * `Option<WorkloadRevision>` - The pod template generation, or None without the
+    ///   annotation the API server sets
+    pub fn of_daemonset(daemonset: &DaemonSet) -> Option<WorkloadRevision> {
+        let value = daemonset
+            .metadata
+            .annotations
+            .as_ref()?
+            .get(DAEMONSET_GENERATION_ANNOTATION)?;
+        Some(WorkloadRevision {
+            label: POD_TEMPLATE_GENERATION_LABEL,
+            value: value.clone(),
+        })
+    }
+
+    /// Check whether a pod belongs to this revision
+    fn matches(&self, pod: &Pod) -> bool {
+        pod.metadata
+            .labels
+            .as_ref()
+            .and_then(|labels| labels.get(self.label))
+            == Some(&self.value)
+    }
+}
+
+/// Pods of a workload running one version of a container image
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ImageVersion {
+    /// The image as written in the pod spec
+    pub image: String,
+    /// The digest the containers run, empty until they have started
+    pub digest: String,
+    /// Whether the pods run the image of the workload's current revision
+    pub desired: bool,
+    /// Number of pods running this version
+    pub pods: usize,
+    /// Nodes the pods run on
+    pub nodes: Vec<String>,
+}
+
+/// Rollout progress of one container of a workload
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ContainerRollout {
+    /// Name of the container
+    pub container: String,
+    /// Whether the container is an init container
+    pub init: bool,
+    /// The image in the workload's pod template
+    pub desired: String,
+    /// The versions pods run, the desired ones first
+    pub versions: Vec<ImageVersion>,
+}
+
+impl ContainerRollout {
+    /// Get the number of pods running the desired image
+    pub fn updated(&self) -> usize {
+        self.versions
+            .iter()
+            .filter(|version| version.desired)
+            .map(|version| version.pods)
+            .sum()
+    }
+
+    /// Get the number of pods running the container
+    pub fn total(&self) -> usize {
+        self.versions.iter().map(|version| version.pods).sum()
+    }
+
+    /// Get the share of pods running the desired image, rounded down
+    ///
+    /// # Returns
+    ///
+    /// * `u32` - The percentage, 100 when the workload has no pods
+    pub fn percent_complete(&self) -> u32 {
+        match self.total() {
+            0 => 100,
+            total => (self.updated() * 100 / total) as u32,
+        }
+    }
+
+    /// Get the nodes still running an old version
+    pub fn outdated_nodes(&self) -> Vec<&str> {
+        let nodes: BTreeSet<&str> = self
+            .versions
+            .iter()
+            .filter(|version| !version.desired)
+            .flat_map(|version| version.nodes.iter().map(String::as_str))
+            .collect();
+        nodes.into_iter().collect()
+    }
+}
+
+/// Image rollout progress of a workload
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RolloutImages {
+    /// Namespace of the workload
+    pub namespace: String,
+    /// The workload as Kind/name (e.g. Deployment/web)
+    pub workload: String,
+    /// Progress per container of the pod template, init containers first
+    pub containers: Vec<ContainerRollout>,
+}
+
+/// Summarize which image versions the pods of a workload run
+///
+/// Pods are matched to the workload through their owner, Deployments through
+/// their ReplicaSets, so pods of every revision are counted. Pods of the
+/// current revision run the desired image. Pods of older revisions do too
+/// when their image is the template's and runs a digest of the current
+/// revision, e.g. a sidecar the rollout did not change; after a tag moved,
+/// they keep the old digest and are outdated. Without a known revision, pods
+/// whose image is the template's are desired.
+///
+/// # Arguments
+///
+/// * `namespace` - Namespace of the workload
+/// * `workload` - The workload as Kind/name (e.g. Deployment/web)
+/// * `template` - The pod spec of the workload's template
+/// * `revision` - The current revision of the workload, if known
+/// * `pods` - Pods to pick the workload's pods from
+///
+/// # Returns
+///
+/// * `RolloutImages` - The progress per container of the template
+pub fn summarize_rollout(
+    namespace: &str,
+    workload: &str,
+    template: &PodSpec,
+    revision: Option<&WorkloadRevision>,
+    pods: &[Pod],
+) -> RolloutImages {
+    let pods: Vec<&Pod> = pods
+        .iter()
+        .filter(|pod| {
+            pod.metadata.namespace.as_deref() == Some(namespace) && pod_workload(pod) == workload
+        })
+        .collect();
+
+    let containers = template
+        .init_containers
+        .iter()
+        .flatten()
+        .map(|container| (true, container))
+        .chain(
+            template
+                .containers
+                .iter()
+                .map(|container| (false, container)),
+        )
+        .map(|(init, container)| {
+            let desired = container.image.clone().unwrap_or_default();
+            // Image, digest and whether the pod is of the current revision, per pod
+            let mut runs: Vec<(String, String, Option<bool>, Option<&String>)> = Vec::new();
+
+            for pod in &pods {
+                let Some(spec) = &pod.spec else {
+                    continue;
+                };
+                let pod_containers = if init {
+                    spec.init_containers.as_deref().unwrap_or_default()
+                } else {
+                    spec.containers.as_slice()
+                };
+                let Some(image) = pod_containers
+                    .iter()
+                    .find(|candidate| candidate.name == container.name)
+                    .and_then(|candidate| candidate.image.clone())
+                else {
+                    continue;
+                };
+                let statuses = pod.status.as_ref().and_then(|status| {
+                    if init {
+                        status.init_container_statuses.as_deref()
+                    } else {
+                        status.container_statuses.as_deref()
+                    }
+                });
+                let digest = statuses
+                    .unwrap_or_default()
+                    .iter()
+                    .find(|status| status.name == container.name)
+                    .and_then(running_digest)
+                    .unwrap_or_default();
+
+                let current = revision.map(|revision| revision.matches(pod));
+                runs.push((image, digest, current, spec.node_name.as_ref()));
+            }
+
+            let current_digests: BTreeSet<&str> = runs
+                .iter()
+                .filter(|(_, digest, current, _)| *current == Some(true) && !digest.is_empty())
+                .map(|(_, digest, _, _)| digest.as_str())
+                .collect();
+            // Pods per (image, digest, desired), with their nodes
+            let mut versions: BTreeMap<(String, String, bool), (usize, BTreeSet<String>)> =
+                BTreeMap::new();
+            for (image, digest, current, node) in &runs {
+                let is_desired = match current {
+                    Some(true) => true,
+                    Some(false) => *image == desired && current_digests.contains(digest.as_str()),
+                    None => *image == desired,
+                };
+                let (count, nodes) = versions
+                    .entry((image.clone(), digest.clone(), is_desired))
+                    .or_default();
+                *count += 1;
+                if let Some(node) = node {
+                    nodes.insert((*node).clone());
+                }
+            }
+
+            let mut versions: Vec<ImageVersion> = versions
+                .into_iter()
+                .map(|((image, digest, desired), (pods, nodes))| ImageVersion {
+                    image,
+                    digest,
+                    desired,
+                    pods,
+                    nodes: nodes.into_iter().collect(),
+                })
+                .collect();
+            versions.sort_by_key(|version| !version.desired);
+
+            ContainerRollout {
+                container: container.name.clone(),
+                init,
+                desired,
+                versions,
+            }
+        })
+        .collect();
+
+    RolloutImages {
+        namespace: namespace.to_string(),
+        workload: workload.to_string(),
+        containers,
+    }
+}
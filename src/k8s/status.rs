@@ -0,0 +1,192 @@
+use crate::registry::ImageReference;
+use k8s_openapi::api::core::v1::{ContainerStatus, Event, Pod};
+
+/// Container waiting reasons that indicate the image could not be pulled or resolved
+pub const IMAGE_ERROR_REASONS: [&str; 7] = [
+    "ErrImagePull",
+    "ImagePullBackOff",
+    "InvalidImageName",
+    "ErrImageNeverPull",
+    "ImageInspectError",
+    "RegistryUnavailable",
+    "SignatureValidationFailed",
+];
+
+/// Represents a container whose image could not be pulled
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ImageError {
+    /// Name of the pod containing the container
+    pub pod_name: String,
+    /// Kubernetes namespace of the pod
+    pub namespace: String,
+    /// Name of the node the pod is scheduled on (if any)
+    pub node_name: String,
+    /// Name of the container that failed to pull its image
+    pub container_name: String,
+    /// Full image reference as requested in the pod spec
+    pub image: String,
+    /// Registry the image is being pulled from
+    pub registry: String,
+    /// Waiting reason reported by the kubelet (e.g. ImagePullBackOff)
+    pub reason: String,
+    /// Most detailed failure message available from the status or related events
+    pub message: String,
+}
+
+/// Describe the current state of a container from its status
+///
+/// # Arguments
+///
+/// * `status` - The container status reported by the kubelet
+///
+/// # Returns
+///
+/// * `String` - The waiting/terminated reason, or "Running"
+pub fn container_state(status: &ContainerStatus) -> String {
+    let Some(state) = status.state.as_ref() else {
+        return String::new();
+    };
+
+    if let Some(waiting) = &state.waiting {
+        return waiting
+            .reason
+            .clone()
+            .unwrap_or_else(|| "Waiting".to_string());
+    }
+
+    if state.running.is_some() {
+        return "Running".to_string();
+    }
+
+    state
+        .terminated
+        .as_ref()
+        .map(|t| t.reason.clone().unwrap_or_else(|| "Terminated".to_string()))
+        .unwrap_or_default()
+}
+
+/// Extract image pull failures from the container statuses of a pod
+///
+/// Both regular and init containers are inspected, since a failing init
+/// container image blocks the whole pod.
+///
+/// # Arguments
+///
+/// * `pod` - The pod to inspect
+///
+/// # Returns
+///
+/// * `Vec<ImageError>` - List of containers stuck on an image error
+pub fn process_pod_errors(pod: &Pod) -> Vec<ImageError> {
+    let Some(status) = pod.status.as_ref() else {
+        return Vec::new();
+    };
+
+    let pod_name = pod.metadata.name.clone().unwrap_or_default();
+    let namespace = pod.metadata.namespace.clone().unwrap_or_default();
+    let node_name = pod
+        .spec
+        .as_ref()
+        .and_then(|spec| spec.node_name.clone())
+        .unwrap_or_default();
+
+    status
+        .init_container_statuses
+        .iter()
+        .flatten()
+        .chain(status.container_statuses.iter().flatten())
+        .filter_map(|cs| {
+            let waiting = cs.state.as_ref()?.waiting.as_ref()?;
+            let reason = waiting.reason.clone()?;
+            if !IMAGE_ERROR_REASONS.contains(&reason.as_str()) {
+                return None;
+            }
+
+            // The status image is the one the kubelet is trying to pull; fall back
+            // to the spec in case the runtime has not filled it in yet.
+            let image = if cs.image.is_empty() {
+                spec_image(pod, &cs.name).unwrap_or_default()
+            } else {
+                cs.image.clone()
+            };
+
+            Some(ImageError {
+                pod_name: pod_name.clone(),
+                namespace: namespace.clone(),
+                node_name: node_name.clone(),
+                container_name: cs.name.clone(),
+                // InvalidImageName errors have no registry to report, and the
+                // invalid reference was already warned about when listing images
+                registry: ImageReference::parse(&image)
+                    .map(|reference| reference.registry().to_string())
+                    .unwrap_or_default(),
+                image,
+                reason,
+                message: waiting.message.clone().unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// Enrich image errors with the message of the latest related `Failed` event
+///
+/// Container statuses only carry the message of the current back-off, while
+/// the kubelet records the underlying registry error (authentication, DNS,
+/// manifest unknown, ...) as an event on the pod.
+///
+/// # Arguments
+///
+/// * `errors` - The image errors to enrich
+/// * `events` - Events to correlate with the failing containers
+pub fn attach_event_messages(errors: &mut [ImageError], events: &[Event]) {
+    for error in errors.iter_mut() {
+        let latest = events
+            .iter()
+            .filter(|event| event.reason.as_deref() == Some("Failed"))
+            .filter(|event| {
+                let obj = &event.involved_object;
+                obj.name.as_deref() == Some(error.pod_name.as_str())
+                    && obj.namespace.as_deref() == Some(error.namespace.as_str())
+                    && obj
+                        .field_path
+                        .as_deref()
+                        .and_then(field_path_container)
+                        .is_some_and(|name| name == error.container_name)
+            })
+            .max_by_key(|event| event_timestamp(event));
+
+        if let Some(message) = latest.and_then(|event| event.message.clone()) {
+            error.message = message;
+        }
+    }
+}
+
+/// Extract the container name from an event field path such as `spec.containers{app}`
+pub(crate) fn field_path_container(field_path: &str) -> Option<&str> {
+    let start = field_path.find('{')?;
+    let end = field_path.rfind('}')?;
+    (start < end).then(|| &field_path[start + 1..end])
+}
+
+/// Get the most recent timestamp recorded on an event, in seconds since the epoch
+pub(crate) fn event_timestamp(event: &Event) -> i64 {
+    event
+        .last_timestamp
+        .as_ref()
+        .map(|t| t.0.as_second())
+        .or_else(|| event.event_time.as_ref().map(|t| t.0.as_second()))
+        .or_else(|| event.first_timestamp.as_ref().map(|t| t.0.as_second()))
+        .unwrap_or_default()
+}
+
+/// Find the image of a (init) container in the pod spec
+fn spec_image(pod: &Pod, container_name: &str) -> Option<String> {
+    let spec = pod.spec.as_ref()?;
+    spec.containers
+        .iter()
+        .chain(spec.init_containers.iter().flatten())
+        .find(|c| c.name == container_name)?
+        .image
+        .clone()
+}
@@ -0,0 +1,242 @@
+use crate::registry::{DEFAULT_TAG, ImageReference};
+use k8s_openapi::api::core::v1::{Container, ContainerStatus, Pod, PodSpec};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Label the pods of a Deployment's ReplicaSets carry, the suffix of the ReplicaSet name
+pub(crate) const POD_TEMPLATE_HASH_LABEL: &str = "pod-template-hash";
+
+/// Get the workload owning a pod
+///
+/// Pods of a Deployment are owned through a ReplicaSet named after the
+/// Deployment and the pod template hash; the Deployment is reported instead.
+///
+/// # Arguments
+///
+/// * `pod` - The pod to look up
+///
+/// # Returns
+///
+/// * `String` - The owner as Kind/name (e.g. Deployment/web), empty without an owner
+pub fn pod_workload(pod: &Pod) -> String {
+    let Some(owner) = pod.metadata.owner_references.as_ref().and_then(|owners| {
+        owners
+            .iter()
+            .find(|owner| owner.controller == Some(true))
+            .or_else(|| owners.first())
+    }) else {
+        return String::new();
+    };
+
+    let template_hash = pod
+        .metadata
+        .labels
+        .as_ref()
+        .and_then(|labels| labels.get(POD_TEMPLATE_HASH_LABEL));
+    if let (Some(hash), "ReplicaSet") = (template_hash, owner.kind.as_str()) {
+        if let Some(deployment) = owner.name.strip_suffix(&format!("-{}", hash)) {
+            return format!("Deployment/{}", deployment);
+        }
+    }
+    format!("{}/{}", owner.kind, owner.name)
+}
+
+/// Get the digest of the image a container runs
+///
+/// # Arguments
+///
+/// * `status` - The status of the container
+///
+/// # Returns
+///
+/// * `Option<String>` - The digest (e.g. sha256:...), or None until the container has
+///   started and for images loaded on the node without a registry
+pub(crate) fn running_digest(status: &ContainerStatus) -> Option<String> {
+    // Images loaded on the node without a registry only report a local image ID
+    let (_, digest) = status.image_id.rsplit_once('@')?;
+    digest.contains(':').then(|| digest.to_string())
+}
+
+/// Pod templates of workloads, keyed by namespace and workload as Kind/name
+pub type WorkloadTemplates = HashMap<(String, String), PodSpec>;
+
+/// Which images of a container disagree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MismatchKind {
+    /// The pod spec differs from the pod template of its workload
+    Template,
+    /// The image the container runs differs from the pod spec
+    Running,
+}
+
+impl fmt::Display for MismatchKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MismatchKind::Template => write!(f, "template"),
+            MismatchKind::Running => write!(f, "running"),
+        }
+    }
+}
+
+/// A container whose image differs from the one it is expected to run
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ImageMismatch {
+    /// Name of the pod containing the container
+    pub pod_name: String,
+    /// Kubernetes namespace of the pod
+    pub namespace: String,
+    /// Name of the container
+    pub container_name: String,
+    /// The owning workload as Kind/name (e.g. Deployment/web), empty without an owner
+    pub workload: String,
+    /// Name of the node the pod runs on
+    pub node_name: String,
+    /// Which images disagree
+    pub kind: MismatchKind,
+    /// The expected image: the template image, or the pod spec image for running mismatches
+    pub expected: String,
+    /// The image found instead: the pod spec image, or the image the container runs
+    pub actual: String,
+}
+
+/// Find containers whose image differs from their workload's template or from what they run
+///
+/// Pods from an old ReplicaSet or StatefulSet revision keep the previous image
+/// until they are replaced, so template mismatches point to stuck rollouts or
+/// left-over pods. Running mismatches are compared after normalization, so
+/// `nginx` and `docker.io/library/nginx:latest` match; digests are compared
+/// when the pod spec pins one.
+///
+/// # Arguments
+///
+/// * `pods` - The pods to check
+/// * `templates` - Pod templates of the workloads owning the pods; pods of other
+///   workloads only get running mismatches
+///
+/// # Returns
+///
+/// * `Vec<ImageMismatch>` - The mismatches, in pod order, init containers first
+pub fn find_image_mismatches(pods: &[Pod], templates: &WorkloadTemplates) -> Vec<ImageMismatch> {
+    let mut mismatches = Vec::new();
+
+    for pod in pods {
+        let Some(spec) = &pod.spec else {
+            continue;
+        };
+        let namespace = pod.metadata.namespace.clone().unwrap_or_default();
+        let workload = pod_workload(pod);
+        let template = templates.get(&(namespace.clone(), workload.clone()));
+        let status = pod.status.as_ref();
+        let mismatch = |container: &Container, kind, expected: &str, actual: &str| ImageMismatch {
+            pod_name: pod.metadata.name.clone().unwrap_or_default(),
+            namespace: namespace.clone(),
+            container_name: container.name.clone(),
+            workload: workload.clone(),
+            node_name: spec.node_name.clone().unwrap_or_default(),
+            kind,
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+        };
+
+        let groups = [
+            (
+                spec.init_containers.as_deref(),
+                template.and_then(|template| template.init_containers.as_deref()),
+                status.and_then(|status| status.init_container_statuses.as_deref()),
+            ),
+            (
+                Some(spec.containers.as_slice()),
+                template.map(|template| template.containers.as_slice()),
+                status.and_then(|status| status.container_statuses.as_deref()),
+            ),
+        ];
+        for (containers, template_containers, statuses) in groups {
+            for container in containers.unwrap_or_default() {
+                let Some(image) = container.image.as_deref() else {
+                    continue;
+                };
+
+                if let Some(expected) = template_containers
+                    .unwrap_or_default()
+                    .iter()
+                    .find(|candidate| candidate.name == container.name)
+                    .and_then(|candidate| candidate.image.as_deref())
+                {
+                    if !same_image(expected, image) {
+                        mismatches.push(mismatch(
+                            container,
+                            MismatchKind::Template,
+                            expected,
+                            image,
+                        ));
+                    }
+                }
+
+                if let Some(running) = statuses
+                    .unwrap_or_default()
+                    .iter()
+                    .find(|status| status.name == container.name)
+                    .and_then(|status| running_mismatch(image, status))
+                {
+                    mismatches.push(mismatch(container, MismatchKind::Running, image, &running));
+                }
+            }
+        }
+    }
+
+    mismatches
+}
+
+/// Get the image a container runs if it differs from its pod spec
+fn running_mismatch(image: &str, status: &ContainerStatus) -> Option<String> {
+    // Containers that have not started yet report no image
+    if status.image.is_empty() {
+        return None;
+    }
+
+    let pinned = ImageReference::parse(image)
+        .ok()
+        .and_then(|reference| reference.digest().map(str::to_string));
+    if let Some(digest) = pinned {
+        let (_, running) = status.image_id.rsplit_once('@')?;
+        return (running != digest).then(|| status.image_id.clone());
+    }
+
+    // Some runtimes only report the image ID when several tags share it
+    if status.image.starts_with("sha256:") {
+        return None;
+    }
+    (!same_image(image, &status.image)).then(|| status.image.clone())
+}
+
+/// Check if two image references resolve to the same image
+///
+/// Digests are compared when both references carry one, tags otherwise (the
+/// implicit `latest` included); a reference pinned only by digest never
+/// matches one pinned only by tag.
+fn same_image(expected: &str, actual: &str) -> bool {
+    let (Ok(expected), Ok(actual)) = (
+        ImageReference::parse(expected),
+        ImageReference::parse(actual),
+    ) else {
+        return expected == actual;
+    };
+    if expected.name() != actual.name() {
+        return false;
+    }
+    if let (Some(expected), Some(actual)) = (expected.digest(), actual.digest()) {
+        return expected == actual;
+    }
+    match (implicit_tag(&expected), implicit_tag(&actual)) {
+        (Some(expected), Some(actual)) => expected == actual,
+        _ => false,
+    }
+}
+
+/// Get the tag of a reference, `latest` when neither a tag nor a digest is given
+fn implicit_tag(reference: &ImageReference) -> Option<&str> {
+    reference
+        .tag()
+        .or_else(|| reference.digest().is_none().then_some(DEFAULT_TAG))
+}
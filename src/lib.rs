@@ -2,21 +2,88 @@
 //!
 //! This crate provides functionality for managing and inspecting container images
 //! in Kubernetes clusters.
+//!
+//! Image inventories are built from an [`ImageQuery`] and any [`PodSource`]: a live
+//! cluster through [`K8sClient`], or pods loaded from elsewhere.
+//!
+//! ```no_run
+//! use kimspect::{ImageQuery, K8sClient};
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let client = K8sClient::new().await?;
+//! let query = ImageQuery::new()
+//!     .all_namespaces(true)
+//!     .exclude_registry("registry.k8s.io");
+//! for image in client.get_pod_images(&query).await? {
+//!     println!("{}/{}:{}", image.registry, image.image_name, image.image_version);
+//! }
+//! # Ok(())
+//! # }
+//! ```
 
 // Public API
-pub use cli::Args;
 pub use k8s::K8sClient;
 
+/// Command-line interface of the kimspect binary
+///
+/// The clap types change with the command line and are not part of the stable
+/// library API.
+#[doc(hidden)]
+pub mod cli;
+
 // Internal modules
-mod cli;
+mod config;
 mod k8s;
+mod registry;
+mod serve;
+mod tui;
 mod utils;
+mod webhook;
 
 // Re-export commonly used items
-pub use cli::{Commands, GetImages, LogFormat, OutputFormat};
-pub use k8s::{K8sError, PodImage, extract_registry, process_pod, split_image};
+pub use config::{CONFIG_ENV, Config, Settings};
+pub use k8s::{
+    AccessStatus, ArchMismatch, COMMAND_PERMISSIONS, ClientOptions, CommandAccess,
+    CommandPermissions, ContainerRollout, FilePodSource, GET_CRONJOBS, GET_DAEMONSETS,
+    GET_DEPLOYMENTS, GET_JOBS, GET_NAMESPACES, GET_REPLICASETS, GET_STATEFULSETS, HELM_CHART_LABEL,
+    HELM_RELEASE_ANNOTATION, IMAGE_ERROR_REASONS, INSTANCE_LABEL, ImageError, ImageField,
+    ImageMismatch, ImagePin, ImagePull, ImageQuery, ImageVersion, Inventory, K8sError,
+    LIST_CRONJOBS, LIST_DAEMONSETS, LIST_DEPLOYMENTS, LIST_EVENTS, LIST_JOBS, LIST_NAMESPACES,
+    LIST_NODES, LIST_PODS, LIST_REPLICASETS, LIST_STATEFULSETS, MANAGED_BY_LABEL, MismatchKind,
+    NodeInfo, PERMISSIONS, Permission, PermissionCheck, PodImage, PodSource, PullGroupBy,
+    PullMessage, PullSummary, RECENT_JOB_WINDOW, ReleaseGroup, ReleaseInfo, RolloutImages,
+    WATCH_NODES, WATCH_PODS, WorkloadKind, WorkloadRevision, WorkloadTemplates, api_error,
+    attach_event_messages, collect_image_pins, collect_pod_images, container_state,
+    evaluate_access, extract_registry, find_arch_mismatches, find_image_mismatches,
+    find_image_pins, format_duration, group_by_release, manifest_reference, object_pods,
+    parse_go_duration, parse_manifests, parse_pull_message, pod_workload, process_node,
+    process_pod, process_pod_errors, process_pull_events, render_kubectl_patches,
+    render_kustomize_images, render_patch_documents, scheduled_pods, sort_pod_images, split_image,
+    summarize_pulls, summarize_rollout,
+};
+pub use registry::{
+    DEFAULT_REGISTRY, DEFAULT_TAG, ImagePolicy, ImageReference, MIRRORS_ENV, MirrorMap, MirrorRule,
+    PUBLIC_CLASS, Platform, PolicyRule, PolicyViolation, REGISTRIES_ENV, ReferenceError,
+    RegistryClassifier, RegistryClient, RegistryRule, UNKNOWN_CLASS, UpstreamGroup,
+    group_by_upstream, parse_bearer_challenge,
+};
+pub use serve::{
+    ApiParams, METRICS_CONTENT_TYPE, RegistryUsage, ServerState, api_router, load_tls_config,
+    metrics_router, parse_listen_addr, registry_usage, render_metrics, serve, serve_tls,
+};
+pub use tui::{
+    ALL_ENTRY, ContainerRow, FilterField, ImageFilter, TuiApp, TuiPane, container_rows,
+    detail_fields, draw_tui, run_tui,
+};
 pub use utils::logging;
-pub use utils::{display_pod_images, display_registries, strip_registry};
+pub use utils::{
+    GoTemplate, JsonPath, TemplateError, display_access_matrix, display_arch_mismatches,
+    display_image_errors, display_image_mismatches, display_image_pulls, display_nodes,
+    display_pod_images, display_pull_summaries, display_registries, display_release_groups,
+    display_rollout_images, display_upstream_groups, image_list, registry_host_matches,
+    registry_rows, render_image_template, strip_registry,
+};
+pub use webhook::{ImageAdmission, webhook_router};
 
 /// Result type for Kimspect operations
 pub type KimspectResult<T> = anyhow::Result<T>;
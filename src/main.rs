@@ -1,79 +1,228 @@
-use anyhow::Context;
-use clap::Parser;
+use anyhow::{Context, bail};
+use clap::{CommandFactory, FromArgMatches};
+use kimspect::cli::{
+    Args, AuthCommands, Commands, CustomColumn, GetImages, ImageGroupBy, OutputFormat, PinFormat,
+    PluginCommands, krew_manifest, parse_checksums, plugin_args, plugin_name,
+};
 use kimspect::{
-    Args, Commands, GetImages, K8sClient, KimspectResult, display_pod_images, display_registries,
-    logging,
+    Config, FilePodSource, ImageAdmission, ImageFilter, ImagePolicy, ImageQuery, K8sClient,
+    K8sError, KimspectResult, MirrorMap, RegistryClassifier, ServerState, Settings, api_router,
+    collect_image_pins, collect_pod_images, display_access_matrix, display_arch_mismatches,
+    display_image_errors, display_image_mismatches, display_image_pulls, display_nodes,
+    display_pod_images, display_pull_summaries, display_registries, display_release_groups,
+    display_rollout_images, display_upstream_groups, evaluate_access, group_by_release,
+    group_by_upstream, load_tls_config, logging, metrics_router, render_image_template,
+    render_kubectl_patches, render_kustomize_images, render_patch_documents, run_tui, serve,
+    serve_tls, sort_pod_images, summarize_pulls, webhook_router,
 };
+use std::sync::Arc;
 use tracing::{debug, info, instrument, warn};
 
 /// Main entry point for the Kimspect application
 #[tokio::main]
 async fn main() -> KimspectResult<()> {
-    let args = Args::parse();
+    // As a kubectl plugin the binary is run as kubectl-kimspect or kubectl-images
+    let mut argv: Vec<_> = std::env::args_os().collect();
+    let mut command = Args::command();
+    if let Some(name) = argv.first().and_then(|argv0| plugin_name(argv0)) {
+        command = command.bin_name(format!("kubectl {}", name));
+        argv = plugin_args(&name, argv);
+    }
+    let matches = command.get_matches_from(argv);
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
     // Initialize logging with the specified format
-    logging::init_logging(logging::configure_logging(args.verbose), args.log_format)
-        .context("Failed to initialize logging")?;
+    logging::init_logging(
+        logging::configure_logging(args.verbose),
+        args.log_format,
+        args.log_file.as_deref(),
+    )
+    .context("Failed to initialize logging")?;
 
-    debug!("Application started with args: {:?}", args);
+    // Options missing on the command line fall back to the config file
+    let settings = Config::load(args.config.as_deref())?.settings(args.profile.as_deref())?;
+    args.apply_settings(&settings, &matches);
 
-    // Create the client with improved error context
-    let client = K8sClient::new()
-        .await
-        .context("Failed to create Kubernetes client")?;
+    debug!("Application started with args: {:?}", args);
+    args.command.check_output().map_err(anyhow::Error::msg)?;
 
-    info!("Successfully connected to Kubernetes cluster");
+    // Offline commands read manifests and must work without a cluster
+    let client = if args.command.needs_cluster() {
+        let client = K8sClient::with_options(&args.client_options())
+            .await
+            .context("Failed to create Kubernetes client")?;
+        info!("Successfully connected to Kubernetes cluster");
+        Some(client)
+    } else {
+        info!("Reading manifests instead of connecting to the cluster");
+        None
+    };
 
-    process_commands(args, client).await?;
+    process_commands(args, client, &settings).await?;
 
     debug!("Application completed successfully");
     Ok(())
 }
 
 /// Process the command line arguments and execute the corresponding command
-#[instrument(skip(client), level = "debug")]
-async fn process_commands(args: Args, client: K8sClient) -> KimspectResult<()> {
+#[instrument(skip(client, settings), level = "debug")]
+async fn process_commands(
+    args: Args,
+    client: Option<K8sClient>,
+    settings: &Settings,
+) -> KimspectResult<()> {
     match args.command {
         Commands::Get { resource } => match resource {
             GetImages::Images {
                 namespace,
+                namespace_selector,
+                exclude_namespace,
                 node,
                 pod,
                 registry,
                 exclude_registry,
                 all_namespaces,
+                include_scheduled,
+                mirror,
+                group_by,
+                from_file,
                 output,
+                columns,
+                sort_by,
+                no_headers,
                 ..
             } => {
                 debug!(
-                    namespace = %namespace,
+                    namespace = %namespace.join(","),
+                    namespace_selector = ?namespace_selector,
+                    exclude_namespace = ?exclude_namespace.join(", "),
                     node = ?node,
                     pod = ?pod,
                     registry = ?registry,
                     exclude_registry = ?exclude_registry.join(", "),
                     all_namespaces = %all_namespaces,
+                    include_scheduled = %include_scheduled,
+                    group_by = ?group_by,
+                    from_file = ?from_file,
                     output = ?output,
+                    columns = ?columns,
+                    sort_by = ?sort_by,
                     "Processing get images command"
                 );
 
-                let pod_images = client
-                    .get_pod_images(
-                        &namespace,
-                        node.as_deref(),
-                        pod.as_deref(),
-                        registry.as_deref(),
-                        &exclude_registry,
-                        all_namespaces,
-                    )
-                    .await
-                    .context("Failed to retrieve pod images")?;
+                let mirrors = MirrorMap::from_env()?
+                    .with_rules(settings.mirror_rules()?)
+                    .with_rules(mirror);
+                let classifier =
+                    RegistryClassifier::from_env()?.with_rules(settings.registry_rules()?);
+
+                let mut query = ImageQuery::new()
+                    .all_namespaces(all_namespaces)
+                    .exclude_namespaces(exclude_namespace)
+                    .exclude_registries(exclude_registry)
+                    .include_scheduled(include_scheduled)
+                    .resolve_owners(group_by == Some(ImageGroupBy::Release));
+                // The selector replaces the "default" namespace, they cannot be combined
+                query = match namespace_selector {
+                    Some(selector) => query.namespace_selector(selector),
+                    None => query.namespaces(namespace),
+                };
+                if let Some(node) = node {
+                    query = query.node(node);
+                }
+                if let Some(pod) = pod {
+                    query = query.pod(pod);
+                }
+                if let Some(registry) = registry {
+                    query = query.registry(registry);
+                }
+
+                let (mut pod_images, nodes) = match from_file {
+                    Some(path) => {
+                        let source = FilePodSource::load(&path)?;
+                        let pod_images = collect_pod_images(&source, &query)
+                            .await
+                            .context("Failed to retrieve pod images")?;
+                        (pod_images, Vec::new())
+                    }
+                    None => {
+                        let client = require_client(client)?;
+                        let pod_images = client
+                            .get_pod_images(&query)
+                            .await
+                            .context("Failed to retrieve pod images")?;
+                        // A node-scoped wide listing also shows the runtime and platform of the node
+                        let nodes = match query.get_node() {
+                            Some(node)
+                                if matches!(output, OutputFormat::Wide)
+                                    && columns.is_empty()
+                                    && group_by.is_none() =>
+                            {
+                                client.get_nodes(Some(node)).await.unwrap_or_else(|e| {
+                                    warn!(
+                                        error = %e,
+                                        "Skipping node info due to node list failure, run `kimspect auth check` to review permissions"
+                                    );
+                                    Vec::new()
+                                })
+                            }
+                            _ => Vec::new(),
+                        };
+                        (pod_images, nodes)
+                    }
+                };
+
+                classifier.apply(&mut pod_images);
+                mirrors.apply(&mut pod_images);
 
                 if pod_images.is_empty() {
                     warn!("No pod images found matching your criteria");
+                } else if let Some(ImageGroupBy::Release) = group_by {
+                    let groups = group_by_release(&pod_images);
+                    debug!(output = ?output, "Displaying images grouped by release");
+                    display_release_groups(&groups, &output)
+                        .context("Failed to display release groups")?;
+                    info!(
+                        count = groups.len(),
+                        "Successfully displayed release groups"
+                    );
+                } else if let Some(ImageGroupBy::Upstream) = group_by {
+                    let groups = group_by_upstream(&pod_images);
+                    debug!(output = ?output, "Displaying images grouped by upstream");
+                    display_upstream_groups(&groups, &output)
+                        .context("Failed to display upstream groups")?;
+                    info!(
+                        count = groups.len(),
+                        "Successfully displayed upstream groups"
+                    );
                 } else {
-                    debug!(output = ?output, "Displaying pod images");
-                    display_pod_images(&pod_images, &output)
-                        .context("Failed to display pod images")?;
+                    // --columns is a shorthand for custom columns under default headers
+                    let output = if columns.is_empty() {
+                        output
+                    } else {
+                        OutputFormat::CustomColumns(
+                            columns.into_iter().map(CustomColumn::from).collect(),
+                        )
+                    };
+                    if let Some(field) = sort_by {
+                        sort_pod_images(&mut pod_images, field);
+                    }
+                    if output.is_template() {
+                        debug!(output = ?output, "Rendering pod images template");
+                        let text = render_image_template(&pod_images, &output)
+                            .context("Failed to render pod images")?;
+                        print!("{}", text);
+                    } else {
+                        if !nodes.is_empty() {
+                            debug!(node = ?query.get_node(), "Displaying node info");
+                            display_nodes(&nodes, &OutputFormat::Normal)
+                                .context("Failed to display nodes")?;
+                            println!();
+                        }
+                        debug!(output = ?output, "Displaying pod images");
+                        display_pod_images(&pod_images, &output, no_headers)
+                            .context("Failed to display pod images")?;
+                    }
                     info!(
                         count = pod_images.len(),
                         "Successfully displayed pod images"
@@ -83,34 +232,465 @@ async fn process_commands(args: Args, client: K8sClient) -> KimspectResult<()> {
             GetImages::Registries {
                 namespace,
                 all_namespaces,
+                include_scheduled,
+                mirror,
                 output,
                 ..
             } => {
                 debug!(
                     namespace = %namespace,
                     all_namespaces = %all_namespaces,
+                    include_scheduled = %include_scheduled,
                     output = ?output,
                     "Processing get registries command"
                 );
 
-                let registries = client
+                let mirrors = MirrorMap::from_env()?
+                    .with_rules(settings.mirror_rules()?)
+                    .with_rules(mirror);
+                let classifier =
+                    RegistryClassifier::from_env()?.with_rules(settings.registry_rules()?);
+
+                let client = require_client(client)?;
+                let scheduled = if include_scheduled {
+                    client
+                        .get_scheduled_registries(&namespace, all_namespaces)
+                        .await
+                        .context("Failed to retrieve scheduled registries")?
+                } else {
+                    Vec::new()
+                };
+                let registries = match client
                     .get_unique_registries(&namespace, all_namespaces)
                     .await
-                    .context("Failed to retrieve registries")?;
+                {
+                    // Namespaces running only batch workloads have no deployments
+                    Err(e)
+                        if !scheduled.is_empty()
+                            && matches!(
+                                e.downcast_ref::<K8sError>(),
+                                Some(K8sError::ResourceNotFound(_))
+                            ) =>
+                    {
+                        Vec::new()
+                    }
+                    registries => registries.context("Failed to retrieve registries")?,
+                };
 
-                if registries.is_empty() {
+                if registries.is_empty() && scheduled.is_empty() {
                     warn!("No registries found in the specified namespace(s)");
                 } else {
                     debug!(output = ?output, "Displaying registries");
-                    display_registries(&registries, &output)
+                    display_registries(&registries, &scheduled, &mirrors, &classifier, &output)
                         .context("Failed to display registries")?;
                     info!(
-                        count = registries.len(),
+                        running = registries.len(),
+                        scheduled = scheduled.len(),
                         "Successfully displayed registries"
                     );
                 }
             }
+            GetImages::ImageErrors {
+                namespace,
+                all_namespaces,
+                output,
+                ..
+            } => {
+                debug!(
+                    namespace = %namespace,
+                    all_namespaces = %all_namespaces,
+                    output = ?output,
+                    "Processing get image-errors command"
+                );
+
+                let errors = require_client(client)?
+                    .get_image_errors(&namespace, all_namespaces)
+                    .await
+                    .context("Failed to retrieve image errors")?;
+
+                if errors.is_empty() {
+                    info!("No image pull errors found in the specified namespace(s)");
+                } else {
+                    debug!(output = ?output, "Displaying image errors");
+                    display_image_errors(&errors, &output)
+                        .context("Failed to display image errors")?;
+                    info!(count = errors.len(), "Successfully displayed image errors");
+                }
+            }
+            GetImages::PullTimes {
+                namespace,
+                all_namespaces,
+                group_by,
+                output,
+                ..
+            } => {
+                debug!(
+                    namespace = %namespace,
+                    all_namespaces = %all_namespaces,
+                    group_by = ?group_by,
+                    output = ?output,
+                    "Processing get pull-times command"
+                );
+
+                let pulls = require_client(client)?
+                    .get_image_pulls(&namespace, all_namespaces)
+                    .await
+                    .context("Failed to retrieve image pulls")?;
+
+                if pulls.is_empty() {
+                    warn!("No image pull events found in the specified namespace(s)");
+                } else if let Some(group_by) = group_by {
+                    let summaries = summarize_pulls(&pulls, group_by);
+                    debug!(group_by = %group_by, "Displaying image pull summaries");
+                    display_pull_summaries(&summaries, &group_by)
+                        .context("Failed to display image pull summaries")?;
+                    info!(
+                        count = summaries.len(),
+                        "Successfully displayed image pull summaries"
+                    );
+                } else {
+                    debug!(output = ?output, "Displaying image pulls");
+                    display_image_pulls(&pulls, &output)
+                        .context("Failed to display image pulls")?;
+                    info!(count = pulls.len(), "Successfully displayed image pulls");
+                }
+            }
+            GetImages::Nodes { node, output, .. } => {
+                debug!(node = ?node, output = ?output, "Processing get nodes command");
+
+                let nodes = require_client(client)?
+                    .get_nodes(node.as_deref())
+                    .await
+                    .context("Failed to retrieve nodes")?;
+
+                debug!(output = ?output, "Displaying nodes");
+                display_nodes(&nodes, &output).context("Failed to display nodes")?;
+                info!(count = nodes.len(), "Successfully displayed nodes");
+            }
+            GetImages::ArchMismatches {
+                namespace,
+                all_namespaces,
+                output,
+                ..
+            } => {
+                debug!(
+                    namespace = %namespace,
+                    all_namespaces = %all_namespaces,
+                    output = ?output,
+                    "Processing get arch-mismatches command"
+                );
+
+                let mismatches = require_client(client)?
+                    .get_arch_mismatches(&namespace, all_namespaces)
+                    .await
+                    .context("Failed to check image architectures")?;
+
+                if mismatches.is_empty() {
+                    info!("No architecture mismatches found in the specified namespace(s)");
+                } else {
+                    for mismatch in &mismatches {
+                        warn!(
+                            pod = %mismatch.pod_name,
+                            namespace = %mismatch.namespace,
+                            image = %mismatch.image,
+                            node = %mismatch.node_name,
+                            "Image has no platform matching its node"
+                        );
+                    }
+                    display_arch_mismatches(&mismatches, &output)
+                        .context("Failed to display architecture mismatches")?;
+                    info!(
+                        count = mismatches.len(),
+                        "Successfully displayed architecture mismatches"
+                    );
+                }
+            }
+            GetImages::RolloutImages {
+                kind,
+                name,
+                namespace,
+                output,
+                ..
+            } => {
+                debug!(
+                    kind = %kind,
+                    name = %name,
+                    namespace = %namespace,
+                    output = ?output,
+                    "Processing get rollout-images command"
+                );
+
+                let rollout = require_client(client)?
+                    .get_rollout_images(kind, &namespace, &name)
+                    .await
+                    .context("Failed to retrieve rollout images")?;
+
+                for container in &rollout.containers {
+                    let outdated = container.outdated_nodes();
+                    if !outdated.is_empty() {
+                        debug!(
+                            container = %container.container,
+                            percent_complete = container.percent_complete(),
+                            nodes = ?outdated,
+                            "Nodes still run old image versions"
+                        );
+                    }
+                }
+                display_rollout_images(&rollout, &output)
+                    .context("Failed to display rollout images")?;
+                info!(
+                    workload = %rollout.workload,
+                    containers = rollout.containers.len(),
+                    "Successfully displayed rollout images"
+                );
+            }
+            GetImages::ImageMismatches {
+                namespace,
+                all_namespaces,
+                output,
+                ..
+            } => {
+                debug!(
+                    namespace = %namespace,
+                    all_namespaces = %all_namespaces,
+                    output = ?output,
+                    "Processing get image-mismatches command"
+                );
+
+                let mismatches = require_client(client)?
+                    .get_image_mismatches(&namespace, all_namespaces)
+                    .await
+                    .context("Failed to compare pod images")?;
+
+                if mismatches.is_empty() {
+                    info!("No image mismatches found in the specified namespace(s)");
+                } else {
+                    for mismatch in &mismatches {
+                        warn!(
+                            pod = %mismatch.pod_name,
+                            namespace = %mismatch.namespace,
+                            container = %mismatch.container_name,
+                            mismatch = %mismatch.kind,
+                            expected = %mismatch.expected,
+                            actual = %mismatch.actual,
+                            "Container image differs from the expected image"
+                        );
+                    }
+                    display_image_mismatches(&mismatches, &output)
+                        .context("Failed to display image mismatches")?;
+                    info!(
+                        count = mismatches.len(),
+                        "Successfully displayed image mismatches"
+                    );
+                }
+            }
+        },
+        Commands::Auth { action } => match action {
+            AuthCommands::Check {
+                namespace,
+                all_namespaces,
+                output,
+                ..
+            } => {
+                debug!(
+                    namespace = %namespace,
+                    all_namespaces = %all_namespaces,
+                    output = ?output,
+                    "Processing auth check command"
+                );
+
+                let checks = require_client(client)?
+                    .check_permissions(&namespace, all_namespaces)
+                    .await
+                    .context("Failed to check permissions")?;
+
+                for check in checks.iter().filter(|check| !check.allowed) {
+                    warn!(
+                        permission = %check.permission,
+                        namespace = ?check.namespace,
+                        reason = %check.reason,
+                        "Permission denied"
+                    );
+                }
+
+                let access = evaluate_access(&checks);
+                debug!(output = ?output, "Displaying access matrix");
+                display_access_matrix(&access, &output)
+                    .context("Failed to display access matrix")?;
+                info!(count = access.len(), "Successfully displayed access matrix");
+            }
+        },
+        Commands::Tui {
+            namespace,
+            all_namespaces,
+            registry,
+            image,
+            node,
+            mirror,
+            ..
+        } => {
+            debug!(
+                namespace = %namespace,
+                all_namespaces = %all_namespaces,
+                registry = ?registry,
+                image = ?image,
+                node = ?node,
+                "Processing tui command"
+            );
+
+            let mirrors = MirrorMap::from_env()?
+                .with_rules(settings.mirror_rules()?)
+                .with_rules(mirror);
+            let classifier = RegistryClassifier::from_env()?.with_rules(settings.registry_rules()?);
+            let query = ImageQuery::new()
+                .namespace(namespace)
+                .all_namespaces(all_namespaces);
+            let filter = ImageFilter::default()
+                .registry(registry.unwrap_or_default())
+                .image(image.unwrap_or_default())
+                .node(node.unwrap_or_default());
+
+            run_tui(&require_client(client)?, query, classifier, mirrors, filter).await?;
+        }
+        Commands::Serve {
+            namespace,
+            all_namespaces,
+            metrics_addr,
+            api_addr,
+            mirror,
+            ..
+        } => {
+            debug!(
+                namespace = %namespace,
+                all_namespaces = %all_namespaces,
+                metrics_addr = %metrics_addr,
+                api_addr = ?api_addr,
+                "Processing serve command"
+            );
+
+            let mirrors = MirrorMap::from_env()?
+                .with_rules(settings.mirror_rules()?)
+                .with_rules(mirror);
+            let classifier = RegistryClassifier::from_env()?.with_rules(settings.registry_rules()?);
+            let (inventory, watch) = require_client(client)?
+                .watch_inventory((!all_namespaces).then_some(namespace.as_str()));
+            let watch = tokio::spawn(watch);
+
+            let query = ImageQuery::new()
+                .namespace(namespace)
+                .all_namespaces(all_namespaces);
+            let state = Arc::new(ServerState::new(inventory, query, classifier, mirrors));
+            // The inventory is served apart from the metrics, it is only reachable when asked for
+            let metrics = serve(metrics_addr, metrics_router(state.clone()));
+            let result = match api_addr {
+                Some(api_addr) => {
+                    if !api_addr.ip().is_loopback() {
+                        warn!(api_addr = %api_addr, "The JSON inventory has no authentication, anyone reaching this address can read it");
+                    }
+                    tokio::try_join!(metrics, serve(api_addr, api_router(state))).map(|_| ())
+                }
+                None => metrics.await,
+            };
+            watch.abort();
+            result?;
+        }
+        Commands::Webhook {
+            addr,
+            tls_cert,
+            tls_key,
+            insecure,
+            enforce,
+            disallow_registry,
+            disallow_class,
+            deny_latest,
+            require_digest,
+        } => {
+            debug!(
+                addr = %addr,
+                enforce = %enforce,
+                "Processing webhook command"
+            );
+
+            let classifier = RegistryClassifier::from_env()?.with_rules(settings.registry_rules()?);
+            let policy = ImagePolicy::new()
+                .disallow_registries(disallow_registry)
+                .disallow_classes(disallow_class)
+                .deny_latest(deny_latest)
+                .require_digest(require_digest);
+            if policy.is_empty() {
+                warn!("The image policy has no rules, every object will be admitted");
+            }
+            let router = webhook_router(Arc::new(ImageAdmission::new(policy, classifier, enforce)));
+
+            match (tls_cert, tls_key) {
+                (Some(cert), Some(key)) => {
+                    serve_tls(addr, router, load_tls_config(&cert, &key)?).await?;
+                }
+                _ if insecure => {
+                    // The API server only calls webhooks over HTTPS, e.g. through a TLS-terminating proxy
+                    warn!(
+                        "Serving plain HTTP, the API server needs a TLS-terminating proxy to call it"
+                    );
+                    serve(addr, router).await?;
+                }
+                _ => bail!(
+                    "The webhook needs --tls-cert and --tls-key, or --insecure for plain HTTP"
+                ),
+            }
+        }
+        Commands::Pin {
+            workload,
+            namespace,
+            all_namespaces,
+            format,
+            ..
+        } => {
+            debug!(
+                workload = ?workload,
+                namespace = %namespace,
+                all_namespaces = %all_namespaces,
+                format = %format,
+                "Processing pin command"
+            );
+
+            let query = ImageQuery::new()
+                .namespace(namespace)
+                .all_namespaces(all_namespaces);
+            let mut pins = collect_image_pins(&require_client(client)?, &query).await?;
+            if let Some(workload) = &workload {
+                pins.retain(|pin| pin.matches_workload(workload));
+            }
+            if pins.is_empty() {
+                warn!("No running images to pin, they are already pinned or have no digest yet");
+                return Ok(());
+            }
+
+            // Patches are only printed, reviewing and applying them is up to the user
+            let rendered = match format {
+                PinFormat::Kubectl => render_kubectl_patches(&pins)?,
+                PinFormat::Patch => render_patch_documents(&pins)?,
+                PinFormat::Kustomize => render_kustomize_images(&pins)?,
+            };
+            print!("{}", rendered);
+            info!(pins = pins.len(), "Successfully printed image pins");
+        }
+        Commands::Plugin { action } => match action {
+            PluginCommands::Manifest { version, checksums } => {
+                debug!(version = ?version, checksums = %checksums.display(), "Processing plugin manifest command");
+
+                let content = std::fs::read_to_string(&checksums)
+                    .with_context(|| format!("Failed to read {}", checksums.display()))?;
+                let version = version.as_deref().unwrap_or(env!("CARGO_PKG_VERSION"));
+                let manifest = krew_manifest(version, &parse_checksums(&content))?;
+                print!("{}", manifest);
+                info!(version = %version, "Successfully generated krew manifest");
+            }
         },
     }
     Ok(())
 }
+
+/// Get the Kubernetes client of a command that needs the cluster
+fn require_client(client: Option<K8sClient>) -> KimspectResult<K8sClient> {
+    client.context("This command requires a connection to the Kubernetes cluster")
+}
@@ -0,0 +1,140 @@
+use crate::k8s::PodImage;
+use crate::utils::{KNOWN_REGISTRIES, registry_host_matches};
+use anyhow::{Context, Result};
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+/// Environment variable holding comma separated `HOST=CLASS` registry rules
+pub const REGISTRIES_ENV: &str = "KIMSPECT_REGISTRIES";
+
+/// Class assigned to the built-in list of known public registries
+pub const PUBLIC_CLASS: &str = "public";
+
+/// Class assigned to registries that match no rule
+pub const UNKNOWN_CLASS: &str = "unknown";
+
+/// A rule classifying a registry host (and its subdomains) with a label
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RegistryRule {
+    /// Registry host, optionally with port (e.g. registry.corp, pkg.dev)
+    pub host: String,
+    /// Classification label (e.g. public, internal, vendor)
+    pub class: String,
+}
+
+impl FromStr for RegistryRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (host, class) = s
+            .split_once('=')
+            .ok_or_else(|| format!("invalid registry rule '{}', expected HOST=CLASS", s))?;
+        let host = host.trim().trim_end_matches('/');
+        let class = class.trim();
+
+        if host.is_empty() || class.is_empty() || host.contains('/') {
+            return Err(format!(
+                "invalid registry rule '{}', expected a registry host and a class",
+                s
+            ));
+        }
+
+        Ok(Self {
+            host: host.to_lowercase(),
+            class: class.to_string(),
+        })
+    }
+}
+
+/// Classifies registries as public, internal, vendor, ... based on configurable rules
+///
+/// User rules are checked first, most specific host first; registries in the
+/// built-in [`KNOWN_REGISTRIES`] list fall back to "public".
+#[derive(Debug, Clone, Default)]
+pub struct RegistryClassifier {
+    /// User classification rules, most specific host first
+    rules: Vec<RegistryRule>,
+}
+
+impl RegistryClassifier {
+    /// Create a classifier from a list of rules
+    ///
+    /// When several rules have the same host, the last one wins.
+    pub fn new(rules: impl IntoIterator<Item = RegistryRule>) -> Self {
+        let mut seen = BTreeSet::new();
+        let mut rules: Vec<RegistryRule> = rules.into_iter().collect();
+        rules.reverse();
+        rules.retain(|rule| seen.insert(rule.host.to_ascii_lowercase()));
+        rules.reverse();
+        rules.sort_by_key(|rule| std::cmp::Reverse(rule.host.len()));
+        Self { rules }
+    }
+
+    /// Create a classifier from the `KIMSPECT_REGISTRIES` environment variable
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self>` - The configured classifier or an error for invalid rules
+    pub fn from_env() -> Result<Self> {
+        let Ok(value) = std::env::var(REGISTRIES_ENV) else {
+            return Ok(Self::default());
+        };
+
+        let rules = value
+            .split(',')
+            .filter(|rule| !rule.trim().is_empty())
+            .map(|rule| rule.parse::<RegistryRule>().map_err(anyhow::Error::msg))
+            .collect::<Result<Vec<_>>>()
+            .with_context(|| format!("Failed to parse {}", REGISTRIES_ENV))?;
+
+        Ok(Self::new(rules))
+    }
+
+    /// Add rules to the classifier, e.g. from a config file
+    ///
+    /// Added rules replace existing rules for the same host, so the config file
+    /// overrides the environment.
+    pub fn with_rules(self, rules: impl IntoIterator<Item = RegistryRule>) -> Self {
+        Self::new(self.rules.into_iter().chain(rules))
+    }
+
+    /// Classify a registry host
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry host, optionally with port
+    ///
+    /// # Returns
+    ///
+    /// * `&str` - The class of the most specific matching rule, or "unknown"
+    pub fn classify(&self, registry: &str) -> &str {
+        if let Some(rule) = self
+            .rules
+            .iter()
+            .find(|rule| registry_host_matches(registry, &rule.host))
+        {
+            return &rule.class;
+        }
+
+        if KNOWN_REGISTRIES
+            .iter()
+            .any(|known| registry_host_matches(registry, known))
+        {
+            return PUBLIC_CLASS;
+        }
+
+        UNKNOWN_CLASS
+    }
+
+    /// Classify pod images by the registry they are pulled from
+    ///
+    /// # Arguments
+    ///
+    /// * `images` - The pod images to classify
+    pub fn apply(&self, images: &mut [PodImage]) {
+        for image in images.iter_mut() {
+            image.registry_class = self.classify(&image.registry).to_string();
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use crate::k8s::{PodImage, extract_registry};
+use crate::registry::ImageReference;
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+use tracing::warn;
+
+/// Environment variable holding comma separated `MIRROR=UPSTREAM` rules
+pub const MIRRORS_ENV: &str = "KIMSPECT_MIRRORS";
+
+/// A rule mapping an image prefix on a pull-through mirror to its upstream origin
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MirrorRule {
+    /// Image prefix as pulled by the cluster (e.g. mirror.corp/dockerhub)
+    pub mirror: String,
+    /// Upstream prefix the mirror proxies (e.g. docker.io)
+    pub upstream: String,
+}
+
+impl FromStr for MirrorRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (mirror, upstream) = s
+            .split_once('=')
+            .ok_or_else(|| format!("invalid mirror rule '{}', expected MIRROR=UPSTREAM", s))?;
+        let mirror = mirror.trim().trim_end_matches('/');
+        let upstream = upstream.trim().trim_end_matches('/');
+
+        if mirror.is_empty() || upstream.is_empty() {
+            return Err(format!(
+                "invalid mirror rule '{}', mirror and upstream must not be empty",
+                s
+            ));
+        }
+
+        Ok(Self {
+            mirror: mirror.to_string(),
+            upstream: upstream.to_string(),
+        })
+    }
+}
+
+/// Images of one upstream repository, possibly pulled through several mirrors
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct UpstreamGroup {
+    /// Registry the images originate from
+    pub upstream_registry: String,
+    /// Image name within the upstream registry
+    pub image_name: String,
+    /// Distinct versions in use
+    pub versions: Vec<String>,
+    /// Registries the images are actually pulled from
+    pub pulled_via: Vec<String>,
+    /// Number of containers using the image
+    pub containers: usize,
+}
+
+/// Mapping of pull-through mirrors to the upstream registries they proxy
+#[derive(Debug, Clone, Default)]
+pub struct MirrorMap {
+    /// Mirror rules, longest mirror prefix first
+    rules: Vec<MirrorRule>,
+}
+
+impl MirrorMap {
+    /// Create a mirror map from a list of rules
+    ///
+    /// When several rules have the same mirror prefix, the last one wins.
+    pub fn new(rules: impl IntoIterator<Item = MirrorRule>) -> Self {
+        let mut seen = BTreeSet::new();
+        let mut rules: Vec<MirrorRule> = rules.into_iter().collect();
+        rules.reverse();
+        rules.retain(|rule| seen.insert(rule.mirror.clone()));
+        rules.reverse();
+        rules.sort_by_key(|rule| std::cmp::Reverse(rule.mirror.len()));
+        Self { rules }
+    }
+
+    /// Create a mirror map from the `KIMSPECT_MIRRORS` environment variable
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self>` - The configured mirror map (empty if unset) or an error for invalid rules
+    pub fn from_env() -> Result<Self> {
+        let Ok(value) = std::env::var(MIRRORS_ENV) else {
+            return Ok(Self::default());
+        };
+
+        let rules = value
+            .split(',')
+            .filter(|rule| !rule.trim().is_empty())
+            .map(|rule| rule.parse::<MirrorRule>().map_err(anyhow::Error::msg))
+            .collect::<Result<Vec<_>>>()
+            .with_context(|| format!("Failed to parse {}", MIRRORS_ENV))?;
+
+        Ok(Self::new(rules))
+    }
+
+    /// Add rules to the mirror map, e.g. from the command line
+    ///
+    /// Added rules replace existing rules for the same mirror prefix, so layers
+    /// are added from the lowest precedence (environment) to the highest.
+    pub fn with_rules(self, rules: impl IntoIterator<Item = MirrorRule>) -> Self {
+        Self::new(self.rules.into_iter().chain(rules))
+    }
+
+    /// Check if no mirror rules are configured
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Resolve an image reference pulled from a mirror to its upstream reference
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The image reference including registry (e.g. mirror.corp/dockerhub/nginx)
+    ///
+    /// # Returns
+    ///
+    /// * `Option<String>` - The upstream reference, or None if no mirror rule matches
+    pub fn resolve(&self, image: &str) -> Option<String> {
+        self.rules.iter().find_map(|rule| {
+            let rest = image.strip_prefix(&rule.mirror)?;
+            if rest.is_empty() {
+                Some(rule.upstream.clone())
+            } else {
+                rest.starts_with('/')
+                    .then(|| format!("{}{}", rule.upstream, rest))
+            }
+        })
+    }
+
+    /// Get the upstream registries proxied by a mirror registry host
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry host images are pulled from
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - Upstream registries configured for this host (empty if not a mirror)
+    pub fn upstream_registries(&self, registry: &str) -> Vec<String> {
+        self.rules
+            .iter()
+            .filter(|rule| prefix_registry(&rule.mirror) == registry)
+            .map(|rule| prefix_registry(&rule.upstream))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Annotate pod images pulled through a mirror with their upstream origin
+    ///
+    /// # Arguments
+    ///
+    /// * `images` - The pod images to annotate
+    pub fn apply(&self, images: &mut [PodImage]) {
+        if self.is_empty() {
+            return;
+        }
+
+        for image in images.iter_mut() {
+            let pulled = format!("{}/{}", image.registry, image.image_name);
+            let Some(upstream) = self.resolve(&pulled) else {
+                continue;
+            };
+            match ImageReference::parse(&upstream) {
+                Ok(reference) => {
+                    image.upstream_image = reference.familiar_repository().to_string();
+                    image.upstream_registry = reference.registry().to_string();
+                }
+                Err(e) => {
+                    warn!(image = %pulled, upstream = %upstream, error = %e, "Mirror rule produced an invalid upstream reference")
+                }
+            }
+        }
+    }
+}
+
+/// Get the registry host of an image prefix such as `mirror.corp/dockerhub`
+///
+/// A prefix may end where a repository would start, so a placeholder repository
+/// is appended to parse it as a complete reference.
+fn prefix_registry(prefix: &str) -> String {
+    extract_registry(&format!("{}/image", prefix))
+}
+
+/// Group pod images by their upstream origin
+///
+/// Images that are not pulled through a mirror are grouped by the registry they
+/// are pulled from, so the result covers every image in use.
+///
+/// # Arguments
+///
+/// * `images` - The pod images to group (annotated with [`MirrorMap::apply`])
+///
+/// # Returns
+///
+/// * `Vec<UpstreamGroup>` - Upstream repositories sorted by registry and image name
+pub fn group_by_upstream(images: &[PodImage]) -> Vec<UpstreamGroup> {
+    let mut groups: BTreeMap<(String, String), UpstreamGroup> = BTreeMap::new();
+
+    for image in images {
+        let (registry, name) = if image.upstream_registry.is_empty() {
+            (image.registry.clone(), image.image_name.clone())
+        } else {
+            (
+                image.upstream_registry.clone(),
+                image.upstream_image.clone(),
+            )
+        };
+
+        let group = groups
+            .entry((registry.clone(), name.clone()))
+            .or_insert_with(|| UpstreamGroup {
+                upstream_registry: registry,
+                image_name: name,
+                versions: Vec::new(),
+                pulled_via: Vec::new(),
+                containers: 0,
+            });
+
+        if !group.versions.contains(&image.image_version) {
+            group.versions.push(image.image_version.clone());
+        }
+        if !group.pulled_via.contains(&image.registry) {
+            group.pulled_via.push(image.registry.clone());
+        }
+        group.containers += 1;
+    }
+
+    groups
+        .into_values()
+        .map(|mut group| {
+            group.versions.sort();
+            group.pulled_via.sort();
+            group
+        })
+        .collect()
+}
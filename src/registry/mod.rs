@@ -0,0 +1,271 @@
+//! Minimal client for the OCI distribution API, used to inspect remote image manifests
+
+use anyhow::{Context, Result, bail};
+use reqwest::{StatusCode, header};
+use serde::Deserialize;
+use std::fmt;
+use std::time::Duration;
+use tracing::{debug, instrument};
+
+mod classes;
+mod mirrors;
+mod policy;
+mod reference;
+
+pub use classes::{PUBLIC_CLASS, REGISTRIES_ENV, RegistryClassifier, RegistryRule, UNKNOWN_CLASS};
+pub use mirrors::{MIRRORS_ENV, MirrorMap, MirrorRule, UpstreamGroup, group_by_upstream};
+pub use policy::{ImagePolicy, PolicyRule, PolicyViolation};
+pub use reference::{DEFAULT_REGISTRY, DEFAULT_TAG, ImageReference, ReferenceError};
+
+/// Media types accepted when fetching manifests, indexes first
+const MANIFEST_ACCEPT: &str = "application/vnd.oci.image.index.v1+json, \
+    application/vnd.docker.distribution.manifest.list.v2+json, \
+    application/vnd.oci.image.manifest.v1+json, \
+    application/vnd.docker.distribution.manifest.v2+json";
+
+/// Platform an image manifest is built for
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[non_exhaustive]
+pub struct Platform {
+    /// Operating system (e.g. linux)
+    pub os: String,
+    /// CPU architecture (e.g. amd64, arm64)
+    pub architecture: String,
+    /// CPU variant (e.g. v8 for arm64)
+    #[serde(default)]
+    pub variant: Option<String>,
+}
+
+impl Platform {
+    /// Create a platform without CPU variant
+    ///
+    /// # Arguments
+    ///
+    /// * `os` - Operating system (e.g. linux)
+    /// * `architecture` - CPU architecture (e.g. amd64)
+    ///
+    /// # Returns
+    ///
+    /// * `Platform` - The platform
+    pub fn new(os: impl Into<String>, architecture: impl Into<String>) -> Self {
+        Self {
+            os: os.into(),
+            architecture: architecture.into(),
+            variant: None,
+        }
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.variant {
+            Some(variant) => write!(f, "{}/{}/{}", self.os, self.architecture, variant),
+            None => write!(f, "{}/{}", self.os, self.architecture),
+        }
+    }
+}
+
+/// Manifest or index as returned by the registry (only the fields kimspect needs)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    #[serde(default)]
+    manifests: Vec<ManifestDescriptor>,
+    #[serde(default)]
+    config: Option<Descriptor>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestDescriptor {
+    #[serde(default)]
+    platform: Option<Platform>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Descriptor {
+    digest: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    #[serde(default)]
+    token: Option<String>,
+    #[serde(default)]
+    access_token: Option<String>,
+}
+
+/// Client for anonymously reading manifests from container registries
+pub struct RegistryClient {
+    /// The underlying HTTP client
+    http: reqwest::Client,
+}
+
+impl RegistryClient {
+    /// Create a new registry client
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self>` - A new RegistryClient instance or an error if initialization fails
+    pub fn new() -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .user_agent(concat!("kimspect/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .context("Failed to create registry HTTP client")?;
+        Ok(Self { http })
+    }
+
+    /// Get the platforms an image is available for
+    ///
+    /// Multi-arch images list their platforms in the index; single-arch images
+    /// only record it in the image config blob, which is fetched instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `registry` - The registry host (e.g. docker.io, ghcr.io)
+    /// * `repository` - The repository path within the registry
+    /// * `reference` - A tag or digest
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<Platform>>` - The platforms listed for the image or an error
+    #[instrument(skip(self))]
+    pub async fn get_platforms(
+        &self,
+        registry: &str,
+        repository: &str,
+        reference: &str,
+    ) -> Result<Vec<Platform>> {
+        let base = registry_base_url(registry);
+        let manifest_url = format!("{}/v2/{}/manifests/{}", base, repository, reference);
+        let (body, token) = self
+            .get_with_auth(&manifest_url, MANIFEST_ACCEPT, None)
+            .await?;
+        let manifest: Manifest =
+            serde_json::from_slice(&body).context("Failed to parse image manifest")?;
+
+        if !manifest.manifests.is_empty() {
+            return Ok(manifest
+                .manifests
+                .into_iter()
+                .filter_map(|m| m.platform)
+                .filter(|p| p.architecture != "unknown")
+                .collect());
+        }
+
+        let Some(config) = manifest.config else {
+            bail!("Manifest for {}/{} has no config", registry, repository);
+        };
+
+        let blob_url = format!("{}/v2/{}/blobs/{}", base, repository, config.digest);
+        let (body, _) = self
+            .get_with_auth(&blob_url, "application/json", token)
+            .await?;
+        let platform: Platform =
+            serde_json::from_slice(&body).context("Failed to parse image config")?;
+        Ok(vec![platform])
+    }
+
+    /// Perform a GET request, negotiating an anonymous bearer token if challenged
+    async fn get_with_auth(
+        &self,
+        url: &str,
+        accept: &str,
+        token: Option<String>,
+    ) -> Result<(Vec<u8>, Option<String>)> {
+        let mut token = token;
+        for _ in 0..2 {
+            let mut request = self.http.get(url).header(header::ACCEPT, accept);
+            if let Some(token) = &token {
+                request = request.bearer_auth(token);
+            }
+
+            let response = request.send().await.context("Registry request failed")?;
+            match response.status() {
+                StatusCode::UNAUTHORIZED if token.is_none() => {
+                    let challenge = response
+                        .headers()
+                        .get(header::WWW_AUTHENTICATE)
+                        .and_then(|v| v.to_str().ok())
+                        .context("Registry requires authentication without a challenge")?
+                        .to_string();
+                    debug!(challenge = %challenge, "Requesting anonymous registry token");
+                    token = Some(self.fetch_token(&challenge).await?);
+                }
+                status if status.is_success() => {
+                    let body = response.bytes().await.context("Failed to read response")?;
+                    return Ok((body.to_vec(), token));
+                }
+                status => bail!("Registry returned {} for {}", status, url),
+            }
+        }
+        bail!("Registry denied anonymous access to {}", url)
+    }
+
+    /// Exchange a `WWW-Authenticate: Bearer` challenge for an anonymous token
+    async fn fetch_token(&self, challenge: &str) -> Result<String> {
+        let params = parse_bearer_challenge(challenge)
+            .context("Unsupported registry authentication challenge")?;
+        let realm = params
+            .iter()
+            .find(|(k, _)| k == "realm")
+            .map(|(_, v)| v.clone())
+            .context("Registry challenge is missing a realm")?;
+        let query: Vec<(String, String)> =
+            params.into_iter().filter(|(k, _)| k != "realm").collect();
+
+        let response: TokenResponse = self
+            .http
+            .get(&realm)
+            .query(&query)
+            .send()
+            .await
+            .context("Registry token request failed")?
+            .error_for_status()
+            .context("Registry token request was rejected")?
+            .json()
+            .await
+            .context("Failed to parse registry token")?;
+
+        response
+            .token
+            .or(response.access_token)
+            .context("Registry token response did not include a token")
+    }
+}
+
+/// Parse the parameters of a `Bearer realm="...",service="...",scope="..."` challenge
+///
+/// # Arguments
+///
+/// * `challenge` - The value of the WWW-Authenticate header
+///
+/// # Returns
+///
+/// * `Option<Vec<(String, String)>>` - The challenge parameters or None if not a bearer challenge
+pub fn parse_bearer_challenge(challenge: &str) -> Option<Vec<(String, String)>> {
+    let params = challenge.strip_prefix("Bearer ")?;
+    let mut result = Vec::new();
+    let mut rest = params.trim();
+
+    while !rest.is_empty() {
+        let (key, after_key) = rest.split_once('=')?;
+        let after_key = after_key.strip_prefix('"')?;
+        let (value, after_value) = after_key.split_once('"')?;
+        result.push((key.trim().to_string(), value.to_string()));
+        rest = after_value.trim_start_matches(',').trim();
+    }
+
+    Some(result)
+}
+
+/// Get the base URL of the distribution API for a registry host
+fn registry_base_url(registry: &str) -> String {
+    match registry {
+        "docker.io" | "registry.hub.docker.com" => "https://registry-1.docker.io".to_string(),
+        host if host.starts_with("localhost") || host.starts_with("127.0.0.1") => {
+            format!("http://{}", host)
+        }
+        host => format!("https://{}", host),
+    }
+}
@@ -0,0 +1,225 @@
+use crate::k8s::PodImage;
+use crate::registry::DEFAULT_TAG;
+use crate::utils::registry_host_matches;
+use std::fmt;
+
+/// A rule of the image policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PolicyRule {
+    /// The image is pulled from a disallowed registry or registry class
+    DisallowedRegistry,
+    /// The image uses the `latest` tag, explicitly or implicitly, without a digest
+    LatestTag,
+    /// The image reference is not pinned to a digest
+    MissingDigest,
+    /// The image reference cannot be parsed, so its registry cannot be checked
+    InvalidReference,
+}
+
+impl PolicyRule {
+    /// Get the name of the rule as shown to users
+    pub fn name(&self) -> &'static str {
+        match self {
+            PolicyRule::DisallowedRegistry => "disallowed-registry",
+            PolicyRule::LatestTag => "latest-tag",
+            PolicyRule::MissingDigest => "missing-digest",
+            PolicyRule::InvalidReference => "invalid-reference",
+        }
+    }
+}
+
+impl fmt::Display for PolicyRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A container image breaking a rule of the policy
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PolicyViolation {
+    /// The broken rule
+    pub rule: PolicyRule,
+    /// Name of the container using the image
+    pub container: String,
+    /// The image reference as pulled
+    pub image: String,
+    /// Why the image breaks the rule
+    pub message: String,
+}
+
+impl fmt::Display for PolicyViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "container {}: {} ({})",
+            self.container, self.message, self.rule
+        )
+    }
+}
+
+/// Rules images must follow, shared by audits and admission control
+///
+/// Registries are matched with their subdomains and classes come from the
+/// [`RegistryClassifier`](crate::RegistryClassifier), so the images must be
+/// classified before they are checked.
+///
+/// ```
+/// use kimspect::{ImagePolicy, PodImage, PolicyRule};
+///
+/// let policy = ImagePolicy::new()
+///     .disallow_registry("docker.io")
+///     .deny_latest(true);
+/// let mut image = PodImage::default();
+/// image.container_name = "web".to_string();
+/// image.registry = "docker.io".to_string();
+/// image.image_name = "nginx".to_string();
+/// image.image_version = "latest".to_string();
+/// let rules: Vec<_> = policy.check(&image).into_iter().map(|v| v.rule).collect();
+/// assert_eq!(rules, vec![PolicyRule::DisallowedRegistry, PolicyRule::LatestTag]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImagePolicy {
+    /// Registry hosts images may not be pulled from
+    disallowed_registries: Vec<String>,
+    /// Registry classes images may not be pulled from
+    disallowed_classes: Vec<String>,
+    /// Whether to reject the `latest` tag
+    deny_latest: bool,
+    /// Whether to require references pinned to a digest
+    require_digest: bool,
+}
+
+impl ImagePolicy {
+    /// Create a policy allowing every image
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Disallow images from a registry host and its subdomains (can be called multiple times)
+    pub fn disallow_registry(mut self, registry: impl Into<String>) -> Self {
+        self.disallowed_registries.push(registry.into());
+        self
+    }
+
+    /// Disallow images from each of the given registry hosts
+    pub fn disallow_registries<I, S>(mut self, registries: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.disallowed_registries
+            .extend(registries.into_iter().map(Into::into));
+        self
+    }
+
+    /// Disallow images from registries of a class (can be called multiple times)
+    pub fn disallow_class(mut self, class: impl Into<String>) -> Self {
+        self.disallowed_classes.push(class.into());
+        self
+    }
+
+    /// Disallow images from registries of each of the given classes
+    pub fn disallow_classes<I, S>(mut self, classes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.disallowed_classes
+            .extend(classes.into_iter().map(Into::into));
+        self
+    }
+
+    /// Reject images using the `latest` tag unless they are pinned to a digest
+    pub fn deny_latest(mut self, deny_latest: bool) -> Self {
+        self.deny_latest = deny_latest;
+        self
+    }
+
+    /// Reject image references that are not pinned to a digest
+    pub fn require_digest(mut self, require_digest: bool) -> Self {
+        self.require_digest = require_digest;
+        self
+    }
+
+    /// Check if the policy has no rules, allowing every image
+    pub fn is_empty(&self) -> bool {
+        self.disallowed_registries.is_empty()
+            && self.disallowed_classes.is_empty()
+            && !self.deny_latest
+            && !self.require_digest
+    }
+
+    /// Check an image against the policy
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The classified pod image to check
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<PolicyViolation>` - The broken rules, empty when the image is allowed
+    pub fn check(&self, image: &PodImage) -> Vec<PolicyViolation> {
+        let reference = image.reference();
+        let violation = |rule: PolicyRule, message: String| PolicyViolation {
+            rule,
+            container: image.container_name.clone(),
+            image: reference.clone(),
+            message,
+        };
+
+        // Invalid references carry no registry or version to check, fail closed
+        if image.registry.is_empty() {
+            if self.is_empty() {
+                return Vec::new();
+            }
+            return vec![violation(
+                PolicyRule::InvalidReference,
+                format!("image {} is not a valid image reference", reference),
+            )];
+        }
+
+        let mut violations = Vec::new();
+
+        if let Some(host) = self
+            .disallowed_registries
+            .iter()
+            .find(|host| registry_host_matches(&image.registry, host))
+        {
+            violations.push(violation(
+                PolicyRule::DisallowedRegistry,
+                format!(
+                    "image {} is pulled from disallowed registry {}",
+                    reference, host
+                ),
+            ));
+        } else if self.disallowed_classes.contains(&image.registry_class) {
+            violations.push(violation(
+                PolicyRule::DisallowedRegistry,
+                format!(
+                    "image {} is pulled from {} registry {}, which is disallowed",
+                    reference, image.registry_class, image.registry
+                ),
+            ));
+        }
+
+        let (tag, digest) = match image.image_version.split_once('@') {
+            Some((tag, digest)) => (tag, Some(digest)),
+            None => (image.image_version.as_str(), None),
+        };
+        if self.deny_latest && tag == DEFAULT_TAG && digest.is_none() {
+            violations.push(violation(
+                PolicyRule::LatestTag,
+                format!("image {} uses the latest tag", reference),
+            ));
+        }
+        if self.require_digest && digest.is_none() {
+            violations.push(violation(
+                PolicyRule::MissingDigest,
+                format!("image {} is not pinned to a digest", reference),
+            ));
+        }
+
+        violations
+    }
+}
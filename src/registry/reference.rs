@@ -0,0 +1,353 @@
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Registry used for references without a domain
+pub const DEFAULT_REGISTRY: &str = "docker.io";
+
+/// Tag implied by references without a tag or digest
+pub const DEFAULT_TAG: &str = "latest";
+
+/// Namespace of Docker Hub official images
+const OFFICIAL_NAMESPACE: &str = "library";
+
+/// Maximum length of a repository name including its domain
+const NAME_TOTAL_LENGTH_MAX: usize = 255;
+
+/// Maximum length of a tag
+const TAG_LENGTH_MAX: usize = 128;
+
+/// Errors that can occur when parsing an image reference
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum ReferenceError {
+    /// The reference is empty
+    #[error("Invalid image reference: reference is empty")]
+    Empty,
+    /// The registry domain is malformed
+    #[error("Invalid image reference: invalid registry domain '{0}'")]
+    InvalidDomain(String),
+    /// The repository name is malformed
+    #[error("Invalid image reference: invalid repository name '{0}'")]
+    InvalidName(String),
+    /// The repository name contains uppercase letters
+    #[error("Invalid image reference: repository name '{0}' must be lowercase")]
+    UppercaseName(String),
+    /// The repository name is longer than 255 characters
+    #[error("Invalid image reference: repository name '{0}' is longer than 255 characters")]
+    NameTooLong(String),
+    /// The tag is malformed
+    #[error("Invalid image reference: invalid tag '{0}'")]
+    InvalidTag(String),
+    /// The digest is malformed
+    #[error("Invalid image reference: invalid digest '{0}'")]
+    InvalidDigest(String),
+}
+
+/// A container image reference following the distribution reference grammar
+///
+/// References are normalized the same way the container runtime resolves them:
+/// `ubuntu`, `library/ubuntu` and `docker.io/library/ubuntu` all refer to the
+/// repository `library/ubuntu` on `docker.io`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ImageReference {
+    /// Registry domain, optionally with port (e.g. docker.io, registry.corp:5000)
+    registry: String,
+    /// Repository path within the registry (e.g. library/nginx)
+    repository: String,
+    /// Tag (if specified)
+    tag: Option<String>,
+    /// Digest (if specified), including the algorithm (e.g. sha256:...)
+    digest: Option<String>,
+}
+
+impl ImageReference {
+    /// Parse and normalize an image reference
+    ///
+    /// # Arguments
+    ///
+    /// * `reference` - The image reference (e.g. nginx:1.25, ghcr.io/org/app@sha256:...)
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Self, ReferenceError>` - The parsed reference or a description of what is invalid
+    pub fn parse(reference: &str) -> Result<Self, ReferenceError> {
+        if reference.is_empty() {
+            return Err(ReferenceError::Empty);
+        }
+
+        let (remainder, digest) = match reference.split_once('@') {
+            Some((name, digest)) => {
+                validate_digest(digest)?;
+                (name, Some(digest.to_string()))
+            }
+            None => (reference, None),
+        };
+
+        let last_slash = remainder.rfind('/').map_or(0, |i| i + 1);
+        let (name, tag) = match remainder[last_slash..].rfind(':') {
+            Some(i) => {
+                let tag = &remainder[last_slash + i + 1..];
+                validate_tag(tag)?;
+                (&remainder[..last_slash + i], Some(tag.to_string()))
+            }
+            None => (remainder, None),
+        };
+
+        if name.len() > NAME_TOTAL_LENGTH_MAX {
+            return Err(ReferenceError::NameTooLong(name.to_string()));
+        }
+
+        let (registry, repository) = split_domain(name);
+        validate_domain(registry)?;
+        validate_repository(repository)?;
+
+        let registry = match registry {
+            "index.docker.io" => DEFAULT_REGISTRY,
+            other => other,
+        };
+        let repository = if registry == DEFAULT_REGISTRY && !repository.contains('/') {
+            format!("{}/{}", OFFICIAL_NAMESPACE, repository)
+        } else {
+            repository.to_string()
+        };
+
+        Ok(Self {
+            registry: registry.to_string(),
+            repository,
+            tag,
+            digest,
+        })
+    }
+
+    /// Get the registry domain
+    pub fn registry(&self) -> &str {
+        &self.registry
+    }
+
+    /// Get the repository path within the registry
+    pub fn repository(&self) -> &str {
+        &self.repository
+    }
+
+    /// Get the tag, if one was specified
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+
+    /// Get the digest, if one was specified
+    pub fn digest(&self) -> Option<&str> {
+        self.digest.as_deref()
+    }
+
+    /// Get the canonical repository name including the registry (e.g. docker.io/library/nginx)
+    pub fn name(&self) -> String {
+        format!("{}/{}", self.registry, self.repository)
+    }
+
+    /// Get the repository path as users usually write it
+    ///
+    /// Docker Hub official images drop the `library/` namespace (e.g. `nginx`).
+    pub fn familiar_repository(&self) -> &str {
+        if self.registry == DEFAULT_REGISTRY {
+            if let Some(name) = self
+                .repository
+                .strip_prefix(OFFICIAL_NAMESPACE)
+                .and_then(|rest| rest.strip_prefix('/'))
+                .filter(|rest| !rest.contains('/'))
+            {
+                return name;
+            }
+        }
+        &self.repository
+    }
+
+    /// Get the shortest name that resolves to the same repository (e.g. nginx, ghcr.io/org/app)
+    pub fn familiar_name(&self) -> String {
+        if self.registry == DEFAULT_REGISTRY {
+            self.familiar_repository().to_string()
+        } else {
+            self.name()
+        }
+    }
+
+    /// Get the version as displayed by kimspect: `tag`, `tag@digest` or `@digest`
+    ///
+    /// References without a tag or digest resolve to the implicit `latest` tag.
+    pub fn version(&self) -> String {
+        match (&self.tag, &self.digest) {
+            (Some(tag), Some(digest)) => format!("{}@{}", tag, digest),
+            (Some(tag), None) => tag.clone(),
+            (None, Some(digest)) => format!("@{}", digest),
+            (None, None) => DEFAULT_TAG.to_string(),
+        }
+    }
+
+    /// Get the tag or digest to request from the registry, preferring the digest
+    pub fn manifest_reference(&self) -> &str {
+        self.digest
+            .as_deref()
+            .or(self.tag.as_deref())
+            .unwrap_or(DEFAULT_TAG)
+    }
+}
+
+impl FromStr for ImageReference {
+    type Err = ReferenceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for ImageReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())?;
+        if let Some(tag) = &self.tag {
+            write!(f, ":{}", tag)?;
+        }
+        if let Some(digest) = &self.digest {
+            write!(f, "@{}", digest)?;
+        }
+        Ok(())
+    }
+}
+
+/// Split a name into its domain and repository path
+///
+/// The first component is a domain if it contains a `.` or `:`, is `localhost`,
+/// or contains uppercase letters (which repository names cannot).
+fn split_domain(name: &str) -> (&str, &str) {
+    match name.split_once('/') {
+        Some((first, rest))
+            if first.contains(['.', ':'])
+                || first == "localhost"
+                || first.chars().any(|c| c.is_ascii_uppercase()) =>
+        {
+            (first, rest)
+        }
+        _ => (DEFAULT_REGISTRY, name),
+    }
+}
+
+/// Validate a registry domain: `host[:port]` where host is a domain name, IPv4 or `[IPv6]`
+fn validate_domain(domain: &str) -> Result<(), ReferenceError> {
+    let invalid = || ReferenceError::InvalidDomain(domain.to_string());
+
+    let (host, port) = if let Some(rest) = domain.strip_prefix('[') {
+        let (ipv6, after) = rest.split_once(']').ok_or_else(invalid)?;
+        if ipv6.is_empty() || !ipv6.chars().all(|c| c.is_ascii_hexdigit() || c == ':') {
+            return Err(invalid());
+        }
+        let port = match after {
+            "" => None,
+            _ => Some(after.strip_prefix(':').ok_or_else(invalid)?),
+        };
+        (None, port)
+    } else {
+        match domain.split_once(':') {
+            Some((host, port)) => (Some(host), Some(port)),
+            None => (Some(domain), None),
+        }
+    };
+
+    if let Some(port) = port {
+        if port.is_empty() || !port.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+    }
+
+    if let Some(host) = host {
+        let valid_component = |c: &str| {
+            !c.is_empty()
+                && c.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '-')
+                && !c.starts_with('-')
+                && !c.ends_with('-')
+        };
+        if !host.split('.').all(valid_component) {
+            return Err(invalid());
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate a repository path: lowercase components joined by `/`
+///
+/// Each component is alphanumeric runs separated by `.`, `_`, `__` or one or more `-`.
+fn validate_repository(repository: &str) -> Result<(), ReferenceError> {
+    if repository.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err(ReferenceError::UppercaseName(repository.to_string()));
+    }
+
+    let valid_component = |component: &str| {
+        let bytes = component.as_bytes();
+        if bytes.is_empty()
+            || !bytes[0].is_ascii_alphanumeric()
+            || !bytes[bytes.len() - 1].is_ascii_alphanumeric()
+        {
+            return false;
+        }
+
+        let mut i = 0;
+        while i < bytes.len() {
+            let c = bytes[i];
+            if c.is_ascii_lowercase() || c.is_ascii_digit() {
+                i += 1;
+                continue;
+            }
+
+            // Separator run between two alphanumeric runs
+            let start = i;
+            while i < bytes.len() && !(bytes[i].is_ascii_lowercase() || bytes[i].is_ascii_digit()) {
+                i += 1;
+            }
+            let separator = &component[start..i];
+            let valid =
+                matches!(separator, "." | "_" | "__") || separator.chars().all(|c| c == '-');
+            if !valid {
+                return false;
+            }
+        }
+        true
+    };
+
+    if repository.split('/').all(valid_component) {
+        Ok(())
+    } else {
+        Err(ReferenceError::InvalidName(repository.to_string()))
+    }
+}
+
+/// Validate a tag: word characters, dots and dashes, not starting with a dot or dash
+fn validate_tag(tag: &str) -> Result<(), ReferenceError> {
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let valid = !tag.is_empty()
+        && tag.len() <= TAG_LENGTH_MAX
+        && tag.starts_with(is_word)
+        && tag.chars().all(|c| is_word(c) || c == '.' || c == '-');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ReferenceError::InvalidTag(tag.to_string()))
+    }
+}
+
+/// Validate a digest: `algorithm:hex` with at least 32 hex characters
+fn validate_digest(digest: &str) -> Result<(), ReferenceError> {
+    let invalid = || ReferenceError::InvalidDigest(digest.to_string());
+    let (algorithm, encoded) = digest.split_once(':').ok_or_else(invalid)?;
+
+    let valid_algorithm = algorithm.split(['+', '.', '-', '_']).all(|component| {
+        component.starts_with(|c: char| c.is_ascii_alphabetic())
+            && component.chars().all(|c| c.is_ascii_alphanumeric())
+    });
+    let valid_encoded = encoded.len() >= 32 && encoded.chars().all(|c| c.is_ascii_hexdigit());
+
+    if valid_algorithm && valid_encoded {
+        Ok(())
+    } else {
+        Err(invalid())
+    }
+}
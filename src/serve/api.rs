@@ -0,0 +1,161 @@
+use crate::k8s::PodImage;
+use crate::registry::MirrorMap;
+use crate::serve::ServerState;
+use crate::utils::image_list;
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::{Json, Router, routing::get};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+use tracing::debug;
+
+/// Query parameters of the JSON API, narrowing the served inventory
+#[derive(Debug, Clone, Default, Deserialize)]
+#[non_exhaustive]
+pub struct ApiParams {
+    /// Only include pods in this namespace
+    pub namespace: Option<String>,
+    /// Only include images pulled from this registry
+    pub registry: Option<String>,
+}
+
+/// Usage of a registry across the served pods, as returned by `GET /registries`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub struct RegistryUsage {
+    /// The registry host images are pulled from
+    pub registry: String,
+    /// Classification of the registry (e.g. public, internal, vendor)
+    pub class: String,
+    /// Upstream registries proxied by this registry when it is a mirror
+    pub upstream: Vec<String>,
+    /// Number of distinct images (name and version) pulled from the registry
+    pub images: usize,
+    /// Number of containers running an image from the registry
+    pub containers: usize,
+}
+
+/// Summarize the registries the pod images are pulled from
+///
+/// # Arguments
+///
+/// * `images` - The classified pod images
+/// * `mirrors` - Mirrors to list the upstream registries of
+///
+/// # Returns
+///
+/// * `Vec<RegistryUsage>` - One entry per registry, sorted by registry
+pub fn registry_usage(images: &[PodImage], mirrors: &MirrorMap) -> Vec<RegistryUsage> {
+    let mut registries: BTreeMap<&str, Vec<&PodImage>> = BTreeMap::new();
+    for image in images {
+        registries
+            .entry(image.registry.as_str())
+            .or_default()
+            .push(image);
+    }
+
+    registries
+        .into_iter()
+        .map(|(registry, containers)| {
+            let distinct: BTreeSet<_> = containers
+                .iter()
+                .map(|image| (&image.image_name, &image.image_version))
+                .collect();
+            RegistryUsage {
+                registry: registry.to_string(),
+                class: containers[0].registry_class.clone(),
+                upstream: mirrors.upstream_registries(registry),
+                images: distinct.len(),
+                containers: containers.len(),
+            }
+        })
+        .collect()
+}
+
+/// Build the router of the JSON API
+///
+/// * `GET /images?namespace=&registry=` - The pod images, as records of `-o jsonpath` templates
+/// * `GET /registries?namespace=&registry=` - The registries in use, with their image counts
+///
+/// Both answer 503 until the pods have been listed, and 404 for a namespace
+/// outside the watched ones.
+///
+/// # Arguments
+///
+/// * `state` - The inventory to serve
+///
+/// # Returns
+///
+/// * `Router` - The routes, ready to serve
+pub fn api_router(state: Arc<ServerState>) -> Router {
+    Router::new()
+        .route("/images", get(images_handler))
+        .route("/registries", get(registries_handler))
+        .with_state(state)
+}
+
+/// List the pod images matching the parameters
+async fn images_handler(
+    State(state): State<Arc<ServerState>>,
+    Query(params): Query<ApiParams>,
+) -> Response {
+    match matching_images(&state, &params).await {
+        Ok(images) => Json(image_list(&images)).into_response(),
+        Err(response) => response,
+    }
+}
+
+/// List the registries of the pod images matching the parameters
+async fn registries_handler(
+    State(state): State<Arc<ServerState>>,
+    Query(params): Query<ApiParams>,
+) -> Response {
+    match matching_images(&state, &params).await {
+        Ok(images) => Json(json!({
+            "apiVersion": "v1",
+            "kind": "List",
+            "items": registry_usage(&images, state.mirrors()),
+        }))
+        .into_response(),
+        Err(response) => response,
+    }
+}
+
+/// Get the pod images matching the parameters, or the error response to send
+async fn matching_images(
+    state: &ServerState,
+    params: &ApiParams,
+) -> Result<Vec<PodImage>, Response> {
+    if !state.inventory().is_ready() {
+        return Err(error_response(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Inventory not ready".to_string(),
+        ));
+    }
+
+    let mut query = state.query().clone();
+    if let Some(namespace) = &params.namespace {
+        if !state.is_watched(namespace) {
+            return Err(error_response(
+                StatusCode::NOT_FOUND,
+                format!("Namespace {} is not watched", namespace),
+            ));
+        }
+        query = query.namespace(namespace).all_namespaces(false);
+    }
+    if let Some(registry) = &params.registry {
+        query = query.registry(registry);
+    }
+
+    debug!(params = ?params, "Answering API request");
+    Ok(state.images_matching(&query).await)
+}
+
+/// Build a JSON error response
+fn error_response(status: StatusCode, message: String) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
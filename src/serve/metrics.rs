@@ -0,0 +1,128 @@
+use crate::k8s::PodImage;
+use k8s_openapi::api::core::v1::Node;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+/// Content type of the Prometheus text exposition format
+pub const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// A gauge family in the text exposition format
+struct Gauge {
+    /// Name of the metric
+    name: &'static str,
+    /// Help text of the metric
+    help: &'static str,
+    /// Label sets and values of the samples
+    samples: Vec<(Vec<(&'static str, String)>, u64)>,
+}
+
+impl Gauge {
+    /// Append the gauge to the exposition text
+    fn write_to(&self, out: &mut String) {
+        let _ = writeln!(out, "# HELP {} {}", self.name, self.help);
+        let _ = writeln!(out, "# TYPE {} gauge", self.name);
+        for (labels, value) in &self.samples {
+            let labels: Vec<String> = labels
+                .iter()
+                .map(|(name, value)| format!("{}=\"{}\"", name, escape_label_value(value)))
+                .collect();
+            let _ = writeln!(out, "{}{{{}}} {}", self.name, labels.join(","), value);
+        }
+    }
+}
+
+/// Escape a label value for the text exposition format
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Render the image inventory as Prometheus metrics
+///
+/// * `kimspect_container_image_info` - One sample per container, always 1, labelled with its image
+/// * `kimspect_registry_images_total` - Number of distinct images in use per registry
+/// * `kimspect_node_image_bytes` - Total size of the images stored on each node
+///
+/// # Arguments
+///
+/// * `images` - The pod images to export
+/// * `nodes` - The nodes whose stored images to sum up
+///
+/// # Returns
+///
+/// * `String` - The metrics in the text exposition format
+pub fn render_metrics(images: &[PodImage], nodes: &[Node]) -> String {
+    let info = Gauge {
+        name: "kimspect_container_image_info",
+        help: "Image of each container, always 1",
+        samples: images
+            .iter()
+            .map(|image| {
+                // Digest references carry no tag
+                let tag = image.image_version.split('@').next().unwrap_or_default();
+                let labels = vec![
+                    ("namespace", image.namespace.clone()),
+                    ("pod", image.pod_name.clone()),
+                    ("container", image.container_name.clone()),
+                    ("registry", image.registry.clone()),
+                    ("image", image.image_name.clone()),
+                    ("tag", tag.to_string()),
+                    ("digest", image.digest.clone()),
+                ];
+                (labels, 1)
+            })
+            .collect(),
+    };
+
+    let mut registries: BTreeMap<&str, BTreeSet<(&str, &str)>> = BTreeMap::new();
+    for image in images {
+        registries
+            .entry(image.registry.as_str())
+            .or_default()
+            .insert((image.image_name.as_str(), image.image_version.as_str()));
+    }
+    let registry_images = Gauge {
+        name: "kimspect_registry_images_total",
+        help: "Number of distinct images in use per registry",
+        samples: registries
+            .into_iter()
+            .map(|(registry, images)| {
+                (
+                    vec![("registry", registry.to_string())],
+                    images.len() as u64,
+                )
+            })
+            .collect(),
+    };
+
+    let node_bytes = Gauge {
+        name: "kimspect_node_image_bytes",
+        help: "Total size in bytes of the images stored on each node",
+        samples: nodes
+            .iter()
+            .filter_map(|node| {
+                let name = node.metadata.name.clone()?;
+                let bytes = node
+                    .status
+                    .as_ref()
+                    .and_then(|status| status.images.as_ref())
+                    .map(|images| {
+                        images
+                            .iter()
+                            .map(|image| image.size_bytes.unwrap_or(0).max(0) as u64)
+                            .sum()
+                    })
+                    .unwrap_or(0);
+                Some((vec![("node", name)], bytes))
+            })
+            .collect(),
+    };
+
+    let mut out = String::new();
+    for gauge in [info, registry_images, node_bytes] {
+        gauge.write_to(&mut out);
+    }
+    out
+}
@@ -0,0 +1,275 @@
+//! Long-running server mode (`kimspect serve`)
+//!
+//! The server watches pods and nodes into an [`Inventory`] once and answers
+//! every request from it, so scrapes and API calls do not list pods on the
+//! API server.
+
+use crate::k8s::{ImageQuery, Inventory, PodImage, collect_pod_images};
+use crate::registry::{MirrorMap, RegistryClassifier};
+use anyhow::{Context, Result};
+use axum::Router;
+use axum::extract::State;
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tracing::{debug, info};
+
+mod api;
+mod metrics;
+mod tls;
+
+pub use api::{ApiParams, RegistryUsage, api_router, registry_usage};
+pub use metrics::{METRICS_CONTENT_TYPE, render_metrics};
+pub use tls::load_tls_config;
+
+/// The inventory a server answers from, with the settings of `get images`
+pub struct ServerState {
+    /// Cache of the watched pods and nodes
+    inventory: Inventory,
+    /// The query selecting the served pods and images
+    query: ImageQuery,
+    /// Classifier of the image registries
+    classifier: RegistryClassifier,
+    /// Mirrors to annotate images with their upstream origin
+    mirrors: MirrorMap,
+}
+
+impl ServerState {
+    /// Create the state of a server
+    ///
+    /// # Arguments
+    ///
+    /// * `inventory` - The inventory to answer from
+    /// * `query` - The query selecting the served pods and images
+    /// * `classifier` - Classifier of the image registries
+    /// * `mirrors` - Mirrors to annotate images with their upstream origin
+    ///
+    /// # Returns
+    ///
+    /// * `ServerState` - The state, to share between requests
+    pub fn new(
+        inventory: Inventory,
+        query: ImageQuery,
+        classifier: RegistryClassifier,
+        mirrors: MirrorMap,
+    ) -> Self {
+        Self {
+            inventory,
+            query,
+            classifier,
+            mirrors,
+        }
+    }
+
+    /// Get the inventory the server answers from
+    pub fn inventory(&self) -> &Inventory {
+        &self.inventory
+    }
+
+    /// Get the query selecting the served pods and images
+    pub fn query(&self) -> &ImageQuery {
+        &self.query
+    }
+
+    /// Get the mirrors images are annotated with
+    pub fn mirrors(&self) -> &MirrorMap {
+        &self.mirrors
+    }
+
+    /// Check if the pods of a namespace are served
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - The namespace to check
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True if the namespace is watched and not excluded
+    pub fn is_watched(&self, namespace: &str) -> bool {
+        let watched =
+            self.query.is_all_namespaces() || self.query.get_namespaces().contains(&namespace);
+        watched
+            && !self
+                .query
+                .get_exclude_namespaces()
+                .iter()
+                .any(|excluded| excluded == namespace)
+    }
+
+    /// Get the images of the served pods, classified and annotated like `get images` does
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<PodImage>` - The pod images, empty when no pods match
+    pub async fn images(&self) -> Vec<PodImage> {
+        self.images_matching(&self.query).await
+    }
+
+    /// Get the images of the pods matching a narrower query than the served one
+    ///
+    /// # Arguments
+    ///
+    /// * `query` - The query selecting the pods and images
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<PodImage>` - The pod images, empty when no pods match
+    pub async fn images_matching(&self, query: &ImageQuery) -> Vec<PodImage> {
+        // No matching pods is an empty inventory rather than an error here
+        let mut images = collect_pod_images(&self.inventory, query)
+            .await
+            .unwrap_or_default();
+        self.classifier.apply(&mut images);
+        self.mirrors.apply(&mut images);
+        images
+    }
+}
+
+/// Build the router of the Prometheus exporter
+///
+/// * `GET /metrics` - The image metrics, 503 until the pods have been listed
+/// * `GET /healthz` - Always 200 while the server runs
+///
+/// # Arguments
+///
+/// * `state` - The inventory to export
+///
+/// # Returns
+///
+/// * `Router` - The routes, ready to serve
+pub fn metrics_router(state: Arc<ServerState>) -> Router {
+    Router::new()
+        .route("/metrics", get(metrics_handler))
+        .route("/healthz", get(|| async { "ok" }))
+        .with_state(state)
+}
+
+/// Render the metrics of the inventory
+async fn metrics_handler(State(state): State<Arc<ServerState>>) -> Response {
+    if !state.inventory.is_ready() {
+        return (StatusCode::SERVICE_UNAVAILABLE, "Inventory not ready\n").into_response();
+    }
+
+    let images = state.images().await;
+    let nodes: Vec<_> = state
+        .inventory
+        .nodes()
+        .iter()
+        .map(|node| node.as_ref().clone())
+        .collect();
+    debug!(
+        images = images.len(),
+        nodes = nodes.len(),
+        "Rendering metrics"
+    );
+    (
+        [(header::CONTENT_TYPE, METRICS_CONTENT_TYPE)],
+        render_metrics(&images, &nodes),
+    )
+        .into_response()
+}
+
+/// Parse a listen address such as `:9090`, `0.0.0.0:9090` or `[::1]:9090`
+///
+/// # Arguments
+///
+/// * `value` - The address, listening on all interfaces when the host is left out
+///
+/// # Returns
+///
+/// * `Result<SocketAddr, String>` - The socket address or an error message
+pub fn parse_listen_addr(value: &str) -> Result<SocketAddr, String> {
+    let value = value.trim();
+    let address = match value.strip_prefix(':') {
+        Some(port) => format!("0.0.0.0:{}", port),
+        None => value.to_string(),
+    };
+    address.parse().map_err(|_| {
+        format!(
+            "Invalid listen address '{}', expected [HOST]:PORT (e.g. :9090)",
+            value
+        )
+    })
+}
+
+/// Serve a router until interrupted with Ctrl-C or SIGTERM
+///
+/// # Arguments
+///
+/// * `addr` - The address to listen on
+/// * `router` - The routes to serve
+///
+/// # Returns
+///
+/// * `Result<()>` - Ok after a graceful shutdown, or an error if the address cannot be bound
+pub async fn serve(addr: SocketAddr, router: Router) -> Result<()> {
+    let listener = bind(addr).await?;
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown_signal())
+        .await
+        .context("Server failed")?;
+    info!("Server stopped");
+    Ok(())
+}
+
+/// Serve a router over HTTPS until interrupted with Ctrl-C or SIGTERM
+///
+/// # Arguments
+///
+/// * `addr` - The address to listen on
+/// * `router` - The routes to serve
+/// * `config` - The TLS configuration, see [`load_tls_config`]
+///
+/// # Returns
+///
+/// * `Result<()>` - Ok after a graceful shutdown, or an error if the address cannot be bound
+pub async fn serve_tls(
+    addr: SocketAddr,
+    router: Router,
+    config: Arc<tokio_rustls::rustls::ServerConfig>,
+) -> Result<()> {
+    let listener = tls::TlsListener::new(bind(addr).await?, config);
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown_signal())
+        .await
+        .context("Server failed")?;
+    info!("Server stopped");
+    Ok(())
+}
+
+/// Listen on an address
+async fn bind(addr: SocketAddr) -> Result<TcpListener> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("Failed to listen on {}", addr))?;
+    info!(addr = %addr, "Listening");
+    Ok(listener)
+}
+
+/// Wait for Ctrl-C, or SIGTERM on Unix as sent when a pod is stopped
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(_) => std::future::pending().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+    info!("Shutting down");
+}
@@ -0,0 +1,558 @@
+use crate::k8s::{PodImage, pod_workload};
+use k8s_openapi::api::core::v1::Pod;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::{BTreeSet, HashMap};
+
+/// Label of the list entries selecting every namespace, workload or pod
+pub const ALL_ENTRY: &str = "(all)";
+
+/// A container of a pod with the details the TUI shows besides its image
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct ContainerRow {
+    /// The container image, classified and annotated like `get images` does
+    pub image: PodImage,
+    /// Workload owning the pod (e.g. Deployment/web), empty for standalone pods
+    pub workload: String,
+    /// Image pull policy of the container (e.g. IfNotPresent)
+    pub pull_policy: String,
+}
+
+/// Build the rows of the TUI from pod images and the pods they were read from
+///
+/// # Arguments
+///
+/// * `pods` - The pods, used for owners and pull policies
+/// * `images` - The pod images to show
+///
+/// # Returns
+///
+/// * `Vec<ContainerRow>` - One row per image, ordered by namespace, pod and container
+pub fn container_rows<'a>(
+    pods: impl IntoIterator<Item = &'a Pod>,
+    images: Vec<PodImage>,
+) -> Vec<ContainerRow> {
+    let pods: HashMap<(&str, &str), &Pod> = pods
+        .into_iter()
+        .map(|pod| {
+            let namespace = pod.metadata.namespace.as_deref().unwrap_or_default();
+            let name = pod.metadata.name.as_deref().unwrap_or_default();
+            ((namespace, name), pod)
+        })
+        .collect();
+
+    let mut rows: Vec<ContainerRow> = images
+        .into_iter()
+        .map(|image| {
+            let pod = pods.get(&(image.namespace.as_str(), image.pod_name.as_str()));
+            let pull_policy = pod
+                .and_then(|pod| pod.spec.as_ref())
+                .and_then(|spec| {
+                    spec.containers
+                        .iter()
+                        .find(|container| container.name == image.container_name)
+                })
+                .and_then(|container| container.image_pull_policy.clone())
+                .unwrap_or_default();
+            ContainerRow {
+                workload: pod.map(|pod| pod_workload(pod)).unwrap_or_default(),
+                pull_policy,
+                image,
+            }
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        (
+            &a.image.namespace,
+            &a.image.pod_name,
+            &a.image.container_name,
+        )
+            .cmp(&(
+                &b.image.namespace,
+                &b.image.pod_name,
+                &b.image.container_name,
+            ))
+    });
+    rows
+}
+
+/// A filter typed into the TUI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterField {
+    /// Matches the registry
+    Registry,
+    /// Matches the image name and version
+    Image,
+    /// Matches the node name
+    Node,
+}
+
+impl FilterField {
+    /// Get the name of the filter as shown in the filter bar
+    pub fn name(&self) -> &'static str {
+        match self {
+            FilterField::Registry => "registry",
+            FilterField::Image => "image",
+            FilterField::Node => "node",
+        }
+    }
+}
+
+/// Live filters narrowing every pane, matched case-insensitively as substrings
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ImageFilter {
+    /// Text the registry must contain
+    pub registry: String,
+    /// Text the image name and version must contain
+    pub image: String,
+    /// Text the node name must contain
+    pub node: String,
+}
+
+impl ImageFilter {
+    /// Set the text the registry must contain
+    pub fn registry(mut self, registry: impl Into<String>) -> Self {
+        self.registry = registry.into();
+        self
+    }
+
+    /// Set the text the image name and version must contain
+    pub fn image(mut self, image: impl Into<String>) -> Self {
+        self.image = image.into();
+        self
+    }
+
+    /// Set the text the node name must contain
+    pub fn node(mut self, node: impl Into<String>) -> Self {
+        self.node = node.into();
+        self
+    }
+
+    /// Get the text of a filter
+    pub fn get(&self, field: FilterField) -> &str {
+        match field {
+            FilterField::Registry => &self.registry,
+            FilterField::Image => &self.image,
+            FilterField::Node => &self.node,
+        }
+    }
+
+    /// Get the text of a filter to edit it
+    fn get_mut(&mut self, field: FilterField) -> &mut String {
+        match field {
+            FilterField::Registry => &mut self.registry,
+            FilterField::Image => &mut self.image,
+            FilterField::Node => &mut self.node,
+        }
+    }
+
+    /// Check if a row passes the filters
+    ///
+    /// # Arguments
+    ///
+    /// * `row` - The row to check
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True if every filter matches
+    pub fn matches(&self, row: &ContainerRow) -> bool {
+        let contains = |value: &str, filter: &str| {
+            filter.is_empty() || value.to_lowercase().contains(&filter.to_lowercase())
+        };
+        let image = format!("{}:{}", row.image.image_name, row.image.image_version);
+        contains(&row.image.registry, &self.registry)
+            && contains(&image, &self.image)
+            && contains(&row.image.node_name, &self.node)
+    }
+}
+
+/// The panes of the TUI, from the widest selection to the narrowest
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TuiPane {
+    /// Namespaces of the containers
+    Namespaces,
+    /// Workloads of the selected namespace
+    Workloads,
+    /// Pods of the selected workload
+    Pods,
+    /// Containers of the selected pods
+    Images,
+}
+
+impl TuiPane {
+    /// Every pane, in the order Tab moves through them
+    pub const ALL: [TuiPane; 4] = [
+        TuiPane::Namespaces,
+        TuiPane::Workloads,
+        TuiPane::Pods,
+        TuiPane::Images,
+    ];
+
+    /// Get the title of the pane
+    pub fn title(&self) -> &'static str {
+        match self {
+            TuiPane::Namespaces => "Namespaces",
+            TuiPane::Workloads => "Workloads",
+            TuiPane::Pods => "Pods",
+            TuiPane::Images => "Images",
+        }
+    }
+
+    /// Get the pane after this one, wrapping around
+    fn next(self) -> Self {
+        let index = TuiPane::ALL
+            .iter()
+            .position(|pane| *pane == self)
+            .unwrap_or(0);
+        TuiPane::ALL[(index + 1) % TuiPane::ALL.len()]
+    }
+
+    /// Get the pane before this one, wrapping around
+    fn previous(self) -> Self {
+        let index = TuiPane::ALL
+            .iter()
+            .position(|pane| *pane == self)
+            .unwrap_or(0);
+        TuiPane::ALL[(index + TuiPane::ALL.len() - 1) % TuiPane::ALL.len()]
+    }
+}
+
+/// State of the TUI: the rows, the selection in each pane and the filters
+///
+/// Selections are kept by name, so they survive rows being refreshed; a
+/// selection that disappears falls back to all entries.
+#[derive(Debug, Clone)]
+pub struct TuiApp {
+    /// Every container, unfiltered
+    rows: Vec<ContainerRow>,
+    /// Whether the rows were loaded at least once
+    loaded: bool,
+    /// The live filters
+    filter: ImageFilter,
+    /// The filter being typed, if any
+    editing: Option<FilterField>,
+    /// The pane receiving navigation keys
+    focus: TuiPane,
+    /// Selected namespace, None for all
+    namespace: Option<String>,
+    /// Selected workload, None for all
+    workload: Option<String>,
+    /// Selected pod, None for all
+    pod: Option<String>,
+    /// Index of the selected container in the images pane
+    image: usize,
+    /// Whether the detail view of the selected container is open
+    detail: bool,
+    /// Error to show in the status bar
+    error: Option<String>,
+    /// Whether the user asked to quit
+    quit: bool,
+}
+
+impl TuiApp {
+    /// Create the state of a TUI with no rows loaded yet
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - The initial filters
+    ///
+    /// # Returns
+    ///
+    /// * `TuiApp` - The state, with focus on the namespaces pane
+    pub fn new(filter: ImageFilter) -> Self {
+        Self {
+            rows: Vec::new(),
+            loaded: false,
+            filter,
+            editing: None,
+            focus: TuiPane::Namespaces,
+            namespace: None,
+            workload: None,
+            pod: None,
+            image: 0,
+            detail: false,
+            error: None,
+            quit: false,
+        }
+    }
+
+    /// Replace the rows, keeping the selections that still exist
+    pub fn set_rows(&mut self, rows: Vec<ContainerRow>) {
+        self.rows = rows;
+        self.loaded = true;
+        self.clamp_selection();
+    }
+
+    /// Set the error shown in the status bar, None to clear it
+    pub fn set_error(&mut self, error: Option<String>) {
+        self.error = error;
+    }
+
+    /// Check if the rows were loaded at least once
+    pub fn is_loaded(&self) -> bool {
+        self.loaded
+    }
+
+    /// Get the error shown in the status bar
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Get the live filters
+    pub fn filter(&self) -> &ImageFilter {
+        &self.filter
+    }
+
+    /// Get the filter being typed, if any
+    pub fn editing(&self) -> Option<FilterField> {
+        self.editing
+    }
+
+    /// Get the pane receiving navigation keys
+    pub fn focus(&self) -> TuiPane {
+        self.focus
+    }
+
+    /// Check if the detail view is open
+    pub fn is_detail_open(&self) -> bool {
+        self.detail && self.selected_row().is_some()
+    }
+
+    /// Check if the user asked to quit
+    pub fn should_quit(&self) -> bool {
+        self.quit
+    }
+
+    /// Get the number of containers, before filtering
+    pub fn total(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Get the rows passing the filters and the selections up to a pane
+    fn rows_within(&self, pane: TuiPane) -> impl Iterator<Item = &ContainerRow> {
+        let namespace = self
+            .namespace
+            .as_deref()
+            .filter(|_| pane != TuiPane::Namespaces);
+        let workload = self
+            .workload
+            .as_deref()
+            .filter(|_| matches!(pane, TuiPane::Pods | TuiPane::Images));
+        let pod = self.pod.as_deref().filter(|_| pane == TuiPane::Images);
+        self.rows.iter().filter(move |row| {
+            self.filter.matches(row)
+                && namespace.is_none_or(|namespace| row.image.namespace == namespace)
+                && workload.is_none_or(|workload| row.workload == workload)
+                && pod.is_none_or(|pod| row.image.pod_name == pod)
+        })
+    }
+
+    /// Get the entries of a list pane, without the entry selecting all of them
+    ///
+    /// # Arguments
+    ///
+    /// * `pane` - The namespaces, workloads or pods pane
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - The sorted names (empty for the images pane)
+    pub fn entries(&self, pane: TuiPane) -> Vec<String> {
+        let key = |row: &ContainerRow| match pane {
+            TuiPane::Namespaces => Some(row.image.namespace.clone()),
+            TuiPane::Workloads => Some(row.workload.clone()),
+            TuiPane::Pods => Some(row.image.pod_name.clone()),
+            TuiPane::Images => None,
+        };
+        self.rows_within(pane)
+            .filter_map(key)
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Get the selected entry of a list pane, None when all entries are selected
+    pub fn selection(&self, pane: TuiPane) -> Option<&str> {
+        match pane {
+            TuiPane::Namespaces => self.namespace.as_deref(),
+            TuiPane::Workloads => self.workload.as_deref(),
+            TuiPane::Pods => self.pod.as_deref(),
+            TuiPane::Images => None,
+        }
+    }
+
+    /// Get the containers shown in the images pane
+    pub fn visible_rows(&self) -> Vec<&ContainerRow> {
+        self.rows_within(TuiPane::Images).collect()
+    }
+
+    /// Get the index of the selected container in the images pane
+    pub fn selected_index(&self) -> usize {
+        self.image
+    }
+
+    /// Get the selected container
+    pub fn selected_row(&self) -> Option<&ContainerRow> {
+        self.rows_within(TuiPane::Images).nth(self.image)
+    }
+
+    /// Handle a key press
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The pressed key
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            self.quit = true;
+            return;
+        }
+
+        if let Some(field) = self.editing {
+            match key.code {
+                KeyCode::Enter => self.editing = None,
+                KeyCode::Esc => {
+                    self.filter.get_mut(field).clear();
+                    self.editing = None;
+                }
+                KeyCode::Backspace => {
+                    self.filter.get_mut(field).pop();
+                }
+                KeyCode::Char(c) => self.filter.get_mut(field).push(c),
+                _ => {}
+            }
+            self.clamp_selection();
+            return;
+        }
+
+        if self.detail {
+            if matches!(key.code, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q')) {
+                self.detail = false;
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Tab | KeyCode::Right | KeyCode::Char('l') => self.focus = self.focus.next(),
+            KeyCode::BackTab | KeyCode::Left | KeyCode::Char('h') => {
+                self.focus = self.focus.previous()
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::PageDown => self.move_selection(10),
+            KeyCode::PageUp => self.move_selection(-10),
+            KeyCode::Enter if self.focus == TuiPane::Images => {
+                self.detail = self.selected_row().is_some()
+            }
+            KeyCode::Enter => self.focus = self.focus.next(),
+            KeyCode::Char('r') => self.editing = Some(FilterField::Registry),
+            KeyCode::Char('i') | KeyCode::Char('/') => self.editing = Some(FilterField::Image),
+            KeyCode::Char('n') => self.editing = Some(FilterField::Node),
+            KeyCode::Char('c') => {
+                self.filter = ImageFilter::default();
+                self.clamp_selection();
+            }
+            _ => {}
+        }
+    }
+
+    /// Move the selection of the focused pane
+    ///
+    /// Changing the namespace, workload or pod resets the selections after it.
+    fn move_selection(&mut self, delta: isize) {
+        if self.focus == TuiPane::Images {
+            let last = self.visible_rows().len().saturating_sub(1);
+            self.image = self.image.saturating_add_signed(delta).min(last);
+            return;
+        }
+
+        // Position 0 is the entry selecting all
+        let entries = self.entries(self.focus);
+        let position = self
+            .selection(self.focus)
+            .and_then(|selected| entries.iter().position(|entry| entry == selected))
+            .map_or(0, |index| index + 1);
+        let position = position.saturating_add_signed(delta).min(entries.len());
+        let selected = position.checked_sub(1).map(|index| entries[index].clone());
+
+        match self.focus {
+            TuiPane::Namespaces => {
+                self.namespace = selected;
+                self.workload = None;
+                self.pod = None;
+            }
+            TuiPane::Workloads => {
+                self.workload = selected;
+                self.pod = None;
+            }
+            TuiPane::Pods => self.pod = selected,
+            TuiPane::Images => {}
+        }
+        self.image = 0;
+    }
+
+    /// Drop selections that no longer exist after the rows or filters changed
+    fn clamp_selection(&mut self) {
+        if let Some(namespace) = &self.namespace {
+            if !self.entries(TuiPane::Namespaces).contains(namespace) {
+                self.namespace = None;
+            }
+        }
+        if let Some(workload) = &self.workload {
+            if !self.entries(TuiPane::Workloads).contains(workload) {
+                self.workload = None;
+            }
+        }
+        if let Some(pod) = &self.pod {
+            if !self.entries(TuiPane::Pods).contains(pod) {
+                self.pod = None;
+            }
+        }
+        self.image = self.image.min(self.visible_rows().len().saturating_sub(1));
+    }
+}
+
+/// Get the fields of the detail view of a container
+///
+/// # Arguments
+///
+/// * `row` - The container to describe
+///
+/// # Returns
+///
+/// * `Vec<(&'static str, String)>` - Labels and values, `<none>` for unknown values
+pub fn detail_fields(row: &ContainerRow) -> Vec<(&'static str, String)> {
+    let image = &row.image;
+    let upstream = if image.upstream_registry.is_empty() {
+        String::new()
+    } else {
+        format!("{}/{}", image.upstream_registry, image.upstream_image)
+    };
+    [
+        ("Namespace", image.namespace.clone()),
+        ("Pod", image.pod_name.clone()),
+        ("Workload", row.workload.clone()),
+        ("Node", image.node_name.clone()),
+        ("Container", image.container_name.clone()),
+        ("Image", image.reference()),
+        ("Registry", image.registry.clone()),
+        ("Class", image.registry_class.clone()),
+        ("Upstream", upstream),
+        ("Digest", image.digest.clone()),
+        ("Size", image.image_size.clone()),
+        ("Pull policy", row.pull_policy.clone()),
+        ("Status", image.status.clone()),
+        ("Release", image.release.clone()),
+        ("Chart", image.chart.clone()),
+    ]
+    .into_iter()
+    .map(|(label, value)| {
+        let value = if value.is_empty() {
+            "<none>".to_string()
+        } else {
+            value
+        };
+        (label, value)
+    })
+    .collect()
+}
@@ -0,0 +1,133 @@
+//! Interactive terminal UI for browsing images (`kimspect tui`)
+//!
+//! The TUI watches pods and nodes into an [`Inventory`] and shows its
+//! containers in panes narrowing from namespaces to workloads, pods and
+//! images, with live filters and a detail view per container.
+
+use crate::k8s::{ImageQuery, Inventory, K8sClient, collect_pod_images};
+use crate::registry::{MirrorMap, RegistryClassifier};
+use anyhow::{Context, Result};
+use ratatui::DefaultTerminal;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+mod app;
+mod ui;
+
+pub use app::{
+    ALL_ENTRY, ContainerRow, FilterField, ImageFilter, TuiApp, TuiPane, container_rows,
+    detail_fields,
+};
+pub use ui::draw as draw_tui;
+
+/// How long to wait for a key press before redrawing
+const TICK: Duration = Duration::from_millis(250);
+
+/// Minimum time between two reloads of the rows, as busy clusters change constantly
+const RELOAD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Run the TUI until the user quits
+///
+/// # Arguments
+///
+/// * `client` - The client to watch the cluster with
+/// * `query` - The query selecting the pods and images to browse
+/// * `classifier` - Classifier of the image registries
+/// * `mirrors` - Mirrors to annotate images with their upstream origin
+/// * `filter` - The initial live filters
+///
+/// # Returns
+///
+/// * `Result<()>` - Ok when the user quits, or an error if the terminal fails
+pub async fn run_tui(
+    client: &K8sClient,
+    query: ImageQuery,
+    classifier: RegistryClassifier,
+    mirrors: MirrorMap,
+    filter: ImageFilter,
+) -> Result<()> {
+    // Several namespaces are watched across the cluster and narrowed by the query
+    let namespaces = query.get_namespaces();
+    let namespace = match namespaces.as_slice() {
+        [namespace] if !query.is_all_namespaces() => Some(*namespace),
+        _ => None,
+    };
+    let (inventory, watch) = client.watch_inventory(namespace);
+    let watch = tokio::spawn(watch);
+
+    let mut terminal = ratatui::init();
+    let result = event_loop(
+        &mut terminal,
+        &inventory,
+        &query,
+        &classifier,
+        &mirrors,
+        TuiApp::new(filter),
+    )
+    .await;
+    ratatui::restore();
+    watch.abort();
+
+    info!("TUI closed");
+    result
+}
+
+/// Draw the TUI and handle key presses until the user quits
+async fn event_loop(
+    terminal: &mut DefaultTerminal,
+    inventory: &Inventory,
+    query: &ImageQuery,
+    classifier: &RegistryClassifier,
+    mirrors: &MirrorMap,
+    mut app: TuiApp,
+) -> Result<()> {
+    let mut loaded_revision = 0;
+    let mut loaded_at: Option<Instant> = None;
+
+    while !app.should_quit() {
+        let revision = inventory.revision();
+        let due = loaded_at.is_none_or(|at| at.elapsed() >= RELOAD_INTERVAL);
+        if revision != loaded_revision && due {
+            debug!(revision = revision, "Reloading containers");
+            app.set_rows(load_rows(inventory, query, classifier, mirrors).await);
+            loaded_revision = revision;
+            loaded_at = Some(Instant::now());
+        }
+        app.set_error(inventory.last_error());
+
+        terminal
+            .draw(|frame| ui::draw(frame, &app))
+            .context("Failed to draw the TUI")?;
+
+        // Polling blocks, keep the runtime free for the watches meanwhile
+        let pressed = tokio::task::block_in_place(|| event::poll(TICK))
+            .context("Failed to read terminal events")?;
+        if pressed {
+            if let Event::Key(key) = event::read().context("Failed to read terminal events")? {
+                if key.kind == KeyEventKind::Press {
+                    app.handle_key(key);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Build the rows of the TUI from the inventory
+async fn load_rows(
+    inventory: &Inventory,
+    query: &ImageQuery,
+    classifier: &RegistryClassifier,
+    mirrors: &MirrorMap,
+) -> Vec<ContainerRow> {
+    // No matching pods is an empty view rather than an error here
+    let mut images = collect_pod_images(inventory, query)
+        .await
+        .unwrap_or_default();
+    classifier.apply(&mut images);
+    mirrors.apply(&mut images);
+
+    let pods = inventory.pods();
+    container_rows(pods.iter().map(|pod| pod.as_ref()), images)
+}
@@ -0,0 +1,200 @@
+use crate::tui::app::{ALL_ENTRY, FilterField, TuiApp, TuiPane, detail_fields};
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Cell, Clear, List, ListState, Paragraph, Row, Table, TableState};
+
+/// Key bindings shown at the bottom of the screen
+const HELP: &str = "Tab/←→ pane  ↑↓ move  Enter details  r/i/n filter registry/image/node  \
+                    c clear filters  q quit";
+
+/// Key bindings shown while typing a filter
+const EDIT_HELP: &str = "Type to filter  Enter apply  Esc clear";
+
+/// Draw the TUI
+///
+/// # Arguments
+///
+/// * `frame` - The frame to draw on
+/// * `app` - The state to draw
+pub fn draw(frame: &mut Frame, app: &TuiApp) {
+    let [status, body, filters, help] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(0),
+        Constraint::Length(1),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [lists, images] =
+        Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)]).areas(body);
+    let [namespaces, workloads, pods] = Layout::vertical([
+        Constraint::Percentage(25),
+        Constraint::Percentage(35),
+        Constraint::Percentage(40),
+    ])
+    .areas(lists);
+
+    draw_status(frame, app, status);
+    draw_list(frame, app, TuiPane::Namespaces, namespaces);
+    draw_list(frame, app, TuiPane::Workloads, workloads);
+    draw_list(frame, app, TuiPane::Pods, pods);
+    draw_images(frame, app, images);
+    draw_filters(frame, app, filters);
+    let help_text = if app.editing().is_some() {
+        EDIT_HELP
+    } else {
+        HELP
+    };
+    frame.render_widget(Paragraph::new(help_text).dark_gray(), help);
+
+    if app.is_detail_open() {
+        draw_detail(frame, app, frame.area());
+    }
+}
+
+/// Draw the status bar with the container counts and watch errors
+fn draw_status(frame: &mut Frame, app: &TuiApp, area: Rect) {
+    let mut spans = vec![Span::from(" kimspect ").bold().reversed()];
+    if app.is_loaded() {
+        spans.push(Span::from(format!(
+            " {} of {} containers",
+            app.visible_rows().len(),
+            app.total()
+        )));
+    } else {
+        spans.push(Span::from(" Loading pods..."));
+    }
+    if let Some(error) = app.error() {
+        spans.push(Span::from(format!("  {}", error)).red());
+    }
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// Get the block around a pane, highlighted when focused
+fn pane_block(app: &TuiApp, pane: TuiPane) -> Block<'static> {
+    let block = Block::bordered().title(pane.title());
+    if app.focus() == pane && app.editing().is_none() {
+        block.border_style(Style::new().fg(Color::Cyan))
+    } else {
+        block
+    }
+}
+
+/// Draw the namespaces, workloads or pods pane
+fn draw_list(frame: &mut Frame, app: &TuiApp, pane: TuiPane, area: Rect) {
+    let entries = app.entries(pane);
+    let selected = app
+        .selection(pane)
+        .and_then(|selected| entries.iter().position(|entry| entry == selected))
+        .map_or(0, |index| index + 1);
+
+    let items = std::iter::once(ALL_ENTRY.to_string()).chain(entries.into_iter().map(|entry| {
+        if entry.is_empty() {
+            "<none>".to_string()
+        } else {
+            entry
+        }
+    }));
+    let list = List::new(items)
+        .block(pane_block(app, pane))
+        .highlight_style(Style::new().reversed());
+    let mut state = ListState::default().with_selected(Some(selected));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+/// Draw the images pane
+fn draw_images(frame: &mut Frame, app: &TuiApp, area: Rect) {
+    let header = Row::new([
+        "POD",
+        "CONTAINER",
+        "REGISTRY",
+        "IMAGE",
+        "VERSION",
+        "NODE",
+        "STATUS",
+    ])
+    .bold();
+    let rows: Vec<Row> = app
+        .visible_rows()
+        .into_iter()
+        .map(|row| {
+            let image = &row.image;
+            Row::new([
+                Cell::from(image.pod_name.clone()),
+                Cell::from(image.container_name.clone()),
+                Cell::from(image.registry.clone()),
+                Cell::from(image.image_name.clone()),
+                Cell::from(image.image_version.clone()),
+                Cell::from(image.node_name.clone()),
+                Cell::from(image.status.clone()),
+            ])
+        })
+        .collect();
+    let widths = [
+        Constraint::Fill(3),
+        Constraint::Fill(2),
+        Constraint::Fill(2),
+        Constraint::Fill(3),
+        Constraint::Fill(2),
+        Constraint::Fill(2),
+        Constraint::Fill(2),
+    ];
+
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(pane_block(app, TuiPane::Images))
+        .row_highlight_style(Style::new().reversed());
+    let mut state = TableState::default().with_selected(
+        (app.focus() == TuiPane::Images || app.is_detail_open()).then_some(app.selected_index()),
+    );
+    frame.render_stateful_widget(table, area, &mut state);
+}
+
+/// Draw the filter bar, highlighting the filter being typed
+fn draw_filters(frame: &mut Frame, app: &TuiApp, area: Rect) {
+    let mut spans = vec![Span::from(" Filters:")];
+    for field in [FilterField::Registry, FilterField::Image, FilterField::Node] {
+        let text = format!(" {}={}", field.name(), app.filter().get(field));
+        if app.editing() == Some(field) {
+            spans.push(Span::from(format!("{}_", text)).yellow().bold());
+        } else {
+            spans.push(Span::from(text));
+        }
+    }
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// Draw the detail view of the selected container over the panes
+fn draw_detail(frame: &mut Frame, app: &TuiApp, area: Rect) {
+    let Some(row) = app.selected_row() else {
+        return;
+    };
+    let fields = detail_fields(row);
+
+    let height = (fields.len() as u16 + 2).min(area.height);
+    let width = (area.width * 4 / 5).max(40).min(area.width);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let lines: Vec<Line> = fields
+        .into_iter()
+        .map(|(label, value)| {
+            Line::from(vec![
+                Span::from(format!("{:<12}", label)).bold(),
+                Span::from(value),
+            ])
+        })
+        .collect();
+    let block = Block::bordered()
+        .title(format!(" {} ", row.image.container_name))
+        .title_bottom(" Esc to close ")
+        .border_style(Style::new().fg(Color::Cyan));
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(Paragraph::new(lines).block(block), popup);
+}
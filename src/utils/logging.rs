@@ -1,32 +1,60 @@
-use crate::LogFormat;
+use crate::cli::LogFormat;
 use anyhow::{Context, Result};
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::sync::Mutex;
 use tracing::Level;
-use tracing_subscriber::{EnvFilter, prelude::*};
+use tracing_subscriber::{EnvFilter, fmt::writer::BoxMakeWriter, prelude::*};
 
 /// Initialize the structured logging system with configurable formatting
 ///
+/// Logs are written to stderr, keeping stdout for command output, or appended
+/// to a file.
+///
 /// # Arguments
 ///
 /// * `level` - The logging level to use
 /// * `format` - The format to use for log messages (JSON or plain text)
+/// * `log_file` - File to append log messages to instead of stderr
 ///
 /// # Returns
 ///
 /// * `Result<()>` - Success or error
-pub fn init_logging(level: Level, format: LogFormat) -> Result<()> {
+pub fn init_logging(level: Level, format: LogFormat, log_file: Option<&Path>) -> Result<()> {
     let filter_layer = create_filter_layer(level)?;
+    let writer = create_writer(log_file)?;
 
     match format {
-        LogFormat::Json => {
-            eprintln!("Warning: JSON logging format is not available. Using plain text format.");
-            init_plain_logging(filter_layer)
-        }
-        LogFormat::Plain => init_plain_logging(filter_layer),
+        LogFormat::Json => init_json_logging(filter_layer, writer),
+        LogFormat::Plain => init_plain_logging(filter_layer, writer),
     }
 
     Ok(())
 }
 
+/// Create the writer log messages are sent to
+///
+/// # Arguments
+///
+/// * `log_file` - File to append log messages to, or None for stderr
+///
+/// # Returns
+///
+/// * `Result<BoxMakeWriter>` - The writer or an error if the file cannot be opened
+fn create_writer(log_file: Option<&Path>) -> Result<BoxMakeWriter> {
+    match log_file {
+        Some(path) => {
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("Failed to open log file {}", path.display()))?;
+            Ok(BoxMakeWriter::new(Mutex::new(file)))
+        }
+        None => Ok(BoxMakeWriter::new(std::io::stderr)),
+    }
+}
+
 /// Create the filter layer for logging
 ///
 /// # Arguments
@@ -62,8 +90,10 @@ fn create_filter_layer(level: Level) -> Result<EnvFilter> {
 /// # Arguments
 ///
 /// * `filter_layer` - The filter layer to use
-fn init_plain_logging(filter_layer: EnvFilter) {
+/// * `writer` - The writer to send log messages to
+fn init_plain_logging(filter_layer: EnvFilter, writer: BoxMakeWriter) {
     let plain_layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
         .with_target(true)
         .with_thread_ids(true)
         .with_thread_names(true)
@@ -76,6 +106,34 @@ fn init_plain_logging(filter_layer: EnvFilter) {
         .init();
 }
 
+/// Initialize JSON format logging, one object per line
+///
+/// Event fields are flattened into the object next to the timestamp, level and
+/// target; the current span and its parents are listed with their fields.
+///
+/// # Arguments
+///
+/// * `filter_layer` - The filter layer to use
+/// * `writer` - The writer to send log messages to
+fn init_json_logging(filter_layer: EnvFilter, writer: BoxMakeWriter) {
+    let json_layer = tracing_subscriber::fmt::layer()
+        .json()
+        .with_writer(writer)
+        .flatten_event(true)
+        .with_current_span(true)
+        .with_span_list(true)
+        .with_target(true)
+        .with_thread_ids(true)
+        .with_thread_names(true)
+        .with_file(true)
+        .with_line_number(true);
+
+    tracing_subscriber::registry()
+        .with(filter_layer)
+        .with(json_layer)
+        .init();
+}
+
 /// Configure the logging level based on verbosity count
 ///
 /// # Arguments
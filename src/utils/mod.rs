@@ -1,9 +1,21 @@
-use crate::{OutputFormat, k8s::PodImage};
+use crate::{
+    cli::{CustomColumn, OutputFormat},
+    k8s::{
+        AccessStatus, ArchMismatch, CommandAccess, ImageError, ImageMismatch, ImagePull, NodeInfo,
+        PERMISSIONS, PodImage, PullGroupBy, PullSummary, ReleaseGroup, RolloutImages,
+        format_duration,
+    },
+    registry::{MirrorMap, RegistryClassifier, UpstreamGroup},
+};
 use anyhow::Result;
 use prettytable::{Cell, Row, Table, format::FormatBuilder};
+use std::collections::BTreeSet;
 use tracing::warn;
 
 pub mod logging;
+mod templates;
+
+pub use templates::{GoTemplate, JsonPath, TemplateError, image_list};
 
 /// List of known container image registries
 pub const KNOWN_REGISTRIES: [&str; 11] = [
@@ -20,6 +32,38 @@ pub const KNOWN_REGISTRIES: [&str; 11] = [
     "pkg.dev",
 ];
 
+/// Check if a registry host matches a configured host or one of its subdomains
+///
+/// Matching happens on label boundaries, so `pkg.dev` matches `us-docker.pkg.dev`
+/// but not `evilpkg.dev`. The port of the registry is ignored unless the
+/// configured host includes one.
+///
+/// # Arguments
+///
+/// * `registry` - The registry host, optionally with port
+/// * `host` - The configured host
+///
+/// # Returns
+///
+/// * `bool` - True if the registry is the host or a subdomain of it
+pub fn registry_host_matches(registry: &str, host: &str) -> bool {
+    let registry = registry.to_lowercase();
+    let host = host.to_lowercase();
+    let registry = if host.contains(':') {
+        registry.as_str()
+    } else {
+        registry
+            .rsplit_once(':')
+            .filter(|(_, port)| port.chars().all(|c| c.is_ascii_digit()))
+            .map_or(registry.as_str(), |(name, _)| name)
+    };
+
+    registry == host
+        || registry
+            .strip_suffix(host.as_str())
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
+
 /// Error type for table display operations
 #[derive(Debug)]
 pub struct TableDisplayError {
@@ -49,6 +93,7 @@ impl TableDisplayError {
 ///
 /// * `images` - List of pod images to display
 /// * `output_format` - Format to use for displaying the images
+/// * `no_headers` - Whether to leave out the header row
 ///
 /// # Returns
 ///
@@ -56,6 +101,7 @@ impl TableDisplayError {
 pub fn display_pod_images(
     images: &[PodImage],
     output_format: &OutputFormat,
+    no_headers: bool,
 ) -> Result<(), TableDisplayError> {
     if images.is_empty() {
         warn!("No images found matching criteria");
@@ -63,11 +109,28 @@ pub fn display_pod_images(
     }
 
     let mut table = create_table()?;
-    let header_row = create_header_row(output_format);
-    table.add_row(header_row);
+    if let OutputFormat::CustomColumns(columns) = output_format {
+        if !no_headers {
+            table.add_row(Row::new(
+                columns.iter().map(|c| Cell::new(&c.header)).collect(),
+            ));
+        }
+        for image in images {
+            table.add_row(create_custom_row(image, columns));
+        }
+        table.printstd();
+        return Ok(());
+    }
+
+    let show_upstream = images
+        .iter()
+        .any(|image| !image.upstream_registry.is_empty());
+    if !no_headers {
+        table.add_row(create_header_row(output_format, show_upstream));
+    }
 
     for image in images {
-        let row = create_image_row(image, output_format)
+        let row = create_image_row(image, output_format, show_upstream)
             .map_err(|e| TableDisplayError::new(&e.message))?;
         table.add_row(row);
     }
@@ -76,6 +139,52 @@ pub fn display_pod_images(
     Ok(())
 }
 
+/// Render pod images with a JSONPath or Go template
+///
+/// # Arguments
+///
+/// * `images` - List of pod images to render
+/// * `output_format` - The template format (other formats render nothing)
+///
+/// # Returns
+///
+/// * `Result<String, TemplateError>` - The rendered text or an error
+pub fn render_image_template(
+    images: &[PodImage],
+    output_format: &OutputFormat,
+) -> Result<String, TemplateError> {
+    let list = image_list(images);
+    match output_format {
+        OutputFormat::JsonPath(template) => Ok(JsonPath::parse(template)?.execute(&list)),
+        OutputFormat::GoTemplate(template) => GoTemplate::parse(template)?.execute(&list),
+        _ => Ok(String::new()),
+    }
+}
+
+/// Create a row of custom columns for a single pod image
+///
+/// Empty values are shown as `<none>`, so every row has the same number of
+/// whitespace-separated fields.
+///
+/// # Arguments
+///
+/// * `image` - The pod image to create a row for
+/// * `columns` - The columns to show
+///
+/// # Returns
+///
+/// * `Row` - A row containing the selected fields
+fn create_custom_row(image: &PodImage, columns: &[CustomColumn]) -> Row {
+    let cells = columns
+        .iter()
+        .map(|column| {
+            let value = image.field(column.field);
+            Cell::new(if value.is_empty() { "<none>" } else { &value })
+        })
+        .collect();
+    Row::new(cells)
+}
+
 /// Create a new table with default formatting
 ///
 /// # Returns
@@ -101,11 +210,12 @@ fn create_table() -> Result<Table, TableDisplayError> {
 /// # Arguments
 ///
 /// * `output_format` - Format to use for displaying the images
+/// * `show_upstream` - Whether to include the upstream origin of mirrored images
 ///
 /// # Returns
 ///
 /// * `Row` - A row containing the table headers
-fn create_header_row(output_format: &OutputFormat) -> Row {
+fn create_header_row(output_format: &OutputFormat, show_upstream: bool) -> Row {
     let mut header_cells = vec![
         Cell::new("POD"),
         Cell::new("NAMESPACE"),
@@ -113,13 +223,17 @@ fn create_header_row(output_format: &OutputFormat) -> Row {
     ];
 
     if matches!(output_format, OutputFormat::Wide) {
-        header_cells.push(Cell::new("REGISTRY"));
+        header_cells.extend_from_slice(&[Cell::new("REGISTRY"), Cell::new("CLASS")]);
+        if show_upstream {
+            header_cells.push(Cell::new("UPSTREAM"));
+        }
     }
 
     header_cells.extend_from_slice(&[Cell::new("IMAGE"), Cell::new("VERSION")]);
 
     if matches!(output_format, OutputFormat::Wide) {
         header_cells.extend_from_slice(&[
+            Cell::new("STATUS"),
             Cell::new("SIZE"),
             Cell::new("DIGEST"),
             Cell::new("NODE"),
@@ -135,6 +249,7 @@ fn create_header_row(output_format: &OutputFormat) -> Row {
 ///
 /// * `image` - The pod image to create a row for
 /// * `output_format` - Format to use for displaying the image
+/// * `show_upstream` - Whether to include the upstream origin of mirrored images
 ///
 /// # Returns
 ///
@@ -142,6 +257,7 @@ fn create_header_row(output_format: &OutputFormat) -> Row {
 fn create_image_row(
     image: &PodImage,
     output_format: &OutputFormat,
+    show_upstream: bool,
 ) -> Result<Row, TableDisplayError> {
     let mut cells = vec![
         Cell::new(&image.pod_name),
@@ -150,7 +266,18 @@ fn create_image_row(
     ];
 
     if matches!(output_format, OutputFormat::Wide) {
-        cells.push(Cell::new(&image.registry).style_spec("Fy"));
+        cells.extend_from_slice(&[
+            Cell::new(&image.registry).style_spec("Fy"),
+            Cell::new(&image.registry_class),
+        ]);
+        if show_upstream {
+            let upstream = if image.upstream_registry.is_empty() {
+                "-".to_string()
+            } else {
+                format!("{}/{}", image.upstream_registry, image.upstream_image)
+            };
+            cells.push(Cell::new(&upstream));
+        }
     }
 
     cells.extend_from_slice(&[
@@ -160,6 +287,7 @@ fn create_image_row(
 
     if matches!(output_format, OutputFormat::Wide) {
         cells.extend_from_slice(&[
+            Cell::new(&image.status),
             Cell::new(&image.image_size),
             Cell::new(&image.digest),
             Cell::new(&image.node_name),
@@ -186,30 +314,674 @@ pub fn strip_registry(image_name: &str, registry: &str) -> String {
         .to_string()
 }
 
+/// Build the rows of the registries table, header first
+///
+/// Registries of running pods and of CronJob and Job templates are merged and
+/// sorted. When some registry is only used by scheduled workloads, a SOURCE
+/// column tells `running` registries from `scheduled` ones.
+///
+/// # Arguments
+///
+/// * `running` - Registries used by running workloads
+/// * `scheduled` - Registries used by CronJob and Job templates
+/// * `mirrors` - Mirror mapping used to show the upstream registries behind a mirror
+/// * `classifier` - Classifier used to label each registry
+///
+/// # Returns
+///
+/// * `Vec<Vec<String>>` - The header and a row per registry
+pub fn registry_rows(
+    running: &[String],
+    scheduled: &[String],
+    mirrors: &MirrorMap,
+    classifier: &RegistryClassifier,
+) -> Vec<Vec<String>> {
+    let registries: BTreeSet<&String> = running.iter().chain(scheduled).collect();
+    let with_source = registries
+        .iter()
+        .any(|registry| !running.contains(registry));
+
+    let mut header = vec!["CONTAINER REGISTRY".to_string(), "CLASS".to_string()];
+    if with_source {
+        header.push("SOURCE".to_string());
+    }
+    if !mirrors.is_empty() {
+        header.push("UPSTREAM".to_string());
+    }
+
+    let mut rows = vec![header];
+    for registry in registries {
+        let mut row = vec![registry.clone(), classifier.classify(registry).to_string()];
+        if with_source {
+            let source = if running.contains(registry) {
+                "running"
+            } else {
+                "scheduled"
+            };
+            row.push(source.to_string());
+        }
+        if !mirrors.is_empty() {
+            let upstream = mirrors.upstream_registries(registry);
+            if upstream.is_empty() {
+                row.push("-".to_string());
+            } else {
+                row.push(upstream.join(","));
+            }
+        }
+        rows.push(row);
+    }
+    rows
+}
+
 /// Display a list of container image registries in the specified format
 ///
 /// # Arguments
 ///
-/// * `registries` - List of registry URLs to display
+/// * `running` - Registries used by running workloads
+/// * `scheduled` - Registries used by CronJob and Job templates
+/// * `mirrors` - Mirror mapping used to show the upstream registries behind a mirror
+/// * `classifier` - Classifier used to label each registry
 /// * `_output_format` - Format to display the registries in (currently unused)
 ///
 /// # Returns
 ///
 /// * `Result<()>` - Success or error
 pub fn display_registries(
-    registries: &[String],
+    running: &[String],
+    scheduled: &[String],
+    mirrors: &MirrorMap,
+    classifier: &RegistryClassifier,
     _output_format: &OutputFormat,
 ) -> Result<(), TableDisplayError> {
-    if registries.is_empty() {
+    if running.is_empty() && scheduled.is_empty() {
         warn!("No registries found");
         return Ok(());
     }
 
     let mut table = create_table()?;
-    table.add_row(Row::new(vec![Cell::new("CONTAINER REGISTRY")]));
+    for row in registry_rows(running, scheduled, mirrors, classifier) {
+        table.add_row(Row::new(row.iter().map(|cell| Cell::new(cell)).collect()));
+    }
 
-    for registry in registries {
-        table.add_row(Row::new(vec![Cell::new(registry)]));
+    table.printstd();
+    Ok(())
+}
+
+/// Display containers with image pull failures in a formatted table
+///
+/// # Arguments
+///
+/// * `errors` - List of image errors to display
+/// * `output_format` - Format to use for displaying the errors
+///
+/// # Returns
+///
+/// * `Result<()>` - Success or error
+pub fn display_image_errors(
+    errors: &[ImageError],
+    output_format: &OutputFormat,
+) -> Result<(), TableDisplayError> {
+    if errors.is_empty() {
+        warn!("No image errors found");
+        return Ok(());
+    }
+
+    let mut table = create_table()?;
+
+    let mut header_cells = vec![
+        Cell::new("POD"),
+        Cell::new("NAMESPACE"),
+        Cell::new("CONTAINER"),
+        Cell::new("REGISTRY"),
+        Cell::new("IMAGE"),
+        Cell::new("REASON"),
+    ];
+    if matches!(output_format, OutputFormat::Wide) {
+        header_cells.push(Cell::new("NODE"));
+    }
+    header_cells.push(Cell::new("MESSAGE"));
+    table.add_row(Row::new(header_cells));
+
+    for error in errors {
+        let mut cells = vec![
+            Cell::new(&error.pod_name),
+            Cell::new(&error.namespace),
+            Cell::new(&error.container_name),
+            Cell::new(&error.registry).style_spec("Fy"),
+            Cell::new(&error.image),
+            Cell::new(&error.reason).style_spec("Fr"),
+        ];
+        if matches!(output_format, OutputFormat::Wide) {
+            cells.push(Cell::new(&error.node_name));
+        }
+        cells.push(Cell::new(&error.message));
+        table.add_row(Row::new(cells));
+    }
+
+    table.printstd();
+    Ok(())
+}
+
+/// Display image pulls in a formatted table, slowest first
+///
+/// # Arguments
+///
+/// * `pulls` - List of image pulls to display
+/// * `output_format` - Format to use for displaying the pulls
+///
+/// # Returns
+///
+/// * `Result<()>` - Success or error
+pub fn display_image_pulls(
+    pulls: &[ImagePull],
+    output_format: &OutputFormat,
+) -> Result<(), TableDisplayError> {
+    if pulls.is_empty() {
+        warn!("No image pulls found");
+        return Ok(());
+    }
+
+    let mut table = create_table()?;
+
+    let mut header_cells = vec![Cell::new("IMAGE"), Cell::new("NODE"), Cell::new("DURATION")];
+    if matches!(output_format, OutputFormat::Wide) {
+        header_cells.extend_from_slice(&[
+            Cell::new("SIZE"),
+            Cell::new("REGISTRY"),
+            Cell::new("POD"),
+            Cell::new("NAMESPACE"),
+            Cell::new("CONTAINER"),
+        ]);
+    }
+    table.add_row(Row::new(header_cells));
+
+    for pull in pulls {
+        let duration = pull
+            .duration
+            .map(format_duration)
+            .unwrap_or_else(|| "cached".to_string());
+
+        let mut cells = vec![
+            Cell::new(&pull.image),
+            Cell::new(&pull.node_name),
+            Cell::new(&duration),
+        ];
+        if matches!(output_format, OutputFormat::Wide) {
+            cells.extend_from_slice(&[
+                Cell::new(&pull.image_size),
+                Cell::new(&pull.registry).style_spec("Fy"),
+                Cell::new(&pull.pod_name),
+                Cell::new(&pull.namespace),
+                Cell::new(&pull.container_name),
+            ]);
+        }
+        table.add_row(Row::new(cells));
+    }
+
+    table.printstd();
+    Ok(())
+}
+
+/// Display aggregated image pull statistics in a formatted table
+///
+/// # Arguments
+///
+/// * `summaries` - Pull statistics per image or node, slowest first
+/// * `group_by` - The key the statistics were grouped by
+///
+/// # Returns
+///
+/// * `Result<()>` - Success or error
+pub fn display_pull_summaries(
+    summaries: &[PullSummary],
+    group_by: &PullGroupBy,
+) -> Result<(), TableDisplayError> {
+    if summaries.is_empty() {
+        warn!("No image pulls found");
+        return Ok(());
+    }
+
+    let mut table = create_table()?;
+    let key_header = match group_by {
+        PullGroupBy::Image => "IMAGE",
+        PullGroupBy::Node => "NODE",
+    };
+    table.add_row(Row::new(vec![
+        Cell::new(key_header),
+        Cell::new("PULLS"),
+        Cell::new("CACHED"),
+        Cell::new("MAX"),
+        Cell::new("AVG"),
+        Cell::new("TOTAL"),
+    ]));
+
+    for summary in summaries {
+        table.add_row(Row::new(vec![
+            Cell::new(&summary.key),
+            Cell::new(&summary.pulls.to_string()),
+            Cell::new(&summary.cached.to_string()),
+            Cell::new(&format_duration(summary.max)),
+            Cell::new(&format_duration(summary.average)),
+            Cell::new(&format_duration(summary.total)),
+        ]));
+    }
+
+    table.printstd();
+    Ok(())
+}
+
+/// Display node runtime and platform information in a formatted table
+///
+/// # Arguments
+///
+/// * `nodes` - List of nodes to display
+/// * `output_format` - Format to use for displaying the nodes
+///
+/// # Returns
+///
+/// * `Result<()>` - Success or error
+pub fn display_nodes(
+    nodes: &[NodeInfo],
+    output_format: &OutputFormat,
+) -> Result<(), TableDisplayError> {
+    if nodes.is_empty() {
+        warn!("No nodes found");
+        return Ok(());
+    }
+
+    let mut table = create_table()?;
+
+    let mut header_cells = vec![
+        Cell::new("NODE"),
+        Cell::new("ARCH"),
+        Cell::new("OS-IMAGE"),
+        Cell::new("CONTAINER-RUNTIME"),
+        Cell::new("KUBELET"),
+    ];
+    if matches!(output_format, OutputFormat::Wide) {
+        header_cells.extend_from_slice(&[Cell::new("OS"), Cell::new("KERNEL")]);
+    }
+    table.add_row(Row::new(header_cells));
+
+    for node in nodes {
+        let mut cells = vec![
+            Cell::new(&node.name),
+            Cell::new(&node.architecture).style_spec("Fy"),
+            Cell::new(&node.os_image),
+            Cell::new(&node.container_runtime),
+            Cell::new(&node.kubelet_version),
+        ];
+        if matches!(output_format, OutputFormat::Wide) {
+            cells.extend_from_slice(&[
+                Cell::new(&node.operating_system),
+                Cell::new(&node.kernel_version),
+            ]);
+        }
+        table.add_row(Row::new(cells));
+    }
+
+    table.printstd();
+    Ok(())
+}
+
+/// Display containers running on a platform their image does not support
+///
+/// # Arguments
+///
+/// * `mismatches` - List of architecture mismatches to display
+/// * `output_format` - Format to use for displaying the mismatches
+///
+/// # Returns
+///
+/// * `Result<()>` - Success or error
+pub fn display_arch_mismatches(
+    mismatches: &[ArchMismatch],
+    output_format: &OutputFormat,
+) -> Result<(), TableDisplayError> {
+    if mismatches.is_empty() {
+        warn!("No architecture mismatches found");
+        return Ok(());
+    }
+
+    let mut table = create_table()?;
+
+    let mut header_cells = vec![
+        Cell::new("POD"),
+        Cell::new("NAMESPACE"),
+        Cell::new("CONTAINER"),
+        Cell::new("IMAGE"),
+        Cell::new("NODE-PLATFORM"),
+        Cell::new("IMAGE-PLATFORMS"),
+    ];
+    if matches!(output_format, OutputFormat::Wide) {
+        header_cells.push(Cell::new("NODE"));
+    }
+    table.add_row(Row::new(header_cells));
+
+    for mismatch in mismatches {
+        let mut cells = vec![
+            Cell::new(&mismatch.pod_name),
+            Cell::new(&mismatch.namespace),
+            Cell::new(&mismatch.container_name),
+            Cell::new(&mismatch.image),
+            Cell::new(&mismatch.node_platform).style_spec("Fr"),
+            Cell::new(&mismatch.image_platforms.join(",")),
+        ];
+        if matches!(output_format, OutputFormat::Wide) {
+            cells.push(Cell::new(&mismatch.node_name));
+        }
+        table.add_row(Row::new(cells));
+    }
+
+    table.printstd();
+    Ok(())
+}
+
+/// Display containers whose image differs from their template or from what they run
+///
+/// # Arguments
+///
+/// * `mismatches` - List of image mismatches to display
+/// * `output_format` - Format to use for displaying the mismatches
+///
+/// # Returns
+///
+/// * `Result<()>` - Success or error
+pub fn display_image_mismatches(
+    mismatches: &[ImageMismatch],
+    output_format: &OutputFormat,
+) -> Result<(), TableDisplayError> {
+    if mismatches.is_empty() {
+        warn!("No image mismatches found");
+        return Ok(());
+    }
+
+    let mut table = create_table()?;
+
+    let mut header_cells = vec![
+        Cell::new("POD"),
+        Cell::new("NAMESPACE"),
+        Cell::new("CONTAINER"),
+        Cell::new("MISMATCH"),
+        Cell::new("EXPECTED"),
+        Cell::new("ACTUAL"),
+    ];
+    if matches!(output_format, OutputFormat::Wide) {
+        header_cells.extend_from_slice(&[Cell::new("WORKLOAD"), Cell::new("NODE")]);
+    }
+    table.add_row(Row::new(header_cells));
+
+    for mismatch in mismatches {
+        let mut cells = vec![
+            Cell::new(&mismatch.pod_name),
+            Cell::new(&mismatch.namespace),
+            Cell::new(&mismatch.container_name),
+            Cell::new(&mismatch.kind.to_string()).style_spec("Fy"),
+            Cell::new(&mismatch.expected),
+            Cell::new(&mismatch.actual).style_spec("Fr"),
+        ];
+        if matches!(output_format, OutputFormat::Wide) {
+            cells.extend_from_slice(&[
+                Cell::new(&mismatch.workload),
+                Cell::new(&mismatch.node_name),
+            ]);
+        }
+        table.add_row(Row::new(cells));
+    }
+
+    table.printstd();
+    Ok(())
+}
+
+/// Display the image versions the pods of a workload run, with the rollout progress
+///
+/// Each container lists the desired image first, with the share of pods running it,
+/// then the old versions with the nodes still running them.
+///
+/// # Arguments
+///
+/// * `rollout` - The rollout progress of the workload
+/// * `output_format` - Format to use, wide also shows the nodes of the desired versions
+///
+/// # Returns
+///
+/// * `Result<()>` - Success or error
+pub fn display_rollout_images(
+    rollout: &RolloutImages,
+    output_format: &OutputFormat,
+) -> Result<(), TableDisplayError> {
+    if rollout.containers.is_empty() {
+        warn!("No containers found in the pod template");
+        return Ok(());
+    }
+
+    let mut table = create_table()?;
+    table.add_row(Row::new(vec![
+        Cell::new("CONTAINER"),
+        Cell::new("VERSION"),
+        Cell::new("IMAGE"),
+        Cell::new("DIGEST"),
+        Cell::new("PODS"),
+        Cell::new("UPDATED"),
+        Cell::new("NODES"),
+    ]));
+
+    for container in &rollout.containers {
+        let name = if container.init {
+            format!("{} (init)", container.container)
+        } else {
+            container.container.clone()
+        };
+        let updated = format!(
+            "{}/{} ({}%)",
+            container.updated(),
+            container.total(),
+            container.percent_complete()
+        );
+        let complete = container.updated() == container.total();
+
+        // The desired image is listed even before any pod runs it
+        let mut rows: Vec<(bool, &str, &str, usize, &[String])> = Vec::new();
+        if !container.versions.iter().any(|version| version.desired) {
+            rows.push((true, &container.desired, "", 0, &[]));
+        }
+        rows.extend(container.versions.iter().map(|version| {
+            (
+                version.desired,
+                version.image.as_str(),
+                version.digest.as_str(),
+                version.pods,
+                version.nodes.as_slice(),
+            )
+        }));
+
+        for (index, (desired, image, digest, pods, nodes)) in rows.into_iter().enumerate() {
+            let first = index == 0;
+            let show_nodes = !desired || matches!(output_format, OutputFormat::Wide);
+            table.add_row(Row::new(vec![
+                Cell::new(if first { &name } else { "" }),
+                if desired {
+                    Cell::new("desired").style_spec("Fg")
+                } else {
+                    Cell::new("old").style_spec("Fy")
+                },
+                Cell::new(image),
+                Cell::new(digest),
+                Cell::new(&pods.to_string()),
+                match (first, complete) {
+                    (false, _) => Cell::new(""),
+                    (true, true) => Cell::new(&updated).style_spec("Fg"),
+                    (true, false) => Cell::new(&updated).style_spec("Fy"),
+                },
+                Cell::new(&if show_nodes {
+                    nodes.join(",")
+                } else {
+                    String::new()
+                }),
+            ]));
+        }
+    }
+
+    table.printstd();
+    Ok(())
+}
+
+/// Display pod images grouped by their upstream origin
+///
+/// # Arguments
+///
+/// * `groups` - Upstream repositories with the registries they are pulled through
+/// * `output_format` - Format to use for displaying the groups
+///
+/// # Returns
+///
+/// * `Result<()>` - Success or error
+pub fn display_upstream_groups(
+    groups: &[UpstreamGroup],
+    output_format: &OutputFormat,
+) -> Result<(), TableDisplayError> {
+    if groups.is_empty() {
+        warn!("No images found matching criteria");
+        return Ok(());
+    }
+
+    let mut table = create_table()?;
+
+    let mut header_cells = vec![
+        Cell::new("UPSTREAM REGISTRY"),
+        Cell::new("IMAGE"),
+        Cell::new("PULLED VIA"),
+        Cell::new("CONTAINERS"),
+    ];
+    if matches!(output_format, OutputFormat::Wide) {
+        header_cells.push(Cell::new("VERSIONS"));
+    }
+    table.add_row(Row::new(header_cells));
+
+    for group in groups {
+        let mut cells = vec![
+            Cell::new(&group.upstream_registry).style_spec("Fy"),
+            Cell::new(&group.image_name),
+            Cell::new(&group.pulled_via.join(",")),
+            Cell::new(&group.containers.to_string()),
+        ];
+        if matches!(output_format, OutputFormat::Wide) {
+            cells.push(Cell::new(&group.versions.join(",")));
+        }
+        table.add_row(Row::new(cells));
+    }
+
+    table.printstd();
+    Ok(())
+}
+
+/// Display pod images grouped by the release that deployed them
+///
+/// # Arguments
+///
+/// * `groups` - Releases with the images they pull
+/// * `output_format` - Format to use for displaying the groups
+///
+/// # Returns
+///
+/// * `Result<()>` - Success or error
+pub fn display_release_groups(
+    groups: &[ReleaseGroup],
+    output_format: &OutputFormat,
+) -> Result<(), TableDisplayError> {
+    if groups.is_empty() {
+        warn!("No images found matching criteria");
+        return Ok(());
+    }
+
+    let mut table = create_table()?;
+
+    let mut header_cells = vec![
+        Cell::new("RELEASE"),
+        Cell::new("NAMESPACE"),
+        Cell::new("CHART"),
+        Cell::new("IMAGES"),
+        Cell::new("CONTAINERS"),
+    ];
+    if matches!(output_format, OutputFormat::Wide) {
+        header_cells.push(Cell::new("MANAGED-BY"));
+    }
+    table.add_row(Row::new(header_cells));
+
+    for group in groups {
+        let release = if group.release.is_empty() {
+            "<none>"
+        } else {
+            &group.release
+        };
+        let mut cells = vec![
+            Cell::new(release),
+            Cell::new(&group.namespace),
+            Cell::new(&group.charts.join(",")),
+            Cell::new(&group.images.join("\n")),
+            Cell::new(&group.containers.to_string()),
+        ];
+        if matches!(output_format, OutputFormat::Wide) {
+            cells.push(Cell::new(&group.managed_by.join(",")));
+        }
+        table.add_row(Row::new(cells));
+    }
+
+    table.printstd();
+    Ok(())
+}
+
+/// Display which commands will work for the current identity
+///
+/// The wide output adds a column per permission with the grants each command uses.
+///
+/// # Arguments
+///
+/// * `access` - Access to each command, with a grant for each of [`PERMISSIONS`]
+/// * `output_format` - Format to use for displaying the matrix
+///
+/// # Returns
+///
+/// * `Result<(), TableDisplayError>` - Success or error
+pub fn display_access_matrix(
+    access: &[CommandAccess],
+    output_format: &OutputFormat,
+) -> Result<(), TableDisplayError> {
+    if access.is_empty() {
+        warn!("No commands to check");
+        return Ok(());
+    }
+
+    let mut table = create_table()?;
+
+    let wide = matches!(output_format, OutputFormat::Wide);
+    let mut header_cells = vec![Cell::new("COMMAND")];
+    if wide {
+        header_cells.extend(PERMISSIONS.iter().map(|permission| {
+            Cell::new(&format!("{}-{}", permission.verb, permission.resource).to_uppercase())
+        }));
+    }
+    header_cells.push(Cell::new("STATUS"));
+    header_cells.push(Cell::new("MISSING"));
+    table.add_row(Row::new(header_cells));
+
+    for command in access {
+        let mut cells = vec![Cell::new(command.command)];
+        if wide {
+            cells.extend(command.permissions.iter().map(|allowed| match allowed {
+                Some(true) => Cell::new("yes"),
+                Some(false) => Cell::new("no").style_spec("Fr"),
+                None => Cell::new("-"),
+            }));
+        }
+        let status = command.status.to_string();
+        cells.push(match command.status {
+            AccessStatus::Ok => Cell::new(&status),
+            AccessStatus::Degraded => Cell::new(&status).style_spec("Fy"),
+            AccessStatus::Denied => Cell::new(&status).style_spec("Fr"),
+        });
+        cells.push(Cell::new(&command.missing.join("\n")));
+        table.add_row(Row::new(cells));
     }
 
     table.printstd();
@@ -0,0 +1,988 @@
+//! Go `text/template` style templates, e.g. `{{range .items}}{{.image}}{{"\n"}}{{end}}`
+//!
+//! Covers what kubectl users write in practice: field chains, variables,
+//! `if`/`else`/`range`/`with`, pipelines, and the `and`, `or`, `not`, `eq`,
+//! `ne`, `lt`, `le`, `gt`, `ge`, `len`, `index`, `print`, `printf` and
+//! `println` functions. Missing fields print `<no value>`, as in Go, and
+//! `range` also counts up to an integer. Printf widths and precisions are
+//! capped at 1024, where Go would pad without limit.
+
+use super::TemplateError;
+use serde_json::{Number, Value};
+use std::cmp::Ordering;
+
+/// A parsed Go template
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoTemplate {
+    nodes: Vec<Node>,
+}
+
+/// A piece of a template
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    /// Text printed as is
+    Text(String),
+    /// A pipeline whose result is printed, unless it declares a variable
+    Action(Pipeline),
+    /// `{{if}}`, with the nodes of the true and false branches
+    If(Pipeline, Vec<Node>, Vec<Node>),
+    /// `{{range}}`, with the nodes of the body and of the empty branch
+    Range(Pipeline, Vec<Node>, Vec<Node>),
+    /// `{{with}}`, with the nodes of the body and of the empty branch
+    With(Pipeline, Vec<Node>, Vec<Node>),
+}
+
+/// Commands chained with `|`, optionally assigned to variables
+#[derive(Debug, Clone, PartialEq)]
+struct Pipeline {
+    /// Variables declared (`:=`) or assigned (`=`) from the result
+    variables: Vec<String>,
+    /// Whether the variables are declared rather than assigned
+    declare: bool,
+    commands: Vec<Vec<Arg>>,
+}
+
+/// An argument of a command
+#[derive(Debug, Clone, PartialEq)]
+enum Arg {
+    /// A field chain on dot (`.`, `.image`, `.items`)
+    Field(Vec<String>),
+    /// A variable with an optional field chain (`$`, `$img.pod`)
+    Variable(String, Vec<String>),
+    /// A string, number, boolean or nil literal
+    Literal(Value),
+    /// A function name
+    Function(String),
+    /// A parenthesized pipeline with an optional field chain
+    Nested(Box<Pipeline>, Vec<String>),
+}
+
+/// How a list of nodes ended
+#[derive(Debug)]
+enum Terminator {
+    End,
+    Else,
+    ElseIf(Pipeline),
+}
+
+/// A token of an action
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Field(Vec<String>),
+    Variable(String, Vec<String>),
+    Literal(Value),
+    Ident(String),
+    Pipe,
+    Open,
+    Close(Vec<String>),
+    Declare,
+    Assign,
+    Comma,
+}
+
+/// A piece of template source
+enum Item {
+    Text(String),
+    Action(String),
+}
+
+impl GoTemplate {
+    /// Parse a Go template
+    ///
+    /// # Arguments
+    ///
+    /// * `template` - The template, text with `{{...}}` actions
+    ///
+    /// # Returns
+    ///
+    /// * `Result<GoTemplate, TemplateError>` - The parsed template or a syntax error
+    pub fn parse(template: &str) -> Result<GoTemplate, TemplateError> {
+        let mut parser = Parser {
+            items: split_actions(template)?.into_iter(),
+        };
+        match parser.parse_nodes()? {
+            (nodes, None) => Ok(GoTemplate { nodes }),
+            (_, Some(Terminator::End)) => Err(parse_error("unexpected {{end}}")),
+            (_, Some(_)) => Err(parse_error("unexpected {{else}}")),
+        }
+    }
+
+    /// Render the template for a value
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The value to render, also available as `$`
+    ///
+    /// # Returns
+    ///
+    /// * `Result<String, TemplateError>` - The rendered text or an error from a function call
+    pub fn execute(&self, data: &Value) -> Result<String, TemplateError> {
+        let mut state = State {
+            variables: vec![("$".to_string(), data.clone())],
+            out: String::new(),
+        };
+        state.render(&self.nodes, data)?;
+        Ok(state.out)
+    }
+}
+
+/// Split a template into text and actions, applying `{{-` and `-}}` trim markers
+fn split_actions(template: &str) -> Result<Vec<Item>, TemplateError> {
+    let mut items = Vec::new();
+    let mut rest = template;
+    let mut trim_next = false;
+
+    while let Some(start) = rest.find("{{") {
+        let mut text = &rest[..start];
+        if trim_next {
+            text = text.trim_start();
+        }
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| parse_error("unclosed action"))?;
+        let mut action = &after[..end];
+
+        if let Some(trimmed) = action.strip_prefix("- ") {
+            text = text.trim_end();
+            action = trimmed;
+        }
+        trim_next = false;
+        if let Some(trimmed) = action.strip_suffix(" -") {
+            trim_next = true;
+            action = trimmed;
+        }
+
+        if !text.is_empty() {
+            items.push(Item::Text(text.to_string()));
+        }
+        let action = action.trim();
+        // Comments print nothing
+        if !(action.starts_with("/*") && action.ends_with("*/")) {
+            items.push(Item::Action(action.to_string()));
+        }
+        rest = &after[end + 2..];
+    }
+
+    let text = if trim_next { rest.trim_start() } else { rest };
+    if !text.is_empty() {
+        items.push(Item::Text(text.to_string()));
+    }
+    Ok(items)
+}
+
+/// Builds nodes from the text and actions of a template
+struct Parser {
+    items: std::vec::IntoIter<Item>,
+}
+
+impl Parser {
+    /// Parse nodes up to an `{{end}}`, `{{else}}` or the end of the template
+    fn parse_nodes(&mut self) -> Result<(Vec<Node>, Option<Terminator>), TemplateError> {
+        let mut nodes = Vec::new();
+
+        while let Some(item) = self.items.next() {
+            let action = match item {
+                Item::Text(text) => {
+                    nodes.push(Node::Text(text));
+                    continue;
+                }
+                Item::Action(action) => action,
+            };
+
+            let tokens = tokenize(&action)?;
+            let keyword = match tokens.first() {
+                Some(Token::Ident(word)) => word.as_str(),
+                _ => "",
+            };
+            let node = match keyword {
+                "end" if tokens.len() == 1 => return Ok((nodes, Some(Terminator::End))),
+                "else" if tokens.len() == 1 => return Ok((nodes, Some(Terminator::Else))),
+                "else" if tokens.get(1) == Some(&Token::Ident("if".to_string())) => {
+                    let pipeline = parse_pipeline(&tokens[2..])?;
+                    return Ok((nodes, Some(Terminator::ElseIf(pipeline))));
+                }
+                "if" | "range" | "with" => {
+                    let pipeline = parse_pipeline(&tokens[1..])?;
+                    let (body, otherwise) = self.parse_branches(keyword)?;
+                    match keyword {
+                        "if" => Node::If(pipeline, body, otherwise),
+                        "range" => Node::Range(pipeline, body, otherwise),
+                        _ => Node::With(pipeline, body, otherwise),
+                    }
+                }
+                "define" | "template" | "block" | "break" | "continue" => {
+                    return Err(parse_error(format!("{{{{{}}}}} is not supported", keyword)));
+                }
+                _ => Node::Action(parse_pipeline(&tokens)?),
+            };
+            nodes.push(node);
+        }
+
+        Ok((nodes, None))
+    }
+
+    /// Parse the body of a control structure and its optional else branch
+    fn parse_branches(&mut self, keyword: &str) -> Result<(Vec<Node>, Vec<Node>), TemplateError> {
+        let (body, terminator) = self.parse_nodes()?;
+        match terminator {
+            Some(Terminator::End) => Ok((body, Vec::new())),
+            Some(Terminator::Else) => match self.parse_nodes()? {
+                (otherwise, Some(Terminator::End)) => Ok((body, otherwise)),
+                _ => Err(parse_error(format!("missing {{{{end}}}} for {}", keyword))),
+            },
+            Some(Terminator::ElseIf(pipeline)) => {
+                let (then, otherwise) = self.parse_branches(keyword)?;
+                Ok((body, vec![Node::If(pipeline, then, otherwise)]))
+            }
+            None => Err(parse_error(format!("missing {{{{end}}}} for {}", keyword))),
+        }
+    }
+}
+
+/// Split the content of an action into tokens
+fn tokenize(action: &str) -> Result<Vec<Token>, TemplateError> {
+    let chars: Vec<char> = action.chars().collect();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < chars.len() {
+        let c = chars[pos];
+        match c {
+            c if c.is_whitespace() => pos += 1,
+            '|' => {
+                tokens.push(Token::Pipe);
+                pos += 1;
+            }
+            '(' => {
+                tokens.push(Token::Open);
+                pos += 1;
+            }
+            ')' => {
+                pos += 1;
+                tokens.push(Token::Close(read_fields(&chars, &mut pos)?));
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                pos += 1;
+            }
+            ':' if chars.get(pos + 1) == Some(&'=') => {
+                tokens.push(Token::Declare);
+                pos += 2;
+            }
+            '=' => {
+                tokens.push(Token::Assign);
+                pos += 1;
+            }
+            '.' => tokens.push(Token::Field(read_fields(&chars, &mut pos)?)),
+            '$' => {
+                pos += 1;
+                let name = format!("${}", read_identifier(&chars, &mut pos));
+                tokens.push(Token::Variable(name, read_fields(&chars, &mut pos)?));
+            }
+            '"' | '`' => {
+                let (literal, end) = read_string(&chars, pos)?;
+                tokens.push(Token::Literal(Value::String(literal)));
+                pos = end;
+            }
+            c if c.is_ascii_digit()
+                || (c == '-' && chars.get(pos + 1).is_some_and(|d| d.is_ascii_digit())) =>
+            {
+                let start = pos;
+                pos += 1;
+                while pos < chars.len() && (chars[pos].is_ascii_alphanumeric() || chars[pos] == '.')
+                {
+                    pos += 1;
+                }
+                let number: String = chars[start..pos].iter().collect();
+                let value = serde_json::from_str::<Number>(&number)
+                    .map_err(|_| parse_error(format!("invalid number {}", number)))?;
+                tokens.push(Token::Literal(Value::Number(value)));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let word = read_identifier(&chars, &mut pos);
+                tokens.push(match word.as_str() {
+                    "true" => Token::Literal(Value::Bool(true)),
+                    "false" => Token::Literal(Value::Bool(false)),
+                    "nil" => Token::Literal(Value::Null),
+                    _ => Token::Ident(word),
+                });
+            }
+            c => {
+                return Err(parse_error(format!(
+                    "unexpected '{}' in {{{{{}}}}}",
+                    c, action
+                )));
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Read an identifier made of letters, digits and `_`
+fn read_identifier(chars: &[char], pos: &mut usize) -> String {
+    let start = *pos;
+    while *pos < chars.len() && (chars[*pos].is_alphanumeric() || chars[*pos] == '_') {
+        *pos += 1;
+    }
+    chars[start..*pos].iter().collect()
+}
+
+/// Read a field chain such as `.items` or `.a.b`; a lone `.` is an empty chain
+fn read_fields(chars: &[char], pos: &mut usize) -> Result<Vec<String>, TemplateError> {
+    let mut fields = Vec::new();
+    while chars.get(*pos) == Some(&'.') {
+        *pos += 1;
+        let name = read_identifier(chars, pos);
+        if name.is_empty() {
+            if fields.is_empty() && chars.get(*pos).is_none_or(|c| !c.is_alphanumeric()) {
+                break;
+            }
+            return Err(parse_error("empty field name"));
+        }
+        fields.push(name);
+    }
+    Ok(fields)
+}
+
+/// Read a double-quoted (with escapes) or backquoted (raw) string starting at `start`
+fn read_string(chars: &[char], start: usize) -> Result<(String, usize), TemplateError> {
+    let quote = chars[start];
+    let mut out = String::new();
+    let mut pos = start + 1;
+
+    while let Some(&c) = chars.get(pos) {
+        pos += 1;
+        if c == quote {
+            return Ok((out, pos));
+        }
+        if c == '\\' && quote == '"' {
+            let escaped = chars
+                .get(pos)
+                .ok_or_else(|| parse_error("unterminated string"))?;
+            pos += 1;
+            out.push(match escaped {
+                'n' => '\n',
+                't' => '\t',
+                'r' => '\r',
+                '0' => '\0',
+                other => *other,
+            });
+        } else {
+            out.push(c);
+        }
+    }
+    Err(parse_error("unterminated string"))
+}
+
+/// Parse tokens into a pipeline, with an optional variable declaration
+fn parse_pipeline(tokens: &[Token]) -> Result<Pipeline, TemplateError> {
+    let mut variables = Vec::new();
+    let mut declare = true;
+    let mut rest = tokens;
+
+    // $x := ..., $i, $v := ... or $x = ...
+    let assignment = tokens
+        .iter()
+        .position(|token| matches!(token, Token::Declare | Token::Assign));
+    if let Some(index) = assignment {
+        for token in &tokens[..index] {
+            match token {
+                Token::Variable(name, fields) if fields.is_empty() => variables.push(name.clone()),
+                Token::Comma => {}
+                _ => return Err(parse_error("invalid variable declaration")),
+            }
+        }
+        declare = tokens[index] == Token::Declare;
+        rest = &tokens[index + 1..];
+    }
+    if variables.len() > 2 {
+        return Err(parse_error("too many variables in declaration"));
+    }
+
+    let mut commands = Vec::new();
+    let mut command = Vec::new();
+    let mut pos = 0;
+    while pos < rest.len() {
+        let arg = match &rest[pos] {
+            Token::Pipe => {
+                if command.is_empty() {
+                    return Err(parse_error("missing command before |"));
+                }
+                commands.push(std::mem::take(&mut command));
+                pos += 1;
+                continue;
+            }
+            Token::Open => {
+                let close = find_close(rest, pos)?;
+                let Token::Close(fields) = &rest[close] else {
+                    unreachable!("find_close returns the position of a closing parenthesis");
+                };
+                let nested = parse_pipeline(&rest[pos + 1..close])?;
+                pos = close;
+                Arg::Nested(Box::new(nested), fields.clone())
+            }
+            Token::Field(fields) => Arg::Field(fields.clone()),
+            Token::Variable(name, fields) => Arg::Variable(name.clone(), fields.clone()),
+            Token::Literal(value) => Arg::Literal(value.clone()),
+            Token::Ident(name) if is_function(name) => Arg::Function(name.clone()),
+            Token::Ident(name) => {
+                return Err(parse_error(format!("function \"{}\" not defined", name)));
+            }
+            token => return Err(parse_error(format!("unexpected {:?}", token))),
+        };
+        command.push(arg);
+        pos += 1;
+    }
+    if command.is_empty() {
+        return Err(parse_error("missing value for command"));
+    }
+    commands.push(command);
+
+    Ok(Pipeline {
+        variables,
+        declare,
+        commands,
+    })
+}
+
+/// Find the closing parenthesis matching the one at `open`
+fn find_close(tokens: &[Token], open: usize) -> Result<usize, TemplateError> {
+    let mut depth = 0;
+    for (pos, token) in tokens.iter().enumerate().skip(open) {
+        match token {
+            Token::Open => depth += 1,
+            Token::Close(_) => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(pos);
+                }
+            }
+            _ => {}
+        }
+    }
+    Err(parse_error("unclosed ("))
+}
+
+/// Check whether a name is a supported function
+fn is_function(name: &str) -> bool {
+    matches!(
+        name,
+        "and"
+            | "or"
+            | "not"
+            | "eq"
+            | "ne"
+            | "lt"
+            | "le"
+            | "gt"
+            | "ge"
+            | "len"
+            | "index"
+            | "print"
+            | "printf"
+            | "println"
+    )
+}
+
+/// Variables in scope and rendered output
+struct State {
+    variables: Vec<(String, Value)>,
+    out: String,
+}
+
+impl State {
+    /// Render nodes with a dot value
+    fn render(&mut self, nodes: &[Node], dot: &Value) -> Result<(), TemplateError> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => self.out.push_str(text),
+                Node::Action(pipeline) => {
+                    let value = self.evaluate(pipeline, dot)?;
+                    if pipeline.variables.is_empty() {
+                        self.out.push_str(&format_value(&value));
+                    }
+                }
+                Node::If(pipeline, then, otherwise) => {
+                    let scope = self.variables.len();
+                    let branch = if is_true(&self.evaluate(pipeline, dot)?) {
+                        then
+                    } else {
+                        otherwise
+                    };
+                    self.render(branch, dot)?;
+                    self.variables.truncate(scope);
+                }
+                Node::With(pipeline, body, otherwise) => {
+                    let scope = self.variables.len();
+                    let value = self.evaluate(pipeline, dot)?;
+                    if is_true(&value) {
+                        self.render(body, &value)?;
+                    } else {
+                        self.render(otherwise, dot)?;
+                    }
+                    self.variables.truncate(scope);
+                }
+                Node::Range(pipeline, body, otherwise) => {
+                    let scope = self.variables.len();
+                    let collection = self.evaluate_commands(&pipeline.commands, dot)?;
+                    let entries: Vec<(Value, Value)> = match collection {
+                        Value::Array(items) => items
+                            .into_iter()
+                            .enumerate()
+                            .map(|(index, item)| (Value::from(index), item))
+                            .collect(),
+                        // Go ranges over maps in key order
+                        Value::Object(map) => {
+                            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+                            entries.sort_by(|a, b| a.0.cmp(&b.0));
+                            entries
+                                .into_iter()
+                                .map(|(key, value)| (Value::String(key), value))
+                                .collect()
+                        }
+                        // Go 1.22 ranges over an integer from 0 to n - 1
+                        Value::Number(n) if n.is_u64() => (0..n.as_u64().unwrap_or_default())
+                            .map(|index| (Value::from(index), Value::from(index)))
+                            .collect(),
+                        Value::Number(n) if n.is_i64() => Vec::new(),
+                        Value::Null => Vec::new(),
+                        other => {
+                            return Err(exec_error(format!(
+                                "range can't iterate over {}",
+                                format_value(&other)
+                            )));
+                        }
+                    };
+
+                    if entries.is_empty() {
+                        self.render(otherwise, dot)?;
+                    }
+                    for (key, item) in entries {
+                        match pipeline.variables.as_slice() {
+                            [value] => self.variables.push((value.clone(), item.clone())),
+                            [index, value] => {
+                                self.variables.push((index.clone(), key));
+                                self.variables.push((value.clone(), item.clone()));
+                            }
+                            _ => {}
+                        }
+                        self.render(body, &item)?;
+                        self.variables.truncate(scope);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluate a pipeline and bind its variables
+    fn evaluate(&mut self, pipeline: &Pipeline, dot: &Value) -> Result<Value, TemplateError> {
+        let value = self.evaluate_commands(&pipeline.commands, dot)?;
+        for name in &pipeline.variables {
+            if pipeline.declare {
+                self.variables.push((name.clone(), value.clone()));
+            } else {
+                let variable = self
+                    .variables
+                    .iter_mut()
+                    .rev()
+                    .find(|(existing, _)| existing == name)
+                    .ok_or_else(|| exec_error(format!("undefined variable: {}", name)))?;
+                variable.1 = value.clone();
+            }
+        }
+        Ok(value)
+    }
+
+    /// Evaluate commands, passing each result as last argument of the next
+    fn evaluate_commands(
+        &self,
+        commands: &[Vec<Arg>],
+        dot: &Value,
+    ) -> Result<Value, TemplateError> {
+        let mut piped: Option<Value> = None;
+        for command in commands {
+            piped = Some(self.evaluate_command(command, dot, piped)?);
+        }
+        Ok(piped.unwrap_or(Value::Null))
+    }
+
+    /// Evaluate a single command: a function call or a value
+    fn evaluate_command(
+        &self,
+        command: &[Arg],
+        dot: &Value,
+        piped: Option<Value>,
+    ) -> Result<Value, TemplateError> {
+        if let [Arg::Function(name), args @ ..] = command {
+            let mut values = args
+                .iter()
+                .map(|arg| self.evaluate_arg(arg, dot))
+                .collect::<Result<Vec<_>, _>>()?;
+            values.extend(piped);
+            return call(name, values);
+        }
+
+        match (command, piped) {
+            ([arg], None) => self.evaluate_arg(arg, dot),
+            _ => Err(exec_error("can't give argument to non-function")),
+        }
+    }
+
+    /// Evaluate a command argument
+    fn evaluate_arg(&self, arg: &Arg, dot: &Value) -> Result<Value, TemplateError> {
+        match arg {
+            Arg::Field(fields) => Ok(lookup(dot, fields)),
+            Arg::Variable(name, fields) => {
+                let (_, value) = self
+                    .variables
+                    .iter()
+                    .rev()
+                    .find(|(existing, _)| existing == name)
+                    .ok_or_else(|| exec_error(format!("undefined variable: {}", name)))?;
+                Ok(lookup(value, fields))
+            }
+            Arg::Literal(value) => Ok(value.clone()),
+            Arg::Function(name) => call(name, Vec::new()),
+            Arg::Nested(pipeline, fields) => {
+                let value = self.evaluate_commands(&pipeline.commands, dot)?;
+                Ok(lookup(&value, fields))
+            }
+        }
+    }
+}
+
+/// Follow a field chain, missing fields giving nil
+fn lookup(value: &Value, fields: &[String]) -> Value {
+    fields
+        .iter()
+        .try_fold(value, |current, field| current.get(field))
+        .cloned()
+        .unwrap_or(Value::Null)
+}
+
+/// Check whether a value is true in a condition: not false, 0, nil or empty
+fn is_true(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+    }
+}
+
+/// Call a builtin function
+fn call(name: &str, args: Vec<Value>) -> Result<Value, TemplateError> {
+    let arity = |min: usize| {
+        if args.len() < min {
+            Err(exec_error(format!(
+                "wrong number of args for {}: want at least {} got {}",
+                name,
+                min,
+                args.len()
+            )))
+        } else {
+            Ok(())
+        }
+    };
+
+    match name {
+        "and" => {
+            arity(1)?;
+            Ok(args
+                .iter()
+                .find(|arg| !is_true(arg))
+                .unwrap_or(&args[args.len() - 1])
+                .clone())
+        }
+        "or" => {
+            arity(1)?;
+            Ok(args
+                .iter()
+                .find(|arg| is_true(arg))
+                .unwrap_or(&args[args.len() - 1])
+                .clone())
+        }
+        "not" => {
+            arity(1)?;
+            Ok(Value::Bool(!is_true(&args[0])))
+        }
+        "eq" => {
+            arity(2)?;
+            let mut equal = false;
+            for arg in &args[1..] {
+                equal |= compare(&args[0], arg)? == Some(Ordering::Equal);
+            }
+            Ok(Value::Bool(equal))
+        }
+        "ne" | "lt" | "le" | "gt" | "ge" => {
+            arity(2)?;
+            let ordering = compare(&args[0], &args[1])?;
+            if ordering.is_none() && name != "ne" {
+                return Err(exec_error(format!(
+                    "invalid type for comparison: {} and {}",
+                    format_value(&args[0]),
+                    format_value(&args[1])
+                )));
+            }
+            Ok(Value::Bool(match name {
+                "ne" => ordering != Some(Ordering::Equal),
+                "lt" => ordering == Some(Ordering::Less),
+                "le" => ordering != Some(Ordering::Greater),
+                "gt" => ordering == Some(Ordering::Greater),
+                _ => ordering != Some(Ordering::Less),
+            }))
+        }
+        "len" => {
+            arity(1)?;
+            match &args[0] {
+                Value::String(s) => Ok(Value::from(s.len())),
+                Value::Array(items) => Ok(Value::from(items.len())),
+                Value::Object(map) => Ok(Value::from(map.len())),
+                other => Err(exec_error(format!("len of {}", format_value(other)))),
+            }
+        }
+        "index" => {
+            arity(1)?;
+            args[1..]
+                .iter()
+                .try_fold(args[0].clone(), |current, key| match (&current, key) {
+                    (Value::Object(map), Value::String(key)) => {
+                        Ok(map.get(key).cloned().unwrap_or(Value::Null))
+                    }
+                    (Value::Array(items), Value::Number(index)) => index
+                        .as_u64()
+                        .and_then(|i| items.get(i as usize))
+                        .cloned()
+                        .ok_or_else(|| exec_error(format!("index out of range: {}", index))),
+                    (Value::Null, _) => Ok(Value::Null),
+                    _ => Err(exec_error(format!(
+                        "can't index {} with {}",
+                        format_value(&current),
+                        format_value(key)
+                    ))),
+                })
+        }
+        "print" => Ok(Value::String(sprint(&args))),
+        "println" => {
+            let line: Vec<String> = args.iter().map(format_value).collect();
+            Ok(Value::String(format!("{}\n", line.join(" "))))
+        }
+        "printf" => {
+            arity(1)?;
+            let Value::String(format) = &args[0] else {
+                return Err(exec_error("printf format must be a string"));
+            };
+            sprintf(format, &args[1..]).map(Value::String)
+        }
+        _ => Err(exec_error(format!("function \"{}\" not defined", name))),
+    }
+}
+
+/// Compare two values of the same kind
+///
+/// Values of different kinds are an error, as in Go, except nil which only
+/// equals nil; maps and lists can only be tested for equality.
+fn compare(a: &Value, b: &Value) -> Result<Option<Ordering>, TemplateError> {
+    Ok(match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a
+            .as_f64()
+            .zip(b.as_f64())
+            .and_then(|(a, b)| a.partial_cmp(&b)),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        (Value::Null, Value::Null) => Some(Ordering::Equal),
+        (Value::Null, _) | (_, Value::Null) => None,
+        (Value::Array(_), Value::Array(_)) | (Value::Object(_), Value::Object(_)) if a == b => {
+            Some(Ordering::Equal)
+        }
+        (Value::Array(_), Value::Array(_)) | (Value::Object(_), Value::Object(_)) => None,
+        _ => {
+            return Err(exec_error(format!(
+                "incompatible types for comparison: {} and {}",
+                go_type(a),
+                go_type(b)
+            )));
+        }
+    })
+}
+
+/// Concatenate values like Go's fmt.Sprint: spaces only between two non-strings
+fn sprint(args: &[Value]) -> String {
+    let mut out = String::new();
+    for (index, arg) in args.iter().enumerate() {
+        if index > 0 && !arg.is_string() && !args[index - 1].is_string() {
+            out.push(' ');
+        }
+        out.push_str(&format_value(arg));
+    }
+    out
+}
+
+/// Largest width or precision printf accepts, to bound the output of a verb
+const MAX_FORMAT_WIDTH: usize = 1024;
+
+/// Format values like Go's fmt.Sprintf, for the %s, %v, %d, %f, %q, %t and %% verbs
+///
+/// Arguments of the wrong type print as `%!verb(type=value)`, as in Go.
+fn sprintf(format: &str, args: &[Value]) -> Result<String, TemplateError> {
+    let mut out = String::new();
+    let mut args = args.iter();
+    let mut chars = format.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+
+        let mut left = false;
+        let mut zero = false;
+        let mut plus = false;
+        while let Some(&flag) = chars.peek() {
+            match flag {
+                '-' => left = true,
+                '0' => zero = true,
+                '+' => plus = true,
+                ' ' | '#' => {}
+                _ => break,
+            }
+            chars.next();
+        }
+        let width = read_format_number(&mut chars)?.unwrap_or(0);
+        let mut precision = None;
+        if chars.peek() == Some(&'.') {
+            chars.next();
+            precision = Some(read_format_number(&mut chars)?.unwrap_or(0));
+        }
+
+        let Some(verb) = chars.next() else {
+            out.push_str("%!(NOVERB)");
+            break;
+        };
+        if verb == '%' {
+            out.push('%');
+            continue;
+        }
+        let Some(arg) = args.next() else {
+            out.push_str(&format!("%!{}(MISSING)", verb));
+            continue;
+        };
+
+        let mut text = match (verb, arg) {
+            ('d', Value::Number(n)) if n.is_i64() || n.is_u64() => n.to_string(),
+            ('f', Value::Number(n)) => {
+                format!(
+                    "{:.*}",
+                    precision.unwrap_or(6),
+                    n.as_f64().unwrap_or_default()
+                )
+            }
+            ('s' | 'v', Value::String(s)) => match precision {
+                Some(max) => s.chars().take(max).collect(),
+                None => s.clone(),
+            },
+            ('q', Value::String(s)) => serde_json::to_string(s).unwrap_or_default(),
+            ('t', Value::Bool(b)) => b.to_string(),
+            ('v', Value::Null) => "<nil>".to_string(),
+            ('v', _) => format_value(arg),
+            ('s', Value::Array(_) | Value::Object(_)) => format_value(arg),
+            (_, Value::Null) => format!("%!{}(<nil>)", verb),
+            _ => format!("%!{}({}={})", verb, go_type(arg), format_value(arg)),
+        };
+        if plus && arg.is_number() && !text.starts_with(['-', '%']) {
+            text.insert(0, '+');
+        }
+
+        let padding = width.saturating_sub(text.chars().count());
+        if left {
+            out.push_str(&text);
+            out.push_str(&" ".repeat(padding));
+        } else if zero && arg.is_number() {
+            // Go puts the sign before the zeros: %05d of -3 is -0003
+            let digits = match text.strip_prefix(['-', '+']) {
+                Some(digits) => {
+                    out.push_str(&text[..1]);
+                    digits
+                }
+                None => &text,
+            };
+            out.push_str(&"0".repeat(padding));
+            out.push_str(digits);
+        } else {
+            out.push_str(&" ".repeat(padding));
+            out.push_str(&text);
+        }
+    }
+
+    Ok(out)
+}
+
+/// Read the width or precision of a printf verb, rejecting values above MAX_FORMAT_WIDTH
+fn read_format_number(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+) -> Result<Option<usize>, TemplateError> {
+    let mut digits = String::new();
+    while let Some(&digit) = chars.peek().filter(|c| c.is_ascii_digit()) {
+        digits.push(digit);
+        chars.next();
+    }
+    if digits.is_empty() {
+        return Ok(None);
+    }
+    match digits.parse::<usize>() {
+        Ok(number) if number <= MAX_FORMAT_WIDTH => Ok(Some(number)),
+        _ => Err(exec_error(format!(
+            "printf width or precision {} is larger than {}",
+            digits, MAX_FORMAT_WIDTH
+        ))),
+    }
+}
+
+/// Name the Go type kubectl decodes a JSON value to, for printf errors
+fn go_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "<nil>",
+        Value::Bool(_) => "bool",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "int64",
+        Value::Number(_) => "float64",
+        Value::String(_) => "string",
+        Value::Array(_) => "[]interface {}",
+        Value::Object(_) => "map[string]interface {}",
+    }
+}
+
+/// Print a value the way Go prints untyped JSON data
+fn format_value(value: &Value) -> String {
+    match value {
+        Value::Null => "<no value>".to_string(),
+        Value::String(s) => s.clone(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(format_value).collect();
+            format!("[{}]", items.join(" "))
+        }
+        Value::Object(map) => {
+            let entries: Vec<String> = map
+                .iter()
+                .map(|(key, value)| format!("{}:{}", key, format_value(value)))
+                .collect();
+            format!("map[{}]", entries.join(" "))
+        }
+    }
+}
+
+/// Build a template syntax error
+fn parse_error(message: impl Into<String>) -> TemplateError {
+    TemplateError::Parse(format!("go-template: {}", message.into()))
+}
+
+/// Build a template execution error
+fn exec_error(message: impl Into<String>) -> TemplateError {
+    TemplateError::Execute(format!("go-template: {}", message.into()))
+}
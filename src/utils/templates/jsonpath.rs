@@ -0,0 +1,616 @@
+//! kubectl flavoured JSONPath templates, e.g. `{range .items[*]}{.image}{"\n"}{end}`
+
+use super::TemplateError;
+use serde_json::Value;
+use std::cmp::Ordering;
+
+/// A parsed JSONPath template
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonPath {
+    nodes: Vec<Node>,
+}
+
+/// A piece of a template
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    /// Text printed as is, from outside braces or a string literal
+    Text(String),
+    /// A path whose results are printed, separated by spaces
+    Path(Path),
+    /// A loop over the results of a path, up to its `{end}`
+    Range(Path, Vec<Node>),
+}
+
+/// A path selecting values, from the root (`$`) or the current value (`@` or `.`)
+#[derive(Debug, Clone, PartialEq)]
+struct Path {
+    root: bool,
+    segments: Vec<Segment>,
+}
+
+/// A step of a path
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    /// A field of an object (`.name`)
+    Field(String),
+    /// Every element of an array or value of an object (`.*`, `[*]`)
+    Wildcard,
+    /// A field at any depth (`..name`), or every nested value (`..*`)
+    Descendants(Option<String>),
+    /// An element of an array, negative from the end (`[0]`, `[-1]`)
+    Index(i64),
+    /// A range of elements of an array (`[1:3]`, `[::2]`)
+    Slice(Option<i64>, Option<i64>, Option<i64>),
+    /// Several fields or elements (`['pod','image']`, `[0,2]`)
+    Union(Vec<Key>),
+    /// The elements of an array matching a condition (`[?(@.registry=="docker.io")]`)
+    Filter(Box<Filter>),
+}
+
+/// A field name or array index in a union
+#[derive(Debug, Clone, PartialEq)]
+enum Key {
+    Name(String),
+    Index(i64),
+}
+
+/// A filter condition, an existence check without a comparison
+#[derive(Debug, Clone, PartialEq)]
+struct Filter {
+    left: Operand,
+    comparison: Option<(Comparison, Operand)>,
+}
+
+/// A side of a filter comparison
+#[derive(Debug, Clone, PartialEq)]
+enum Operand {
+    Path(Path),
+    Literal(Value),
+}
+
+/// A filter comparison operator
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl JsonPath {
+    /// Parse a JSONPath template
+    ///
+    /// # Arguments
+    ///
+    /// * `template` - The template, text with `{...}` expressions
+    ///
+    /// # Returns
+    ///
+    /// * `Result<JsonPath, TemplateError>` - The parsed template or a syntax error
+    pub fn parse(template: &str) -> Result<JsonPath, TemplateError> {
+        let mut stack: Vec<(Path, Vec<Node>)> = Vec::new();
+        let mut nodes = Vec::new();
+
+        for token in tokenize(template)? {
+            let node = match token {
+                Token::Text(text) => Node::Text(text),
+                Token::Action(action) if action == "end" => {
+                    let Some((path, parent)) = stack.pop() else {
+                        return Err(parse_error("{end} without {range}"));
+                    };
+                    let body = std::mem::replace(&mut nodes, parent);
+                    Node::Range(path, body)
+                }
+                Token::Action(action) => {
+                    if let Some(path) = action.strip_prefix("range ") {
+                        stack.push((parse_path(path.trim())?, std::mem::take(&mut nodes)));
+                        continue;
+                    }
+                    if action.starts_with('"') || action.starts_with('\'') {
+                        Node::Text(parse_string(&action)?)
+                    } else {
+                        Node::Path(parse_path(&action)?)
+                    }
+                }
+            };
+            nodes.push(node);
+        }
+
+        if !stack.is_empty() {
+            return Err(parse_error("{range} without {end}"));
+        }
+        Ok(JsonPath { nodes })
+    }
+
+    /// Render the template for a value
+    ///
+    /// Paths that select nothing print nothing, like kubectl with
+    /// `--allow-missing-template-keys`.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The value to render
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The rendered text
+    pub fn execute(&self, data: &Value) -> String {
+        let mut out = String::new();
+        render(&self.nodes, data, data, &mut out);
+        out
+    }
+}
+
+/// Render template nodes with a current value into a string
+fn render(nodes: &[Node], root: &Value, current: &Value, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Path(path) => {
+                let values: Vec<String> = evaluate(path, root, current)
+                    .iter()
+                    .map(format_value)
+                    .collect();
+                out.push_str(&values.join(" "));
+            }
+            Node::Range(path, body) => {
+                let values = evaluate(path, root, current);
+                // Ranging over a single array walks its elements
+                let items = match values.as_slice() {
+                    [Value::Array(items)] => items.iter().collect(),
+                    _ => values,
+                };
+                for item in items {
+                    render(body, root, item, out);
+                }
+            }
+        }
+    }
+}
+
+/// Select the values a path points to
+fn evaluate<'a>(path: &Path, root: &'a Value, current: &'a Value) -> Vec<&'a Value> {
+    let start = if path.root { root } else { current };
+    path.segments.iter().fold(vec![start], |values, segment| {
+        values
+            .into_iter()
+            .flat_map(|value| apply(segment, value, root))
+            .collect()
+    })
+}
+
+/// Apply a path segment to a value
+fn apply<'a>(segment: &Segment, value: &'a Value, root: &'a Value) -> Vec<&'a Value> {
+    match segment {
+        Segment::Field(name) => value.get(name).into_iter().collect(),
+        Segment::Wildcard => children(value),
+        Segment::Descendants(name) => {
+            let mut all = Vec::new();
+            descendants(value, &mut all);
+            match name {
+                Some(name) => all.into_iter().filter_map(|v| v.get(name)).collect(),
+                None => all.into_iter().skip(1).collect(),
+            }
+        }
+        Segment::Index(index) => value
+            .as_array()
+            .and_then(|items| resolve_index(*index, items.len()).map(|i| &items[i]))
+            .into_iter()
+            .collect(),
+        Segment::Slice(start, end, step) => match value.as_array() {
+            Some(items) => slice(items, *start, *end, *step),
+            None => Vec::new(),
+        },
+        Segment::Union(keys) => keys
+            .iter()
+            .filter_map(|key| match key {
+                Key::Name(name) => value.get(name),
+                Key::Index(index) => value
+                    .as_array()
+                    .and_then(|items| resolve_index(*index, items.len()).map(|i| &items[i])),
+            })
+            .collect(),
+        Segment::Filter(filter) => children(value)
+            .into_iter()
+            .filter(|item| matches_filter(filter, root, item))
+            .collect(),
+    }
+}
+
+/// Get the elements of an array or the values of an object
+fn children(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(map) => map.values().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Collect a value and every value nested in it, depth first
+fn descendants<'a>(value: &'a Value, all: &mut Vec<&'a Value>) {
+    all.push(value);
+    for child in children(value) {
+        descendants(child, all);
+    }
+}
+
+/// Turn a possibly negative index into a position in an array
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    let len = len as i64;
+    let index = if index < 0 { len + index } else { index };
+    (0..len).contains(&index).then_some(index as usize)
+}
+
+/// Select a range of array elements, Python style
+fn slice(items: &[Value], start: Option<i64>, end: Option<i64>, step: Option<i64>) -> Vec<&Value> {
+    let len = items.len() as i64;
+    let clamp = |index: i64| {
+        if index < 0 {
+            (len + index).max(0)
+        } else {
+            index.min(len)
+        }
+    };
+    let start = start.map_or(0, clamp);
+    let end = end.map_or(len, clamp);
+    let step = step.unwrap_or(1).max(1) as usize;
+    (start..end)
+        .step_by(step)
+        .map(|index| &items[index as usize])
+        .collect()
+}
+
+/// Check whether an array element matches a filter
+fn matches_filter(filter: &Filter, root: &Value, item: &Value) -> bool {
+    let resolve = |operand: &Operand| match operand {
+        Operand::Path(path) => evaluate(path, root, item).first().map(|v| (*v).clone()),
+        Operand::Literal(value) => Some(value.clone()),
+    };
+    let Some(left) = resolve(&filter.left) else {
+        return false;
+    };
+    let Some((comparison, right)) = &filter.comparison else {
+        return !matches!(left, Value::Null | Value::Bool(false));
+    };
+    let Some(right) = resolve(right) else {
+        return false;
+    };
+
+    let ordering = match (&left, &right) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64().partial_cmp(&b.as_f64()),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ if left == right => Some(Ordering::Equal),
+        _ => None,
+    };
+    match comparison {
+        Comparison::Eq => ordering == Some(Ordering::Equal),
+        Comparison::Ne => ordering != Some(Ordering::Equal),
+        Comparison::Lt => ordering == Some(Ordering::Less),
+        Comparison::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        Comparison::Gt => ordering == Some(Ordering::Greater),
+        Comparison::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+    }
+}
+
+/// A piece of template source
+enum Token {
+    /// Text outside braces
+    Text(String),
+    /// The trimmed content of braces
+    Action(String),
+}
+
+/// Split a template into text and `{...}` actions
+fn tokenize(template: &str) -> Result<Vec<Token>, TemplateError> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                if !text.is_empty() {
+                    tokens.push(Token::Text(std::mem::take(&mut text)));
+                }
+                let mut action = String::new();
+                let mut quote = None;
+                loop {
+                    let Some(c) = chars.next() else {
+                        return Err(parse_error("unclosed {"));
+                    };
+                    match (c, quote) {
+                        ('}', None) => break,
+                        ('"' | '\'', None) => quote = Some(c),
+                        ('\\', Some(_)) => {
+                            action.push(c);
+                            if let Some(escaped) = chars.next() {
+                                action.push(escaped);
+                            }
+                            continue;
+                        }
+                        (c, Some(q)) if c == q => quote = None,
+                        _ => {}
+                    }
+                    action.push(c);
+                }
+                tokens.push(Token::Action(action.trim().to_string()));
+            }
+            '}' => return Err(parse_error("unexpected }")),
+            _ => text.push(c),
+        }
+    }
+
+    if !text.is_empty() {
+        tokens.push(Token::Text(text));
+    }
+    Ok(tokens)
+}
+
+/// Parse a path such as `.items[*].image`, `$.items[0]` or `@.registry`
+fn parse_path(source: &str) -> Result<Path, TemplateError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut pos = 0;
+    let root = match chars.first() {
+        Some('$') => {
+            pos = 1;
+            true
+        }
+        Some('@') => {
+            pos = 1;
+            false
+        }
+        Some('.' | '[') => false,
+        _ => {
+            return Err(parse_error(format!(
+                "unrecognized expression '{}', paths start with ., $ or @",
+                source
+            )));
+        }
+    };
+
+    let mut segments = Vec::new();
+    while pos < chars.len() {
+        match chars[pos] {
+            '.' if chars.get(pos + 1) == Some(&'.') => {
+                pos += 2;
+                if chars.get(pos) == Some(&'*') {
+                    pos += 1;
+                    segments.push(Segment::Descendants(None));
+                } else {
+                    let name = read_name(&chars, &mut pos);
+                    if name.is_empty() {
+                        return Err(parse_error(format!(
+                            "missing field after .. in '{}'",
+                            source
+                        )));
+                    }
+                    segments.push(Segment::Descendants(Some(name)));
+                }
+            }
+            '.' => {
+                pos += 1;
+                if chars.get(pos) == Some(&'*') {
+                    pos += 1;
+                    segments.push(Segment::Wildcard);
+                } else {
+                    let name = read_name(&chars, &mut pos);
+                    // A lone dot is the current value
+                    if !name.is_empty() {
+                        segments.push(Segment::Field(name));
+                    }
+                }
+            }
+            '[' => {
+                let end = find_bracket_end(&chars, pos)
+                    .ok_or_else(|| parse_error(format!("unclosed [ in '{}'", source)))?;
+                let inner: String = chars[pos + 1..end].iter().collect();
+                segments.push(parse_bracket(inner.trim())?);
+                pos = end + 1;
+            }
+            c => {
+                return Err(parse_error(format!("unexpected '{}' in '{}'", c, source)));
+            }
+        }
+    }
+
+    Ok(Path { root, segments })
+}
+
+/// Read a field name made of letters, digits, `_` and `-`
+fn read_name(chars: &[char], pos: &mut usize) -> String {
+    let start = *pos;
+    while *pos < chars.len() && (chars[*pos].is_alphanumeric() || matches!(chars[*pos], '_' | '-'))
+    {
+        *pos += 1;
+    }
+    chars[start..*pos].iter().collect()
+}
+
+/// Find the `]` closing the bracket at `start`, skipping quoted and nested brackets
+fn find_bracket_end(chars: &[char], start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote = None;
+    for (pos, &c) in chars.iter().enumerate().skip(start) {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('[', None) => depth += 1,
+            (']', None) => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(pos);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parse the content of brackets: wildcard, index, slice, union or filter
+fn parse_bracket(inner: &str) -> Result<Segment, TemplateError> {
+    if inner == "*" {
+        return Ok(Segment::Wildcard);
+    }
+    if let Some(filter) = inner
+        .strip_prefix("?(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        return parse_filter(filter.trim()).map(|filter| Segment::Filter(Box::new(filter)));
+    }
+    if inner.contains(':') && !inner.starts_with(['"', '\'']) {
+        let parts: Vec<&str> = inner.split(':').collect();
+        if parts.len() > 3 {
+            return Err(parse_error(format!("invalid slice [{}]", inner)));
+        }
+        let bound = |index: usize| -> Result<Option<i64>, TemplateError> {
+            match parts.get(index).map(|part| part.trim()) {
+                None | Some("") => Ok(None),
+                Some(number) => number
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| parse_error(format!("invalid slice [{}]", inner))),
+            }
+        };
+        return Ok(Segment::Slice(bound(0)?, bound(1)?, bound(2)?));
+    }
+
+    let keys = split_outside_quotes(inner, ',')
+        .iter()
+        .map(|key| {
+            let key = key.trim();
+            if key.starts_with(['"', '\'']) {
+                parse_string(key).map(Key::Name)
+            } else if let Ok(index) = key.parse() {
+                Ok(Key::Index(index))
+            } else if !key.is_empty() {
+                Ok(Key::Name(key.to_string()))
+            } else {
+                Err(parse_error(format!("empty key in [{}]", inner)))
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(match keys.as_slice() {
+        [Key::Index(index)] => Segment::Index(*index),
+        [Key::Name(name)] => Segment::Field(name.clone()),
+        _ => Segment::Union(keys),
+    })
+}
+
+/// Parse a filter condition such as `@.registry=="docker.io"` or `@.digest`
+fn parse_filter(source: &str) -> Result<Filter, TemplateError> {
+    const OPERATORS: [(&str, Comparison); 6] = [
+        ("==", Comparison::Eq),
+        ("!=", Comparison::Ne),
+        ("<=", Comparison::Le),
+        (">=", Comparison::Ge),
+        ("<", Comparison::Lt),
+        (">", Comparison::Gt),
+    ];
+
+    for (symbol, comparison) in OPERATORS {
+        if let Some((left, right)) = split_once_outside_quotes(source, symbol) {
+            return Ok(Filter {
+                left: parse_operand(left.trim())?,
+                comparison: Some((comparison, parse_operand(right.trim())?)),
+            });
+        }
+    }
+    Ok(Filter {
+        left: parse_operand(source)?,
+        comparison: None,
+    })
+}
+
+/// Parse a side of a filter: a path or a string, number or boolean literal
+fn parse_operand(source: &str) -> Result<Operand, TemplateError> {
+    if source.starts_with(['@', '$', '.']) {
+        return parse_path(source).map(Operand::Path);
+    }
+    if source.starts_with(['"', '\'']) {
+        return parse_string(source).map(|s| Operand::Literal(Value::String(s)));
+    }
+    serde_json::from_str(source)
+        .map(Operand::Literal)
+        .map_err(|_| parse_error(format!("invalid filter operand '{}'", source)))
+}
+
+/// Parse a quoted string literal, unescaping `\n`, `\t` and friends
+fn parse_string(source: &str) -> Result<String, TemplateError> {
+    let quote = source.chars().next();
+    let inner = source
+        .get(1..source.len().saturating_sub(1))
+        .filter(|_| source.len() >= 2 && source.chars().last() == quote)
+        .ok_or_else(|| parse_error(format!("unterminated string {}", source)))?;
+
+    let mut out = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    Ok(out)
+}
+
+/// Split text on a separator, ignoring separators inside quotes
+fn split_outside_quotes(source: &str, separator: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut quote = None;
+    for c in source.chars() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            (c, None) if c == separator => {
+                parts.push(String::new());
+                continue;
+            }
+            _ => {}
+        }
+        if let Some(part) = parts.last_mut() {
+            part.push(c);
+        }
+    }
+    parts
+}
+
+/// Split text at the first operator outside quotes
+fn split_once_outside_quotes<'a>(source: &'a str, operator: &str) -> Option<(&'a str, &'a str)> {
+    let mut quote = None;
+    for (pos, c) in source.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            (_, None) if source[pos..].starts_with(operator) => {
+                return Some((&source[..pos], &source[pos + operator.len()..]));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Print a selected value: text as is, other values as JSON
+fn format_value(value: &&Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Build a JSONPath syntax error
+fn parse_error(message: impl Into<String>) -> TemplateError {
+    TemplateError::Parse(format!("jsonpath: {}", message.into()))
+}
@@ -0,0 +1,57 @@
+//! Template output for scripting (`-o jsonpath=...`, `-o go-template=...`)
+//!
+//! Templates run over a kubectl-style list, `{"kind": "List", "items": [...]}`,
+//! with one item per pod image whose keys are the image fields (`pod`,
+//! `registry`, `image`, `version`, `managedBy`, ...) as text, plus the image
+//! size in bytes as a number, `sizeBytes`, null when unknown.
+
+use crate::k8s::{ImageField, PodImage};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+mod gotemplate;
+mod jsonpath;
+
+pub use gotemplate::GoTemplate;
+pub use jsonpath::JsonPath;
+
+/// Errors that can occur when parsing or rendering a template
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[non_exhaustive]
+pub enum TemplateError {
+    /// The template is not valid
+    #[error("Invalid template: {0}")]
+    Parse(String),
+    /// The template failed while rendering
+    #[error("Failed to render template: {0}")]
+    Execute(String),
+}
+
+/// Build the list of pod image records templates run over
+///
+/// # Arguments
+///
+/// * `images` - The pod images to include
+///
+/// # Returns
+///
+/// * `Value` - A List object with one item per image
+pub fn image_list(images: &[PodImage]) -> Value {
+    let items = images
+        .iter()
+        .map(|image| {
+            let mut record: Map<String, Value> = ImageField::ALL
+                .iter()
+                .map(|field| (field.key(), Value::String(image.field(*field))))
+                .collect();
+            record.insert("sizeBytes".to_string(), Value::from(image.image_size_bytes));
+            Value::Object(record)
+        })
+        .collect();
+
+    let mut list = Map::new();
+    list.insert("apiVersion".to_string(), Value::from("v1"));
+    list.insert("kind".to_string(), Value::from("List"));
+    list.insert("items".to_string(), Value::Array(items));
+    Value::Object(list)
+}
@@ -0,0 +1,197 @@
+//! Validating admission webhook (`kimspect webhook`)
+//!
+//! The webhook checks the images of Pods and workload pod templates submitted
+//! to the API server against an [`ImagePolicy`]. In dry-run mode violations are
+//! returned as warnings and the object is admitted; in enforce mode it is denied.
+
+use crate::k8s::{object_pods, process_pod};
+use crate::registry::{ImagePolicy, PolicyViolation, RegistryClassifier};
+use anyhow::Result;
+use axum::extract::State;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use k8s_openapi::api::core::v1::Container;
+use kube::core::DynamicObject;
+use kube::core::admission::{AdmissionRequest, AdmissionResponse, AdmissionReview};
+use serde_json::Value;
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+/// Prefix of the messages returned to API clients
+const MESSAGE_PREFIX: &str = "kimspect";
+
+/// Checks objects submitted for admission against an image policy
+pub struct ImageAdmission {
+    /// The rules images must follow
+    policy: ImagePolicy,
+    /// Classifier of the image registries
+    classifier: RegistryClassifier,
+    /// Whether to deny objects breaking the policy rather than warn
+    enforce: bool,
+}
+
+impl ImageAdmission {
+    /// Create an admission check
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The rules images must follow
+    /// * `classifier` - Classifier of the image registries
+    /// * `enforce` - Whether to deny objects breaking the policy, or only warn (dry-run)
+    ///
+    /// # Returns
+    ///
+    /// * `ImageAdmission` - The check, to share between requests
+    pub fn new(policy: ImagePolicy, classifier: RegistryClassifier, enforce: bool) -> Self {
+        Self {
+            policy,
+            classifier,
+            enforce,
+        }
+    }
+
+    /// Check if objects breaking the policy are denied
+    pub fn is_enforcing(&self) -> bool {
+        self.enforce
+    }
+
+    /// Check the images of an object against the policy
+    ///
+    /// Init and ephemeral (debug) containers are checked along with the regular containers.
+    ///
+    /// # Arguments
+    ///
+    /// * `object` - A Pod or a workload with a pod template
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<PolicyViolation>>` - The violations, empty for allowed objects
+    ///   and kinds without pods, or an error for a malformed object
+    pub fn violations(&self, object: Value) -> Result<Vec<PolicyViolation>> {
+        let mut violations = Vec::new();
+        for mut pod in object_pods(object)? {
+            if let Some(spec) = pod.spec.as_mut() {
+                let init_containers = spec.init_containers.take().unwrap_or_default();
+                spec.containers.splice(0..0, init_containers);
+                // Debug containers are added to running pods through pods/ephemeralcontainers
+                let ephemeral_containers = spec.ephemeral_containers.take().unwrap_or_default();
+                spec.containers
+                    .extend(ephemeral_containers.into_iter().map(|container| Container {
+                        name: container.name,
+                        image: container.image,
+                        ..Default::default()
+                    }));
+            }
+            let mut images = process_pod(&pod);
+            self.classifier.apply(&mut images);
+            violations.extend(images.iter().flat_map(|image| self.policy.check(image)));
+        }
+        Ok(violations)
+    }
+
+    /// Review an admission request
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request sent by the API server
+    ///
+    /// # Returns
+    ///
+    /// * `AdmissionResponse` - Allowed, with warnings in dry-run mode, or denied
+    pub fn review(&self, request: &AdmissionRequest<DynamicObject>) -> AdmissionResponse {
+        let response = AdmissionResponse::from(request);
+        // Deletions carry no object to check
+        let Some(object) = &request.object else {
+            return response;
+        };
+
+        let mut object = match serde_json::to_value(object) {
+            Ok(object) => object,
+            Err(e) => {
+                let message = format!("{}: invalid object: {}", MESSAGE_PREFIX, e);
+                return self.reject(response, vec![message]);
+            }
+        };
+        // The kind of the object is only guaranteed on the request
+        if let Some(fields) = object.as_object_mut() {
+            fields
+                .entry("kind")
+                .or_insert_with(|| Value::String(request.kind.kind.clone()));
+        }
+
+        let violations = match self.violations(object) {
+            Ok(violations) => violations,
+            Err(e) => {
+                let message = format!("{}: invalid object: {:#}", MESSAGE_PREFIX, e);
+                return self.reject(response, vec![message]);
+            }
+        };
+        if violations.is_empty() {
+            return response;
+        }
+
+        info!(
+            kind = %request.kind.kind,
+            namespace = request.namespace.as_deref().unwrap_or_default(),
+            name = %request.name,
+            violations = violations.len(),
+            enforce = self.enforce,
+            "Image policy violated"
+        );
+        let messages = violations
+            .iter()
+            .map(|violation| format!("{}: {}", MESSAGE_PREFIX, violation))
+            .collect();
+        self.reject(response, messages)
+    }
+
+    /// Deny a request in enforce mode, or admit it with warnings in dry-run mode
+    fn reject(&self, mut response: AdmissionResponse, messages: Vec<String>) -> AdmissionResponse {
+        if self.enforce {
+            response.deny(messages.join("; "))
+        } else {
+            response.warnings = Some(messages);
+            response
+        }
+    }
+}
+
+/// Build the router of the admission webhook
+///
+/// * `POST /validate` - Review an `AdmissionReview` from the API server
+/// * `GET /healthz` - Always 200 while the server runs
+///
+/// # Arguments
+///
+/// * `admission` - The check to run on each request
+///
+/// # Returns
+///
+/// * `Router` - The routes, ready to serve
+pub fn webhook_router(admission: Arc<ImageAdmission>) -> Router {
+    Router::new()
+        .route("/validate", post(validate_handler))
+        .route("/healthz", get(|| async { "ok" }))
+        .with_state(admission)
+}
+
+/// Review an admission request
+async fn validate_handler(
+    State(admission): State<Arc<ImageAdmission>>,
+    Json(review): Json<AdmissionReview<DynamicObject>>,
+) -> Json<AdmissionReview<DynamicObject>> {
+    let request: AdmissionRequest<DynamicObject> = match review.try_into() {
+        Ok(request) => request,
+        Err(e) => {
+            warn!(error = %e, "Invalid admission review");
+            return Json(AdmissionResponse::invalid(e).into_review());
+        }
+    };
+    debug!(
+        uid = %request.uid,
+        kind = %request.kind.kind,
+        operation = ?request.operation,
+        "Reviewing admission request"
+    );
+    Json(admission.review(&request).into_review())
+}
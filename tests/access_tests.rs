@@ -0,0 +1,247 @@
+use kimspect::{
+    AccessStatus, COMMAND_PERMISSIONS, GET_CRONJOBS, GET_DAEMONSETS, GET_DEPLOYMENTS, GET_JOBS,
+    GET_NAMESPACES, GET_REPLICASETS, GET_STATEFULSETS, LIST_CRONJOBS, LIST_DAEMONSETS,
+    LIST_DEPLOYMENTS, LIST_EVENTS, LIST_JOBS, LIST_NAMESPACES, LIST_NODES, LIST_PODS,
+    LIST_REPLICASETS, LIST_STATEFULSETS, PERMISSIONS, Permission, PermissionCheck, WATCH_NODES,
+    WATCH_PODS, evaluate_access,
+};
+
+fn check(permission: Permission, allowed: bool) -> PermissionCheck {
+    PermissionCheck::new(
+        permission,
+        permission.namespaced.then(|| "team-a".to_string()),
+        allowed,
+        "",
+    )
+}
+
+#[test]
+fn test_evaluate_access_all_allowed() {
+    let checks: Vec<PermissionCheck> = PERMISSIONS.iter().map(|p| check(*p, true)).collect();
+    let access = evaluate_access(&checks);
+
+    assert_eq!(access.len(), COMMAND_PERMISSIONS.len());
+    assert!(
+        access
+            .iter()
+            .all(|command| command.status == AccessStatus::Ok)
+    );
+    assert!(access.iter().all(|command| command.missing.is_empty()));
+
+    // get nodes only uses the node list
+    let nodes = access.iter().find(|c| c.command == "get nodes").unwrap();
+    assert_eq!(
+        nodes.permissions,
+        vec![
+            None,
+            Some(true),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None
+        ]
+    );
+
+    // Long-running commands watch what they list
+    let serve = access.iter().find(|c| c.command == "serve").unwrap();
+    assert_eq!(
+        serve.permissions,
+        vec![
+            Some(true),
+            Some(true),
+            None,
+            None,
+            None,
+            None,
+            Some(true),
+            Some(true),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None
+        ]
+    );
+}
+
+#[test]
+fn test_evaluate_access_namespace_scoped_user() {
+    // A typical namespace-scoped role: pods, deployments and events only
+    let checks = vec![
+        check(LIST_PODS, true),
+        check(LIST_NODES, false),
+        check(GET_NAMESPACES, false),
+        check(LIST_NAMESPACES, false),
+        check(LIST_DEPLOYMENTS, true),
+        check(LIST_EVENTS, true),
+        check(LIST_CRONJOBS, true),
+        check(LIST_JOBS, false),
+    ];
+    let access = evaluate_access(&checks);
+    let status = |name: &str| access.iter().find(|c| c.command == name).unwrap();
+
+    let images = status("get images");
+    assert_eq!(images.status, AccessStatus::Degraded);
+    assert_eq!(
+        images.missing,
+        vec![
+            "list nodes: no image sizes and node info",
+            "get namespaces: no unknown namespace errors",
+            "list namespaces: no namespace selectors",
+            "list jobs: no scheduled Job images"
+        ]
+    );
+
+    assert_eq!(status("get nodes").status, AccessStatus::Denied);
+    assert_eq!(status("get nodes").missing, vec!["list nodes: required"]);
+    assert_eq!(status("get arch-mismatches").status, AccessStatus::Denied);
+    assert_eq!(status("get pull-times").status, AccessStatus::Degraded);
+}
+
+#[test]
+fn test_evaluate_access_without_workload_templates() {
+    let checks = vec![
+        check(LIST_PODS, true),
+        check(GET_NAMESPACES, true),
+        check(LIST_DEPLOYMENTS, true),
+        check(LIST_STATEFULSETS, false),
+        check(LIST_DAEMONSETS, false),
+    ];
+    let access = evaluate_access(&checks);
+    let mismatches = access
+        .iter()
+        .find(|c| c.command == "get image-mismatches")
+        .unwrap();
+
+    // Pods are still compared with what they run
+    assert_eq!(mismatches.status, AccessStatus::Degraded);
+    assert_eq!(
+        mismatches.missing,
+        vec![
+            "list statefulsets: no StatefulSet template checks",
+            "list daemonsets: no DaemonSet template checks"
+        ]
+    );
+}
+
+#[test]
+fn test_evaluate_access_rollouts_need_their_workload() {
+    let checks = vec![
+        check(LIST_PODS, true),
+        check(GET_NAMESPACES, false),
+        check(LIST_DEPLOYMENTS, true),
+        check(LIST_REPLICASETS, false),
+        check(LIST_STATEFULSETS, true),
+        check(LIST_DAEMONSETS, false),
+    ];
+    let access = evaluate_access(&checks);
+    let status = |name: &str| access.iter().find(|c| c.command == name).unwrap();
+
+    let deployments = status("get rollout-images deployment");
+    assert_eq!(deployments.status, AccessStatus::Denied);
+    assert_eq!(
+        deployments.missing,
+        vec![
+            "list replicasets: required",
+            "get namespaces: no unknown namespace errors"
+        ]
+    );
+    assert_eq!(
+        status("get rollout-images statefulset").status,
+        AccessStatus::Degraded
+    );
+    assert_eq!(
+        status("get rollout-images daemonset").missing,
+        vec![
+            "list daemonsets: required",
+            "get namespaces: no unknown namespace errors"
+        ]
+    );
+}
+
+#[test]
+fn test_evaluate_access_release_owner_lookups() {
+    let checks = vec![
+        check(LIST_PODS, true),
+        check(GET_REPLICASETS, true),
+        check(GET_DEPLOYMENTS, false),
+        check(GET_STATEFULSETS, true),
+        check(GET_DAEMONSETS, true),
+        check(GET_JOBS, true),
+        check(GET_CRONJOBS, false),
+    ];
+    let access = evaluate_access(&checks);
+    let images = access.iter().find(|c| c.command == "get images").unwrap();
+
+    // Images are still listed, only their releases may be unknown
+    assert_eq!(images.status, AccessStatus::Degraded);
+    assert_eq!(
+        images.missing,
+        vec![
+            "get deployments: no releases of Deployment owners",
+            "get cronjobs: no releases of CronJob owners"
+        ]
+    );
+}
+
+#[test]
+fn test_evaluate_access_without_watch() {
+    let checks = vec![
+        check(LIST_PODS, true),
+        check(WATCH_PODS, false),
+        check(LIST_NODES, true),
+        check(WATCH_NODES, false),
+    ];
+    let access = evaluate_access(&checks);
+    let status = |name: &str| access.iter().find(|c| c.command == name).unwrap();
+
+    assert_eq!(status("get images").status, AccessStatus::Ok);
+    assert_eq!(status("tui").status, AccessStatus::Denied);
+    assert_eq!(
+        status("serve").missing,
+        vec!["watch pods: required", "watch nodes: no image size updates"]
+    );
+}
+
+#[test]
+fn test_evaluate_access_skips_unreviewed_permissions() {
+    // Namespace lookups are not reviewed when checking all namespaces
+    let checks = vec![
+        check(LIST_PODS, true),
+        check(LIST_NODES, true),
+        check(LIST_DEPLOYMENTS, true),
+        check(LIST_EVENTS, false),
+    ];
+    let access = evaluate_access(&checks);
+
+    let images = &access[0];
+    assert_eq!(images.command, "get images");
+    assert_eq!(images.status, AccessStatus::Ok);
+    assert_eq!(images.permissions[2], None);
+
+    let errors = access
+        .iter()
+        .find(|c| c.command == "get image-errors")
+        .unwrap();
+    assert_eq!(errors.status, AccessStatus::Degraded);
+    assert_eq!(errors.missing, vec!["list events: no failure messages"]);
+}
@@ -1,29 +1,55 @@
 use clap::Parser;
-use kimspect::{Args, Commands, GetImages, OutputFormat};
+use kimspect::cli::{
+    Args, AuthCommands, Commands, CustomColumn, GetImages, ImageGroupBy, LogFormat, OutputFormat,
+    PinFormat, PluginCommands,
+};
+use kimspect::{ImageField, PullGroupBy, WorkloadKind};
+use std::path::PathBuf;
+use std::time::Duration;
 
 #[test]
 fn test_cli_parse_get_images_default() {
     let args = Args::parse_from(["kimspect", "get", "images"]);
 
-    let Commands::Get { resource } = args.command;
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
     if let GetImages::Images {
         namespace,
+        namespace_selector,
+        exclude_namespace,
         node,
         pod,
         registry,
         exclude_registry,
         all_namespaces,
+        include_scheduled,
+        mirror,
+        group_by,
+        from_file,
         output,
+        columns,
+        sort_by,
+        no_headers,
         kubeconfig: _,
     } = resource
     {
-        assert_eq!(namespace, "default");
+        assert_eq!(namespace, ["default"]);
         assert!(node.is_none());
         assert!(pod.is_none());
         assert!(registry.is_none());
         assert!(exclude_registry.is_empty());
         assert!(!all_namespaces);
         assert_eq!(output, OutputFormat::Normal);
+        assert!(mirror.is_empty());
+        assert!(group_by.is_none());
+        assert!(from_file.is_none());
+        assert!(namespace_selector.is_none());
+        assert!(exclude_namespace.is_empty());
+        assert!(columns.is_empty());
+        assert!(sort_by.is_none());
+        assert!(!no_headers);
+        assert!(!include_scheduled);
     } else {
         panic!("Expected GetImages::Images variant");
     }
@@ -41,25 +67,38 @@ fn test_cli_parse_get_images_multiple_exclude_registry() {
         "ghcr.io",
     ]);
 
-    let Commands::Get { resource } = args.command;
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
     if let GetImages::Images {
         namespace,
+        namespace_selector: _,
+        exclude_namespace: _,
         node,
         pod,
         registry,
         exclude_registry,
         all_namespaces,
+        include_scheduled: _,
+        mirror,
+        group_by,
+        from_file: _,
         output,
+        columns: _,
+        sort_by: _,
+        no_headers: _,
         kubeconfig: _,
     } = resource
     {
-        assert_eq!(namespace, "default");
+        assert_eq!(namespace, ["default"]);
         assert!(node.is_none());
         assert!(pod.is_none());
         assert!(registry.is_none());
         assert_eq!(exclude_registry, vec!["docker.io", "ghcr.io"]);
         assert!(!all_namespaces);
         assert_eq!(output, OutputFormat::Normal);
+        assert!(mirror.is_empty());
+        assert!(group_by.is_none());
     } else {
         panic!("Expected GetImages::Images variant");
     }
@@ -68,25 +107,38 @@ fn test_cli_parse_get_images_multiple_exclude_registry() {
 #[test]
 fn test_cli_parse_get_images_namespace() {
     let args = Args::parse_from(["kimspect", "get", "images", "--namespace", "test-ns"]);
-    let Commands::Get { resource } = args.command;
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
     if let GetImages::Images {
         namespace,
+        namespace_selector: _,
+        exclude_namespace: _,
         node,
         pod,
         registry,
         exclude_registry,
         all_namespaces,
+        include_scheduled: _,
+        mirror,
+        group_by,
+        from_file: _,
         output,
+        columns: _,
+        sort_by: _,
+        no_headers: _,
         kubeconfig: _,
     } = resource
     {
-        assert_eq!(namespace, "test-ns");
+        assert_eq!(namespace, ["test-ns"]);
         assert!(node.is_none());
         assert!(pod.is_none());
         assert!(registry.is_none());
         assert!(exclude_registry.is_empty());
         assert!(!all_namespaces);
         assert_eq!(output, OutputFormat::Normal);
+        assert!(mirror.is_empty());
+        assert!(group_by.is_none());
     } else {
         panic!("Expected GetImages::Images variant");
     }
@@ -95,26 +147,39 @@ fn test_cli_parse_get_images_namespace() {
 #[test]
 fn test_cli_parse_get_images_all_namespaces() {
     let args = Args::parse_from(["kimspect", "get", "images", "--all-namespaces"]);
-    let Commands::Get { resource } = args.command;
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
     if let GetImages::Images {
         namespace,
+        namespace_selector: _,
+        exclude_namespace: _,
         node,
         pod,
         registry,
         exclude_registry,
         all_namespaces,
+        include_scheduled: _,
+        mirror,
+        group_by,
+        from_file: _,
         output,
+        columns: _,
+        sort_by: _,
+        no_headers: _,
         kubeconfig: _,
     } = resource
     {
         // namespace should still be default, but all_namespaces flag should be true
-        assert_eq!(namespace, "default");
+        assert_eq!(namespace, ["default"]);
         assert!(node.is_none());
         assert!(pod.is_none());
         assert!(registry.is_none());
         assert!(exclude_registry.is_empty());
         assert!(all_namespaces);
         assert_eq!(output, OutputFormat::Normal);
+        assert!(mirror.is_empty());
+        assert!(group_by.is_none());
     } else {
         panic!("Expected GetImages::Images variant");
     }
@@ -124,25 +189,38 @@ fn test_cli_parse_get_images_all_namespaces() {
 fn test_cli_parse_get_images_all_namespaces_short() {
     // Test the short flag version (-A)
     let args = Args::parse_from(["kimspect", "get", "images", "-A"]);
-    let Commands::Get { resource } = args.command;
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
     if let GetImages::Images {
         namespace,
+        namespace_selector: _,
+        exclude_namespace: _,
         node,
         pod,
         registry,
         exclude_registry,
         all_namespaces,
+        include_scheduled: _,
+        mirror,
+        group_by,
+        from_file: _,
         output,
+        columns: _,
+        sort_by: _,
+        no_headers: _,
         kubeconfig: _,
     } = resource
     {
-        assert_eq!(namespace, "default");
+        assert_eq!(namespace, ["default"]);
         assert!(node.is_none());
         assert!(pod.is_none());
         assert!(registry.is_none());
         assert!(exclude_registry.is_empty());
         assert!(all_namespaces);
         assert_eq!(output, OutputFormat::Normal);
+        assert!(mirror.is_empty());
+        assert!(group_by.is_none());
     } else {
         panic!("Expected GetImages::Images variant");
     }
@@ -152,25 +230,38 @@ fn test_cli_parse_get_images_all_namespaces_short() {
 fn test_cli_parse_get_images_node() {
     // Test combining node filter
     let args = Args::parse_from(["kimspect", "get", "images", "--node", "worker1"]);
-    let Commands::Get { resource } = args.command;
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
     if let GetImages::Images {
         namespace,
+        namespace_selector: _,
+        exclude_namespace: _,
         node,
         pod,
         registry,
         exclude_registry,
         all_namespaces,
+        include_scheduled: _,
+        mirror,
+        group_by,
+        from_file: _,
         output,
+        columns: _,
+        sort_by: _,
+        no_headers: _,
         kubeconfig: _,
     } = resource
     {
-        assert_eq!(namespace, "default");
+        assert_eq!(namespace, ["default"]);
         assert_eq!(node, Some("worker1".to_string()));
         assert!(pod.is_none());
         assert!(registry.is_none());
         assert!(exclude_registry.is_empty());
         assert!(!all_namespaces);
         assert_eq!(output, OutputFormat::Normal);
+        assert!(mirror.is_empty());
+        assert!(group_by.is_none());
     } else {
         panic!("Expected GetImages::Images variant");
     }
@@ -187,25 +278,38 @@ fn test_cli_parse_get_images_pod_and_all_namespaces() {
         "nginx-pod",
         "--all-namespaces",
     ]);
-    let Commands::Get { resource } = args.command;
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
     if let GetImages::Images {
         namespace,
+        namespace_selector: _,
+        exclude_namespace: _,
         node,
         pod,
         registry,
         exclude_registry,
         all_namespaces,
+        include_scheduled: _,
+        mirror,
+        group_by,
+        from_file: _,
         output,
+        columns: _,
+        sort_by: _,
+        no_headers: _,
         kubeconfig: _,
     } = resource
     {
-        assert_eq!(namespace, "default");
+        assert_eq!(namespace, ["default"]);
         assert!(node.is_none());
         assert_eq!(pod, Some("nginx-pod".to_string()));
         assert!(registry.is_none());
         assert!(exclude_registry.is_empty());
         assert!(all_namespaces);
         assert_eq!(output, OutputFormat::Normal);
+        assert!(mirror.is_empty());
+        assert!(group_by.is_none());
     } else {
         panic!("Expected GetImages::Images variant");
     }
@@ -215,25 +319,38 @@ fn test_cli_parse_get_images_pod_and_all_namespaces() {
 fn test_cli_parse_get_images_wide_output() {
     // Test wide output format
     let args = Args::parse_from(["kimspect", "get", "images", "-o", "wide"]);
-    let Commands::Get { resource } = args.command;
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
     if let GetImages::Images {
         namespace,
+        namespace_selector: _,
+        exclude_namespace: _,
         node,
         pod,
         registry,
         exclude_registry,
         all_namespaces,
+        include_scheduled: _,
+        mirror,
+        group_by,
+        from_file: _,
         output,
+        columns: _,
+        sort_by: _,
+        no_headers: _,
         kubeconfig: _,
     } = resource
     {
-        assert_eq!(namespace, "default");
+        assert_eq!(namespace, ["default"]);
         assert!(node.is_none());
         assert!(pod.is_none());
         assert!(registry.is_none());
         assert!(exclude_registry.is_empty());
         assert!(!all_namespaces);
         assert_eq!(output, OutputFormat::Wide);
+        assert!(mirror.is_empty());
+        assert!(group_by.is_none());
     } else {
         panic!("Expected GetImages::Images variant");
     }
@@ -243,25 +360,38 @@ fn test_cli_parse_get_images_wide_output() {
 fn test_cli_parse_get_images_wide_output_long() {
     // Test wide output format with long flag
     let args = Args::parse_from(["kimspect", "get", "images", "--output", "wide"]);
-    let Commands::Get { resource } = args.command;
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
     if let GetImages::Images {
         namespace,
+        namespace_selector: _,
+        exclude_namespace: _,
         node,
         pod,
         registry,
         exclude_registry,
         all_namespaces,
+        include_scheduled: _,
+        mirror,
+        group_by,
+        from_file: _,
         output,
+        columns: _,
+        sort_by: _,
+        no_headers: _,
         kubeconfig: _,
     } = resource
     {
-        assert_eq!(namespace, "default");
+        assert_eq!(namespace, ["default"]);
         assert!(node.is_none());
         assert!(pod.is_none());
         assert!(registry.is_none());
         assert!(exclude_registry.is_empty());
         assert!(!all_namespaces);
         assert_eq!(output, OutputFormat::Wide);
+        assert!(mirror.is_empty());
+        assert!(group_by.is_none());
     } else {
         panic!("Expected GetImages::Images variant");
     }
@@ -309,16 +439,21 @@ fn test_cli_parse_get_pods_namespace_and_all_namespaces_conflict() {
 #[test]
 fn test_cli_parse_get_registries_default() {
     let args = Args::parse_from(["kimspect", "get", "registries"]);
-    let Commands::Get { resource } = args.command;
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
     if let GetImages::Registries {
         namespace,
         all_namespaces,
+        include_scheduled,
+        mirror: _,
         output,
         kubeconfig: _,
     } = resource
     {
         assert_eq!(namespace, "default");
         assert!(!all_namespaces);
+        assert!(!include_scheduled);
         assert_eq!(output, OutputFormat::Normal);
     } else {
         panic!("Expected GetImages::Registries variant");
@@ -328,10 +463,14 @@ fn test_cli_parse_get_registries_default() {
 #[test]
 fn test_cli_parse_get_registries_namespace() {
     let args = Args::parse_from(["kimspect", "get", "registries", "--namespace", "test-ns"]);
-    let Commands::Get { resource } = args.command;
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
     if let GetImages::Registries {
         namespace,
         all_namespaces,
+        include_scheduled: _,
+        mirror: _,
         output,
         kubeconfig: _,
     } = resource
@@ -347,10 +486,14 @@ fn test_cli_parse_get_registries_namespace() {
 #[test]
 fn test_cli_parse_get_registries_all_namespaces() {
     let args = Args::parse_from(["kimspect", "get", "registries", "--all-namespaces"]);
-    let Commands::Get { resource } = args.command;
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
     if let GetImages::Registries {
         namespace,
         all_namespaces,
+        include_scheduled: _,
+        mirror: _,
         output,
         kubeconfig: _,
     } = resource
@@ -363,6 +506,42 @@ fn test_cli_parse_get_registries_all_namespaces() {
     }
 }
 
+#[test]
+fn test_cli_parse_include_scheduled() {
+    let args = Args::parse_from(["kimspect", "get", "images", "-A", "--include-scheduled"]);
+    let Commands::Get {
+        resource: GetImages::Images {
+            include_scheduled, ..
+        },
+    } = args.command
+    else {
+        panic!("Expected GetImages::Images variant");
+    };
+    assert!(include_scheduled);
+
+    let args = Args::parse_from(["kimspect", "get", "registries", "--include-scheduled"]);
+    let Commands::Get {
+        resource: GetImages::Registries {
+            include_scheduled, ..
+        },
+    } = args.command
+    else {
+        panic!("Expected GetImages::Registries variant");
+    };
+    assert!(include_scheduled);
+
+    // Manifests already include their CronJob and Job templates
+    let result = Args::try_parse_from([
+        "kimspect",
+        "get",
+        "images",
+        "--include-scheduled",
+        "--from-file",
+        "manifests/",
+    ]);
+    assert!(result.is_err());
+}
+
 #[test]
 fn test_cli_parse_get_registries_namespace_and_all_namespaces_conflict() {
     let result = Args::try_parse_from([
@@ -378,3 +557,680 @@ fn test_cli_parse_get_registries_namespace_and_all_namespaces_conflict() {
         "Expected parser to reject conflicting arguments for 'get registries'"
     );
 }
+
+#[test]
+fn test_cli_parse_get_image_errors_all_namespaces() {
+    let args = Args::parse_from(["kimspect", "get", "image-errors", "-A", "-o", "wide"]);
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
+    if let GetImages::ImageErrors {
+        namespace,
+        all_namespaces,
+        output,
+        kubeconfig: _,
+    } = resource
+    {
+        assert_eq!(namespace, "default");
+        assert!(all_namespaces);
+        assert_eq!(output, OutputFormat::Wide);
+    } else {
+        panic!("Expected GetImages::ImageErrors variant");
+    }
+}
+
+#[test]
+fn test_cli_parse_get_pull_times_group_by() {
+    let args = Args::parse_from(["kimspect", "get", "pull-times", "-A", "--group-by", "node"]);
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
+    if let GetImages::PullTimes {
+        namespace,
+        all_namespaces,
+        group_by,
+        output,
+        kubeconfig: _,
+    } = resource
+    {
+        assert_eq!(namespace, "default");
+        assert!(all_namespaces);
+        assert_eq!(group_by, Some(PullGroupBy::Node));
+        assert_eq!(output, OutputFormat::Normal);
+    } else {
+        panic!("Expected GetImages::PullTimes variant");
+    }
+}
+
+#[test]
+fn test_cli_parse_get_nodes() {
+    let args = Args::parse_from(["kimspect", "get", "nodes", "-N", "worker-1", "-o", "wide"]);
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
+    if let GetImages::Nodes {
+        node,
+        output,
+        kubeconfig: _,
+    } = resource
+    {
+        assert_eq!(node.as_deref(), Some("worker-1"));
+        assert_eq!(output, OutputFormat::Wide);
+    } else {
+        panic!("Expected GetImages::Nodes variant");
+    }
+}
+
+#[test]
+fn test_cli_parse_get_arch_mismatches() {
+    let args = Args::parse_from(["kimspect", "get", "arch-mismatches", "-n", "payments"]);
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
+    if let GetImages::ArchMismatches {
+        namespace,
+        all_namespaces,
+        output,
+        kubeconfig: _,
+    } = resource
+    {
+        assert_eq!(namespace, "payments");
+        assert!(!all_namespaces);
+        assert_eq!(output, OutputFormat::Normal);
+    } else {
+        panic!("Expected GetImages::ArchMismatches variant");
+    }
+}
+
+#[test]
+fn test_cli_parse_get_image_mismatches() {
+    let args = Args::parse_from(["kimspect", "get", "image-mismatches", "-A", "-o", "wide"]);
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
+    assert_eq!(resource.name(), "get image-mismatches");
+    if let GetImages::ImageMismatches {
+        all_namespaces,
+        output,
+        ..
+    } = resource
+    {
+        assert!(all_namespaces);
+        assert_eq!(output, OutputFormat::Wide);
+    } else {
+        panic!("Expected GetImages::ImageMismatches variant");
+    }
+}
+
+#[test]
+fn test_cli_parse_get_rollout_images() {
+    let args = Args::parse_from([
+        "kimspect",
+        "get",
+        "rollout-images",
+        "deploy",
+        "web",
+        "-n",
+        "shop",
+    ]);
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
+    assert_eq!(resource.get_namespace(), "shop");
+    assert!(!resource.is_all_namespaces());
+    if let GetImages::RolloutImages {
+        kind, name, output, ..
+    } = resource
+    {
+        assert_eq!(kind, WorkloadKind::Deployment);
+        assert_eq!(kind.kind(), "Deployment");
+        assert_eq!(name, "web");
+        assert_eq!(output, OutputFormat::Normal);
+    } else {
+        panic!("Expected GetImages::RolloutImages variant");
+    }
+
+    let args = Args::parse_from(["kimspect", "get", "rollout-images", "sts", "db"]);
+    let Commands::Get {
+        resource: GetImages::RolloutImages { kind, .. },
+    } = args.command
+    else {
+        panic!("Expected GetImages::RolloutImages variant");
+    };
+    assert_eq!(kind.kind(), "StatefulSet");
+    // A workload is a single object, it cannot span namespaces
+    assert!(Args::try_parse_from(["kimspect", "get", "rollout-images", "cronjob", "x"]).is_err());
+    assert!(Args::try_parse_from(["kimspect", "get", "rollout-images", "ds", "x", "-A"]).is_err());
+}
+
+#[test]
+fn test_cli_parse_get_images_mirror_and_group_by() {
+    let args = Args::parse_from([
+        "kimspect",
+        "get",
+        "images",
+        "-A",
+        "--mirror",
+        "mirror.corp/dockerhub=docker.io",
+        "--group-by",
+        "upstream",
+    ]);
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
+    if let GetImages::Images {
+        mirror, group_by, ..
+    } = resource
+    {
+        assert_eq!(mirror.len(), 1);
+        assert_eq!(mirror[0].mirror, "mirror.corp/dockerhub");
+        assert_eq!(mirror[0].upstream, "docker.io");
+        assert_eq!(group_by, Some(ImageGroupBy::Upstream));
+    } else {
+        panic!("Expected GetImages::Images variant");
+    }
+}
+
+#[test]
+fn test_cli_parse_get_images_group_by_release() {
+    let args = Args::parse_from(["kimspect", "get", "images", "-A", "--group-by", "release"]);
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
+    if let GetImages::Images { group_by, .. } = resource {
+        assert_eq!(group_by, Some(ImageGroupBy::Release));
+    } else {
+        panic!("Expected GetImages::Images variant");
+    }
+}
+
+#[test]
+fn test_cli_parse_get_images_invalid_mirror() {
+    let result = Args::try_parse_from(["kimspect", "get", "images", "--mirror", "mirror.corp"]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_cli_parse_get_images_from_file() {
+    let args = Args::parse_from(["kimspect", "get", "images", "--from-file", "manifests/"]);
+    assert!(!args.command.needs_cluster());
+
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
+    assert_eq!(
+        resource.get_from_file(),
+        Some(std::path::Path::new("manifests/"))
+    );
+
+    let args = Args::parse_from(["kimspect", "get", "images", "--from-file", "-"]);
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
+    assert_eq!(resource.get_from_file(), Some(std::path::Path::new("-")));
+
+    let args = Args::parse_from(["kimspect", "get", "images"]);
+    assert!(args.command.needs_cluster());
+}
+
+#[test]
+fn test_cli_parse_auth_check() {
+    let args = Args::parse_from(["kimspect", "auth", "check", "-n", "team-a", "-o", "wide"]);
+    assert!(args.command.needs_cluster());
+
+    let Commands::Auth {
+        action:
+            AuthCommands::Check {
+                namespace,
+                all_namespaces,
+                output,
+                ..
+            },
+    } = args.command
+    else {
+        panic!("Expected Commands::Auth variant");
+    };
+    assert_eq!(namespace, "team-a");
+    assert!(!all_namespaces);
+    assert_eq!(output, OutputFormat::Wide);
+
+    let result = Args::try_parse_from(["kimspect", "auth", "check", "-A", "-n", "team-a"]);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_cli_parse_get_images_multiple_namespaces() {
+    let args = Args::parse_from([
+        "kimspect",
+        "get",
+        "images",
+        "-n",
+        "payments,payments-jobs",
+        "--exclude-namespace",
+        "kube-system",
+        "--exclude-namespace",
+        "istio-system,linkerd",
+    ]);
+
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
+    if let GetImages::Images {
+        namespace,
+        exclude_namespace,
+        ..
+    } = resource
+    {
+        assert_eq!(namespace, ["payments", "payments-jobs"]);
+        assert_eq!(
+            exclude_namespace,
+            ["kube-system", "istio-system", "linkerd"]
+        );
+    } else {
+        panic!("Expected GetImages::Images variant");
+    }
+}
+
+#[test]
+fn test_cli_parse_get_images_namespace_selector() {
+    let args = Args::parse_from([
+        "kimspect",
+        "get",
+        "images",
+        "--namespace-selector",
+        "team=payments",
+    ]);
+
+    let Commands::Get { resource } = args.command else {
+        panic!("Expected Commands::Get variant");
+    };
+    if let GetImages::Images {
+        namespace_selector, ..
+    } = resource
+    {
+        assert_eq!(namespace_selector.as_deref(), Some("team=payments"));
+    } else {
+        panic!("Expected GetImages::Images variant");
+    }
+
+    for conflicting in ["-A", "-n=payments"] {
+        let result = Args::try_parse_from([
+            "kimspect",
+            "get",
+            "images",
+            "--namespace-selector",
+            "team=payments",
+            conflicting,
+        ]);
+        assert!(result.is_err());
+    }
+}
+
+#[test]
+fn test_cli_parse_logging_options() {
+    let args = Args::parse_from(["kimspect", "get", "images"]);
+    assert_eq!(args.verbose, 0);
+    assert!(args.log_file.is_none());
+
+    let args = Args::parse_from([
+        "kimspect",
+        "get",
+        "images",
+        "-vv",
+        "--log-format",
+        "json",
+        "--log-file",
+        "/var/log/kimspect.json",
+    ]);
+    assert_eq!(args.verbose, 2);
+    assert!(matches!(args.log_format, LogFormat::Json));
+    assert_eq!(
+        args.log_file.as_deref(),
+        Some(std::path::Path::new("/var/log/kimspect.json"))
+    );
+}
+
+#[test]
+fn test_cli_parse_get_images_custom_columns() {
+    let args = Args::parse_from([
+        "kimspect",
+        "get",
+        "images",
+        "-o",
+        "custom-columns=NS:namespace,POD:pod,image",
+        "--sort-by",
+        "size",
+        "--no-headers",
+    ]);
+    assert!(args.command.check_output().is_ok());
+
+    let Commands::Get {
+        resource:
+            GetImages::Images {
+                output,
+                sort_by,
+                no_headers,
+                ..
+            },
+    } = args.command
+    else {
+        panic!("Expected GetImages::Images variant");
+    };
+    assert_eq!(
+        output,
+        OutputFormat::CustomColumns(vec![
+            CustomColumn {
+                header: "NS".to_string(),
+                field: ImageField::Namespace,
+            },
+            CustomColumn {
+                header: "POD".to_string(),
+                field: ImageField::Pod,
+            },
+            CustomColumn {
+                header: "IMAGE".to_string(),
+                field: ImageField::Image,
+            },
+        ])
+    );
+    assert_eq!(sort_by, Some(ImageField::Size));
+    assert!(no_headers);
+}
+
+#[test]
+fn test_cli_parse_get_images_columns() {
+    let args = Args::parse_from([
+        "kimspect",
+        "get",
+        "images",
+        "--columns",
+        "namespace,pod,image,digest",
+    ]);
+
+    let Commands::Get {
+        resource: GetImages::Images {
+            columns, output, ..
+        },
+    } = args.command
+    else {
+        panic!("Expected GetImages::Images variant");
+    };
+    assert_eq!(
+        columns,
+        [
+            ImageField::Namespace,
+            ImageField::Pod,
+            ImageField::Image,
+            ImageField::Digest
+        ]
+    );
+    assert_eq!(output, OutputFormat::Normal);
+
+    for conflicting in [
+        ["--columns", "pod", "-o", "wide"],
+        ["--columns", "pod", "--group-by", "upstream"],
+        ["--sort-by", "pod", "--group-by", "release"],
+        ["--columns", "pod,bogus", "--sort-by", "pod"],
+    ] {
+        let mut argv = vec!["kimspect", "get", "images"];
+        argv.extend(conflicting);
+        assert!(Args::try_parse_from(argv).is_err());
+    }
+}
+
+#[test]
+fn test_cli_custom_columns_only_for_image_listing() {
+    let args = Args::parse_from(["kimspect", "get", "nodes", "-o", "custom-columns=NODE:node"]);
+    assert_eq!(
+        args.command.check_output().unwrap_err(),
+        "Output format custom-columns is only supported by get images, not by get nodes"
+    );
+
+    let args = Args::parse_from([
+        "kimspect",
+        "get",
+        "images",
+        "--group-by",
+        "release",
+        "-o",
+        "custom-columns=POD:pod",
+    ]);
+    assert!(args.command.check_output().is_err());
+}
+
+#[test]
+fn test_cli_parse_kubectl_global_flags() {
+    let args = Args::parse_from([
+        "kimspect",
+        "get",
+        "images",
+        "--context",
+        "staging",
+        "--request-timeout",
+        "1m30s",
+        "--kubeconfig",
+        "/tmp/kubeconfig",
+    ]);
+    let options = args.client_options();
+    assert_eq!(options.kubeconfig, Some(PathBuf::from("/tmp/kubeconfig")));
+    assert_eq!(options.context, Some("staging".to_string()));
+    assert_eq!(options.request_timeout, Some(Duration::from_secs(90)));
+
+    let args = Args::parse_from(["kimspect", "--request-timeout", "5", "get", "nodes"]);
+    assert_eq!(
+        args.client_options().request_timeout,
+        Some(Duration::from_secs(5))
+    );
+    assert_eq!(args.client_options().context, None);
+
+    let argv = ["kimspect", "--request-timeout", "soon", "get", "nodes"];
+    assert!(Args::try_parse_from(argv).is_err());
+
+    // Overflowing timeouts are a usage error, not a crash
+    for timeout in ["99999999999999999999h", "99999999999999999999", "169h"] {
+        let argv = ["kimspect", "--request-timeout", timeout, "get", "images"];
+        let err = Args::try_parse_from(argv).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ValueValidation);
+    }
+}
+
+#[test]
+fn test_cli_parse_plugin_manifest() {
+    let args = Args::parse_from([
+        "kimspect",
+        "plugin",
+        "manifest",
+        "--checksums",
+        "checksums.txt",
+        "--version",
+        "1.2.3",
+    ]);
+    assert!(!args.command.needs_cluster());
+
+    let Commands::Plugin {
+        action: PluginCommands::Manifest { version, checksums },
+    } = args.command
+    else {
+        panic!("Expected Commands::Plugin variant");
+    };
+    assert_eq!(version.as_deref(), Some("1.2.3"));
+    assert_eq!(checksums, PathBuf::from("checksums.txt"));
+
+    assert!(Args::try_parse_from(["kimspect", "plugin", "manifest"]).is_err());
+}
+
+#[test]
+fn test_cli_parse_tui() {
+    let args = Args::parse_from([
+        "kimspect", "tui", "-A", "-R", "ghcr.io", "-i", "web", "-N", "worker-1",
+    ]);
+    assert!(args.command.needs_cluster());
+    assert!(args.command.check_output().is_ok());
+
+    let Commands::Tui {
+        namespace,
+        all_namespaces,
+        registry,
+        image,
+        node,
+        mirror,
+        kubeconfig,
+    } = args.command
+    else {
+        panic!("Expected Commands::Tui variant");
+    };
+    assert_eq!(namespace, "default");
+    assert!(all_namespaces);
+    assert_eq!(registry.as_deref(), Some("ghcr.io"));
+    assert_eq!(image.as_deref(), Some("web"));
+    assert_eq!(node.as_deref(), Some("worker-1"));
+    assert!(mirror.is_empty());
+    assert_eq!(kubeconfig, None);
+
+    assert!(Args::try_parse_from(["kimspect", "tui", "-n", "shop", "-A"]).is_err());
+}
+
+#[test]
+fn test_cli_parse_serve() {
+    let args = Args::parse_from(["kimspect", "serve", "-A"]);
+    assert!(args.command.needs_cluster());
+    let Commands::Serve {
+        all_namespaces,
+        metrics_addr,
+        api_addr,
+        ..
+    } = args.command
+    else {
+        panic!("Expected Commands::Serve variant");
+    };
+    assert!(all_namespaces);
+    assert_eq!(metrics_addr, "0.0.0.0:9090".parse().unwrap());
+    // The JSON inventory is opt-in
+    assert_eq!(api_addr, None);
+
+    let args = Args::parse_from([
+        "kimspect",
+        "serve",
+        "--metrics-addr",
+        "127.0.0.1:8080",
+        "--api-addr",
+        "127.0.0.1:9091",
+    ]);
+    let Commands::Serve {
+        metrics_addr,
+        api_addr,
+        ..
+    } = args.command
+    else {
+        panic!("Expected Commands::Serve variant");
+    };
+    assert_eq!(metrics_addr, "127.0.0.1:8080".parse().unwrap());
+    assert_eq!(api_addr, Some("127.0.0.1:9091".parse().unwrap()));
+
+    assert!(Args::try_parse_from(["kimspect", "serve", "--metrics-addr", "9090"]).is_err());
+}
+
+#[test]
+fn test_cli_parse_webhook() {
+    let args = Args::parse_from([
+        "kimspect",
+        "webhook",
+        "--insecure",
+        "--enforce",
+        "--disallow-registry",
+        "docker.io",
+        "--disallow-registry",
+        "quay.io",
+        "--deny-latest",
+    ]);
+    assert!(!args.command.needs_cluster());
+    let Commands::Webhook {
+        addr,
+        tls_cert,
+        enforce,
+        disallow_registry,
+        deny_latest,
+        require_digest,
+        ..
+    } = args.command
+    else {
+        panic!("Expected Commands::Webhook variant");
+    };
+    assert_eq!(addr, "0.0.0.0:8443".parse().unwrap());
+    assert_eq!(tls_cert, None);
+    assert!(enforce);
+    assert_eq!(disallow_registry, ["docker.io", "quay.io"]);
+    assert!(deny_latest);
+    assert!(!require_digest);
+
+    // A certificate needs its key
+    assert!(Args::try_parse_from(["kimspect", "webhook", "--tls-cert", "tls.crt"]).is_err());
+    // Plain HTTP must be asked for, the API server cannot call it
+    assert!(Args::try_parse_from(["kimspect", "webhook", "--enforce"]).is_err());
+    assert!(
+        Args::try_parse_from([
+            "kimspect",
+            "webhook",
+            "--insecure",
+            "--tls-cert",
+            "tls.crt",
+            "--tls-key",
+            "tls.key"
+        ])
+        .is_err()
+    );
+    assert!(
+        Args::try_parse_from([
+            "kimspect",
+            "webhook",
+            "--tls-cert",
+            "tls.crt",
+            "--tls-key",
+            "tls.key"
+        ])
+        .is_ok()
+    );
+}
+
+#[test]
+fn test_cli_parse_pin() {
+    let args = Args::parse_from(["kimspect", "pin"]);
+    assert!(args.command.needs_cluster());
+    let Commands::Pin {
+        workload,
+        namespace,
+        all_namespaces,
+        format,
+        ..
+    } = args.command
+    else {
+        panic!("Expected Commands::Pin variant");
+    };
+    assert_eq!(workload, None);
+    assert_eq!(namespace, "default");
+    assert!(!all_namespaces);
+    assert_eq!(format, PinFormat::Kubectl);
+
+    let args = Args::parse_from([
+        "kimspect",
+        "pin",
+        "deploy/web",
+        "-n",
+        "shop",
+        "--format",
+        "kustomize",
+    ]);
+    let Commands::Pin {
+        workload,
+        namespace,
+        format,
+        ..
+    } = args.command
+    else {
+        panic!("Expected Commands::Pin variant");
+    };
+    assert_eq!(workload.as_deref(), Some("deploy/web"));
+    assert_eq!(namespace, "shop");
+    assert_eq!(format, PinFormat::Kustomize);
+    assert!(Args::try_parse_from(["kimspect", "pin", "--format", "helm"]).is_err());
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use common::pod_image;
+use kimspect::cli::{CustomColumn, OutputFormat};
+use kimspect::{ImageField, PodImage, sort_pod_images};
+
+fn create_pod_image(pod: &str, namespace: &str, registry: &str, size: Option<u64>) -> PodImage {
+    let mut image = pod_image(pod, namespace, registry, "org/app", "1.0");
+    image.image_size_bytes = size;
+    image
+}
+
+#[test]
+fn test_parse_output_format() {
+    assert_eq!("wide".parse::<OutputFormat>(), Ok(OutputFormat::Wide));
+    assert_eq!(
+        "custom-columns=REF:reference,managed_by".parse::<OutputFormat>(),
+        Ok(OutputFormat::CustomColumns(vec![
+            CustomColumn {
+                header: "REF".to_string(),
+                field: ImageField::Reference,
+            },
+            CustomColumn {
+                header: "MANAGED-BY".to_string(),
+                field: ImageField::ManagedBy,
+            },
+        ]))
+    );
+
+    let invalid = [
+        "yaml",
+        "custom-columns=",
+        "custom-columns=:pod",
+        "custom-columns=A:b",
+    ];
+    for value in invalid {
+        assert!(value.parse::<OutputFormat>().is_err(), "{}", value);
+    }
+}
+
+#[test]
+fn test_output_format_round_trip() {
+    let format: OutputFormat = "custom-columns=NS:namespace,POD:pod".parse().unwrap();
+    assert!(format.selects_fields());
+    assert_eq!(format.to_string(), "custom-columns=NS:namespace,POD:pod");
+    assert_eq!(format.to_string().parse::<OutputFormat>(), Ok(format));
+}
+
+#[test]
+fn test_pod_image_field() {
+    let mut image = create_pod_image("web", "shop", "ghcr.io", None);
+    image.upstream_registry = "docker.io".to_string();
+    image.upstream_image = "library/nginx".to_string();
+
+    assert_eq!(image.field(ImageField::Pod), "web");
+    assert_eq!(image.field(ImageField::Reference), "ghcr.io/org/app:1.0");
+    assert_eq!(image.field(ImageField::Upstream), "docker.io/library/nginx");
+    assert_eq!(image.field(ImageField::Digest), "");
+    assert_eq!(ImageField::ManagedBy.header(), "MANAGED-BY");
+}
+
+#[test]
+fn test_sort_pod_images() {
+    let mut images = vec![
+        create_pod_image("a", "web", "quay.io", Some(2048)),
+        create_pod_image("b", "api", "ghcr.io", None),
+        create_pod_image("c", "web", "docker.io", Some(1 << 30)),
+        create_pod_image("d", "api", "docker.io", Some(10)),
+    ];
+    let pods = |images: &[PodImage]| -> Vec<String> {
+        images.iter().map(|image| image.pod_name.clone()).collect()
+    };
+
+    // Stable: pods of the same namespace keep their order
+    sort_pod_images(&mut images, ImageField::Namespace);
+    assert_eq!(pods(&images), ["b", "d", "a", "c"]);
+
+    sort_pod_images(&mut images, ImageField::Registry);
+    assert_eq!(pods(&images), ["d", "c", "b", "a"]);
+
+    // Largest first, unknown sizes last
+    sort_pod_images(&mut images, ImageField::Size);
+    assert_eq!(pods(&images), ["c", "a", "d", "b"]);
+}
@@ -0,0 +1,174 @@
+#![allow(dead_code)]
+
+use k8s_openapi::api::core::v1::{Container, ContainerStatus, Pod, PodSpec};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::OwnerReference;
+use kimspect::PodImage;
+
+pub const DIGEST: &str = "sha256:a3ed95caeb02ffe68cdd9fd84406680ae93d633cb16422d00e8a7c22955b46d4";
+pub const OTHER_DIGEST: &str =
+    "sha256:0b6f8d4e6c2e1f36e4bb7dd2a5d7ae4a02fc3c3b8b2bdf2e0a3c1ad3c3a1c2b7";
+
+/// Builder of the pods the tests hand to the library, as the API server would return them
+pub struct PodBuilder {
+    pod: Pod,
+}
+
+impl PodBuilder {
+    /// Start a pod without containers
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the pod
+    /// * `namespace` - Namespace of the pod
+    pub fn new(name: &str, namespace: &str) -> Self {
+        let mut pod = Pod::default();
+        pod.metadata.name = Some(name.to_string());
+        pod.metadata.namespace = Some(namespace.to_string());
+        pod.spec = Some(PodSpec::default());
+        Self { pod }
+    }
+
+    /// Schedule the pod on a node
+    pub fn node(mut self, node: &str) -> Self {
+        self.spec().node_name = Some(node.to_string());
+        self
+    }
+
+    /// Add a label to the pod
+    pub fn label(mut self, key: &str, value: &str) -> Self {
+        self.pod
+            .metadata
+            .labels
+            .get_or_insert_default()
+            .insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Set the controller owning the pod
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - Kind of the owner (e.g. ReplicaSet)
+    /// * `name` - Name of the owner
+    pub fn owner(mut self, kind: &str, name: &str) -> Self {
+        self.pod.metadata.owner_references = Some(vec![OwnerReference {
+            api_version: "apps/v1".to_string(),
+            kind: kind.to_string(),
+            name: name.to_string(),
+            controller: Some(true),
+            ..Default::default()
+        }]);
+        self
+    }
+
+    /// Add a container to the pod spec
+    pub fn container(mut self, name: &str, image: &str) -> Self {
+        self.spec().containers.push(Container {
+            name: name.to_string(),
+            image: Some(image.to_string()),
+            ..Default::default()
+        });
+        self
+    }
+
+    /// Set the pull policy of the last container added
+    pub fn pull_policy(mut self, policy: &str) -> Self {
+        if let Some(container) = self.spec().containers.last_mut() {
+            container.image_pull_policy = Some(policy.to_string());
+        }
+        self
+    }
+
+    /// Report a ready container status
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the container
+    /// * `image` - Image the container runs
+    /// * `image_id` - Image ID the runtime resolved the image to
+    pub fn status(mut self, name: &str, image: &str, image_id: &str) -> Self {
+        self.pod
+            .status
+            .get_or_insert_default()
+            .container_statuses
+            .get_or_insert_default()
+            .push(ContainerStatus {
+                name: name.to_string(),
+                image: image.to_string(),
+                image_id: image_id.to_string(),
+                ready: true,
+                ..Default::default()
+            });
+        self
+    }
+
+    /// Add a container to the spec and report it running the same image
+    pub fn running(self, name: &str, image: &str, image_id: &str) -> Self {
+        self.container(name, image).status(name, image, image_id)
+    }
+
+    /// Add an init container that ran to completion with the image
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - Name of the init container
+    /// * `image` - Image of the init container
+    /// * `image_id` - Image ID the runtime resolved the image to
+    pub fn init_container(mut self, name: &str, image: &str, image_id: &str) -> Self {
+        self.spec()
+            .init_containers
+            .get_or_insert_default()
+            .push(Container {
+                name: name.to_string(),
+                image: Some(image.to_string()),
+                ..Default::default()
+            });
+        self.pod
+            .status
+            .get_or_insert_default()
+            .init_container_statuses
+            .get_or_insert_default()
+            .push(ContainerStatus {
+                name: name.to_string(),
+                image: image.to_string(),
+                image_id: image_id.to_string(),
+                ..Default::default()
+            });
+        self
+    }
+
+    /// Finish the pod
+    pub fn build(self) -> Pod {
+        self.pod
+    }
+
+    fn spec(&mut self) -> &mut PodSpec {
+        self.pod.spec.get_or_insert_default()
+    }
+}
+
+/// Build the image of a container named app, as process_pod would report it
+///
+/// # Arguments
+///
+/// * `pod` - Name of the pod
+/// * `namespace` - Namespace of the pod
+/// * `registry` - Registry the image is pulled from
+/// * `name` - Image name without registry and tag
+/// * `version` - Tag of the image
+pub fn pod_image(
+    pod: &str,
+    namespace: &str,
+    registry: &str,
+    name: &str,
+    version: &str,
+) -> PodImage {
+    let mut image = PodImage::default();
+    image.pod_name = pod.to_string();
+    image.namespace = namespace.to_string();
+    image.container_name = "app".to_string();
+    image.registry = registry.to_string();
+    image.image_name = name.to_string();
+    image.image_version = version.to_string();
+    image
+}
@@ -0,0 +1,302 @@
+use anyhow::Result;
+use clap::{CommandFactory, FromArgMatches};
+use kimspect::cli::{Args, Commands, GetImages, OutputFormat};
+use kimspect::{Config, ImageField, Settings};
+
+const CONFIG: &str = r#"
+[defaults]
+exclude-registries = ["registry.k8s.io"]
+exclude-namespaces = ["kube-system"]
+
+[defaults.registries]
+"registry.corp" = "internal"
+
+[defaults.mirrors]
+"mirror.corp/dockerhub" = "docker.io"
+
+[profiles.audit]
+all-namespaces = true
+output = "wide"
+
+[profiles.audit.registries]
+"registry.corp" = "first-party"
+"vendor.io" = "vendor"
+
+[profiles.payments]
+namespaces = ["payments", "payments-jobs"]
+exclude-registries = []
+
+[profiles.inventory]
+columns = ["namespace", "pod", "image", "digest"]
+sort-by = "namespace"
+"#;
+
+/// Parse a command line and apply config settings to it
+fn parse_with_settings(settings: &Settings, argv: &[&str]) -> Args {
+    let matches = Args::command().get_matches_from(argv);
+    let mut args = Args::from_arg_matches(&matches).unwrap();
+    args.apply_settings(settings, &matches);
+    args
+}
+
+#[test]
+fn test_config_profiles_override_defaults() -> Result<()> {
+    let config = Config::parse(CONFIG)?;
+    assert_eq!(config.profiles.len(), 3);
+
+    let defaults = config.settings(None)?;
+    assert_eq!(defaults.output, None);
+    assert_eq!(
+        defaults.exclude_registries,
+        Some(vec!["registry.k8s.io".to_string()])
+    );
+
+    let audit = config.settings(Some("audit"))?;
+    assert_eq!(audit.output, Some(OutputFormat::Wide));
+    assert_eq!(audit.all_namespaces, Some(true));
+    assert_eq!(audit.exclude_registries, defaults.exclude_registries);
+    let rules = audit.registry_rules()?;
+    assert_eq!(rules.len(), 2);
+    assert!(
+        rules
+            .iter()
+            .any(|rule| rule.host == "registry.corp" && rule.class == "first-party")
+    );
+    assert_eq!(audit.mirror_rules()?[0].upstream, "docker.io");
+
+    // An empty list in a profile clears the default
+    let payments = config.settings(Some("payments"))?;
+    assert_eq!(payments.exclude_registries, Some(Vec::new()));
+
+    let err = config.settings(Some("nope")).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Profile 'nope' not found, available profiles: audit, inventory, payments"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_config_profile_namespaces_replace_all_namespaces() -> Result<()> {
+    let config = Config::parse(
+        r#"
+[defaults]
+all-namespaces = true
+
+[profiles.payments]
+namespaces = ["payments"]
+
+[profiles.audit]
+output = "wide"
+"#,
+    )?;
+
+    let payments = config.settings(Some("payments"))?;
+    assert_eq!(payments.namespaces, Some(vec!["payments".to_string()]));
+    assert_eq!(payments.all_namespaces, None);
+
+    let args = parse_with_settings(&payments, &["kimspect", "get", "images"]);
+    let Commands::Get {
+        resource:
+            GetImages::Images {
+                namespace,
+                all_namespaces,
+                ..
+            },
+    } = args.command
+    else {
+        panic!("Expected get images");
+    };
+    assert_eq!(namespace, vec!["payments"]);
+    assert!(!all_namespaces);
+
+    // A profile without namespaces keeps the default scope
+    let audit = config.settings(Some("audit"))?;
+    assert_eq!(audit.all_namespaces, Some(true));
+    Ok(())
+}
+
+#[test]
+fn test_config_rejects_invalid_files() {
+    assert!(Config::parse("[defaults]\nouptut = \"wide\"\n").is_err());
+    assert!(Config::parse("[defaults]\noutput = \"yaml\"\n").is_err());
+    assert!(Config::parse("[defaults]\ncolumns = [\"pod\", \"bogus\"]\n").is_err());
+    assert!(Config::parse("[defaults\n").is_err());
+
+    let config = Config::parse("[defaults.registries]\n\"registry.corp/team\" = \"internal\"\n");
+    assert!(config.unwrap().defaults.registry_rules().is_err());
+}
+
+#[test]
+fn test_config_missing_default_file_is_empty() -> Result<()> {
+    assert_eq!(Config::parse("")?, Config::default());
+    assert!(Config::load(Some(std::path::Path::new("/nonexistent/config.toml"))).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_apply_settings_fills_missing_options() -> Result<()> {
+    let config = Config::parse(CONFIG)?;
+    let settings = config.settings(Some("audit"))?;
+
+    let args = parse_with_settings(&settings, &["kimspect", "get", "images"]);
+    let Commands::Get {
+        resource:
+            GetImages::Images {
+                all_namespaces,
+                exclude_registry,
+                exclude_namespace,
+                output,
+                ..
+            },
+    } = args.command
+    else {
+        panic!("Expected GetImages::Images variant");
+    };
+    assert!(all_namespaces);
+    assert_eq!(exclude_registry, ["registry.k8s.io"]);
+    assert_eq!(exclude_namespace, ["kube-system"]);
+    assert_eq!(output, OutputFormat::Wide);
+
+    // Commands with a single namespace use the first configured one
+    let settings = config.settings(Some("payments"))?;
+    let args = parse_with_settings(&settings, &["kimspect", "get", "image-errors"]);
+    let Commands::Get {
+        resource: GetImages::ImageErrors { namespace, .. },
+    } = args.command
+    else {
+        panic!("Expected GetImages::ImageErrors variant");
+    };
+    assert_eq!(namespace, "payments");
+    Ok(())
+}
+
+#[test]
+fn test_apply_settings_command_line_wins() -> Result<()> {
+    let config = Config::parse(CONFIG)?;
+    let settings = config.settings(Some("audit"))?;
+
+    let args = parse_with_settings(
+        &settings,
+        &[
+            "kimspect",
+            "get",
+            "images",
+            "-n",
+            "web",
+            "-o",
+            "normal",
+            "--registry",
+            "ghcr.io",
+        ],
+    );
+    let Commands::Get {
+        resource:
+            GetImages::Images {
+                namespace,
+                all_namespaces,
+                exclude_registry,
+                output,
+                ..
+            },
+    } = args.command
+    else {
+        panic!("Expected GetImages::Images variant");
+    };
+    assert_eq!(namespace, ["web"]);
+    assert!(!all_namespaces);
+    assert!(exclude_registry.is_empty());
+    assert_eq!(output, OutputFormat::Normal);
+    Ok(())
+}
+
+#[test]
+fn test_apply_settings_columns() -> Result<()> {
+    let config = Config::parse(CONFIG)?;
+    let settings = config.settings(Some("inventory"))?;
+
+    let args = parse_with_settings(&settings, &["kimspect", "get", "images"]);
+    let Commands::Get {
+        resource: GetImages::Images {
+            columns, sort_by, ..
+        },
+    } = args.command
+    else {
+        panic!("Expected GetImages::Images variant");
+    };
+    assert_eq!(
+        columns,
+        [
+            ImageField::Namespace,
+            ImageField::Pod,
+            ImageField::Image,
+            ImageField::Digest
+        ]
+    );
+    assert_eq!(sort_by, Some(ImageField::Namespace));
+
+    // An output format on the command line replaces the configured columns
+    let args = parse_with_settings(&settings, &["kimspect", "get", "images", "-o", "wide"]);
+    let Commands::Get {
+        resource: GetImages::Images {
+            columns, output, ..
+        },
+    } = args.command
+    else {
+        panic!("Expected GetImages::Images variant");
+    };
+    assert!(columns.is_empty());
+    assert_eq!(output, OutputFormat::Wide);
+
+    // Configured custom columns are ignored by other commands
+    let settings =
+        Config::parse("[defaults]\noutput = \"custom-columns=NODE:node\"\n")?.settings(None)?;
+    let args = parse_with_settings(&settings, &["kimspect", "get", "nodes"]);
+    assert!(args.command.check_output().is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_apply_settings_webhook_policy() -> Result<()> {
+    let settings = Config::parse(
+        "[defaults]\ndisallowed-registries = [\"docker.io\"]\ndisallowed-classes = [\"unknown\"]\n\
+         deny-latest = true\n",
+    )?
+    .settings(None)?;
+
+    let args = parse_with_settings(&settings, &["kimspect", "webhook", "--insecure"]);
+    let Commands::Webhook {
+        disallow_registry,
+        disallow_class,
+        deny_latest,
+        require_digest,
+        ..
+    } = args.command
+    else {
+        panic!("Expected Commands::Webhook variant");
+    };
+    assert_eq!(disallow_registry, ["docker.io"]);
+    assert_eq!(disallow_class, ["unknown"]);
+    assert!(deny_latest);
+    assert!(!require_digest);
+
+    // Registries on the command line replace the configured ones
+    let args = parse_with_settings(
+        &settings,
+        &[
+            "kimspect",
+            "webhook",
+            "--insecure",
+            "--disallow-registry",
+            "quay.io",
+        ],
+    );
+    let Commands::Webhook {
+        disallow_registry, ..
+    } = args.command
+    else {
+        panic!("Expected Commands::Webhook variant");
+    };
+    assert_eq!(disallow_registry, ["quay.io"]);
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use kimspect::{K8sClient, K8sError};
+use kimspect::{ImageQuery, K8sClient, K8sError};
 
 #[tokio::test]
 async fn test_k8s_client_creation() -> Result<()> {
@@ -11,10 +11,7 @@ async fn test_k8s_client_creation() -> Result<()> {
 #[tokio::test]
 async fn test_get_pod_images() -> Result<()> {
     let client = K8sClient::new().await?;
-    // Updated to include the new all_namespaces parameter
-    let result = client
-        .get_pod_images("default", None, None, None, &[], false)
-        .await;
+    let result = client.get_pod_images(&ImageQuery::new()).await;
 
     // In CI environments, there might not be any pods in the default namespace
     // So we should accept both successful empty results and ResourceNotFound errors
@@ -44,7 +41,7 @@ async fn test_get_pod_images_with_node() -> Result<()> {
     let client = K8sClient::new().await?;
     // Test that we get a ResourceNotFound error when querying a non-existent node
     let result = client
-        .get_pod_images("default", Some("non-existent-node"), None, None, &[], false)
+        .get_pod_images(&ImageQuery::new().node("non-existent-node"))
         .await;
     assert!(matches!(result, Err(e) if e.downcast_ref::<K8sError>().is_some()));
     Ok(())
@@ -55,7 +52,7 @@ async fn test_get_pod_images_all_namespaces() -> Result<()> {
     let client = K8sClient::new().await?;
     // Test the new all_namespaces functionality
     let _images = client
-        .get_pod_images("default", None, None, None, &[], true)
+        .get_pod_images(&ImageQuery::new().all_namespaces(true))
         .await?;
     // We can't assert specific values here as they depend on the cluster state
     // but we can verify the function doesn't panic
@@ -67,7 +64,11 @@ async fn test_get_pod_images_with_node_and_all_namespaces() -> Result<()> {
     let client = K8sClient::new().await?;
     // Test that we get a ResourceNotFound error when querying a non-existent node across all namespaces
     let result = client
-        .get_pod_images("default", Some("non-existent-node"), None, None, &[], true)
+        .get_pod_images(
+            &ImageQuery::new()
+                .node("non-existent-node")
+                .all_namespaces(true),
+        )
         .await;
     assert!(matches!(result, Err(e) if e.downcast_ref::<K8sError>().is_some()));
     Ok(())
@@ -78,7 +79,11 @@ async fn test_get_pod_images_with_pod_and_all_namespaces() -> Result<()> {
     let client = K8sClient::new().await?;
     // Test that we get a ResourceNotFound error when querying a non-existent pod across all namespaces
     let result = client
-        .get_pod_images("default", None, Some("non-existent-pod"), None, &[], true)
+        .get_pod_images(
+            &ImageQuery::new()
+                .pod("non-existent-pod")
+                .all_namespaces(true),
+        )
         .await;
     assert!(matches!(result, Err(e) if e.downcast_ref::<K8sError>().is_some()));
     Ok(())
@@ -1,6 +1,13 @@
-use k8s_openapi::api::core::v1::{Container, Pod, PodSpec};
+use k8s_openapi::api::core::v1::{
+    Container, ContainerState, ContainerStateRunning, ContainerStateWaiting, ContainerStatus,
+    Event, ObjectReference, Pod, PodSpec, PodStatus,
+};
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
-use kimspect::{extract_registry, process_pod, split_image};
+use kimspect::{
+    K8sError, api_error, attach_event_messages, extract_registry, process_pod, process_pod_errors,
+    split_image,
+};
+use kube::core::Status;
 
 fn create_test_pod(name: &str, namespace: &str, containers: Vec<Container>) -> Pod {
     Pod {
@@ -25,6 +32,25 @@ fn create_test_container(name: &str, image: &str) -> Container {
     }
 }
 
+fn create_container_status(name: &str, image: &str, state: ContainerState) -> ContainerStatus {
+    ContainerStatus {
+        name: name.to_string(),
+        image: image.to_string(),
+        state: Some(state),
+        ..Default::default()
+    }
+}
+
+fn waiting_state(reason: &str, message: &str) -> ContainerState {
+    ContainerState {
+        waiting: Some(ContainerStateWaiting {
+            reason: Some(reason.to_string()),
+            message: Some(message.to_string()),
+        }),
+        ..Default::default()
+    }
+}
+
 #[test]
 fn test_extract_registry() {
     let test_cases = vec![
@@ -36,8 +62,9 @@ fn test_extract_registry() {
         ("localhost:5000/nginx:latest", "localhost:5000"),     // Localhost with port
         ("127.0.0.1:5000/nginx:latest", "127.0.0.1:5000"),     // Localhost IP with port
         ("nginx", "docker.io"),                                // Bare image name
-        ("", "docker.io"),                                     // Empty string
-        ("invalid/registry", "docker.io"),                     // Invalid registry
+        ("", ""),                                              // Empty string
+        ("Invalid/Name:tag", ""),                              // Invalid reference
+        ("invalid/registry", "docker.io"),                     // Docker Hub repository
         (
             "registry.example.com:8080/image:tag",
             "registry.example.com:8080",
@@ -50,6 +77,10 @@ fn test_extract_registry() {
         ), // Domain with subpath
         ("192.168.1.100:5000/image:latest", "192.168.1.100:5000"), // IP address registry
         ("0.0.0.0:5000/image:latest", "0.0.0.0:5000"),         // Zero IP address
+        (
+            "us-docker.pkg.dev/project/repo/image:v1",
+            "us-docker.pkg.dev",
+        ), // Artifact Registry
     ];
 
     for (image, expected) in test_cases {
@@ -78,7 +109,8 @@ fn test_split_image() {
             "my-registry:5000/nginx",
             "1.21",
         ),
-        ("", "", "latest"),
+        ("", "", ""),
+        ("Invalid/Name:tag", "Invalid/Name:tag", ""),
         (
             "registry.example.com:8080/image:tag",
             "registry.example.com:8080/image",
@@ -99,18 +131,24 @@ fn test_split_image() {
             "registry.example.com/image/subpath",
             "tag",
         ),
-        ("image:tag@sha256:abc123", "image", "tag@sha256:abc123"),
-        ("image@sha256:abc123", "image", "latest@sha256:abc123"),
         (
-            "my-registry:5000/nginx:latest",
-            "my-registry:5000/nginx",
-            "latest",
+            "image:tag@sha256:a3ed95caeb02ffe68cdd9fd84406680ae93d633cb16422d00e8a7c22955b46d4",
+            "image",
+            "tag@sha256:a3ed95caeb02ffe68cdd9fd84406680ae93d633cb16422d00e8a7c22955b46d4",
         ),
         (
-            "docker.io/library/nginx:latest",
-            "docker.io/library/nginx",
+            "image@sha256:a3ed95caeb02ffe68cdd9fd84406680ae93d633cb16422d00e8a7c22955b46d4",
+            "image",
+            "@sha256:a3ed95caeb02ffe68cdd9fd84406680ae93d633cb16422d00e8a7c22955b46d4",
+        ),
+        (
+            "my-registry:5000/nginx:latest",
+            "my-registry:5000/nginx",
             "latest",
         ),
+        ("docker.io/library/nginx:latest", "nginx", "latest"),
+        ("library/nginx:1.25", "nginx", "1.25"),
+        ("index.docker.io/bitnami/redis:7", "bitnami/redis", "7"),
     ];
 
     for (image, expected_name, expected_version) in test_cases {
@@ -120,8 +158,6 @@ fn test_split_image() {
     }
 }
 
-// docker pull kubeflownotebookswg/base:v1.10.0-rc.1@sha256:asdasd
-
 #[test]
 fn test_process_pod() {
     let pod = create_test_pod(
@@ -211,19 +247,42 @@ fn test_process_pod_with_complex_image() {
     assert_eq!(images[0].registry, "quay.io");
 }
 
+#[test]
+fn test_process_pod_with_invalid_image() {
+    let pod = create_test_pod(
+        "test-pod",
+        "default",
+        vec![create_test_container("app", "Invalid/Name:tag")],
+    );
+
+    let images = process_pod(&pod);
+    assert_eq!(images.len(), 1);
+
+    // Invalid references keep the raw string with an empty registry and version
+    assert_eq!(images[0].image_name, "Invalid/Name:tag");
+    assert_eq!(images[0].image_version, "");
+    assert_eq!(images[0].registry, "");
+}
+
 #[test]
 fn test_process_pod_with_digest() {
     let pod = create_test_pod(
         "test-pod",
         "default",
-        vec![create_test_container("nginx", "nginx@sha256:abc123def456")],
+        vec![create_test_container(
+            "nginx",
+            "nginx@sha256:a3ed95caeb02ffe68cdd9fd84406680ae93d633cb16422d00e8a7c22955b46d4",
+        )],
     );
 
     let images = process_pod(&pod);
     assert_eq!(images.len(), 1);
 
     assert_eq!(images[0].image_name, "nginx");
-    assert_eq!(images[0].image_version, "latest@sha256:abc123def456");
+    assert_eq!(
+        images[0].image_version,
+        "@sha256:a3ed95caeb02ffe68cdd9fd84406680ae93d633cb16422d00e8a7c22955b46d4"
+    );
     assert_eq!(images[0].registry, "docker.io");
 }
 
@@ -234,7 +293,7 @@ fn test_process_pod_with_tag_and_digest() {
         "default",
         vec![create_test_container(
             "nginx",
-            "nginx:1.21@sha256:abc123def456",
+            "nginx:1.21@sha256:a3ed95caeb02ffe68cdd9fd84406680ae93d633cb16422d00e8a7c22955b46d4",
         )],
     );
 
@@ -242,7 +301,10 @@ fn test_process_pod_with_tag_and_digest() {
     assert_eq!(images.len(), 1);
 
     assert_eq!(images[0].image_name, "nginx");
-    assert_eq!(images[0].image_version, "1.21@sha256:abc123def456");
+    assert_eq!(
+        images[0].image_version,
+        "1.21@sha256:a3ed95caeb02ffe68cdd9fd84406680ae93d633cb16422d00e8a7c22955b46d4"
+    );
     assert_eq!(images[0].registry, "docker.io");
 }
 
@@ -253,7 +315,7 @@ fn test_process_pod_with_registry_and_digest() {
         "default",
         vec![create_test_container(
             "nginx",
-            "gcr.io/nginx@sha256:abc123def456",
+            "gcr.io/nginx@sha256:a3ed95caeb02ffe68cdd9fd84406680ae93d633cb16422d00e8a7c22955b46d4",
         )],
     );
 
@@ -261,7 +323,10 @@ fn test_process_pod_with_registry_and_digest() {
     assert_eq!(images.len(), 1);
 
     assert_eq!(images[0].image_name, "nginx");
-    assert_eq!(images[0].image_version, "latest@sha256:abc123def456");
+    assert_eq!(
+        images[0].image_version,
+        "@sha256:a3ed95caeb02ffe68cdd9fd84406680ae93d633cb16422d00e8a7c22955b46d4"
+    );
     assert_eq!(images[0].registry, "gcr.io");
 }
 
@@ -272,7 +337,7 @@ fn test_process_pod_with_full_path_and_digest() {
         "default",
         vec![create_test_container(
             "nginx",
-            "gcr.io/project/nginx:1.21@sha256:abc123def456",
+            "gcr.io/project/nginx:1.21@sha256:a3ed95caeb02ffe68cdd9fd84406680ae93d633cb16422d00e8a7c22955b46d4",
         )],
     );
 
@@ -280,7 +345,10 @@ fn test_process_pod_with_full_path_and_digest() {
     assert_eq!(images.len(), 1);
 
     assert_eq!(images[0].image_name, "project/nginx");
-    assert_eq!(images[0].image_version, "1.21@sha256:abc123def456");
+    assert_eq!(
+        images[0].image_version,
+        "1.21@sha256:a3ed95caeb02ffe68cdd9fd84406680ae93d633cb16422d00e8a7c22955b46d4"
+    );
     assert_eq!(images[0].registry, "gcr.io");
 }
 
@@ -290,9 +358,18 @@ fn test_process_pod_with_multiple_digest_containers() {
         "test-pod",
         "default",
         vec![
-            create_test_container("nginx", "nginx:1.21@sha256:abc123def456"),
-            create_test_container("redis", "redis:6.2@sha256:def456ghi789"),
-            create_test_container("etcd", "quay.io/coreos/etcd@sha256:ghi789jkl012"),
+            create_test_container(
+                "nginx",
+                "nginx:1.21@sha256:a3ed95caeb02ffe68cdd9fd84406680ae93d633cb16422d00e8a7c22955b46d4",
+            ),
+            create_test_container(
+                "redis",
+                "redis:6.2@sha256:5b0bcabd1ed22e9fb1310cf6c2dec7cdef19f0ad69efa1f392e94a4333501270",
+            ),
+            create_test_container(
+                "etcd",
+                "quay.io/coreos/etcd@sha256:e6a0117ec169eda93dc5ca978c6ac87580e36765a66097a6bfb6639a3bd4038a",
+            ),
         ],
     );
 
@@ -300,15 +377,24 @@ fn test_process_pod_with_multiple_digest_containers() {
     assert_eq!(images.len(), 3);
 
     assert_eq!(images[0].image_name, "nginx");
-    assert_eq!(images[0].image_version, "1.21@sha256:abc123def456");
+    assert_eq!(
+        images[0].image_version,
+        "1.21@sha256:a3ed95caeb02ffe68cdd9fd84406680ae93d633cb16422d00e8a7c22955b46d4"
+    );
     assert_eq!(images[0].registry, "docker.io");
 
     assert_eq!(images[1].image_name, "redis");
-    assert_eq!(images[1].image_version, "6.2@sha256:def456ghi789");
+    assert_eq!(
+        images[1].image_version,
+        "6.2@sha256:5b0bcabd1ed22e9fb1310cf6c2dec7cdef19f0ad69efa1f392e94a4333501270"
+    );
     assert_eq!(images[1].registry, "docker.io");
 
     assert_eq!(images[2].image_name, "coreos/etcd");
-    assert_eq!(images[2].image_version, "latest@sha256:ghi789jkl012");
+    assert_eq!(
+        images[2].image_version,
+        "@sha256:e6a0117ec169eda93dc5ca978c6ac87580e36765a66097a6bfb6639a3bd4038a"
+    );
     assert_eq!(images[2].registry, "quay.io");
 }
 
@@ -442,3 +528,175 @@ fn test_registry_filtering_with_empty_pod() {
         .collect();
     assert_eq!(filtered_images.len(), 0);
 }
+
+#[test]
+fn test_process_pod_with_status() {
+    let mut pod = create_test_pod(
+        "test-pod",
+        "default",
+        vec![
+            create_test_container("nginx", "nginx:latest"),
+            create_test_container("app", "ghcr.io/org/app:v1"),
+        ],
+    );
+    pod.status = Some(PodStatus {
+        container_statuses: Some(vec![
+            create_container_status(
+                "nginx",
+                "docker.io/library/nginx:latest",
+                ContainerState {
+                    running: Some(ContainerStateRunning::default()),
+                    ..Default::default()
+                },
+            ),
+            create_container_status(
+                "app",
+                "ghcr.io/org/app:v1",
+                waiting_state("ImagePullBackOff", "Back-off pulling image"),
+            ),
+        ]),
+        ..Default::default()
+    });
+
+    let images = process_pod(&pod);
+    assert_eq!(images.len(), 2);
+    assert_eq!(images[0].status, "Running");
+    assert_eq!(images[1].status, "ImagePullBackOff");
+}
+
+#[test]
+fn test_process_pod_errors() {
+    let mut pod = create_test_pod(
+        "test-pod",
+        "default",
+        vec![
+            create_test_container("nginx", "nginx:latest"),
+            create_test_container("app", "ghcr.io/org/app:v1"),
+            create_test_container("sidecar", "quay.io/org/sidecar:v2"),
+        ],
+    );
+    pod.status = Some(PodStatus {
+        container_statuses: Some(vec![
+            create_container_status(
+                "nginx",
+                "docker.io/library/nginx:latest",
+                waiting_state("ContainerCreating", ""),
+            ),
+            create_container_status(
+                "app",
+                "ghcr.io/org/app:v1",
+                waiting_state("ErrImagePull", "pull access denied"),
+            ),
+            create_container_status(
+                "sidecar",
+                "",
+                waiting_state("InvalidImageName", "couldn't parse image name"),
+            ),
+        ]),
+        ..Default::default()
+    });
+
+    let errors = process_pod_errors(&pod);
+    assert_eq!(errors.len(), 2);
+
+    assert_eq!(errors[0].container_name, "app");
+    assert_eq!(errors[0].reason, "ErrImagePull");
+    assert_eq!(errors[0].registry, "ghcr.io");
+    assert_eq!(errors[0].message, "pull access denied");
+
+    // Falls back to the spec image when the status image is not reported yet
+    assert_eq!(errors[1].container_name, "sidecar");
+    assert_eq!(errors[1].image, "quay.io/org/sidecar:v2");
+    assert_eq!(errors[1].registry, "quay.io");
+}
+
+#[test]
+fn test_process_pod_errors_without_status() {
+    let pod = create_test_pod(
+        "test-pod",
+        "default",
+        vec![create_test_container("nginx", "nginx:latest")],
+    );
+    assert!(process_pod_errors(&pod).is_empty());
+}
+
+#[test]
+fn test_attach_event_messages() {
+    let mut pod = create_test_pod(
+        "test-pod",
+        "default",
+        vec![create_test_container("app", "ghcr.io/org/app:v1")],
+    );
+    pod.status = Some(PodStatus {
+        container_statuses: Some(vec![create_container_status(
+            "app",
+            "ghcr.io/org/app:v1",
+            waiting_state("ImagePullBackOff", "Back-off pulling image"),
+        )]),
+        ..Default::default()
+    });
+
+    let event = |name: &str, field_path: &str, message: &str| Event {
+        involved_object: ObjectReference {
+            kind: Some("Pod".to_string()),
+            name: Some(name.to_string()),
+            namespace: Some("default".to_string()),
+            field_path: Some(field_path.to_string()),
+            ..Default::default()
+        },
+        reason: Some("Failed".to_string()),
+        message: Some(message.to_string()),
+        ..Default::default()
+    };
+
+    let events = vec![
+        event("other-pod", "spec.containers{app}", "unrelated failure"),
+        event(
+            "test-pod",
+            "spec.containers{app}",
+            "Failed to pull image: 401 Unauthorized",
+        ),
+    ];
+
+    let mut errors = process_pod_errors(&pod);
+    attach_event_messages(&mut errors, &events);
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "Failed to pull image: 401 Unauthorized");
+}
+
+fn status_error(code: u16, reason: &str) -> kube::Error {
+    kube::Error::Api(Box::new(Status {
+        code,
+        reason: reason.to_string(),
+        message: format!("request failed with {}", reason),
+        ..Default::default()
+    }))
+}
+
+#[test]
+fn test_api_error_names_missing_permission() {
+    let err = api_error(status_error(403, "Forbidden"), "list", "pods", Some("web"));
+    assert!(matches!(err, K8sError::PermissionDenied { .. }));
+    assert_eq!(
+        err.to_string(),
+        "Permission denied: cannot list pods in namespace \"web\""
+    );
+
+    let err = api_error(status_error(403, "Forbidden"), "list", "nodes", None);
+    assert_eq!(
+        err.to_string(),
+        "Permission denied: cannot list nodes cluster-wide"
+    );
+}
+
+#[test]
+fn test_api_error_classifies_status_codes() {
+    let err = api_error(status_error(401, "Unauthorized"), "list", "pods", None);
+    assert!(
+        matches!(err, K8sError::ConnectionError(message) if message.starts_with("Unauthorized"))
+    );
+
+    let err = api_error(status_error(500, "InternalError"), "list", "pods", None);
+    assert!(matches!(err, K8sError::ApiError(message) if message.contains("InternalError")));
+}
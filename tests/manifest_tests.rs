@@ -0,0 +1,297 @@
+use anyhow::Result;
+use k8s_openapi::api::batch::v1::{CronJob, Job};
+use k8s_openapi::jiff::Timestamp;
+use kimspect::{
+    FilePodSource, ImageQuery, RECENT_JOB_WINDOW, collect_pod_images, parse_manifests,
+    scheduled_pods,
+};
+use serde_json::json;
+use std::path::PathBuf;
+
+const HELM_RENDERED: &str = r#"
+---
+# Source: web/templates/deployment.yaml
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: web
+spec:
+  template:
+    metadata:
+      labels:
+        app: web
+    spec:
+      containers:
+        - name: nginx
+          image: nginx:1.25
+        - name: exporter
+          image: quay.io/prometheus/nginx-exporter:v1.1
+---
+apiVersion: v1
+kind: Service
+metadata:
+  name: web
+spec:
+  ports:
+    - port: 80
+---
+apiVersion: batch/v1
+kind: CronJob
+metadata:
+  name: backup
+  namespace: ops
+spec:
+  schedule: "0 3 * * *"
+  jobTemplate:
+    spec:
+      template:
+        spec:
+          restartPolicy: OnFailure
+          containers:
+            - name: backup
+              image: ghcr.io/org/backup:2.0
+"#;
+
+const POD_LIST: &str = r#"{
+  "apiVersion": "v1",
+  "kind": "PodList",
+  "items": [
+    {
+      "metadata": { "name": "api-7d9f", "namespace": "default" },
+      "spec": {
+        "nodeName": "worker-1",
+        "containers": [{ "name": "api", "image": "ghcr.io/org/api:v2" }]
+      },
+      "status": {
+        "containerStatuses": [
+          {
+            "name": "api",
+            "image": "ghcr.io/org/api:v2",
+            "imageID": "",
+            "ready": true,
+            "restartCount": 0,
+            "state": { "running": {} }
+          }
+        ]
+      }
+    }
+  ]
+}"#;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("kimspect-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_parse_workload_manifests() -> Result<()> {
+    let pods = parse_manifests(HELM_RENDERED)?;
+    assert_eq!(pods.len(), 2);
+
+    let web = &pods[0];
+    assert_eq!(web.metadata.name.as_deref(), Some("web"));
+    assert!(web.metadata.namespace.is_none());
+    assert_eq!(
+        web.metadata.labels.as_ref().unwrap().get("app"),
+        Some(&"web".to_string())
+    );
+    let owner = &web.metadata.owner_references.as_ref().unwrap()[0];
+    assert_eq!(owner.kind, "Deployment");
+    assert_eq!(owner.name, "web");
+    assert_eq!(web.spec.as_ref().unwrap().containers.len(), 2);
+
+    let backup = &pods[1];
+    assert_eq!(backup.metadata.namespace.as_deref(), Some("ops"));
+    assert_eq!(
+        backup.metadata.owner_references.as_ref().unwrap()[0].kind,
+        "CronJob"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_parse_pod_list_dump() -> Result<()> {
+    let pods = parse_manifests(POD_LIST)?;
+    assert_eq!(pods.len(), 1);
+    assert_eq!(pods[0].metadata.name.as_deref(), Some("api-7d9f"));
+    assert!(pods[0].status.is_some());
+    Ok(())
+}
+
+#[test]
+fn test_parse_invalid_manifest() {
+    let result = parse_manifests("kind: Pod\nmetadata: [not, a, map]\n");
+    assert!(result.is_err());
+
+    let result = parse_manifests("kind: Deployment\nmetadata:\n  name: broken\n");
+    assert!(
+        result
+            .unwrap_err()
+            .to_string()
+            .contains("Deployment broken has no pod template")
+    );
+}
+
+#[tokio::test]
+async fn test_file_pod_source_images() -> Result<()> {
+    let source = FilePodSource::new(parse_manifests(HELM_RENDERED)?);
+
+    // Manifests without a namespace belong to the queried namespace
+    let images = collect_pod_images(&source, &ImageQuery::new().namespace("web")).await?;
+    assert_eq!(images.len(), 2);
+    assert_eq!(images[0].namespace, "web");
+    assert_eq!(images[0].image_name, "nginx");
+    assert_eq!(images[1].registry, "quay.io");
+
+    let images = collect_pod_images(&source, &ImageQuery::new().namespace("ops")).await?;
+    assert_eq!(images.len(), 3);
+
+    let query = ImageQuery::new().all_namespaces(true).registry("ghcr.io");
+    let images = collect_pod_images(&source, &query).await?;
+    assert_eq!(images.len(), 1);
+    assert_eq!(images[0].pod_name, "backup");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_file_pod_source_load_directory() -> Result<()> {
+    let dir = temp_dir("manifests");
+    std::fs::create_dir_all(dir.join("charts"))?;
+    std::fs::write(dir.join("charts").join("web.yaml"), HELM_RENDERED)?;
+    std::fs::write(dir.join("pods.json"), POD_LIST)?;
+    std::fs::write(dir.join("README.md"), "not a manifest")?;
+
+    let source = FilePodSource::load(&dir)?;
+    assert_eq!(source.pods().len(), 3);
+
+    let images = collect_pod_images(&source, &ImageQuery::new().all_namespaces(true)).await?;
+    assert_eq!(images.len(), 4);
+    assert!(
+        images
+            .iter()
+            .any(|image| image.pod_name == "api-7d9f" && image.status == "Running")
+    );
+
+    let single = FilePodSource::load(&dir.join("pods.json"))?;
+    assert_eq!(single.pods().len(), 1);
+
+    assert!(FilePodSource::load(&dir.join("missing.yaml")).is_err());
+
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+/// Build a Job running one image, with an optional owner and status
+fn create_job(name: &str, owner: Option<&str>, status: serde_json::Value) -> Job {
+    let owners: Vec<serde_json::Value> = owner
+        .map(|owner| {
+            json!({
+                "apiVersion": "batch/v1",
+                "kind": "CronJob",
+                "name": owner,
+                "uid": "c-1",
+                "controller": true,
+            })
+        })
+        .into_iter()
+        .collect();
+    serde_json::from_value(json!({
+        "metadata": {
+            "name": name,
+            "namespace": "batch",
+            "creationTimestamp": "2026-10-01T00:00:00Z",
+            "ownerReferences": owners,
+        },
+        "spec": {
+            "template": {
+                "spec": {
+                    "restartPolicy": "Never",
+                    "containers": [{ "name": "main", "image": format!("ghcr.io/org/{}:1", name) }],
+                },
+            },
+        },
+        "status": status,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_scheduled_pods_filter_jobs() {
+    let now: Timestamp = "2026-10-18T12:00:00Z".parse().unwrap();
+    let cronjob: CronJob = serde_json::from_value(json!({
+        "metadata": { "name": "report", "namespace": "batch", "uid": "c-1" },
+        "spec": {
+            "schedule": "0 * * * *",
+            "jobTemplate": {
+                "spec": {
+                    "template": {
+                        "spec": {
+                            "restartPolicy": "Never",
+                            "containers": [{ "name": "main", "image": "ghcr.io/org/report:1" }],
+                        },
+                    },
+                },
+            },
+        },
+    }))
+    .unwrap();
+    let jobs = vec![
+        // Created by the CronJob, whose template is already listed
+        create_job(
+            "report-29100",
+            Some("report"),
+            json!({ "completionTime": "2026-10-18T11:00:00Z" }),
+        ),
+        // Running, its pods are listed as running
+        create_job("migrate", None, json!({ "active": 1 })),
+        create_job(
+            "backfill",
+            None,
+            json!({ "completionTime": "2026-10-18T06:00:00Z" }),
+        ),
+        create_job(
+            "failed",
+            None,
+            json!({
+                "failed": 1,
+                "conditions": [{
+                    "type": "Failed",
+                    "status": "True",
+                    "lastTransitionTime": "2026-10-18T10:00:00Z",
+                }],
+            }),
+        ),
+        // Finished longer than the window ago
+        create_job(
+            "seed",
+            None,
+            json!({ "completionTime": "2026-10-16T06:00:00Z" }),
+        ),
+    ];
+
+    let pods = scheduled_pods(vec![cronjob], jobs, now);
+    let names: Vec<(&str, &str)> = pods
+        .iter()
+        .map(|pod| {
+            let owner = &pod.metadata.owner_references.as_ref().unwrap()[0];
+            (owner.kind.as_str(), pod.metadata.name.as_deref().unwrap())
+        })
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            ("CronJob", "report"),
+            ("Job", "backfill"),
+            ("Job", "failed")
+        ]
+    );
+    assert_eq!(
+        pods[0].spec.as_ref().unwrap().containers[0]
+            .image
+            .as_deref(),
+        Some("ghcr.io/org/report:1")
+    );
+    assert_eq!(RECENT_JOB_WINDOW.as_secs(), 24 * 60 * 60);
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use common::pod_image;
+use k8s_openapi::api::core::v1::{Node, NodeStatus, NodeSystemInfo};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use kimspect::{
+    NodeInfo, Platform, PodImage, find_arch_mismatches, manifest_reference, parse_bearer_challenge,
+    process_node,
+};
+use std::collections::HashMap;
+
+fn create_test_node(name: &str, arch: &str) -> Node {
+    Node {
+        metadata: ObjectMeta {
+            name: Some(name.to_string()),
+            ..Default::default()
+        },
+        status: Some(NodeStatus {
+            node_info: Some(NodeSystemInfo {
+                architecture: arch.to_string(),
+                operating_system: "linux".to_string(),
+                os_image: "Ubuntu 22.04.4 LTS".to_string(),
+                container_runtime_version: "containerd://1.7.13".to_string(),
+                kubelet_version: "v1.31.0".to_string(),
+                kernel_version: "6.5.0".to_string(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn create_pod_image(pod: &str, node: &str, registry: &str, name: &str, version: &str) -> PodImage {
+    let mut image = pod_image(pod, "default", registry, name, version);
+    image.node_name = node.to_string();
+    image
+}
+
+fn platform(arch: &str) -> Platform {
+    Platform::new("linux", arch)
+}
+
+#[test]
+fn test_process_node() {
+    let info = process_node(&create_test_node("worker-1", "arm64")).unwrap();
+    assert_eq!(info.name, "worker-1");
+    assert_eq!(info.architecture, "arm64");
+    assert_eq!(info.operating_system, "linux");
+    assert_eq!(info.container_runtime, "containerd://1.7.13");
+    assert_eq!(info.kubelet_version, "v1.31.0");
+
+    let no_status = Node {
+        metadata: ObjectMeta {
+            name: Some("worker-2".to_string()),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert!(process_node(&no_status).is_none());
+}
+
+#[test]
+fn test_manifest_reference() {
+    let test_cases = vec![
+        (("docker.io", "nginx", "1.25"), ("library/nginx", "1.25")),
+        (("docker.io", "bitnami/redis", "7"), ("bitnami/redis", "7")),
+        (
+            ("ghcr.io", "org/app", "v1@sha256:abc123"),
+            ("org/app", "sha256:abc123"),
+        ),
+        (
+            ("quay.io", "coreos/etcd", "@sha256:abc123"),
+            ("coreos/etcd", "sha256:abc123"),
+        ),
+    ];
+
+    for ((registry, name, version), (repository, reference)) in test_cases {
+        let image = create_pod_image("pod", "node", registry, name, version);
+        assert_eq!(
+            manifest_reference(&image),
+            (repository.to_string(), reference.to_string())
+        );
+    }
+}
+
+#[test]
+fn test_find_arch_mismatches() {
+    let nodes: Vec<NodeInfo> = vec![
+        process_node(&create_test_node("amd-node", "amd64")).unwrap(),
+        process_node(&create_test_node("arm-node", "arm64")).unwrap(),
+    ];
+    let images = vec![
+        create_pod_image("single-on-amd", "amd-node", "ghcr.io", "org/app", "v1"),
+        create_pod_image("single-on-arm", "arm-node", "ghcr.io", "org/app", "v1"),
+        create_pod_image("multi-on-arm", "arm-node", "docker.io", "nginx", "1.25"),
+        create_pod_image("unknown-on-arm", "arm-node", "corp.io", "private", "v1"),
+    ];
+
+    let mut platforms = HashMap::new();
+    platforms.insert(
+        (
+            "ghcr.io".to_string(),
+            "org/app".to_string(),
+            "v1".to_string(),
+        ),
+        vec![platform("amd64")],
+    );
+    platforms.insert(
+        (
+            "docker.io".to_string(),
+            "library/nginx".to_string(),
+            "1.25".to_string(),
+        ),
+        vec![platform("amd64"), platform("arm64")],
+    );
+
+    let mismatches = find_arch_mismatches(&images, &nodes, &platforms);
+    assert_eq!(mismatches.len(), 1);
+    assert_eq!(mismatches[0].pod_name, "single-on-arm");
+    assert_eq!(mismatches[0].node_platform, "linux/arm64");
+    assert_eq!(mismatches[0].image_platforms, vec!["linux/amd64"]);
+}
+
+#[test]
+fn test_parse_bearer_challenge() {
+    let params = parse_bearer_challenge(
+        "Bearer realm=\"https://auth.docker.io/token\",service=\"registry.docker.io\",scope=\"repository:library/nginx:pull\"",
+    )
+    .unwrap();
+    assert_eq!(
+        params,
+        vec![
+            (
+                "realm".to_string(),
+                "https://auth.docker.io/token".to_string()
+            ),
+            ("service".to_string(), "registry.docker.io".to_string()),
+            (
+                "scope".to_string(),
+                "repository:library/nginx:pull".to_string()
+            ),
+        ]
+    );
+
+    assert!(parse_bearer_challenge("Basic realm=\"registry\"").is_none());
+}
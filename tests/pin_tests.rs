@@ -0,0 +1,253 @@
+mod common;
+
+use common::{DIGEST, OTHER_DIGEST, PodBuilder};
+use kimspect::{
+    ImagePin, find_image_pins, render_kubectl_patches, render_kustomize_images,
+    render_patch_documents,
+};
+use serde_json::{Value, json};
+
+/// Build a running pod owned by a workload, with a pod-template-hash label if given
+fn create_pod(name: &str, owner: (&str, &str), hash: Option<&str>) -> PodBuilder {
+    let pod = PodBuilder::new(name, "shop").owner(owner.0, owner.1);
+    match hash {
+        Some(hash) => pod.label("pod-template-hash", hash),
+        None => pod,
+    }
+}
+
+fn create_pin(kind: &str, workload: &str, container: &str, image: &str, digest: &str) -> ImagePin {
+    let mut pin = ImagePin::default();
+    pin.namespace = "shop".to_string();
+    pin.kind = kind.to_string();
+    pin.workload = workload.to_string();
+    pin.container = container.to_string();
+    pin.image = image.to_string();
+    pin.digest = digest.to_string();
+    pin
+}
+
+fn init_pin(mut pin: ImagePin) -> ImagePin {
+    pin.init = true;
+    pin
+}
+
+#[test]
+fn test_find_image_pins() {
+    let web = |name: &str, digest: &str| {
+        create_pod(name, ("ReplicaSet", "web-7d9f8"), Some("7d9f8"))
+            .running(
+                "web",
+                "ghcr.io/org/web:1.2",
+                &format!("ghcr.io/org/web@{}", digest),
+            )
+            .running(
+                "proxy",
+                &format!("envoyproxy/envoy@{}", DIGEST),
+                &format!("docker.io/envoyproxy/envoy@{}", DIGEST),
+            )
+            .build()
+    };
+    let with_init = create_pod("db-0", ("StatefulSet", "db"), None)
+        .running(
+            "db",
+            "postgres",
+            &format!("docker.io/library/postgres@{}", DIGEST),
+        )
+        .init_container(
+            "migrate",
+            "ghcr.io/org/migrate:3",
+            &format!("ghcr.io/org/migrate@{}", OTHER_DIGEST),
+        )
+        .build();
+    let job = create_pod("backup-x1", ("Job", "backup"), None)
+        .running(
+            "backup",
+            "ghcr.io/org/backup:1",
+            &format!("ghcr.io/org/backup@{}", DIGEST),
+        )
+        .build();
+
+    let pods = [
+        web("web-7d9f8-a", DIGEST),
+        web("web-7d9f8-b", DIGEST),
+        with_init,
+        job,
+    ];
+    let pins = find_image_pins(&pods);
+    assert_eq!(
+        pins,
+        vec![
+            create_pin("Deployment", "web", "web", "ghcr.io/org/web:1.2", DIGEST),
+            create_pin("StatefulSet", "db", "db", "postgres", DIGEST),
+            init_pin(create_pin(
+                "StatefulSet",
+                "db",
+                "migrate",
+                "ghcr.io/org/migrate:3",
+                OTHER_DIGEST
+            )),
+        ]
+    );
+    assert_eq!(
+        pins[0].pinned_image(),
+        format!("ghcr.io/org/web:1.2@{}", DIGEST)
+    );
+    // The implicit tag is kept visible
+    assert_eq!(
+        pins[1].pinned_image(),
+        format!("postgres:latest@{}", DIGEST)
+    );
+    assert!(pins[0].matches_workload("deployment/web"));
+    assert!(pins[0].matches_workload("deploy/web"));
+    assert!(pins[0].matches_workload("deployments/web"));
+    assert!(!pins[0].matches_workload("statefulset/web"));
+    assert!(pins[1].matches_workload("sts/db"));
+    assert!(!pins[1].matches_workload("db"));
+
+    // Pods running two digests of the tag cannot be pinned
+    let moved = [web("web-7d9f8-a", DIGEST), web("web-7d9f8-b", OTHER_DIGEST)];
+    assert!(find_image_pins(&moved).is_empty());
+}
+
+#[test]
+fn test_find_image_pins_during_rollout() {
+    let web = |name: &str, tag: &str, digest: &str| {
+        let hash = tag.replace('.', "");
+        create_pod(name, ("ReplicaSet", &format!("web-{}", hash)), Some(&hash))
+            .running(
+                "web",
+                &format!("ghcr.io/org/web:{}", tag),
+                &format!("ghcr.io/org/web@{}", digest),
+            )
+            .running(
+                "proxy",
+                "envoyproxy/envoy:v1.30",
+                &format!("docker.io/envoyproxy/envoy@{}", DIGEST),
+            )
+            .build()
+    };
+    // Pods of the old and the new ReplicaSet of a Deployment run different tags
+    let pods = [
+        web("web-19-a", "1.9", DIGEST),
+        web("web-110-a", "1.10", OTHER_DIGEST),
+    ];
+
+    // The container being rolled out is left out, the unchanged one is still pinned
+    assert_eq!(
+        find_image_pins(&pods),
+        vec![create_pin(
+            "Deployment",
+            "web",
+            "proxy",
+            "envoyproxy/envoy:v1.30",
+            DIGEST
+        )]
+    );
+}
+
+#[test]
+fn test_render_kubectl_patches() {
+    let pins = vec![
+        create_pin("Deployment", "web", "web", "ghcr.io/org/web:1.2", DIGEST),
+        create_pin(
+            "Deployment",
+            "web",
+            "proxy",
+            "envoyproxy/envoy:v1.30",
+            DIGEST,
+        ),
+    ];
+    let rendered = render_kubectl_patches(&pins).unwrap();
+    let lines: Vec<&str> = rendered.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], "# Deployment shop/web");
+    assert!(lines[1].starts_with(
+        "kubectl patch deployment 'web' --namespace 'shop' --type strategic --patch '"
+    ));
+
+    let patch: Value = serde_json::from_str(
+        lines[1]
+            .split_once("--patch '")
+            .unwrap()
+            .1
+            .trim_end_matches('\''),
+    )
+    .unwrap();
+    assert_eq!(
+        patch,
+        json!({
+            "spec": { "template": { "spec": { "containers": [
+                { "name": "web", "image": format!("ghcr.io/org/web:1.2@{}", DIGEST) },
+                { "name": "proxy", "image": format!("envoyproxy/envoy:v1.30@{}", DIGEST) },
+            ] } } }
+        })
+    );
+}
+
+#[test]
+fn test_render_patch_documents() {
+    let pins = vec![
+        create_pin("Deployment", "web", "web", "ghcr.io/org/web:1.2", DIGEST),
+        init_pin(create_pin(
+            "StatefulSet",
+            "db",
+            "migrate",
+            "ghcr.io/org/migrate:3",
+            DIGEST,
+        )),
+    ];
+    let rendered = render_patch_documents(&pins).unwrap();
+    let documents: Vec<Value> = rendered
+        .split("---\n")
+        .map(|document| serde_yaml::from_str(document).unwrap())
+        .collect();
+    assert_eq!(documents.len(), 2);
+    assert_eq!(
+        documents[0],
+        json!({
+            "apiVersion": "apps/v1",
+            "kind": "Deployment",
+            "metadata": { "name": "web", "namespace": "shop" },
+            "spec": { "template": { "spec": { "containers": [
+                { "name": "web", "image": format!("ghcr.io/org/web:1.2@{}", DIGEST) },
+            ] } } }
+        })
+    );
+    assert_eq!(
+        documents[1]["spec"]["template"]["spec"]["initContainers"][0]["name"],
+        "migrate"
+    );
+}
+
+#[test]
+fn test_render_kustomize_images() {
+    let pins = vec![
+        create_pin("Deployment", "web", "web", "ghcr.io/org/web:1.2", DIGEST),
+        create_pin("Deployment", "api", "web", "ghcr.io/org/web:1.2", DIGEST),
+        create_pin(
+            "Deployment",
+            "old",
+            "web",
+            "ghcr.io/org/web:1.1",
+            OTHER_DIGEST,
+        ),
+        create_pin("StatefulSet", "db", "db", "localhost:5000/postgres", DIGEST),
+    ];
+    let rendered = render_kustomize_images(&pins).unwrap();
+    assert!(
+        rendered.starts_with(
+            "# ghcr.io/org/web runs several versions (1.1, 1.2), pin it per workload\n"
+        )
+    );
+
+    let kustomization: Value = serde_yaml::from_str(&rendered).unwrap();
+    assert_eq!(
+        kustomization,
+        json!({
+            "images": [
+                { "name": "localhost:5000/postgres", "newTag": "latest", "digest": DIGEST },
+            ]
+        })
+    );
+}
@@ -0,0 +1,144 @@
+use kimspect::cli::{krew_manifest, parse_checksums, plugin_args, plugin_name};
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+
+fn argv(args: &[&str]) -> Vec<OsString> {
+    args.iter().map(OsString::from).collect()
+}
+
+#[test]
+fn test_plugin_name() {
+    assert_eq!(
+        plugin_name(OsStr::new("/usr/local/bin/kubectl-kimspect")),
+        Some("kimspect".to_string())
+    );
+    assert_eq!(
+        plugin_name(OsStr::new("kubectl-images")),
+        Some("images".to_string())
+    );
+    assert_eq!(
+        plugin_name(OsStr::new("kubectl-image_inspect.exe")),
+        Some("image-inspect".to_string())
+    );
+    assert_eq!(plugin_name(OsStr::new("/usr/bin/kimspect")), None);
+    assert_eq!(plugin_name(OsStr::new("kubectl-")), None);
+}
+
+#[test]
+fn test_plugin_args_images_shortcut() {
+    assert_eq!(
+        plugin_args("images", argv(&["kubectl-images", "-o", "wide"])),
+        argv(&["kubectl-images", "get", "images", "-o", "wide"])
+    );
+    assert_eq!(
+        plugin_args("kimspect", argv(&["kubectl-kimspect", "get", "nodes"])),
+        argv(&["kubectl-kimspect", "get", "nodes"])
+    );
+}
+
+#[test]
+fn test_plugin_args_moves_namespace_after_command() {
+    assert_eq!(
+        plugin_args(
+            "kimspect",
+            argv(&[
+                "kubectl-kimspect",
+                "-n",
+                "shop",
+                "--context",
+                "prod",
+                "get",
+                "images"
+            ])
+        ),
+        argv(&[
+            "kubectl-kimspect",
+            "--context",
+            "prod",
+            "get",
+            "images",
+            "-n",
+            "shop"
+        ])
+    );
+    assert_eq!(
+        plugin_args(
+            "images",
+            argv(&["kubectl-images", "--namespace=shop", "-nweb"])
+        ),
+        argv(&[
+            "kubectl-images",
+            "get",
+            "images",
+            "--namespace=shop",
+            "-nweb"
+        ])
+    );
+    assert_eq!(
+        plugin_args(
+            "images",
+            argv(&["kubectl-images", "-n", "shop", "--", "-n"])
+        ),
+        argv(&["kubectl-images", "get", "images", "-n", "shop", "--", "-n"])
+    );
+}
+
+#[test]
+fn test_parse_checksums() {
+    let checksums = parse_checksums(
+        "ABC123  kimspect-x86_64-unknown-linux-musl.tar.gz\n\
+         def456 *dist/kimspect-x86_64-pc-windows-msvc.zip\n\
+         \n\
+         not-a-checksum-line\n",
+    );
+    assert_eq!(checksums.len(), 2);
+    assert_eq!(
+        checksums["kimspect-x86_64-unknown-linux-musl.tar.gz"],
+        "abc123"
+    );
+    assert_eq!(checksums["kimspect-x86_64-pc-windows-msvc.zip"], "def456");
+}
+
+#[test]
+fn test_krew_manifest() {
+    let checksums: HashMap<String, String> = [
+        "kimspect-x86_64-unknown-linux-musl.tar.gz",
+        "kimspect-x86_64-apple-darwin.tar.gz",
+        "kimspect-aarch64-apple-darwin.tar.gz",
+        "kimspect-x86_64-pc-windows-msvc.zip",
+    ]
+    .iter()
+    .enumerate()
+    .map(|(i, asset)| (asset.to_string(), format!("sha{}", i)))
+    .collect();
+
+    let manifest: serde_yaml::Value =
+        serde_yaml::from_str(&krew_manifest("1.2.3", &checksums).unwrap()).unwrap();
+    assert_eq!(manifest["kind"], "Plugin");
+    assert_eq!(manifest["metadata"]["name"], "kimspect");
+    assert_eq!(manifest["spec"]["version"], "v1.2.3");
+
+    let platforms = manifest["spec"]["platforms"].as_sequence().unwrap();
+    assert_eq!(platforms.len(), 4);
+    assert_eq!(platforms[0]["selector"]["matchLabels"]["os"], "linux");
+    assert_eq!(
+        platforms[0]["uri"],
+        "https://github.com/koithos/kimspect/releases/download/v1.2.3/kimspect-x86_64-unknown-linux-musl.tar.gz"
+    );
+    assert_eq!(platforms[0]["sha256"], "sha0");
+    assert_eq!(platforms[3]["bin"], "kimspect.exe");
+
+    assert_eq!(
+        krew_manifest("v1.2.3", &checksums).unwrap(),
+        krew_manifest("1.2.3", &checksums).unwrap()
+    );
+}
+
+#[test]
+fn test_krew_manifest_missing_checksum() {
+    let err = krew_manifest("1.2.3", &HashMap::new()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "No checksum for kimspect-x86_64-unknown-linux-musl.tar.gz in the checksum file"
+    );
+}
@@ -0,0 +1,182 @@
+mod common;
+
+use common::pod_image;
+use k8s_openapi::api::core::v1::{Event, EventSource, ObjectReference};
+use kimspect::{
+    PodImage, PullGroupBy, format_duration, parse_go_duration, parse_pull_message,
+    process_pull_events, summarize_pulls,
+};
+use std::time::Duration;
+
+fn create_pull_event(pod: &str, container: &str, reason: &str, message: &str) -> Event {
+    Event {
+        involved_object: ObjectReference {
+            kind: Some("Pod".to_string()),
+            name: Some(pod.to_string()),
+            namespace: Some("default".to_string()),
+            field_path: Some(format!("spec.containers{{{}}}", container)),
+            ..Default::default()
+        },
+        reason: Some(reason.to_string()),
+        message: Some(message.to_string()),
+        source: Some(EventSource {
+            host: Some("event-node".to_string()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+fn create_pod_image(pod: &str, container: &str, node: &str) -> PodImage {
+    let mut image = pod_image(pod, "default", "docker.io", "nginx", "1.25");
+    image.node_name = node.to_string();
+    image.container_name = container.to_string();
+    image.status = "Running".to_string();
+    image
+}
+
+#[test]
+fn test_parse_go_duration() {
+    let infinite = format!("{}s", "9".repeat(400));
+    let test_cases = vec![
+        ("1.5s", Some(Duration::from_millis(1500))),
+        ("850ms", Some(Duration::from_millis(850))),
+        ("1m2s", Some(Duration::from_secs(62))),
+        ("1h0m0s", Some(Duration::from_secs(3600))),
+        ("", None),
+        ("12", None),
+        ("abc", None),
+        // Overflowing and non-finite values are rejected instead of panicking
+        ("99999999999999999999h", None),
+        (infinite.as_str(), None),
+    ];
+
+    for (value, expected) in test_cases {
+        assert_eq!(
+            parse_go_duration(value),
+            expected,
+            "Failed for duration: {}",
+            value
+        );
+    }
+}
+
+#[test]
+fn test_parse_pull_message() {
+    let parsed = parse_pull_message(
+        "Successfully pulled image \"nginx:1.25\" in 2.5s (3.1s including waiting). Image size: 67211321 bytes.",
+    )
+    .unwrap();
+    assert_eq!(parsed.image, "nginx:1.25");
+    assert_eq!(parsed.duration, Some(Duration::from_millis(2500)));
+    assert_eq!(parsed.size_bytes, Some(67211321));
+
+    let legacy = parse_pull_message("Successfully pulled image \"redis:7\" in 850ms").unwrap();
+    assert_eq!(legacy.duration, Some(Duration::from_millis(850)));
+    assert_eq!(legacy.size_bytes, None);
+
+    let cached =
+        parse_pull_message("Container image \"nginx:1.25\" already present on machine").unwrap();
+    assert_eq!(cached.image, "nginx:1.25");
+    assert_eq!(cached.duration, None);
+
+    assert!(parse_pull_message("Pulling image \"nginx:1.25\"").is_none());
+}
+
+#[test]
+fn test_format_duration() {
+    assert_eq!(format_duration(Duration::from_millis(850)), "850ms");
+    assert_eq!(format_duration(Duration::from_millis(2500)), "2.5s");
+    assert_eq!(format_duration(Duration::from_secs(62)), "1m2.0s");
+    // Rounding carries into the minutes
+    assert_eq!(format_duration(Duration::from_millis(119_960)), "2m0.0s");
+    assert_eq!(format_duration(Duration::from_millis(59_960)), "1m0.0s");
+    assert_eq!(format_duration(Duration::from_millis(1_049)), "1.0s");
+}
+
+#[test]
+fn test_process_pull_events() {
+    let events = vec![
+        create_pull_event(
+            "fast",
+            "app",
+            "Pulled",
+            "Successfully pulled image \"nginx:1.25\" in 1s (1s including waiting). Image size: 2048 bytes.",
+        ),
+        create_pull_event(
+            "slow",
+            "app",
+            "Pulled",
+            "Successfully pulled image \"nginx:1.25\" in 1m30s (1m30s including waiting). Image size: 2048 bytes.",
+        ),
+        create_pull_event(
+            "cached",
+            "app",
+            "Pulled",
+            "Container image \"nginx:1.25\" already present on machine",
+        ),
+        create_pull_event("fast", "app", "Pulling", "Pulling image \"nginx:1.25\""),
+    ];
+    let pod_images = vec![create_pod_image("fast", "app", "node-a")];
+
+    let pulls = process_pull_events(&events, &pod_images);
+    assert_eq!(pulls.len(), 3);
+
+    assert_eq!(pulls[0].pod_name, "slow");
+    assert_eq!(pulls[0].duration, Some(Duration::from_secs(90)));
+    // Falls back to the event source when the pod is gone
+    assert_eq!(pulls[0].node_name, "event-node");
+
+    assert_eq!(pulls[1].pod_name, "fast");
+    assert_eq!(pulls[1].node_name, "node-a");
+    assert_eq!(pulls[1].image_size, "2.0KiB");
+
+    assert!(pulls[2].is_cached());
+}
+
+#[test]
+fn test_summarize_pulls() {
+    let events = vec![
+        create_pull_event(
+            "a",
+            "app",
+            "Pulled",
+            "Successfully pulled image \"nginx:1.25\" in 10s",
+        ),
+        create_pull_event(
+            "b",
+            "app",
+            "Pulled",
+            "Successfully pulled image \"nginx:1.25\" in 20s",
+        ),
+        create_pull_event(
+            "c",
+            "app",
+            "Pulled",
+            "Successfully pulled image \"redis:7\" in 5s",
+        ),
+        create_pull_event(
+            "d",
+            "app",
+            "Pulled",
+            "Container image \"redis:7\" already present on machine",
+        ),
+    ];
+
+    let pulls = process_pull_events(&events, &[]);
+    let summaries = summarize_pulls(&pulls, PullGroupBy::Image);
+    assert_eq!(summaries.len(), 2);
+
+    assert_eq!(summaries[0].key, "nginx:1.25");
+    assert_eq!(summaries[0].pulls, 2);
+    assert_eq!(summaries[0].max, Duration::from_secs(20));
+    assert_eq!(summaries[0].average, Duration::from_secs(15));
+
+    assert_eq!(summaries[1].key, "redis:7");
+    assert_eq!(summaries[1].pulls, 1);
+    assert_eq!(summaries[1].cached, 1);
+
+    let by_node = summarize_pulls(&pulls, PullGroupBy::Node);
+    assert_eq!(by_node.len(), 1);
+    assert_eq!(by_node[0].key, "event-node");
+}
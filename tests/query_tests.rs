@@ -0,0 +1,322 @@
+mod common;
+
+use anyhow::Result;
+use common::{DIGEST, PodBuilder};
+use k8s_openapi::api::core::v1::{
+    ContainerImage, ContainerStatus, Node, NodeStatus, Pod, PodStatus,
+};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use kimspect::{ImageQuery, K8sError, PodSource, collect_pod_images, object_pods};
+use serde_json::json;
+
+fn create_test_pod(name: &str, namespace: &str, node: &str, images: &[&str]) -> Pod {
+    images
+        .iter()
+        .enumerate()
+        .fold(
+            PodBuilder::new(name, namespace).node(node),
+            |pod, (i, image)| pod.container(&format!("c{}", i), image),
+        )
+        .build()
+}
+
+fn pods() -> Vec<Pod> {
+    vec![
+        create_test_pod(
+            "web",
+            "default",
+            "worker-1",
+            &["nginx:1.25", "ghcr.io/org/sidecar:v1"],
+        ),
+        create_test_pod("api", "default", "worker-2", &["ghcr.io/org/api:v2"]),
+        create_test_pod(
+            "dns",
+            "kube-system",
+            "worker-1",
+            &["registry.k8s.io/coredns:v1.11"],
+        ),
+    ]
+}
+
+/// Pod source with node information, as a cluster would provide
+struct ClusterFixture {
+    pods: Vec<Pod>,
+    nodes: Vec<Node>,
+}
+
+impl PodSource for ClusterFixture {
+    async fn list_pods(&self, query: &ImageQuery) -> Result<Vec<Pod>> {
+        self.pods.list_pods(query).await
+    }
+
+    async fn list_nodes(&self) -> Result<Vec<Node>> {
+        Ok(self.nodes.clone())
+    }
+}
+
+#[test]
+fn test_query_builder() {
+    let query = ImageQuery::new();
+    assert_eq!(query.get_namespace(), "default");
+    assert!(!query.is_all_namespaces());
+    assert_eq!(query.get_node(), None);
+    assert!(query.get_exclude_registries().is_empty());
+
+    let query = ImageQuery::new()
+        .namespace("kube-system")
+        .all_namespaces(true)
+        .node("worker-1")
+        .pod("dns")
+        .registry("registry.k8s.io")
+        .exclude_registry("docker.io")
+        .exclude_registries(["quay.io"]);
+    assert_eq!(query.get_namespace(), "kube-system");
+    assert!(query.is_all_namespaces());
+    assert_eq!(query.get_node(), Some("worker-1"));
+    assert_eq!(query.get_pod(), Some("dns"));
+    assert_eq!(query.get_registry(), Some("registry.k8s.io"));
+    assert_eq!(query.get_exclude_registries(), ["docker.io", "quay.io"]);
+}
+
+#[tokio::test]
+async fn test_collect_pod_images_filters() -> Result<()> {
+    let pods = pods();
+
+    let images = collect_pod_images(&pods, &ImageQuery::new()).await?;
+    assert_eq!(images.len(), 3);
+
+    let images = collect_pod_images(&pods, &ImageQuery::new().all_namespaces(true)).await?;
+    assert_eq!(images.len(), 4);
+
+    let query = ImageQuery::new().all_namespaces(true).node("worker-1");
+    let images = collect_pod_images(&pods, &query).await?;
+    let names: Vec<&str> = images.iter().map(|i| i.pod_name.as_str()).collect();
+    assert_eq!(names, vec!["web", "web", "dns"]);
+
+    let query = ImageQuery::new().pod("api");
+    let images = collect_pod_images(&pods, &query).await?;
+    assert_eq!(images.len(), 1);
+    assert_eq!(images[0].image_name, "org/api");
+
+    let query = ImageQuery::new().registry("ghcr.io");
+    let images = collect_pod_images(&pods, &query).await?;
+    assert_eq!(images.len(), 2);
+
+    let query = ImageQuery::new().exclude_registry("ghcr.io");
+    let images = collect_pod_images(&pods, &query).await?;
+    assert_eq!(images.len(), 1);
+    assert_eq!(images[0].registry, "docker.io");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_collect_pod_images_not_found() {
+    let pods = pods();
+    let query = ImageQuery::new().node("non-existent-node");
+    let result = collect_pod_images(&pods, &query).await;
+
+    let err = result.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<K8sError>(),
+        Some(K8sError::ResourceNotFound(resource)) if resource == "pods on node non-existent-node"
+    ));
+}
+
+#[tokio::test]
+async fn test_collect_pod_images_sizes_from_nodes() -> Result<()> {
+    let mut pod = create_test_pod("web", "default", "worker-1", &["nginx:1.25"]);
+    pod.status = Some(PodStatus {
+        container_statuses: Some(vec![ContainerStatus {
+            name: "c0".to_string(),
+            image_id: format!("docker.io/library/nginx@{}", DIGEST),
+            ..Default::default()
+        }]),
+        ..Default::default()
+    });
+
+    let node = Node {
+        metadata: ObjectMeta {
+            name: Some("worker-1".to_string()),
+            ..Default::default()
+        },
+        status: Some(NodeStatus {
+            images: Some(vec![ContainerImage {
+                names: Some(vec![format!("docker.io/library/nginx@{}", DIGEST)]),
+                size_bytes: Some(72 * 1024 * 1024),
+            }]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let source = ClusterFixture {
+        pods: vec![pod],
+        nodes: vec![node],
+    };
+    let images = collect_pod_images(&source, &ImageQuery::new()).await?;
+    assert_eq!(images[0].digest, DIGEST);
+    assert_eq!(images[0].image_size, "72.0MiB");
+    assert_eq!(images[0].image_size_bytes, Some(72 * 1024 * 1024));
+    Ok(())
+}
+
+/// Pod source whose namespaces carry team labels
+struct TeamNamespaces {
+    pods: Vec<Pod>,
+    teams: Vec<(&'static str, &'static str)>,
+}
+
+impl PodSource for TeamNamespaces {
+    async fn list_pods(&self, query: &ImageQuery) -> Result<Vec<Pod>> {
+        // Sources are queried one namespace at a time
+        assert!(query.is_all_namespaces() || query.get_namespaces().len() == 1);
+        self.pods.list_pods(query).await
+    }
+
+    async fn list_namespaces(&self, selector: &str) -> Result<Vec<String>> {
+        Ok(self
+            .teams
+            .iter()
+            .filter(|(_, team)| selector == format!("team={}", team))
+            .map(|(namespace, _)| namespace.to_string())
+            .collect())
+    }
+}
+
+fn team_pods() -> TeamNamespaces {
+    TeamNamespaces {
+        pods: vec![
+            create_test_pod("web", "default", "worker-1", &["nginx:1.25"]),
+            create_test_pod("api", "payments", "worker-1", &["ghcr.io/org/api:v2"]),
+            create_test_pod("job", "payments-jobs", "worker-2", &["ghcr.io/org/job:v1"]),
+            create_test_pod(
+                "dns",
+                "kube-system",
+                "worker-1",
+                &["registry.k8s.io/coredns:v1.11"],
+            ),
+        ],
+        teams: vec![("payments", "payments"), ("payments-jobs", "payments")],
+    }
+}
+
+#[tokio::test]
+async fn test_collect_pod_images_multiple_namespaces() -> Result<()> {
+    let source = team_pods();
+
+    let query = ImageQuery::new().namespaces(["payments-jobs", "payments", "payments"]);
+    assert_eq!(query.get_namespace(), "payments-jobs");
+    let images = collect_pod_images(&source, &query).await?;
+    let names: Vec<&str> = images.iter().map(|i| i.pod_name.as_str()).collect();
+    assert_eq!(names, vec!["job", "api"]);
+
+    let query = ImageQuery::new()
+        .all_namespaces(true)
+        .exclude_namespaces(["kube-system", "default"]);
+    let images = collect_pod_images(&source, &query).await?;
+    assert_eq!(images.len(), 2);
+    assert!(images.iter().all(|i| i.namespace.starts_with("payments")));
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_collect_pod_images_namespace_selector() -> Result<()> {
+    let source = team_pods();
+
+    let query = ImageQuery::new().namespace_selector("team=payments");
+    assert!(query.get_namespaces().is_empty());
+    let images = collect_pod_images(&source, &query).await?;
+    let namespaces: Vec<&str> = images.iter().map(|i| i.namespace.as_str()).collect();
+    assert_eq!(namespaces, vec!["payments", "payments-jobs"]);
+
+    let query = query.exclude_namespace("payments-jobs");
+    let images = collect_pod_images(&source, &query).await?;
+    assert_eq!(images.len(), 1);
+
+    // The selected namespaces replace the named ones
+    let query = ImageQuery::new()
+        .namespaces(["kube-system"])
+        .namespace_selector("team=payments");
+    assert!(query.get_namespaces().is_empty());
+    let images = collect_pod_images(&source, &query).await?;
+    let namespaces: Vec<&str> = images.iter().map(|i| i.namespace.as_str()).collect();
+    assert_eq!(namespaces, vec!["payments", "payments-jobs"]);
+
+    let query = ImageQuery::new().namespace_selector("team=search");
+    let err = collect_pod_images(&source, &query).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<K8sError>(),
+        Some(K8sError::ResourceNotFound(resource)) if resource == "pods in namespaces matching team=search"
+    ));
+    Ok(())
+}
+
+/// Pod source with batch workloads, whose templates are listed as scheduled pods
+struct BatchWorkloads {
+    pods: Vec<Pod>,
+    scheduled: Vec<Pod>,
+}
+
+impl PodSource for BatchWorkloads {
+    async fn list_pods(&self, query: &ImageQuery) -> Result<Vec<Pod>> {
+        self.pods.list_pods(query).await
+    }
+
+    async fn list_scheduled_pods(&self, query: &ImageQuery) -> Result<Vec<Pod>> {
+        self.scheduled.list_pods(query).await
+    }
+}
+
+#[tokio::test]
+async fn test_collect_pod_images_include_scheduled() -> Result<()> {
+    let cron_job = json!({
+        "apiVersion": "batch/v1",
+        "kind": "CronJob",
+        "metadata": { "name": "backup", "namespace": "ops" },
+        "spec": {
+            "schedule": "0 3 * * *",
+            "jobTemplate": { "spec": { "template": { "spec": {
+                "containers": [{ "name": "backup", "image": "ghcr.io/org/backup:1.4" }],
+                "restartPolicy": "OnFailure",
+            } } } },
+        },
+    });
+    let source = BatchWorkloads {
+        pods: vec![create_test_pod(
+            "web",
+            "ops",
+            "worker-1",
+            &[&format!("nginx@{}", DIGEST)],
+        )],
+        scheduled: object_pods(cron_job)?,
+    };
+
+    let query = ImageQuery::new().namespace("ops");
+    assert!(!query.is_including_scheduled());
+    let images = collect_pod_images(&source, &query).await?;
+    assert_eq!(images.len(), 1);
+
+    let query = query.include_scheduled(true);
+    let images = collect_pod_images(&source, &query).await?;
+    let found: Vec<(&str, &str)> = images
+        .iter()
+        .map(|i| (i.pod_name.as_str(), i.status.as_str()))
+        .collect();
+    assert_eq!(found, vec![("web", ""), ("backup", "Scheduled")]);
+    assert_eq!(images[1].registry, "ghcr.io");
+    assert_eq!(images[1].node_name, "");
+
+    // Workloads without running pods are found through their templates alone
+    let source = BatchWorkloads {
+        pods: Vec::new(),
+        scheduled: source.scheduled,
+    };
+    let images = collect_pod_images(&source, &query).await?;
+    assert_eq!(images.len(), 1);
+    assert!(
+        collect_pod_images(&source, &query.include_scheduled(false))
+            .await
+            .is_err()
+    );
+    Ok(())
+}
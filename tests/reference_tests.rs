@@ -0,0 +1,179 @@
+use kimspect::{ImageReference, ReferenceError};
+
+const DIGEST: &str = "sha256:a3ed95caeb02ffe68cdd9fd84406680ae93d633cb16422d00e8a7c22955b46d4";
+
+#[test]
+fn test_parse_normalizes_docker_hub() {
+    for image in [
+        "ubuntu",
+        "library/ubuntu",
+        "docker.io/ubuntu",
+        "docker.io/library/ubuntu",
+        "index.docker.io/library/ubuntu",
+    ] {
+        let reference = ImageReference::parse(image).unwrap();
+        assert_eq!(reference.registry(), "docker.io", "Failed for {}", image);
+        assert_eq!(
+            reference.repository(),
+            "library/ubuntu",
+            "Failed for {}",
+            image
+        );
+        assert_eq!(reference.familiar_name(), "ubuntu", "Failed for {}", image);
+        assert_eq!(reference.tag(), None);
+        assert_eq!(reference.version(), "latest");
+    }
+
+    let reference = ImageReference::parse("bitnami/redis:7").unwrap();
+    assert_eq!(reference.name(), "docker.io/bitnami/redis");
+    assert_eq!(reference.familiar_name(), "bitnami/redis");
+}
+
+#[test]
+fn test_parse_components() {
+    let test_cases = vec![
+        // (reference, registry, repository, tag, digest)
+        (
+            "nginx:1.25",
+            "docker.io",
+            "library/nginx",
+            Some("1.25"),
+            None,
+        ),
+        ("localhost/app", "localhost", "app", None, None),
+        (
+            "localhost:5000/team/app:v1",
+            "localhost:5000",
+            "team/app",
+            Some("v1"),
+            None,
+        ),
+        (
+            "192.168.1.100:5000/image:latest",
+            "192.168.1.100:5000",
+            "image",
+            Some("latest"),
+            None,
+        ),
+        (
+            "[::1]:5000/image:dev",
+            "[::1]:5000",
+            "image",
+            Some("dev"),
+            None,
+        ),
+        (
+            "us-docker.pkg.dev/project/repo/image:v1.2.3-rc.1",
+            "us-docker.pkg.dev",
+            "project/repo/image",
+            Some("v1.2.3-rc.1"),
+            None,
+        ),
+        (
+            "my_registry/a__b/c-d---e:tag_1",
+            "docker.io",
+            "my_registry/a__b/c-d---e",
+            Some("tag_1"),
+            None,
+        ),
+    ];
+
+    for (image, registry, repository, tag, digest) in test_cases {
+        let reference = ImageReference::parse(image).unwrap();
+        assert_eq!(reference.registry(), registry, "Failed for {}", image);
+        assert_eq!(reference.repository(), repository, "Failed for {}", image);
+        assert_eq!(reference.tag(), tag, "Failed for {}", image);
+        assert_eq!(reference.digest(), digest, "Failed for {}", image);
+    }
+}
+
+#[test]
+fn test_parse_digest() {
+    let reference = ImageReference::parse(&format!("ghcr.io/org/app:v1@{}", DIGEST)).unwrap();
+    assert_eq!(reference.tag(), Some("v1"));
+    assert_eq!(reference.digest(), Some(DIGEST));
+    assert_eq!(reference.version(), format!("v1@{}", DIGEST));
+    assert_eq!(reference.manifest_reference(), DIGEST);
+
+    // A digest without a tag does not imply "latest"
+    let reference = ImageReference::parse(&format!("nginx@{}", DIGEST)).unwrap();
+    assert_eq!(reference.tag(), None);
+    assert_eq!(reference.version(), format!("@{}", DIGEST));
+    assert_eq!(
+        reference.to_string(),
+        format!("docker.io/library/nginx@{}", DIGEST)
+    );
+}
+
+#[test]
+fn test_display_round_trip() {
+    let reference: ImageReference = "quay.io/coreos/etcd:v3.5.0".parse().unwrap();
+    assert_eq!(reference.to_string(), "quay.io/coreos/etcd:v3.5.0");
+    assert_eq!(reference.manifest_reference(), "v3.5.0");
+    assert_eq!(
+        reference.to_string().parse::<ImageReference>(),
+        Ok(reference)
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    let long_name = "a".repeat(256);
+    let long_tag = format!("nginx:{}", "a".repeat(129));
+
+    let test_cases = vec![
+        ("", ReferenceError::Empty),
+        (
+            "Nginx/App",
+            ReferenceError::UppercaseName("App".to_string()),
+        ),
+        (
+            "org/App",
+            ReferenceError::UppercaseName("org/App".to_string()),
+        ),
+        ("nginx:", ReferenceError::InvalidTag(String::new())),
+        ("nginx:-dev", ReferenceError::InvalidTag("-dev".to_string())),
+        (
+            long_tag.as_str(),
+            ReferenceError::InvalidTag("a".repeat(129)),
+        ),
+        (
+            "nginx@sha256:abc123",
+            ReferenceError::InvalidDigest("sha256:abc123".to_string()),
+        ),
+        (
+            "nginx@abc123",
+            ReferenceError::InvalidDigest("abc123".to_string()),
+        ),
+        (
+            "registry-.corp/app",
+            ReferenceError::InvalidDomain("registry-.corp".to_string()),
+        ),
+        (
+            "registry.corp:port/app",
+            ReferenceError::InvalidDomain("registry.corp:port".to_string()),
+        ),
+        (
+            "org//app",
+            ReferenceError::InvalidName("org//app".to_string()),
+        ),
+        ("-app", ReferenceError::InvalidName("-app".to_string())),
+        (
+            "org/a...b",
+            ReferenceError::InvalidName("org/a...b".to_string()),
+        ),
+        (
+            long_name.as_str(),
+            ReferenceError::NameTooLong(long_name.clone()),
+        ),
+    ];
+
+    for (image, expected) in test_cases {
+        assert_eq!(
+            ImageReference::parse(image),
+            Err(expected),
+            "Failed for image: {}",
+            image
+        );
+    }
+}
@@ -0,0 +1,269 @@
+mod common;
+
+use common::PodBuilder;
+use k8s_openapi::api::core::v1::Pod;
+use kimspect::{
+    MirrorMap, MirrorRule, PodImage, RegistryClassifier, RegistryRule, group_by_upstream,
+    process_pod, registry_host_matches, registry_rows,
+};
+
+fn create_test_pod(name: &str, images: &[&str]) -> Pod {
+    images
+        .iter()
+        .enumerate()
+        .fold(PodBuilder::new(name, "default"), |pod, (i, image)| {
+            pod.container(&format!("c{}", i), image)
+        })
+        .build()
+}
+
+fn mirrors() -> MirrorMap {
+    MirrorMap::new(vec![
+        "mirror.corp/dockerhub=docker.io"
+            .parse::<MirrorRule>()
+            .unwrap(),
+        "mirror.corp/quay=quay.io".parse::<MirrorRule>().unwrap(),
+    ])
+}
+
+#[test]
+fn test_parse_mirror_rule() {
+    let rule: MirrorRule = "mirror.corp/dockerhub/=docker.io".parse().unwrap();
+    assert_eq!(rule.mirror, "mirror.corp/dockerhub");
+    assert_eq!(rule.upstream, "docker.io");
+
+    assert!("mirror.corp".parse::<MirrorRule>().is_err());
+    assert!("=docker.io".parse::<MirrorRule>().is_err());
+}
+
+#[test]
+fn test_resolve_mirror() {
+    let mirrors = mirrors();
+    assert_eq!(
+        mirrors.resolve("mirror.corp/dockerhub/library/nginx"),
+        Some("docker.io/library/nginx".to_string())
+    );
+    assert_eq!(
+        mirrors.resolve("mirror.corp/quay/coreos/etcd"),
+        Some("quay.io/coreos/etcd".to_string())
+    );
+    // Prefixes only match on path boundaries
+    assert_eq!(mirrors.resolve("mirror.corp/dockerhubx/nginx"), None);
+    assert_eq!(mirrors.resolve("ghcr.io/org/app"), None);
+}
+
+#[test]
+fn test_mirror_rule_precedence() {
+    let rule = |s: &str| s.parse::<MirrorRule>().unwrap();
+    // Environment, then config file, then command line
+    let mirrors = MirrorMap::new(vec![
+        rule("mirror.corp/dockerhub=env.io"),
+        rule("mirror.corp/quay=quay.io"),
+    ])
+    .with_rules(vec![rule("mirror.corp/dockerhub=config.io")])
+    .with_rules(vec![rule("mirror.corp/dockerhub/=docker.io")]);
+
+    assert_eq!(
+        mirrors.resolve("mirror.corp/dockerhub/library/nginx"),
+        Some("docker.io/library/nginx".to_string())
+    );
+    assert_eq!(
+        mirrors.resolve("mirror.corp/quay/coreos/etcd"),
+        Some("quay.io/coreos/etcd".to_string())
+    );
+    assert_eq!(
+        mirrors.upstream_registries("mirror.corp"),
+        vec!["docker.io", "quay.io"]
+    );
+}
+
+#[test]
+fn test_upstream_registries() {
+    let mirrors = mirrors();
+    assert_eq!(
+        mirrors.upstream_registries("mirror.corp"),
+        vec!["docker.io", "quay.io"]
+    );
+    assert!(mirrors.upstream_registries("ghcr.io").is_empty());
+}
+
+#[test]
+fn test_apply_and_group_by_upstream() {
+    let pod = create_test_pod(
+        "test-pod",
+        &[
+            "mirror.corp/dockerhub/library/nginx:1.25",
+            "docker.io/library/nginx:1.24",
+            "mirror.corp/quay/coreos/etcd:v3.5.0",
+            "ghcr.io/org/app:v1",
+            "mirror.corp/dockerhub/nginx:1.23",
+        ],
+    );
+
+    let mut images: Vec<PodImage> = process_pod(&pod);
+    mirrors().apply(&mut images);
+
+    assert_eq!(images[0].registry, "mirror.corp");
+    assert_eq!(images[0].upstream_registry, "docker.io");
+    assert_eq!(images[0].upstream_image, "nginx");
+    assert_eq!(images[4].upstream_image, "nginx");
+    assert!(images[3].upstream_registry.is_empty());
+
+    let groups = group_by_upstream(&images);
+    assert_eq!(groups.len(), 3);
+
+    assert_eq!(groups[0].upstream_registry, "docker.io");
+    assert_eq!(groups[0].image_name, "nginx");
+    assert_eq!(groups[0].pulled_via, vec!["docker.io", "mirror.corp"]);
+    assert_eq!(groups[0].versions, vec!["1.23", "1.24", "1.25"]);
+    assert_eq!(groups[0].containers, 3);
+
+    assert_eq!(groups[1].upstream_registry, "ghcr.io");
+    assert_eq!(groups[2].upstream_registry, "quay.io");
+    assert_eq!(groups[2].pulled_via, vec!["mirror.corp"]);
+}
+
+fn classifier() -> RegistryClassifier {
+    RegistryClassifier::new(vec![
+        "registry.corp=internal".parse::<RegistryRule>().unwrap(),
+        "harbor=internal".parse::<RegistryRule>().unwrap(),
+        "vendor.example.com=vendor".parse::<RegistryRule>().unwrap(),
+        "eu.gcr.io=vendor".parse::<RegistryRule>().unwrap(),
+    ])
+}
+
+#[test]
+fn test_registry_host_matches() {
+    let test_cases = vec![
+        ("pkg.dev", "pkg.dev", true),
+        ("us-docker.pkg.dev", "pkg.dev", true),
+        ("evilpkg.dev", "pkg.dev", false),
+        ("gcr.io", "gcr.io", true),
+        ("notgcr.io", "gcr.io", false),
+        ("registry.corp:5000", "registry.corp", true),
+        ("registry.corp:5000", "registry.corp:5001", false),
+        ("Registry.Corp", "registry.corp", true),
+    ];
+
+    for (registry, host, expected) in test_cases {
+        assert_eq!(
+            registry_host_matches(registry, host),
+            expected,
+            "Failed for registry {} and host {}",
+            registry,
+            host
+        );
+    }
+}
+
+#[test]
+fn test_parse_registry_rule() {
+    let rule: RegistryRule = "Registry.Corp=internal".parse().unwrap();
+    assert_eq!(rule.host, "registry.corp");
+    assert_eq!(rule.class, "internal");
+
+    assert!("registry.corp".parse::<RegistryRule>().is_err());
+    assert!(
+        "registry.corp/team=internal"
+            .parse::<RegistryRule>()
+            .is_err()
+    );
+    assert!("registry.corp=".parse::<RegistryRule>().is_err());
+}
+
+#[test]
+fn test_classify_registry() {
+    let classifier = classifier();
+    let test_cases = vec![
+        ("docker.io", "public"),
+        ("us-docker.pkg.dev", "public"),
+        ("evilpkg.dev", "unknown"),
+        ("registry.corp", "internal"),
+        ("cache.registry.corp:5000", "internal"),
+        ("vendor.example.com", "vendor"),
+        // User rules win over the built-in public list
+        ("eu.gcr.io", "vendor"),
+        ("gcr.io", "public"),
+        ("my-registry:5000", "unknown"),
+    ];
+
+    for (registry, expected) in test_cases {
+        assert_eq!(
+            classifier.classify(registry),
+            expected,
+            "Failed for registry: {}",
+            registry
+        );
+    }
+}
+
+#[test]
+fn test_registry_rule_precedence() {
+    let rule = |s: &str| s.parse::<RegistryRule>().unwrap();
+    // Environment, then config file
+    let classifier =
+        RegistryClassifier::new(vec![rule("registry.corp=env"), rule("vendor.io=vendor")])
+            .with_rules(vec![rule("Registry.Corp=internal")]);
+
+    assert_eq!(classifier.classify("registry.corp"), "internal");
+    assert_eq!(classifier.classify("vendor.io"), "vendor");
+}
+
+#[test]
+fn test_apply_registry_classes() {
+    let pod = create_test_pod(
+        "test-pod",
+        &[
+            "nginx:1.25",
+            "harbor/team/app:v1",
+            "registry.corp/team/api:v2",
+            "other/app:v1",
+        ],
+    );
+
+    let mut images = process_pod(&pod);
+    classifier().apply(&mut images);
+
+    assert_eq!(images[0].registry, "docker.io");
+    assert_eq!(images[0].registry_class, "public");
+
+    // A first component without a domain or port is a Docker Hub namespace,
+    // even when a rule names it
+    assert_eq!(images[1].registry, "docker.io");
+    assert_eq!(images[1].image_name, "harbor/team/app");
+    assert_eq!(images[1].registry_class, "public");
+
+    assert_eq!(images[2].registry, "registry.corp");
+    assert_eq!(images[2].registry_class, "internal");
+
+    assert_eq!(images[3].registry, "docker.io");
+    assert_eq!(images[3].image_name, "other/app");
+}
+
+#[test]
+fn test_registry_rows_source() {
+    let registries =
+        |names: &[&str]| -> Vec<String> { names.iter().map(|name| name.to_string()).collect() };
+    let running = registries(&["docker.io", "registry.corp"]);
+    let scheduled = registries(&["registry.corp", "ghcr.io"]);
+
+    let rows = registry_rows(&running, &scheduled, &MirrorMap::default(), &classifier());
+    assert_eq!(
+        rows,
+        vec![
+            registries(&["CONTAINER REGISTRY", "CLASS", "SOURCE"]),
+            registries(&["docker.io", "public", "running"]),
+            registries(&["ghcr.io", "public", "scheduled"]),
+            // Used by both, a running workload pulls from it
+            registries(&["registry.corp", "internal", "running"]),
+        ]
+    );
+
+    // Without scheduled-only registries there is no SOURCE column
+    let rows = registry_rows(&running, &running[..1], &mirrors(), &classifier());
+    assert_eq!(
+        rows[0],
+        registries(&["CONTAINER REGISTRY", "CLASS", "UPSTREAM"])
+    );
+    assert_eq!(rows.len(), 3);
+}
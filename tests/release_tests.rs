@@ -0,0 +1,200 @@
+use anyhow::Result;
+use k8s_openapi::api::core::v1::{Container, Pod, PodSpec};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::{ObjectMeta, OwnerReference};
+use kimspect::{
+    HELM_CHART_LABEL, HELM_RELEASE_ANNOTATION, INSTANCE_LABEL, ImageQuery, MANAGED_BY_LABEL,
+    PodSource, ReleaseInfo, collect_pod_images, group_by_release, parse_manifests, process_pod,
+};
+use std::collections::{BTreeMap, HashMap};
+
+fn metadata(name: &str, labels: &[(&str, &str)], annotations: &[(&str, &str)]) -> ObjectMeta {
+    let map = |pairs: &[(&str, &str)]| {
+        (!pairs.is_empty()).then(|| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<BTreeMap<_, _>>()
+        })
+    };
+    ObjectMeta {
+        name: Some(name.to_string()),
+        namespace: Some("default".to_string()),
+        labels: map(labels),
+        annotations: map(annotations),
+        ..Default::default()
+    }
+}
+
+fn owned_by(mut meta: ObjectMeta, kind: &str, name: &str) -> ObjectMeta {
+    meta.owner_references = Some(vec![OwnerReference {
+        kind: kind.to_string(),
+        name: name.to_string(),
+        controller: Some(true),
+        ..Default::default()
+    }]);
+    meta
+}
+
+fn create_test_pod(metadata: ObjectMeta, image: &str) -> Pod {
+    Pod {
+        metadata,
+        spec: Some(PodSpec {
+            containers: vec![Container {
+                name: "app".to_string(),
+                image: Some(image.to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Pod source whose owners are looked up from a map, as a cluster would
+struct OwnedPods {
+    pods: Vec<Pod>,
+    owners: HashMap<String, ObjectMeta>,
+}
+
+impl PodSource for OwnedPods {
+    async fn list_pods(&self, query: &ImageQuery) -> Result<Vec<Pod>> {
+        self.pods.list_pods(query).await
+    }
+
+    async fn owner_metadata(
+        &self,
+        _namespace: &str,
+        owner: &OwnerReference,
+    ) -> Result<Option<ObjectMeta>> {
+        Ok(self.owners.get(&owner.name).cloned())
+    }
+}
+
+#[test]
+fn test_release_info_from_metadata() {
+    let helm = metadata(
+        "web",
+        &[
+            (MANAGED_BY_LABEL, "Helm"),
+            (INSTANCE_LABEL, "web-prod"),
+            (HELM_CHART_LABEL, "nginx-15.4.2"),
+        ],
+        &[],
+    );
+    let info = ReleaseInfo::from_metadata(&helm);
+    assert_eq!(info.release, "web-prod");
+    assert_eq!(info.chart, "nginx-15.4.2");
+    assert_eq!(info.managed_by, "Helm");
+
+    // The Helm annotation takes precedence over the instance label
+    let annotated = metadata(
+        "web",
+        &[(INSTANCE_LABEL, "web")],
+        &[(HELM_RELEASE_ANNOTATION, "web-prod")],
+    );
+    assert_eq!(ReleaseInfo::from_metadata(&annotated).release, "web-prod");
+
+    // The instance label alone does not name a release
+    let unmanaged = metadata("web", &[(INSTANCE_LABEL, "web")], &[]);
+    assert!(ReleaseInfo::from_metadata(&unmanaged).is_empty());
+}
+
+#[test]
+fn test_process_pod_reads_release_labels() {
+    let pod = create_test_pod(
+        metadata(
+            "web-1",
+            &[(MANAGED_BY_LABEL, "Helm"), (INSTANCE_LABEL, "web")],
+            &[],
+        ),
+        "nginx:1.25",
+    );
+    let images = process_pod(&pod);
+    assert_eq!(images[0].release, "web");
+    assert_eq!(images[0].managed_by, "Helm");
+    assert!(images[0].chart.is_empty());
+}
+
+#[tokio::test]
+async fn test_collect_pod_images_resolves_owners() -> Result<()> {
+    let source = OwnedPods {
+        pods: vec![
+            create_test_pod(
+                owned_by(metadata("web-abc-1", &[], &[]), "ReplicaSet", "web-abc"),
+                "nginx:1.25",
+            ),
+            create_test_pod(
+                owned_by(metadata("web-abc-2", &[], &[]), "ReplicaSet", "web-abc"),
+                "nginx:1.25",
+            ),
+            create_test_pod(metadata("debug", &[], &[]), "busybox:1.36"),
+        ],
+        owners: HashMap::from([
+            (
+                "web-abc".to_string(),
+                owned_by(
+                    metadata("web-abc", &[(HELM_CHART_LABEL, "nginx-15.4.2")], &[]),
+                    "Deployment",
+                    "web",
+                ),
+            ),
+            (
+                "web".to_string(),
+                metadata(
+                    "web",
+                    &[(MANAGED_BY_LABEL, "Helm")],
+                    &[(HELM_RELEASE_ANNOTATION, "web-prod")],
+                ),
+            ),
+        ]),
+    };
+
+    // Owners are only looked up when requested
+    let images = collect_pod_images(&source, &ImageQuery::new()).await?;
+    assert!(images.iter().all(|image| image.release.is_empty()));
+
+    let query = ImageQuery::new().resolve_owners(true);
+    let images = collect_pod_images(&source, &query).await?;
+    assert_eq!(images[0].release, "web-prod");
+    assert_eq!(images[0].chart, "nginx-15.4.2");
+    assert_eq!(images[0].managed_by, "Helm");
+    assert!(images[2].release.is_empty());
+
+    let groups = group_by_release(&images);
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].release, "web-prod");
+    assert_eq!(groups[0].charts, vec!["nginx-15.4.2"]);
+    assert_eq!(groups[0].images, vec!["docker.io/nginx:1.25"]);
+    assert_eq!(groups[0].containers, 2);
+    assert!(groups[1].release.is_empty());
+    assert_eq!(groups[1].images, vec!["docker.io/busybox:1.36"]);
+    Ok(())
+}
+
+#[test]
+fn test_manifests_keep_workload_release() -> Result<()> {
+    let pods = parse_manifests(
+        r#"
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: web
+  labels:
+    app.kubernetes.io/managed-by: Helm
+    helm.sh/chart: nginx-15.4.2
+  annotations:
+    meta.helm.sh/release-name: web-prod
+spec:
+  template:
+    spec:
+      containers:
+        - name: nginx
+          image: nginx:1.25
+"#,
+    )?;
+
+    let images = process_pod(&pods[0]);
+    assert_eq!(images[0].release, "web-prod");
+    assert_eq!(images[0].chart, "nginx-15.4.2");
+    Ok(())
+}
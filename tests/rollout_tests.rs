@@ -0,0 +1,273 @@
+mod common;
+
+use common::{DIGEST, OTHER_DIGEST, PodBuilder};
+use k8s_openapi::api::apps::v1::{DaemonSet, Deployment, ReplicaSet, StatefulSet};
+use k8s_openapi::api::core::v1::{Pod, PodSpec};
+use kimspect::{ImageVersion, WorkloadRevision, summarize_rollout};
+use serde_json::json;
+
+/// Build a running pod of a ReplicaSet of the Deployment web
+fn create_pod(name: &str, hash: &str, node: &str, image: &str, digest: &str) -> Pod {
+    PodBuilder::new(name, "shop")
+        .label("pod-template-hash", hash)
+        .owner("ReplicaSet", &format!("web-{}", hash))
+        .node(node)
+        .running("web", image, &format!("ghcr.io/org/web@{}", digest))
+        .container("proxy", "envoyproxy/envoy:v1.30")
+        .status(
+            "proxy",
+            "docker.io/envoyproxy/envoy:v1.30",
+            &format!("docker.io/envoyproxy/envoy@{}", DIGEST),
+        )
+        .build()
+}
+
+fn template(image: &str) -> PodSpec {
+    serde_json::from_value(json!({
+        "containers": [
+            { "name": "web", "image": image },
+            { "name": "proxy", "image": "envoyproxy/envoy:v1.30" },
+        ],
+    }))
+    .unwrap()
+}
+
+fn revision(hash: &str) -> WorkloadRevision {
+    WorkloadRevision::new("pod-template-hash", hash)
+}
+
+fn version(image: &str, digest: &str, desired: bool, nodes: &[&str]) -> ImageVersion {
+    let mut version = ImageVersion::default();
+    version.image = image.to_string();
+    version.digest = digest.to_string();
+    version.desired = desired;
+    version.pods = nodes.len();
+    version.nodes = nodes.iter().map(|node| node.to_string()).collect();
+    version
+}
+
+#[test]
+fn test_summarize_rollout_in_progress() {
+    let mut other = create_pod(
+        "api-5c6d7-a",
+        "5c6d7",
+        "worker-1",
+        "ghcr.io/org/web:1.1",
+        OTHER_DIGEST,
+    );
+    other.metadata.owner_references.as_mut().unwrap()[0].name = "api-5c6d7".to_string();
+    let pods = [
+        create_pod(
+            "web-8f9a1-a",
+            "8f9a1",
+            "worker-1",
+            "ghcr.io/org/web:1.2",
+            DIGEST,
+        ),
+        create_pod(
+            "web-7d9f8-a",
+            "7d9f8",
+            "worker-2",
+            "ghcr.io/org/web:1.1",
+            OTHER_DIGEST,
+        ),
+        create_pod(
+            "web-7d9f8-b",
+            "7d9f8",
+            "worker-3",
+            "ghcr.io/org/web:1.1",
+            OTHER_DIGEST,
+        ),
+        // Pods of other workloads are left out
+        other,
+    ];
+
+    let rollout = summarize_rollout(
+        "shop",
+        "Deployment/web",
+        &template("ghcr.io/org/web:1.2"),
+        Some(&revision("8f9a1")),
+        &pods,
+    );
+    assert_eq!(rollout.workload, "Deployment/web");
+    assert_eq!(rollout.containers.len(), 2);
+
+    let web = &rollout.containers[0];
+    assert_eq!(web.container, "web");
+    assert_eq!(web.desired, "ghcr.io/org/web:1.2");
+    assert_eq!(
+        web.versions,
+        vec![
+            version("ghcr.io/org/web:1.2", DIGEST, true, &["worker-1"]),
+            version(
+                "ghcr.io/org/web:1.1",
+                OTHER_DIGEST,
+                false,
+                &["worker-2", "worker-3"]
+            ),
+        ]
+    );
+    assert_eq!((web.updated(), web.total()), (1, 3));
+    assert_eq!(web.percent_complete(), 33);
+    assert_eq!(web.outdated_nodes(), vec!["worker-2", "worker-3"]);
+
+    // The sidecar did not change, old pods run the same digest: it is fully rolled out
+    let proxy = &rollout.containers[1];
+    assert_eq!(proxy.percent_complete(), 100);
+    assert!(proxy.outdated_nodes().is_empty());
+}
+
+#[test]
+fn test_summarize_rollout_without_pods() {
+    let rollout = summarize_rollout(
+        "shop",
+        "Deployment/web",
+        &template("ghcr.io/org/web:1.3"),
+        None,
+        &[create_pod(
+            "web-7d9f8-a",
+            "7d9f8",
+            "worker-1",
+            "ghcr.io/org/web:1.2",
+            DIGEST,
+        )],
+    );
+    let web = &rollout.containers[0];
+    // No pod runs the desired image yet
+    assert_eq!(web.percent_complete(), 0);
+    assert_eq!(web.outdated_nodes(), vec!["worker-1"]);
+
+    let empty = summarize_rollout(
+        "shop",
+        "Deployment/web",
+        &template("ghcr.io/org/web:1.3"),
+        None,
+        &[],
+    );
+    assert!(empty.containers[0].versions.is_empty());
+    assert_eq!(empty.containers[0].percent_complete(), 100);
+}
+
+#[test]
+fn test_summarize_rollout_by_revision() {
+    let pods = [
+        create_pod(
+            "web-8f9a1-a",
+            "8f9a1",
+            "worker-1",
+            "ghcr.io/org/web:1.2",
+            DIGEST,
+        ),
+        // The tag moved: the old revision runs the same tag, another digest
+        create_pod(
+            "web-7d9f8-a",
+            "7d9f8",
+            "worker-2",
+            "ghcr.io/org/web:1.2",
+            OTHER_DIGEST,
+        ),
+    ];
+    let rollout = summarize_rollout(
+        "shop",
+        "Deployment/web",
+        &template("ghcr.io/org/web:1.2"),
+        Some(&revision("8f9a1")),
+        &pods,
+    );
+    let web = &rollout.containers[0];
+    assert_eq!(
+        web.versions,
+        vec![
+            version("ghcr.io/org/web:1.2", DIGEST, true, &["worker-1"]),
+            version("ghcr.io/org/web:1.2", OTHER_DIGEST, false, &["worker-2"]),
+        ]
+    );
+    assert_eq!(web.percent_complete(), 50);
+
+    // A template pinned by digest is not satisfied by a tag
+    let pinned = format!("ghcr.io/org/web@{}", DIGEST);
+    for revision in [Some(revision("0a1b2")), None] {
+        let rollout = summarize_rollout(
+            "shop",
+            "Deployment/web",
+            &template(&pinned),
+            revision.as_ref(),
+            &pods,
+        );
+        assert_eq!(rollout.containers[0].percent_complete(), 0);
+        assert_eq!(
+            rollout.containers[0].outdated_nodes(),
+            vec!["worker-1", "worker-2"]
+        );
+    }
+}
+
+#[test]
+fn test_workload_revision() {
+    let deployment: Deployment = serde_json::from_value(json!({
+        "metadata": {
+            "name": "web",
+            "namespace": "shop",
+            "uid": "d-1",
+            "annotations": { "deployment.kubernetes.io/revision": "3" },
+        },
+    }))
+    .unwrap();
+    let replica_set = |name: &str, hash: &str, revision: &str, owner: &str| -> ReplicaSet {
+        serde_json::from_value(json!({
+            "metadata": {
+                "name": name,
+                "namespace": "shop",
+                "labels": { "pod-template-hash": hash },
+                "annotations": { "deployment.kubernetes.io/revision": revision },
+                "ownerReferences": [{
+                    "apiVersion": "apps/v1",
+                    "kind": "Deployment",
+                    "name": owner,
+                    "uid": "d-1",
+                    "controller": true,
+                }],
+            },
+        }))
+        .unwrap()
+    };
+    let replica_sets = [
+        replica_set("web-7d9f8", "7d9f8", "2", "web"),
+        replica_set("api-5c6d7", "5c6d7", "3", "api"),
+        replica_set("web-8f9a1", "8f9a1", "3", "web"),
+    ];
+    assert_eq!(
+        WorkloadRevision::of_deployment(&deployment, &replica_sets),
+        Some(revision("8f9a1"))
+    );
+    // The ReplicaSet of the new revision is not created yet
+    assert_eq!(
+        WorkloadRevision::of_deployment(&deployment, &replica_sets[..2]),
+        None
+    );
+
+    let statefulset: StatefulSet = serde_json::from_value(json!({
+        "metadata": { "name": "db" },
+        "status": { "replicas": 3, "updateRevision": "db-6b8c9" },
+    }))
+    .unwrap();
+    assert_eq!(
+        WorkloadRevision::of_statefulset(&statefulset),
+        Some(WorkloadRevision::new(
+            "controller-revision-hash",
+            "db-6b8c9"
+        ))
+    );
+
+    let daemonset: DaemonSet = serde_json::from_value(json!({
+        "metadata": {
+            "name": "agent",
+            "annotations": { "deprecated.daemonset.template.generation": "4" },
+        },
+    }))
+    .unwrap();
+    assert_eq!(
+        WorkloadRevision::of_daemonset(&daemonset),
+        Some(WorkloadRevision::new("pod-template-generation", "4"))
+    );
+}
@@ -0,0 +1,331 @@
+mod common;
+
+use anyhow::Result;
+use axum::Router;
+use axum::body::{Body, Bytes};
+use axum::extract::{Path, Query};
+use axum::routing::get;
+use common::{DIGEST, PodBuilder, pod_image};
+use k8s_openapi::api::core::v1::{ContainerImage, Node, NodeStatus, Pod};
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use kimspect::{
+    ImageQuery, Inventory, K8sClient, MirrorMap, MirrorRule, RegistryClassifier, ServerState,
+    api_router, metrics_router, parse_listen_addr, registry_usage, render_metrics,
+};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+fn create_pod(name: &str, namespace: &str, image: &str) -> Pod {
+    PodBuilder::new(name, namespace)
+        .node("worker-1")
+        .running(
+            "app",
+            image,
+            &format!("docker-pullable://{}@{}", image, DIGEST),
+        )
+        .build()
+}
+
+fn create_node(name: &str, sizes: &[i64]) -> Node {
+    Node {
+        metadata: ObjectMeta {
+            name: Some(name.to_string()),
+            ..Default::default()
+        },
+        status: Some(NodeStatus {
+            images: Some(
+                sizes
+                    .iter()
+                    .map(|size| ContainerImage {
+                        names: Some(vec![format!("ghcr.io/org/web@{}", DIGEST)]),
+                        size_bytes: Some(*size),
+                    })
+                    .collect(),
+            ),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Serve a router on a free local port
+async fn spawn_server(router: Router) -> Result<SocketAddr> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    Ok(addr)
+}
+
+/// A Kubernetes API server listing fixed pods and nodes, whose watches never report changes
+fn mock_api_server(pods: Vec<Pod>, nodes: Vec<Node>) -> Router {
+    let list = |kind: &'static str, items: Vec<serde_json::Value>| {
+        move |Query(params): Query<HashMap<String, String>>| {
+            let items = items.clone();
+            async move {
+                if params.get("watch").map(String::as_str) == Some("true") {
+                    let events = futures::stream::pending::<Result<Bytes, Infallible>>();
+                    return Body::from_stream(events);
+                }
+                let list = serde_json::json!({
+                    "apiVersion": "v1",
+                    "kind": kind,
+                    "metadata": { "resourceVersion": "1" },
+                    "items": items,
+                });
+                Body::from(list.to_string())
+            }
+        }
+    };
+    let pods: Vec<_> = pods
+        .iter()
+        .map(|pod| serde_json::to_value(pod).unwrap())
+        .collect();
+    let nodes: Vec<_> = nodes
+        .iter()
+        .map(|node| serde_json::to_value(node).unwrap())
+        .collect();
+
+    let namespaced_pods = pods.clone();
+    Router::new()
+        .route("/api/v1/pods", get(list("PodList", pods)))
+        .route(
+            "/api/v1/namespaces/{namespace}/pods",
+            get(move |Path(namespace): Path<String>, query| {
+                let items = namespaced_pods
+                    .iter()
+                    .filter(|pod| pod["metadata"]["namespace"] == namespace.as_str())
+                    .cloned()
+                    .collect();
+                list("PodList", items)(query)
+            }),
+        )
+        .route("/api/v1/nodes", get(list("NodeList", nodes)))
+}
+
+#[test]
+fn test_parse_listen_addr() {
+    assert_eq!(
+        parse_listen_addr(":9090"),
+        Ok("0.0.0.0:9090".parse().unwrap())
+    );
+    assert_eq!(
+        parse_listen_addr("127.0.0.1:8080"),
+        Ok("127.0.0.1:8080".parse().unwrap())
+    );
+    assert_eq!(
+        parse_listen_addr("[::1]:9090"),
+        Ok("[::1]:9090".parse().unwrap())
+    );
+    assert_eq!(
+        parse_listen_addr("9090"),
+        Err("Invalid listen address '9090', expected [HOST]:PORT (e.g. :9090)".to_string())
+    );
+}
+
+#[test]
+fn test_render_metrics() {
+    let image = |pod: &str, container: &str, registry: &str, name: &str, version: &str| {
+        let mut image = pod_image(pod, "shop", registry, name, version);
+        image.container_name = container.to_string();
+        image
+    };
+    let mut web = image("web", "app", "ghcr.io", "org/web", "1.2@sha256:abcd");
+    web.digest = "sha256:abcd".to_string();
+    let images = vec![
+        web,
+        image(
+            "web-2",
+            "app \"main\"",
+            "ghcr.io",
+            "org/web",
+            "1.2@sha256:abcd",
+        ),
+        image("db", "db", "docker.io", "postgres", "@sha256:ef01"),
+    ];
+    let nodes = vec![
+        create_node("worker-1", &[100, 50]),
+        create_node("worker-2", &[]),
+    ];
+
+    let metrics = render_metrics(&images, &nodes);
+    assert!(metrics.contains("# TYPE kimspect_container_image_info gauge\n"));
+    assert!(metrics.contains(
+        "kimspect_container_image_info{namespace=\"shop\",pod=\"web\",container=\"app\",\
+         registry=\"ghcr.io\",image=\"org/web\",tag=\"1.2\",digest=\"sha256:abcd\"} 1\n"
+    ));
+    assert!(metrics.contains("container=\"app \\\"main\\\"\""));
+    assert!(metrics.contains("image=\"postgres\",tag=\"\",digest=\"\"} 1\n"));
+    assert!(metrics.contains("kimspect_registry_images_total{registry=\"docker.io\"} 1\n"));
+    assert!(metrics.contains("kimspect_registry_images_total{registry=\"ghcr.io\"} 1\n"));
+    assert!(metrics.contains("kimspect_node_image_bytes{node=\"worker-1\"} 150\n"));
+    assert!(metrics.contains("kimspect_node_image_bytes{node=\"worker-2\"} 0\n"));
+}
+
+#[tokio::test]
+async fn test_metrics_endpoint() -> Result<()> {
+    let inventory = Inventory::from_objects(
+        vec![create_pod("web", "shop", "ghcr.io/org/web:1.2")],
+        vec![create_node("worker-1", &[1024])],
+    );
+    let state = ServerState::new(
+        inventory,
+        ImageQuery::new().all_namespaces(true),
+        RegistryClassifier::default(),
+        MirrorMap::default(),
+    );
+    let addr = spawn_server(metrics_router(Arc::new(state))).await?;
+
+    let response = reqwest::get(format!("http://{}/metrics", addr)).await?;
+    assert_eq!(response.status(), 200);
+    assert_eq!(
+        response.headers()["content-type"],
+        "text/plain; version=0.0.4; charset=utf-8"
+    );
+    let body = response.text().await?;
+    assert!(body.contains("pod=\"web\""));
+    assert!(body.contains(&format!("digest=\"{}\"", DIGEST)));
+    assert!(body.contains("kimspect_node_image_bytes{node=\"worker-1\"} 1024"));
+
+    let health = reqwest::get(format!("http://{}/healthz", addr)).await?;
+    assert_eq!(health.status(), 200);
+
+    // The inventory is not readable from the metrics listener
+    for path in ["images", "registries"] {
+        let response = reqwest::get(format!("http://{}/{}", addr, path)).await?;
+        assert_eq!(response.status(), 404);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_watch_inventory_from_api_server() -> Result<()> {
+    let api = spawn_server(mock_api_server(
+        vec![
+            create_pod("web", "shop", "ghcr.io/org/web:1.2"),
+            create_pod("api", "billing", "quay.io/org/api:2.0"),
+        ],
+        vec![create_node("worker-1", &[2048])],
+    ))
+    .await?;
+    let config = kube::Config::new(format!("http://{}", api).parse()?);
+    let client = K8sClient::from(kube::Client::try_from(config)?);
+
+    let (inventory, watch) = client.watch_inventory(Some("shop"));
+    let watch = tokio::spawn(watch);
+    tokio::time::timeout(Duration::from_secs(10), inventory.wait_until_ready()).await??;
+    assert!(inventory.is_ready());
+    assert_eq!(inventory.pods().len(), 1);
+    assert_eq!(inventory.last_error(), None);
+
+    let state = ServerState::new(
+        inventory,
+        ImageQuery::new().namespace("shop"),
+        RegistryClassifier::default(),
+        MirrorMap::default(),
+    );
+    let images = state.images().await;
+    assert_eq!(images.len(), 1);
+    assert_eq!(images[0].registry, "ghcr.io");
+
+    watch.abort();
+    Ok(())
+}
+
+#[test]
+fn test_registry_usage() {
+    let image = |pod: &str, registry: &str, name: &str, version: &str| {
+        let mut image = pod_image(pod, "", registry, name, version);
+        image.registry_class = "public".to_string();
+        image
+    };
+    let images = vec![
+        image("web-1", "mirror.corp", "library/nginx", "1.25"),
+        image("web-2", "mirror.corp", "library/nginx", "1.25"),
+        image("db", "mirror.corp", "library/postgres", "16"),
+        image("api", "ghcr.io", "org/api", "2.0"),
+    ];
+    let mirrors = MirrorMap::default()
+        .with_rules(vec!["mirror.corp=docker.io".parse::<MirrorRule>().unwrap()]);
+
+    let usage: Vec<_> = registry_usage(&images, &mirrors)
+        .into_iter()
+        .map(|usage| {
+            (
+                usage.registry,
+                usage.class,
+                usage.upstream,
+                usage.images,
+                usage.containers,
+            )
+        })
+        .collect();
+    assert_eq!(
+        usage,
+        vec![
+            ("ghcr.io".to_string(), "public".to_string(), vec![], 1, 1),
+            (
+                "mirror.corp".to_string(),
+                "public".to_string(),
+                vec!["docker.io".to_string()],
+                2,
+                3
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_api_endpoints() -> Result<()> {
+    let inventory = Inventory::from_objects(
+        vec![
+            create_pod("web", "shop", "ghcr.io/org/web:1.2"),
+            create_pod("db", "shop", "docker.io/library/postgres:16"),
+            create_pod("api", "billing", "ghcr.io/org/api:2.0"),
+            create_pod("agent", "ops", "ghcr.io/org/agent:1.0"),
+        ],
+        vec![],
+    );
+    let state = ServerState::new(
+        inventory,
+        ImageQuery::new()
+            .all_namespaces(true)
+            .exclude_namespace("ops"),
+        RegistryClassifier::default(),
+        MirrorMap::default(),
+    );
+    let addr = spawn_server(api_router(Arc::new(state))).await?;
+    let get = |path: &str| reqwest::get(format!("http://{}{}", addr, path));
+
+    let images: serde_json::Value = get("/images").await?.json().await?;
+    assert_eq!(images["kind"], "List");
+    assert_eq!(images["items"].as_array().unwrap().len(), 3);
+
+    let response = get("/images?namespace=shop&registry=ghcr.io").await?;
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.headers()["content-type"], "application/json");
+    let images: serde_json::Value = response.json().await?;
+    let items = images["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["pod"], "web");
+    assert_eq!(items[0]["registry"], "ghcr.io");
+    assert_eq!(items[0]["digest"], DIGEST);
+
+    let registries: serde_json::Value = get("/registries?namespace=shop").await?.json().await?;
+    assert_eq!(
+        registries["items"],
+        serde_json::json!([
+            { "registry": "docker.io", "class": "public", "upstream": [], "images": 1, "containers": 1 },
+            { "registry": "ghcr.io", "class": "public", "upstream": [], "images": 1, "containers": 1 },
+        ])
+    );
+
+    let response = get("/images?namespace=ops").await?;
+    assert_eq!(response.status(), 404);
+    let error: serde_json::Value = response.json().await?;
+    assert_eq!(error["error"], "Namespace ops is not watched");
+    Ok(())
+}
@@ -0,0 +1,247 @@
+mod common;
+
+use common::pod_image;
+use kimspect::cli::OutputFormat;
+use kimspect::{GoTemplate, JsonPath, PodImage, TemplateError, image_list, render_image_template};
+
+fn create_pod_image(pod: &str, namespace: &str, registry: &str, name: &str) -> PodImage {
+    let mut image = pod_image(pod, namespace, registry, name, "1.0");
+    image.managed_by = "Helm".to_string();
+    image
+}
+
+fn images() -> Vec<PodImage> {
+    let mut web = create_pod_image("web", "shop", "ghcr.io", "org/web");
+    web.image_size_bytes = Some(52_428_800);
+    let mut cache = create_pod_image("cache", "shop", "docker.io", "library/redis");
+    cache.image_size_bytes = Some(120_000_000);
+    vec![
+        web,
+        cache,
+        create_pod_image("api", "billing", "quay.io", "org/api"),
+    ]
+}
+
+fn jsonpath(template: &str) -> String {
+    JsonPath::parse(template)
+        .unwrap()
+        .execute(&image_list(&images()))
+}
+
+fn go_template(template: &str) -> Result<String, TemplateError> {
+    GoTemplate::parse(template)?.execute(&image_list(&images()))
+}
+
+#[test]
+fn test_image_list_records() {
+    let list = image_list(&images());
+    assert_eq!(list["kind"], "List");
+    assert_eq!(list["items"][0]["reference"], "ghcr.io/org/web:1.0");
+    assert_eq!(list["items"][0]["managedBy"], "Helm");
+    assert_eq!(list["items"][0]["digest"], "");
+    assert_eq!(list["items"][0]["sizeBytes"], 52_428_800);
+    assert!(list["items"][2]["sizeBytes"].is_null());
+}
+
+#[test]
+fn test_jsonpath_range() {
+    assert_eq!(
+        jsonpath(r#"{range .items[*]}{.registry}/{.image}:{.version}{"\n"}{end}"#),
+        "ghcr.io/org/web:1.0\ndocker.io/library/redis:1.0\nquay.io/org/api:1.0\n"
+    );
+    assert_eq!(
+        jsonpath(r#"{range .items[*]}{@.pod}{"\t"}{$.kind}{"\n"}{end}"#),
+        "web\tList\ncache\tList\napi\tList\n"
+    );
+}
+
+#[test]
+fn test_jsonpath_selectors() {
+    let test_cases = vec![
+        ("{.items[*].pod}", "web cache api"),
+        ("{.items[0].namespace}", "shop"),
+        ("{.items[-1].pod}", "api"),
+        ("{.items[1:].pod}", "cache api"),
+        ("{.items[::2].pod}", "web api"),
+        ("{.items[0,2].pod}", "web api"),
+        ("{.items[0]['pod','image']}", "web org/web"),
+        (r#"{.items[?(@.registry=="docker.io")].pod}"#, "cache"),
+        (r#"{.items[?(@.namespace!='shop')].pod}"#, "api"),
+        ("{..registry}", "ghcr.io docker.io quay.io"),
+        ("{.items[5].pod}", ""),
+        ("{.items[0].missing}", ""),
+        ("pods: {.items[0].pod}", "pods: web"),
+        ("{.items[0].sizeBytes}", "52428800"),
+        ("{.items[?(@.sizeBytes>100000000)].pod}", "cache"),
+        ("{.items[?(@.sizeBytes<=52428800)].pod}", "web"),
+    ];
+
+    for (template, expected) in test_cases {
+        assert_eq!(jsonpath(template), expected, "{}", template);
+    }
+}
+
+#[test]
+fn test_jsonpath_invalid() {
+    let invalid = [
+        "{range .items[*]}{.pod}",
+        "{end}",
+        "{.items[*].pod",
+        "{items}",
+        "{.items[1:2:3:4]}",
+        r#"{"unterminated}"#,
+    ];
+    for template in invalid {
+        assert!(
+            matches!(JsonPath::parse(template), Err(TemplateError::Parse(_))),
+            "{}",
+            template
+        );
+    }
+}
+
+#[test]
+fn test_go_template_range_and_conditions() -> Result<(), TemplateError> {
+    assert_eq!(
+        go_template(r#"{{range .items}}{{.registry}}/{{.image}}:{{.version}}{{"\n"}}{{end}}"#)?,
+        "ghcr.io/org/web:1.0\ndocker.io/library/redis:1.0\nquay.io/org/api:1.0\n"
+    );
+    assert_eq!(
+        go_template(
+            r#"{{range .items}}{{if eq .registry "docker.io"}}hub{{else if eq .namespace "billing"}}billing{{else}}-{{end}} {{end}}"#
+        )?,
+        "- hub billing "
+    );
+    assert_eq!(
+        go_template("{{range $i, $img := .items}}{{$i}}={{$img.pod}} {{end}}")?,
+        "0=web 1=cache 2=api "
+    );
+    assert_eq!(
+        go_template("{{range .missing}}x{{else}}none{{end}}")?,
+        "none"
+    );
+    assert_eq!(
+        go_template("{{with index .items 1}}{{.pod}}{{end}}")?,
+        "cache"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_go_template_functions_and_trimming() -> Result<(), TemplateError> {
+    let test_cases = vec![
+        ("{{len .items}}", "3"),
+        ("{{(index .items 2).pod}}", "api"),
+        ("{{index .items 0 \"namespace\"}}", "shop"),
+        ("{{printf \"%-6s|%5s\" \"a\" \"b\"}}", "a     |    b"),
+        ("{{.kind | printf \"%q\"}}", "\"List\""),
+        ("{{$n := len .items}}{{if gt $n 2}}many{{end}}", "many"),
+        (
+            "{{and .kind .missing}}|{{or .missing .kind}}",
+            "<no value>|List",
+        ),
+        ("{{not .items}}", "false"),
+        ("{{.missing}}", "<no value>"),
+        ("a  {{- .kind -}}  b", "aListb"),
+        ("{{/* comment */}}x", "x"),
+    ];
+
+    for (template, expected) in test_cases {
+        assert_eq!(go_template(template)?, expected, "{}", template);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_go_template_typed_values() -> Result<(), TemplateError> {
+    // Expected output is what kubectl prints for the same template
+    let test_cases = vec![
+        ("{{printf \"%5d|%-5d|%05d\" 42 42 -3}}", "   42|42   |-0003"),
+        ("{{printf \"%+d %.2f %t\" 5 1.5 true}}", "+5 1.50 true"),
+        ("{{printf \"%.3s|%v|%v\" \"abcdef\" 7 .kind}}", "abc|7|List"),
+        ("{{printf \"%d\" .kind}}", "%!d(string=List)"),
+        (
+            "{{printf \"%s\" (index .items 0).sizeBytes}}",
+            "%!s(int64=52428800)",
+        ),
+        ("{{printf \"%d %d\" 1}}", "1 %!d(MISSING)"),
+        ("{{range 3}}{{.}}{{end}}", "012"),
+        ("{{range $i := 2}}{{$i}},{{end}}", "0,1,"),
+        ("{{range 0}}x{{else}}none{{end}}", "none"),
+        (
+            "{{range .items}}{{with .sizeBytes}}{{if gt . 100000000}}big{{else}}small{{end}}{{else}}unknown{{end}} {{end}}",
+            "small big unknown ",
+        ),
+        ("{{(index .items 0).sizeBytes}}", "52428800"),
+        ("{{lt 9 10}} {{lt \"9\" \"10\"}}", "true false"),
+        ("{{eq .kind \"Pod\" \"List\"}}", "true"),
+        ("{{eq .missing .kind}}", "false"),
+    ];
+
+    for (template, expected) in test_cases {
+        assert_eq!(go_template(template)?, expected, "{}", template);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_go_template_errors() {
+    let invalid = [
+        "{{range .items}}",
+        "{{end}}",
+        "{{.kind",
+        "{{foo .kind}}",
+        "{{if .kind}}{{else}}",
+        "{{template \"x\"}}",
+    ];
+    for template in invalid {
+        assert!(
+            matches!(GoTemplate::parse(template), Err(TemplateError::Parse(_))),
+            "{}",
+            template
+        );
+    }
+
+    assert!(matches!(
+        go_template("{{range .kind}}{{end}}"),
+        Err(TemplateError::Execute(_))
+    ));
+    let failing = [
+        "{{lt .kind 3}}",
+        "{{eq .kind 3}}",
+        "{{ne (len .items) \"3\"}}",
+        "{{printf \"%999999999d\" 1}}",
+        "{{printf \"%.99999999999999999999f\" 1.5}}",
+        "{{printf \"%1025s\" .kind}}",
+    ];
+    for template in failing {
+        assert!(
+            matches!(go_template(template), Err(TemplateError::Execute(_))),
+            "{}",
+            template
+        );
+    }
+    assert_eq!(
+        go_template("{{printf \"%1024s\" .kind}}").unwrap().len(),
+        1024
+    );
+}
+
+#[test]
+fn test_render_image_template() -> Result<(), TemplateError> {
+    let output: OutputFormat = "jsonpath={.items[*].namespace}".parse().unwrap();
+    assert!(output.is_template());
+    assert_eq!(
+        render_image_template(&images(), &output)?,
+        "shop shop billing"
+    );
+
+    let output: OutputFormat = "go-template={{range .items}}{{.pod}},{{end}}"
+        .parse()
+        .unwrap();
+    assert_eq!(render_image_template(&images(), &output)?, "web,cache,api,");
+
+    assert!("jsonpath={.items[".parse::<OutputFormat>().is_err());
+    assert!("go-template={{end}}".parse::<OutputFormat>().is_err());
+    Ok(())
+}
@@ -0,0 +1,198 @@
+mod common;
+
+use anyhow::Result;
+use common::PodBuilder;
+use k8s_openapi::api::core::v1::Pod;
+use kimspect::{
+    ContainerRow, FilterField, ImageFilter, ImageQuery, Inventory, TuiApp, TuiPane,
+    collect_pod_images, container_rows, detail_fields, draw_tui, pod_workload,
+};
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+
+fn create_pod(name: &str, namespace: &str, owner: Option<(&str, &str)>, image: &str) -> Pod {
+    let pod = PodBuilder::new(name, namespace)
+        .label("pod-template-hash", "5d8f7c")
+        .node(&format!("node-{}", name))
+        .container("app", image)
+        .pull_policy("IfNotPresent");
+    match owner {
+        Some((kind, owner)) => pod.owner(kind, owner).build(),
+        None => pod.build(),
+    }
+}
+
+fn pods() -> Vec<Pod> {
+    vec![
+        create_pod(
+            "web-5d8f7c-abcde",
+            "shop",
+            Some(("ReplicaSet", "web-5d8f7c")),
+            "ghcr.io/org/web:1.2",
+        ),
+        create_pod("db-0", "shop", Some(("StatefulSet", "db")), "postgres:16"),
+        create_pod("debug", "ops", None, "busybox:latest"),
+    ]
+}
+
+async fn rows() -> Result<Vec<ContainerRow>> {
+    let inventory = Inventory::from_objects(pods(), Vec::new());
+    let images = collect_pod_images(&inventory, &ImageQuery::new().all_namespaces(true)).await?;
+    let pods = inventory.pods();
+    Ok(container_rows(pods.iter().map(|pod| pod.as_ref()), images))
+}
+
+fn press(app: &mut TuiApp, keys: &[KeyCode]) {
+    for key in keys {
+        app.handle_key(KeyEvent::from(*key));
+    }
+}
+
+#[test]
+fn test_pod_workload() {
+    let pods = pods();
+    assert_eq!(pod_workload(&pods[0]), "Deployment/web");
+    assert_eq!(pod_workload(&pods[1]), "StatefulSet/db");
+    assert_eq!(pod_workload(&pods[2]), "");
+
+    // A ReplicaSet not named after the template hash is not a Deployment's
+    let pod = create_pod("x", "shop", Some(("ReplicaSet", "standalone")), "nginx");
+    assert_eq!(pod_workload(&pod), "ReplicaSet/standalone");
+}
+
+#[tokio::test]
+async fn test_inventory_container_rows() -> Result<()> {
+    let rows = rows().await?;
+    assert_eq!(rows.len(), 3);
+
+    // Ordered by namespace, then pod
+    assert_eq!(rows[0].image.namespace, "ops");
+    assert_eq!(rows[1].image.pod_name, "db-0");
+    assert_eq!(rows[1].workload, "StatefulSet/db");
+    assert_eq!(rows[2].workload, "Deployment/web");
+    assert_eq!(rows[2].pull_policy, "IfNotPresent");
+    assert_eq!(rows[2].image.registry, "ghcr.io");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tui_navigation() -> Result<()> {
+    let mut app = TuiApp::new(ImageFilter::default());
+    assert!(!app.is_loaded());
+    app.set_rows(rows().await?);
+
+    assert_eq!(app.entries(TuiPane::Namespaces), vec!["ops", "shop"]);
+    assert_eq!(app.visible_rows().len(), 3);
+
+    // Select the second namespace, then its first workload
+    press(&mut app, &[KeyCode::Down, KeyCode::Down]);
+    assert_eq!(app.selection(TuiPane::Namespaces), Some("shop"));
+    assert_eq!(
+        app.entries(TuiPane::Workloads),
+        vec!["Deployment/web", "StatefulSet/db"]
+    );
+    press(&mut app, &[KeyCode::Tab, KeyCode::Down]);
+    assert_eq!(app.focus(), TuiPane::Workloads);
+    assert_eq!(app.selection(TuiPane::Workloads), Some("Deployment/web"));
+    assert_eq!(app.entries(TuiPane::Pods), vec!["web-5d8f7c-abcde"]);
+    assert_eq!(app.visible_rows().len(), 1);
+
+    // Changing the namespace resets the workload
+    press(&mut app, &[KeyCode::BackTab, KeyCode::Up]);
+    assert_eq!(app.selection(TuiPane::Namespaces), Some("ops"));
+    assert_eq!(app.selection(TuiPane::Workloads), None);
+
+    press(&mut app, &[KeyCode::Up, KeyCode::Up]);
+    assert_eq!(app.selection(TuiPane::Namespaces), None);
+    assert!(!app.should_quit());
+    press(&mut app, &[KeyCode::Char('q')]);
+    assert!(app.should_quit());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tui_filters() -> Result<()> {
+    let mut app = TuiApp::new(ImageFilter::default().registry("GHCR"));
+    app.set_rows(rows().await?);
+    assert_eq!(app.visible_rows().len(), 1);
+    assert_eq!(app.entries(TuiPane::Namespaces), vec!["shop"]);
+
+    press(&mut app, &[KeyCode::Char('c'), KeyCode::Char('i')]);
+    assert_eq!(app.editing(), Some(FilterField::Image));
+    press(
+        &mut app,
+        &[KeyCode::Char('p'), KeyCode::Char('o'), KeyCode::Char('s')],
+    );
+    assert_eq!(app.filter().image, "pos");
+    press(&mut app, &[KeyCode::Enter]);
+    assert_eq!(app.editing(), None);
+    assert_eq!(app.visible_rows().len(), 1);
+    assert_eq!(app.visible_rows()[0].image.image_name, "postgres");
+
+    // Escape clears the filter being typed
+    press(
+        &mut app,
+        &[KeyCode::Char('n'), KeyCode::Char('x'), KeyCode::Esc],
+    );
+    assert_eq!(app.filter().node, "");
+    assert!(!app.should_quit());
+
+    // A selection hidden by a filter falls back to all entries
+    press(&mut app, &[KeyCode::Down]);
+    assert_eq!(app.selection(TuiPane::Namespaces), Some("shop"));
+    press(&mut app, &[KeyCode::Char('c'), KeyCode::Char('i')]);
+    press(
+        &mut app,
+        &[KeyCode::Char('b'), KeyCode::Char('u'), KeyCode::Enter],
+    );
+    assert_eq!(app.selection(TuiPane::Namespaces), None);
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_tui_detail_view() -> Result<()> {
+    let mut app = TuiApp::new(ImageFilter::default());
+    app.set_rows(rows().await?);
+
+    // Enter moves through the panes up to the images, where it opens the detail view
+    press(&mut app, &[KeyCode::Enter, KeyCode::Enter, KeyCode::Enter]);
+    assert_eq!(app.focus(), TuiPane::Images);
+    press(&mut app, &[KeyCode::Down, KeyCode::Down, KeyCode::Down]);
+    assert_eq!(app.selected_index(), 2);
+    press(&mut app, &[KeyCode::Enter]);
+    assert!(app.is_detail_open());
+
+    let fields = detail_fields(app.selected_row().unwrap());
+    let field = |label: &str| {
+        fields
+            .iter()
+            .find(|(name, _)| *name == label)
+            .map(|(_, value)| value.as_str())
+    };
+    assert_eq!(field("Image"), Some("ghcr.io/org/web:1.2"));
+    assert_eq!(field("Workload"), Some("Deployment/web"));
+    assert_eq!(field("Pull policy"), Some("IfNotPresent"));
+    assert_eq!(field("Digest"), Some("<none>"));
+
+    let mut terminal = Terminal::new(TestBackend::new(120, 30))?;
+    terminal.draw(|frame| draw_tui(frame, &app))?;
+    let screen: String = terminal
+        .backend()
+        .buffer()
+        .content()
+        .iter()
+        .map(|cell| cell.symbol())
+        .collect();
+    assert!(screen.contains("3 of 3 containers"));
+    assert!(screen.contains("IfNotPresent"));
+
+    press(&mut app, &[KeyCode::Esc]);
+    assert!(!app.is_detail_open());
+    assert!(!app.should_quit());
+
+    // Small terminals still render
+    let mut terminal = Terminal::new(TestBackend::new(20, 5))?;
+    terminal.draw(|frame| draw_tui(frame, &app))?;
+    Ok(())
+}
@@ -0,0 +1,313 @@
+mod common;
+
+use anyhow::Result;
+use axum::Router;
+use common::{DIGEST, pod_image};
+use kimspect::{
+    ImageAdmission, ImagePolicy, PodImage, PolicyRule, RegistryClassifier, RegistryRule,
+    load_tls_config, serve_tls, webhook_router,
+};
+use serde_json::{Value, json};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+fn create_image(registry: &str, name: &str, version: &str, class: &str) -> PodImage {
+    let mut image = pod_image("", "", registry, name, version);
+    image.registry_class = class.to_string();
+    image
+}
+
+fn rules(policy: &ImagePolicy, image: &PodImage) -> Vec<PolicyRule> {
+    policy
+        .check(image)
+        .into_iter()
+        .map(|violation| violation.rule)
+        .collect()
+}
+
+fn deployment(containers: Value, init_containers: Value) -> Value {
+    json!({
+        "apiVersion": "apps/v1",
+        "kind": "Deployment",
+        "metadata": { "name": "web", "namespace": "shop" },
+        "spec": {
+            "selector": { "matchLabels": { "app": "web" } },
+            "template": {
+                "metadata": { "labels": { "app": "web" } },
+                "spec": { "containers": containers, "initContainers": init_containers },
+            },
+        },
+    })
+}
+
+fn admission_review(object: Value) -> Value {
+    json!({
+        "apiVersion": "admission.k8s.io/v1",
+        "kind": "AdmissionReview",
+        "request": {
+            "uid": "705ab4f5-6393-11e8-b7cc-42010a800002",
+            "kind": { "group": "apps", "version": "v1", "kind": "Deployment" },
+            "resource": { "group": "apps", "version": "v1", "resource": "deployments" },
+            "requestKind": { "group": "apps", "version": "v1", "kind": "Deployment" },
+            "requestResource": { "group": "apps", "version": "v1", "resource": "deployments" },
+            "name": "web",
+            "namespace": "shop",
+            "operation": "CREATE",
+            "userInfo": { "username": "admin" },
+            "object": object,
+            "dryRun": false,
+        },
+    })
+}
+
+fn strict_policy() -> ImagePolicy {
+    ImagePolicy::new()
+        .disallow_registry("docker.io")
+        .deny_latest(true)
+        .require_digest(true)
+}
+
+/// Serve a router on a free local port
+async fn spawn_server(router: Router) -> Result<SocketAddr> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move { axum::serve(listener, router).await });
+    Ok(addr)
+}
+
+#[test]
+fn test_policy_check() {
+    let policy = strict_policy().disallow_class("vendor");
+
+    assert_eq!(
+        rules(
+            &policy,
+            &create_image("ghcr.io", "org/web", &format!("1.2@{}", DIGEST), "public")
+        ),
+        vec![]
+    );
+    // The implicit tag is latest
+    assert_eq!(
+        rules(
+            &policy,
+            &create_image("ghcr.io", "org/web", "latest", "public")
+        ),
+        vec![PolicyRule::LatestTag, PolicyRule::MissingDigest]
+    );
+    // A digest pins the latest tag
+    assert_eq!(
+        rules(
+            &policy,
+            &create_image(
+                "ghcr.io",
+                "org/web",
+                &format!("latest@{}", DIGEST),
+                "public"
+            )
+        ),
+        vec![]
+    );
+    // Subdomains and ports of a disallowed registry are disallowed too
+    assert_eq!(
+        rules(
+            &policy,
+            &create_image(
+                "registry-1.docker.io:443",
+                "nginx",
+                &format!("@{}", DIGEST),
+                "public"
+            )
+        ),
+        vec![PolicyRule::DisallowedRegistry]
+    );
+    assert_eq!(
+        rules(
+            &policy,
+            &create_image("vendor.io", "agent", &format!("@{}", DIGEST), "vendor")
+        ),
+        vec![PolicyRule::DisallowedRegistry]
+    );
+
+    let violations = policy.check(&create_image("docker.io", "nginx", "1.25", "public"));
+    assert_eq!(
+        violations[0].to_string(),
+        "container app: image docker.io/nginx:1.25 is pulled from disallowed registry docker.io \
+         (disallowed-registry)"
+    );
+    // Invalid references fail closed as soon as the policy has rules
+    let invalid = create_image("", "Invalid/Name:tag", "", "");
+    assert_eq!(rules(&policy, &invalid), vec![PolicyRule::InvalidReference]);
+    assert_eq!(
+        policy.check(&invalid)[0].to_string(),
+        "container app: image Invalid/Name:tag is not a valid image reference (invalid-reference)"
+    );
+    assert!(ImagePolicy::new().check(&invalid).is_empty());
+
+    assert!(ImagePolicy::new().is_empty());
+    assert!(
+        ImagePolicy::new()
+            .check(&create_image("docker.io", "nginx", "latest", "public"))
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_admission_violations() -> Result<()> {
+    let classifier =
+        RegistryClassifier::new(vec!["vendor.io=vendor".parse::<RegistryRule>().unwrap()]);
+    let admission = ImageAdmission::new(
+        ImagePolicy::new()
+            .disallow_class("vendor")
+            .deny_latest(true),
+        classifier,
+        true,
+    );
+
+    let violations = admission.violations(deployment(
+        json!([
+            { "name": "web", "image": "ghcr.io/org/web:1.2" },
+            { "name": "agent", "image": "vendor.io/agent:3.0" },
+        ]),
+        json!([{ "name": "migrate", "image": "ghcr.io/org/migrate" }]),
+    ))?;
+    let found: Vec<_> = violations
+        .iter()
+        .map(|violation| (violation.container.as_str(), violation.rule))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("migrate", PolicyRule::LatestTag),
+            ("agent", PolicyRule::DisallowedRegistry),
+        ]
+    );
+
+    // A debug container added through pods/ephemeralcontainers is checked too
+    let debugged = json!({
+        "apiVersion": "v1",
+        "kind": "Pod",
+        "metadata": { "name": "web-0", "namespace": "shop" },
+        "spec": {
+            "containers": [{ "name": "web", "image": "ghcr.io/org/web:1.2" }],
+            "ephemeralContainers": [{ "name": "debugger", "image": "vendor.io/shell:1" }],
+        },
+    });
+    let found: Vec<_> = admission
+        .violations(debugged)?
+        .into_iter()
+        .map(|violation| (violation.container, violation.rule))
+        .collect();
+    assert_eq!(
+        found,
+        vec![("debugger".to_string(), PolicyRule::DisallowedRegistry)]
+    );
+
+    // Names without a domain or port are pulled from Docker Hub, whatever the rules say
+    let admission = ImageAdmission::new(
+        ImagePolicy::new().disallow_registry("docker.io"),
+        RegistryClassifier::new(vec!["harbor=internal".parse::<RegistryRule>().unwrap()]),
+        true,
+    );
+    let found: Vec<_> = admission
+        .violations(deployment(
+            json!([{ "name": "web", "image": "harbor/anything:1.0" }]),
+            json!([]),
+        ))?
+        .into_iter()
+        .map(|violation| (violation.image, violation.rule))
+        .collect();
+    assert_eq!(
+        found,
+        vec![(
+            "docker.io/harbor/anything:1.0".to_string(),
+            PolicyRule::DisallowedRegistry
+        )]
+    );
+
+    // Objects without pods are not checked
+    let config_map =
+        json!({ "apiVersion": "v1", "kind": "ConfigMap", "metadata": { "name": "x" } });
+    assert!(admission.violations(config_map)?.is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_webhook_enforce_and_dry_run() -> Result<()> {
+    let object = deployment(
+        json!([{ "name": "web", "image": "nginx:latest" }]),
+        json!([]),
+    );
+    let review = |enforce: bool, object: Value| async move {
+        let admission =
+            ImageAdmission::new(strict_policy(), RegistryClassifier::default(), enforce);
+        let addr = spawn_server(webhook_router(Arc::new(admission))).await?;
+        let response: Value = reqwest::Client::new()
+            .post(format!("http://{}/validate", addr))
+            .json(&admission_review(object))
+            .send()
+            .await?
+            .json()
+            .await?;
+        anyhow::Ok(response)
+    };
+
+    let enforced = review(true, object.clone()).await?;
+    assert_eq!(enforced["kind"], "AdmissionReview");
+    let response = &enforced["response"];
+    assert_eq!(response["uid"], "705ab4f5-6393-11e8-b7cc-42010a800002");
+    assert_eq!(response["allowed"], false);
+    let message = response["status"]["message"].as_str().unwrap();
+    assert!(message.starts_with("kimspect: container web: image docker.io/nginx:latest"));
+    assert!(message.contains("(disallowed-registry); kimspect: container web"));
+    assert!(message.contains("(latest-tag)"));
+    assert!(message.contains("(missing-digest)"));
+
+    let dry_run = review(false, object).await?;
+    let response = &dry_run["response"];
+    assert_eq!(response["allowed"], true);
+    assert_eq!(response["warnings"].as_array().unwrap().len(), 3);
+
+    let pinned = deployment(
+        json!([{ "name": "web", "image": format!("ghcr.io/org/web:1.2@{}", DIGEST) }]),
+        json!([]),
+    );
+    let allowed = review(true, pinned).await?;
+    assert_eq!(allowed["response"]["allowed"], true);
+    assert!(allowed["response"].get("warnings").is_none());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_webhook_over_tls() -> Result<()> {
+    let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tls");
+    let config = load_tls_config(&fixtures.join("cert.pem"), &fixtures.join("key.pem"))?;
+    assert!(load_tls_config(&fixtures.join("missing.pem"), &fixtures.join("key.pem")).is_err());
+
+    // Find a free port, then serve on it
+    let addr = TcpListener::bind("127.0.0.1:0").await?.local_addr()?;
+    let admission = ImageAdmission::new(strict_policy(), RegistryClassifier::default(), true);
+    tokio::spawn(serve_tls(addr, webhook_router(Arc::new(admission)), config));
+
+    let cert = reqwest::Certificate::from_pem(&std::fs::read(fixtures.join("cert.pem"))?)?;
+    let client = reqwest::Client::builder()
+        .add_root_certificate(cert)
+        .timeout(Duration::from_secs(10))
+        .build()?;
+    let mut response = None;
+    for _ in 0..50 {
+        match client.get(format!("https://{}/healthz", addr)).send().await {
+            Ok(ok) => {
+                response = Some(ok);
+                break;
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(100)).await,
+        }
+    }
+    let response = response.expect("webhook did not start");
+    assert_eq!(response.status(), 200);
+    assert_eq!(response.text().await?, "ok");
+    Ok(())
+}
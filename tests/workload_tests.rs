@@ -0,0 +1,142 @@
+mod common;
+
+use common::{DIGEST, OTHER_DIGEST, PodBuilder};
+use k8s_openapi::api::core::v1::{Pod, PodSpec};
+use kimspect::{ImageMismatch, MismatchKind, WorkloadTemplates, find_image_mismatches};
+use serde_json::{Value, json};
+
+/// Build a pod of the Deployment web, with its spec images and the images its containers run
+fn create_pod(name: &str, spec_image: &str, running_image: &str, image_id: &str) -> Pod {
+    PodBuilder::new(name, "shop")
+        .label("pod-template-hash", "7d9f8")
+        .owner("ReplicaSet", "web-7d9f8")
+        .node("worker-1")
+        .container("web", spec_image)
+        .status("web", running_image, image_id)
+        .build()
+}
+
+fn template(containers: Value) -> PodSpec {
+    serde_json::from_value(json!({ "containers": containers })).unwrap()
+}
+
+fn web_template(image: &str) -> WorkloadTemplates {
+    WorkloadTemplates::from([(
+        ("shop".to_string(), "Deployment/web".to_string()),
+        template(json!([{ "name": "web", "image": image }])),
+    )])
+}
+
+fn kinds(mismatches: &[ImageMismatch]) -> Vec<MismatchKind> {
+    mismatches.iter().map(|mismatch| mismatch.kind).collect()
+}
+
+#[test]
+fn test_find_template_mismatches() {
+    let image_id = format!("ghcr.io/org/web@{}", DIGEST);
+    let pods = [
+        create_pod(
+            "web-7d9f8-a",
+            "ghcr.io/org/web:1.2",
+            "ghcr.io/org/web:1.2",
+            &image_id,
+        ),
+        create_pod(
+            "web-7d9f8-b",
+            "ghcr.io/org/web:1.1",
+            "ghcr.io/org/web:1.1",
+            &image_id,
+        ),
+    ];
+
+    let mismatches = find_image_mismatches(&pods, &web_template("ghcr.io/org/web:1.2"));
+    assert_eq!(mismatches.len(), 1);
+    let mismatch = &mismatches[0];
+    assert_eq!(mismatch.pod_name, "web-7d9f8-b");
+    assert_eq!(mismatch.namespace, "shop");
+    assert_eq!(mismatch.container_name, "web");
+    assert_eq!(mismatch.workload, "Deployment/web");
+    assert_eq!(mismatch.node_name, "worker-1");
+    assert_eq!(mismatch.kind, MismatchKind::Template);
+    assert_eq!(mismatch.expected, "ghcr.io/org/web:1.2");
+    assert_eq!(mismatch.actual, "ghcr.io/org/web:1.1");
+
+    // Without the template only what the pods run is checked
+    assert!(find_image_mismatches(&pods, &WorkloadTemplates::new()).is_empty());
+}
+
+#[test]
+fn test_find_running_mismatches() {
+    let templates = WorkloadTemplates::new();
+    let check = |spec_image: &str, running_image: &str, image_id: &str| {
+        let pod = create_pod("web-7d9f8-a", spec_image, running_image, image_id);
+        kinds(&find_image_mismatches(&[pod], &templates))
+    };
+    let nginx_id = format!("docker.io/library/nginx@{}", DIGEST);
+
+    // The runtime reports normalized references
+    assert_eq!(
+        check("nginx", "docker.io/library/nginx:latest", &nginx_id),
+        vec![]
+    );
+    assert_eq!(
+        check("nginx:1.25", "docker.io/library/nginx:1.24", &nginx_id),
+        vec![MismatchKind::Running]
+    );
+    assert_eq!(
+        check("nginx:1.25", "docker.io/library/httpd:1.25", &nginx_id),
+        vec![MismatchKind::Running]
+    );
+    // Image IDs and containers that have not started are skipped
+    assert_eq!(check("nginx:1.25", DIGEST, &nginx_id), vec![]);
+    assert_eq!(check("nginx:1.25", "", ""), vec![]);
+
+    // Pinned images are compared by digest
+    let pinned = format!("nginx:1.25@{}", DIGEST);
+    assert_eq!(
+        check(&pinned, "docker.io/library/nginx:1.25", &nginx_id),
+        vec![]
+    );
+    let pod = create_pod(
+        "web-7d9f8-a",
+        &pinned,
+        "docker.io/library/nginx:1.25",
+        &format!("docker.io/library/nginx@{}", OTHER_DIGEST),
+    );
+    let mismatches = find_image_mismatches(&[pod], &templates);
+    assert_eq!(kinds(&mismatches), vec![MismatchKind::Running]);
+    assert_eq!(mismatches[0].expected, pinned);
+    assert_eq!(
+        mismatches[0].actual,
+        format!("docker.io/library/nginx@{}", OTHER_DIGEST)
+    );
+}
+
+#[test]
+fn test_find_mismatches_of_init_containers() {
+    let mut pod = create_pod(
+        "web-7d9f8-a",
+        "ghcr.io/org/web:1.2",
+        "ghcr.io/org/web:1.2",
+        &format!("ghcr.io/org/web@{}", DIGEST),
+    );
+    let mut spec = pod.spec.take().unwrap();
+    spec.init_containers = Some(vec![
+        serde_json::from_value(json!({ "name": "migrate", "image": "ghcr.io/org/migrate:2" }))
+            .unwrap(),
+    ]);
+    pod.spec = Some(spec);
+
+    let mut templates = web_template("ghcr.io/org/web:1.2");
+    templates.values_mut().for_each(|template| {
+        template.init_containers = Some(vec![
+            serde_json::from_value(json!({ "name": "migrate", "image": "ghcr.io/org/migrate:3" }))
+                .unwrap(),
+        ])
+    });
+
+    let mismatches = find_image_mismatches(&[pod], &templates);
+    assert_eq!(kinds(&mismatches), vec![MismatchKind::Template]);
+    assert_eq!(mismatches[0].container_name, "migrate");
+    assert_eq!(mismatches[0].kind.to_string(), "template");
+}